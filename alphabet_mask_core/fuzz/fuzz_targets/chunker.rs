@@ -0,0 +1,32 @@
+//! Chunking arbitrary lists of strings with arbitrary length limits.
+//!
+//! - Chunking never panics, and never returns an empty chunk.
+//! - A chunk is at most `length_limit` bytes long, unless it is a single string.
+//! - A chunk only ends early if the next string would not have fitted.
+//! - The chunks concatenate back into the original strings, in order.
+//!
+#![no_main]
+
+use alphabet_mask_core::chunk_strings_by;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (Vec<String>, u16)| {
+    let (strings, length_limit) = input;
+    let length_limit = usize::from(length_limit);
+    let length = |chunk: &[&String]| chunk.iter().map(|string| string.len()).sum::<usize>();
+
+    let chunks: Vec<Box<[&String]>> =
+        chunk_strings_by(strings.iter(), Some(length_limit)).collect();
+
+    for (position, chunk) in chunks.iter().enumerate() {
+        assert!(!chunk.is_empty());
+        assert!(chunk.len() == 1 || length(chunk) <= length_limit);
+        if let Some(next) = chunks.get(position + 1) {
+            assert!(length(chunk) + next[0].len() > length_limit);
+        }
+    }
+    assert!(chunks
+        .iter()
+        .flat_map(|chunk| chunk.iter().copied())
+        .eq(&strings));
+});
@@ -0,0 +1,52 @@
+//! Masking arbitrary strings, and the characters of arbitrary masks.
+//!
+//! - Masking never panics, and only fails for strings with a character outside
+//!   the alphabet.
+//! - The characters of a mask mask back to the same mask, for all 32 bits.
+//! - The mask of a string is the union of the masks of its characters.
+//! - Masking agrees with `mask_string_with` and the standard spec, errors
+//!   included, and with the low 32 bits of the extended layout.
+//!
+#![no_main]
+
+use alphabet_mask_core::layout::EXTENDED;
+use alphabet_mask_core::models::{AlphabetSpec, Mask};
+use alphabet_mask_core::{mask_string, mask_string_with, mask_to_chars};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (&str, u32)| {
+    let (string, bits) = input;
+
+    let chars = mask_to_chars(Mask(bits));
+    assert_eq!(chars.chars().count(), Mask(bits).len() as usize);
+    assert_eq!(mask_string(&chars).ok(), Some(Mask(bits)));
+
+    let union = string.chars().try_fold(Mask::EMPTY, |union, character| {
+        let mut buffer = [0; 4];
+        mask_string(character.encode_utf8(&mut buffer)).map(|mask| union | mask)
+    });
+    match mask_string(string) {
+        Ok(mask) => {
+            assert_eq!(union.ok(), Some(mask));
+            assert_eq!(mask_string(&mask_to_chars(mask)).ok(), Some(mask));
+        }
+        Err(_) => assert!(
+            union.is_err(),
+            "{string:?} failed but its characters did not"
+        ),
+    }
+
+    let mask = mask_string(string);
+    assert_eq!(
+        format!("{mask:?}"),
+        format!("{:?}", mask_string_with(string, &AlphabetSpec::standard())),
+        "{string:?} differs from the standard spec"
+    );
+    if let (Ok(mask), Ok(extended)) = (mask, EXTENDED.mask_string(string)) {
+        assert_eq!(
+            mask,
+            Mask(extended.0[0] as u32),
+            "{string:?} differs from the extended layout"
+        );
+    }
+});
@@ -0,0 +1,462 @@
+//! User-defined alphabets of any characters.
+//!
+//! An [`AlphabetSpec`](crate::models::AlphabetSpec) is limited to 32 bits, and a
+//! [`ClassTable`](crate::table::ClassTable) to U+0000 to U+00FF; an [`Alphabet`]
+//! maps any characters to up to 128 bits, each set by one symbol or by a group of
+//! them, and is built at runtime with [`Alphabet::builder`]:
+//!
+//! ```
+//! use alphabet_mask_core::alphabet::Alphabet;
+//!
+//! let notes = Alphabet::builder()
+//!     .symbols("♩♪♫")
+//!     .group("♭b")
+//!     .symbol('♯')
+//!     .build()
+//!     .unwrap();
+//!
+//! let mask = notes.mask_string("♪b♫").unwrap();
+//! assert_eq!(notes.chars(mask), "♪♫♭");
+//! assert_eq!(notes.chars(notes.find_common_mask(&["♩♯♪", "♪♩"], 1024).unwrap()), "♩♪");
+//! assert!(notes.mask_string("♮").is_err());
+//! ```
+//!
+//! [`Alphabet::preset`] returns one of the [`PRESETS`] for the scripts of common
+//! corpora, each with a space and the punctuation of the standard layout:
+//!
+//! ```
+//! use alphabet_mask_core::alphabet::Alphabet;
+//!
+//! let cyrillic = Alphabet::preset("cyrillic").unwrap();
+//! let common = cyrillic.find_common_mask(&["Добрый день", "Ночь"], 1024).unwrap();
+//! assert_eq!(cyrillic.chars(common), "ноь");
+//! ```
+//!
+use alloc::{format, string::String, vec::Vec};
+
+use crate::classify::CharClassifier;
+use crate::models::{BitMask, CasePolicy, LocalError, Mask128};
+use crate::{find_common_mask_dispatch_by, find_union_mask_by, mask_string_by};
+
+/// The number of characters looked up in a table rather than searched for.
+const ASCII: usize = 128;
+
+/// The entry of an ASCII character that is not in the alphabet.
+const NONE: u8 = u8::MAX;
+
+/// Returns the form of `character` that is looked up under `case`, as
+/// [`AlphabetSpec::fold`](crate::models::AlphabetSpec::fold) does.
+fn fold(character: char, case: CasePolicy) -> char {
+    match case {
+        CasePolicy::Sensitive => character,
+        CasePolicy::Insensitive => {
+            let mut lowercase = character.to_lowercase();
+            match (lowercase.next(), lowercase.next()) {
+                (Some(folded), None) => folded,
+                _ => character,
+            }
+        }
+    }
+}
+
+/// The names of the alphabets returned by [`Alphabet::preset`].
+pub const PRESETS: [&str; 3] = ["greek", "cyrillic", "latin_extended"];
+
+/// The punctuation of the standard layout, in bit order.
+const PUNCTUATION: &str = ".,'-\"";
+
+/// The Greek alphabet, with each vowel sharing its bit with its accented forms
+/// and final sigma with sigma.
+fn greek() -> AlphabetBuilder {
+    Alphabet::builder()
+        .symbol(' ')
+        .group("αά")
+        .symbols("βγδ")
+        .group("εέ")
+        .symbol('ζ')
+        .group("ηή")
+        .symbol('θ')
+        .group("ιίϊΐ")
+        .symbols("κλμνξ")
+        .group("οό")
+        .symbols("πρ")
+        .group("σς")
+        .symbol('τ')
+        .group("υύϋΰ")
+        .symbols("φχψ")
+        .group("ωώ")
+        .symbols(PUNCTUATION)
+}
+
+/// The Russian alphabet, followed by the other letters of the Ukrainian,
+/// Belarusian, Serbian and Macedonian alphabets.
+fn cyrillic() -> AlphabetBuilder {
+    Alphabet::builder()
+        .symbol(' ')
+        .symbols("абвгдежзийклмнопрстуфхцчшщъыьэюяё")
+        .symbols("іїєґўђјљњћџѓќѕ")
+        .symbols(PUNCTUATION)
+}
+
+/// The standard layout on its own bits, followed by the letters of Latin-1 and
+/// the most common letters of Latin Extended-A and -B.
+fn latin_extended() -> AlphabetBuilder {
+    Alphabet::builder()
+        .symbol(' ')
+        .symbols("abcdefghijklmnopqrstuvwxyz")
+        .symbols(PUNCTUATION)
+        .symbols("àáâãäåæçèéêëìíîïðñòóôõöøùúûüýþÿß")
+        .symbols("āăąćčďđēėęěğīįıłńňőœřśşšťţūůűźżžșț")
+}
+
+/// The configuration of an [`Alphabet`], returned by [`Alphabet::builder`].
+///
+/// Bits are assigned in the order the symbols are added, from bit 0.
+#[derive(Debug, Clone, Default)]
+pub struct AlphabetBuilder {
+    symbols: Vec<(char, u8)>,
+    width: usize,
+    case: CasePolicy,
+}
+
+impl AlphabetBuilder {
+    /// Add `character` on the next bit.
+    pub fn symbol(self, character: char) -> Self {
+        self.group(character.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Add each of `characters` on a bit of its own.
+    pub fn symbols(self, characters: &str) -> Self {
+        characters
+            .chars()
+            .fold(self, |builder, character| builder.symbol(character))
+    }
+
+    /// Add every one of `characters` on the next bit, shared between them; the
+    /// first represents the bit.
+    pub fn group(mut self, characters: &str) -> Self {
+        // Bits beyond `u8::MAX` are reported by `build`.
+        let bit = u8::try_from(self.width).unwrap_or(u8::MAX);
+        self.symbols
+            .extend(characters.chars().map(|character| (character, bit)));
+        self.width += 1;
+        self
+    }
+
+    /// Mask letters regardless of case, which is the default.
+    pub fn case_insensitive(mut self) -> Self {
+        self.case = CasePolicy::Insensitive;
+        self
+    }
+
+    /// Mask characters exactly as given.
+    pub fn case_sensitive(mut self) -> Self {
+        self.case = CasePolicy::Sensitive;
+        self
+    }
+
+    /// Build the alphabet, or return an error if it has more than 128 bits, a
+    /// group without a symbol, or a symbol listed more than once after case
+    /// folding.
+    pub fn build(self) -> Result<Alphabet, LocalError> {
+        let invalid = |context: String| Err(LocalError::InvalidSpec { context });
+        if self.width > Mask128::BITS {
+            return invalid(format!(
+                "{} bits do not fit in {} bits",
+                self.width,
+                Mask128::BITS
+            ));
+        }
+
+        let mut ascii = [NONE; ASCII];
+        let mut others = Vec::new();
+        let mut represented = Mask128::EMPTY;
+        for &(symbol, bit) in &self.symbols {
+            let folded = fold(symbol, self.case);
+            let entry = match ascii.get_mut(folded as usize) {
+                Some(entry) if *entry == NONE => Some(entry),
+                Some(_) => return invalid(format!("{symbol:?} is listed more than once")),
+                None => None,
+            };
+            match entry {
+                Some(entry) => *entry = bit,
+                None => others.push((folded, bit)),
+            }
+            represented |= Mask128::from_bit(bit.into());
+        }
+        if (represented.len() as usize) < self.width {
+            return invalid(String::from("a group has no symbol"));
+        }
+
+        others.sort_unstable();
+        if let Some(window) = others.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return invalid(format!("{:?} is listed more than once", window[0].0));
+        }
+
+        Ok(Alphabet {
+            symbols: self.symbols,
+            width: self.width as u8,
+            case: self.case,
+            ascii,
+            others,
+        })
+    }
+}
+
+/// An alphabet of any characters on up to 128 bits, built by [`AlphabetBuilder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    symbols: Vec<(char, u8)>,
+    width: u8,
+    case: CasePolicy,
+    ascii: [u8; ASCII],
+    /// The folded symbols outside ASCII with their bits, sorted for searching.
+    others: Vec<(char, u8)>,
+}
+
+impl Alphabet {
+    /// Start building an alphabet, with no symbols and regardless of case.
+    pub fn builder() -> AlphabetBuilder {
+        AlphabetBuilder::default()
+    }
+
+    /// Returns the alphabet called `name`, one of [`PRESETS`]:
+    ///
+    /// - `greek`, the 24 letters of the Greek alphabet, each vowel sharing its
+    ///   bit with its accented forms, such as `ά`, and `σ` with `ς`;
+    /// - `cyrillic`, the 33 letters of the Russian alphabet, each on a bit of its
+    ///   own, then the other letters of the Ukrainian, Belarusian, Serbian and
+    ///   Macedonian alphabets, such as `і` and `ђ`;
+    /// - `latin_extended`, the standard layout on its own bits, then the letters
+    ///   of Latin-1, such as `é` and `ß`, and the common letters of Latin
+    ///   Extended-A and -B, such as `ł`, `ő` and `ș`.
+    ///
+    /// Each has a space and the punctuation of the standard layout, and masks
+    /// letters regardless of case.
+    pub fn preset(name: &str) -> Result<Self, LocalError> {
+        let builder = match name {
+            "greek" => greek(),
+            "cyrillic" => cyrillic(),
+            "latin_extended" => latin_extended(),
+            _ => {
+                return Err(LocalError::InvalidArgument {
+                    name: "alphabet",
+                    value: name.into(),
+                })
+            }
+        };
+        builder.build()
+    }
+
+    /// Start building an alphabet from the symbols and case policy of this one.
+    pub fn to_builder(&self) -> AlphabetBuilder {
+        AlphabetBuilder {
+            symbols: self.symbols.clone(),
+            width: self.width.into(),
+            case: self.case,
+        }
+    }
+
+    /// The number of bits in the masks of this alphabet.
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    /// The case policy of this alphabet.
+    pub fn case(&self) -> CasePolicy {
+        self.case
+    }
+
+    /// Every symbol with its bit, in the order they were added.
+    pub fn symbols(&self) -> &[(char, u8)] {
+        &self.symbols
+    }
+
+    /// Returns the bit of `character`, or [`None`] if it is not in the alphabet.
+    pub fn bit(&self, character: char) -> Option<u8> {
+        let folded = fold(character, self.case);
+        match self.ascii.get(folded as usize) {
+            Some(&NONE) => None,
+            Some(&bit) => Some(bit),
+            None => self
+                .others
+                .binary_search_by_key(&folded, |&(symbol, _)| symbol)
+                .ok()
+                .map(|index| self.others[index].1),
+        }
+    }
+
+    /// Returns the character representing `bit`, or [`None`] if it is beyond
+    /// the width of this alphabet.
+    pub fn symbol(&self, bit: usize) -> Option<char> {
+        self.symbols
+            .iter()
+            .find(|&&(_, symbol_bit)| usize::from(symbol_bit) == bit)
+            .map(|&(symbol, _)| symbol)
+    }
+
+    /// The mask of every bit of this alphabet.
+    pub fn defined(&self) -> Mask128 {
+        (0..self.width.into()).fold(Mask128::EMPTY, |mask, bit| mask | Mask128::from_bit(bit))
+    }
+
+    /// Returns `bits` as a mask if it only sets bits of this alphabet, or an
+    /// error otherwise.
+    pub fn validate_mask(&self, bits: i128) -> Result<Mask128, LocalError> {
+        let defined = u128::from(self.defined());
+        match u128::try_from(bits) {
+            Ok(bits) if bits & !defined == 0 => Ok(bits.into()),
+            _ => Err(LocalError::InvalidMask {
+                context: format!(
+                    "{bits:#x} sets bits beyond the {} of the alphabet",
+                    self.width
+                ),
+            }),
+        }
+    }
+
+    /// Mask a string; any character outside the alphabet is an error, as for
+    /// [`mask_string`](crate::mask_string).
+    pub fn mask_string(&self, string: &str) -> Result<Mask128, LocalError> {
+        mask_string_by(string, self)
+    }
+
+    /// Returns the characters of `mask` in bit order.
+    pub fn chars(&self, mask: Mask128) -> String {
+        mask.bits_set().filter_map(|bit| self.symbol(bit)).collect()
+    }
+
+    /// Returns the common mask of `strings`, in parallel chunks of
+    /// `length_limit` bytes if they are longer in total, as for
+    /// [`find_common_mask_dispatch`](crate::find_common_mask_dispatch).
+    pub fn find_common_mask<S: AsRef<str> + Sync>(
+        &self,
+        strings: &[S],
+        length_limit: usize,
+    ) -> Result<Mask128, LocalError> {
+        find_common_mask_dispatch_by(strings, length_limit, self)
+    }
+
+    /// Returns the combined mask of `strings`.
+    pub fn find_union_mask<S: AsRef<str>>(
+        &self,
+        strings: impl Iterator<Item = S>,
+    ) -> Result<Mask128, LocalError> {
+        find_union_mask_by(strings, self)
+    }
+}
+
+impl CharClassifier for Alphabet {
+    type Mask = Mask128;
+
+    #[inline]
+    fn classify(&self, character: char) -> Result<Option<usize>, LocalError> {
+        self.bit(character)
+            .map(|bit| Some(usize::from(bit)))
+            .ok_or_else(|| LocalError::outside_alphabet(character))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn greek() {
+        let greek = Alphabet::builder()
+            .symbol(' ')
+            .symbols("αβγδ")
+            .group("σς")
+            .build()
+            .unwrap();
+
+        assert_eq!(greek.width(), 6);
+        assert_eq!(greek.bit('Σ'), Some(5));
+        assert_eq!(greek.bit('ς'), Some(5));
+        assert!(greek.mask_string("Γάς").is_err());
+        assert_eq!(greek.chars(greek.mask_string("ΒΑΣ γς").unwrap()), " αβγσ");
+        assert!(matches!(
+            greek.mask_string("α1"),
+            Err(LocalError::InvalidCharacter { character: '1' })
+        ));
+        assert!(greek.validate_mask(1 << 6).is_err());
+        assert_eq!(greek.validate_mask(0b11_1111).unwrap(), greek.defined());
+    }
+
+    #[test]
+    fn case_sensitive() {
+        let codes = Alphabet::builder()
+            .symbols("aA")
+            .case_sensitive()
+            .build()
+            .unwrap();
+
+        assert_eq!(u128::from(codes.mask_string("A").unwrap()), 0b10);
+        assert!(Alphabet::builder().symbols("aA").build().is_err());
+        assert!(Alphabet::builder().symbols("日本日").build().is_err());
+    }
+
+    #[test]
+    fn widths() {
+        let symbols: String = ('\u{4e00}'..).take(128).collect();
+        let wide = Alphabet::builder().symbols(&symbols).build().unwrap();
+
+        assert_eq!(wide.symbol(127), symbols.chars().last());
+        assert_eq!(wide.defined().len(), 128);
+        assert!(wide.to_builder().symbol('!').build().is_err());
+        assert!(Alphabet::builder().group("").build().is_err());
+    }
+
+    #[test]
+    fn presets() {
+        for name in PRESETS {
+            let alphabet = Alphabet::preset(name).unwrap();
+            assert_eq!(alphabet.bit(' '), Some(0), "{name}");
+            let punctuation = alphabet.mask_string(" .,'-\"").unwrap();
+            assert_eq!(punctuation.len(), 6, "{name}");
+        }
+        assert!(Alphabet::preset("klingon").is_err());
+
+        let greek = Alphabet::preset("greek").unwrap();
+        assert_eq!(greek.width(), 30);
+        assert_eq!(
+            greek.chars(greek.mask_string("Καλή νύχτα, ΟΔΥΣΣΕΥΣ.").unwrap()),
+            " αδεηκλνοστυχ.,"
+        );
+
+        let cyrillic = Alphabet::preset("cyrillic").unwrap();
+        assert_eq!(cyrillic.width(), 53);
+        assert_eq!(
+            cyrillic.chars(cyrillic.mask_string("Їжак").unwrap()),
+            "ажкї"
+        );
+    }
+
+    #[test]
+    fn latin_extended_extends_standard() {
+        let latin = Alphabet::preset("latin_extended").unwrap();
+
+        assert_eq!(latin.width(), 98);
+        for string in crate::conftest::COLLECTION_OF_50_CHARS_STRINGS {
+            assert_eq!(
+                u128::from(latin.mask_string(string).unwrap()),
+                u128::from(crate::mask_string(string).unwrap().bits())
+            );
+        }
+        assert_eq!(
+            latin.chars(latin.mask_string("Łódź, Straße").unwrap()),
+            " aderst,óßłź"
+        );
+    }
+
+    #[test]
+    fn common_in_parallel() {
+        let alphabet = Alphabet::builder().symbols("→←↑↓").build().unwrap();
+        let strings = ["↑↑↓↓←→←→"; 1000];
+
+        assert_eq!(
+            alphabet.chars(alphabet.find_common_mask(&strings, 64).unwrap()),
+            "→←↑↓"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Validation of nucleotide sequences.
+//!
+//! The DNA alphabet is `A`, `C`, `G`, `T` and the ambiguity code `N`, regardless
+//! of case; any other character in a sequence is an invalid base:
+//!
+//! ```
+//! use alphabet_mask_core::bio::{invalid_bases, InvalidBases};
+//!
+//! let invalid = invalid_bases(&["GATTACA", "GAUUACA", "acgtn"]);
+//!
+//! assert!(invalid[0].is_empty());
+//! assert_eq!(invalid[1].characters, ['U']);
+//! assert_eq!(invalid[1].positions, [2, 3]);
+//! assert!(invalid[2].is_empty());
+//! ```
+//!
+use alloc::vec::Vec;
+
+#[cfg(feature = "parallel")]
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::models::CasePolicy;
+use crate::table::ClassTable;
+
+/// The DNA alphabet, with `A`, `C`, `G`, `T` and `N` on bits 0 to 4.
+pub static NUCLEOTIDES: ClassTable = ClassTable::new(
+    &[('a', 0), ('c', 1), ('g', 2), ('t', 3), ('n', 4)],
+    CasePolicy::Insensitive,
+);
+
+/// The characters of a sequence outside the DNA alphabet.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct InvalidBases {
+    /// Every distinct invalid character, sorted.
+    pub characters: Vec<char>,
+    /// The position in characters of every invalid character, in order.
+    pub positions: Vec<usize>,
+}
+
+impl InvalidBases {
+    /// Find the invalid bases of one sequence.
+    pub fn of(sequence: &str) -> Self {
+        let mut invalid = Self::default();
+        for (position, character) in sequence.chars().enumerate() {
+            if NUCLEOTIDES.bit(character).is_none() {
+                invalid.characters.push(character);
+                invalid.positions.push(position);
+            }
+        }
+        invalid.characters.sort_unstable();
+        invalid.characters.dedup();
+
+        invalid
+    }
+
+    /// Whether the sequence was valid.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+}
+
+/// Find the invalid bases of every sequence, in parallel with the `parallel`
+/// feature.
+pub fn invalid_bases<S: AsRef<str> + Sync>(sequences: &[S]) -> Vec<InvalidBases> {
+    let of = |sequence: &S| InvalidBases::of(sequence.as_ref());
+
+    #[cfg(feature = "parallel")]
+    return sequences.par_iter().map(of).collect();
+    #[cfg(not(feature = "parallel"))]
+    return sequences.iter().map(of).collect();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mask_string_by;
+    use crate::models::Mask;
+
+    #[test]
+    fn nucleotides() {
+        assert_eq!(
+            mask_string_by("GATTACA", &NUCLEOTIDES).unwrap(),
+            Mask(0b1111)
+        );
+        assert_eq!(mask_string_by("nnn", &NUCLEOTIDES).unwrap(), Mask(0b10000));
+    }
+
+    #[test]
+    fn invalid() {
+        let invalid = InvalidBases::of("AXGT-X\u{e9}a");
+
+        assert_eq!(invalid.characters, ['-', 'X', '\u{e9}']);
+        assert_eq!(invalid.positions, [1, 4, 5, 6]);
+        assert!(InvalidBases::of("").is_empty());
+        assert_eq!(invalid_bases::<&str>(&[]), []);
+    }
+}
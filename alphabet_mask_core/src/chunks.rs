@@ -1,24 +1,112 @@
 //! A chunker struct to chunk strings into chunks of at most `LENGTH_LIMIT_PER_CHUNK` bytes,
 //! or a chunk of a single string if it is larger than `LENGTH_LIMIT_PER_CHUNK`.
 //!
-use std::iter::Peekable;
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::iter::Peekable;
+
+use crate::exceeds_length_limit;
 
 /// The maximum number of bytes to process in a single chunk.
 pub const LENGTH_LIMIT_PER_CHUNK: usize = 1 << 20; // 1 MiB
 
+/// One chunk of a [`Plan`], masked as a unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlannedChunk {
+    /// The position of the first string of the chunk.
+    pub start: usize,
+    /// The position after the last string of the chunk.
+    pub end: usize,
+    /// The total length in bytes of the strings of the chunk.
+    pub bytes: usize,
+}
+
+/// How [`find_common_mask_dispatch`](crate::find_common_mask_dispatch) splits
+/// the work, returned by [`plan`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Plan {
+    /// Whether the chunks are masked in parallel.
+    pub parallel: bool,
+    /// The chunks in order; a single chunk of every string if not parallel.
+    pub chunks: Vec<PlannedChunk>,
+}
+
+/// The total length in bytes of `strings`, saturating on overflow.
+pub(crate) fn total_length<S: AsRef<str>>(strings: impl Iterator<Item = S>) -> usize {
+    strings.fold(0, |bytes, string| {
+        bytes.saturating_add(string.as_ref().len())
+    })
+}
+
+/// Plan the work of masking `strings` with `length_limit`, without masking them.
+///
+/// ```
+/// use alphabet_mask_core::chunks::{plan, PlannedChunk};
+///
+/// let plan = plan(&["ab", "cd", "efgh"], 3);
+///
+/// assert_eq!(plan.parallel, cfg!(feature = "parallel"));
+/// # #[cfg(feature = "parallel")]
+/// assert_eq!(
+///     plan.chunks,
+///     [
+///         PlannedChunk { start: 0, end: 1, bytes: 2 },
+///         PlannedChunk { start: 1, end: 2, bytes: 2 },
+///         PlannedChunk { start: 2, end: 3, bytes: 4 },
+///     ]
+/// );
+/// ```
+pub fn plan<S: AsRef<str>>(strings: &[S], length_limit: usize) -> Plan {
+    if cfg!(feature = "parallel") && exceeds_length_limit(strings, length_limit) {
+        let mut start = 0;
+        let chunks = Chunker::with_length_limit(strings.iter(), length_limit)
+            .map(|chunk| {
+                let planned = PlannedChunk {
+                    start,
+                    end: start + chunk.len(),
+                    bytes: total_length(chunk.iter()),
+                };
+                start = planned.end;
+                planned
+            })
+            .collect();
+
+        Plan {
+            parallel: true,
+            chunks,
+        }
+    } else {
+        let chunks = if strings.is_empty() {
+            Vec::new()
+        } else {
+            vec![PlannedChunk {
+                start: 0,
+                end: strings.len(),
+                bytes: total_length(strings.iter()),
+            }]
+        };
+
+        Plan {
+            parallel: false,
+            chunks,
+        }
+    }
+}
+
 /// A chunker struct to chunk strings into chunks of a maximum length, or a
 /// chunk of a single string if it is larger than the maximum length.
-pub(crate) struct Chunker<'s, T>
+pub(crate) struct Chunker<T>
 where
-    T: Iterator<Item = &'s str>,
+    T: Iterator,
+    T::Item: AsRef<str>,
 {
     strings: Peekable<T>,
     length_limit: usize,
 }
 
-impl<'s, T> Chunker<'s, T>
+impl<T> Chunker<T>
 where
-    T: Iterator<Item = &'s str>,
+    T: Iterator,
+    T::Item: AsRef<str>,
 {
     /// Create a new chunker.
     pub(crate) fn new(strings: T) -> Self {
@@ -34,18 +122,20 @@ where
     }
 }
 
-impl<'s, T> Iterator for Chunker<'s, T>
+impl<T> Iterator for Chunker<T>
 where
-    T: Iterator<Item = &'s str>,
+    T: Iterator,
+    T::Item: AsRef<str>,
 {
-    type Item = Box<[&'s str]>;
+    type Item = Box<[T::Item]>;
 
-    fn next(&mut self) -> Option<Box<[&'s str]>> {
+    fn next(&mut self) -> Option<Box<[T::Item]>> {
+        span!(TRACE, "chunk", length_limit = self.length_limit);
         let mut chunk = Vec::new();
         let mut length = 0;
 
         while let Some(string) = self.strings.peek() {
-            let string_length = string.len();
+            let string_length = string.as_ref().len();
             if length + string_length > self.length_limit {
                 if chunk.is_empty() {
                     // This is safe because we already peeked.
@@ -122,6 +212,34 @@ mod test {
         assert_eq!(chunks[3], vec!["Ending"].into_boxed_slice());
     }
 
+    #[test]
+    fn plans() {
+        let strings = conftest::COLLECTION_OF_50_CHARS_STRINGS;
+
+        let serial = plan(&strings, LENGTH_LIMIT_PER_CHUNK);
+        assert!(!serial.parallel);
+        assert_eq!(serial.chunks.len(), 1);
+        assert_eq!(serial.chunks[0].end, strings.len());
+        assert_eq!(
+            serial.chunks[0].bytes,
+            strings.iter().map(|s| s.len()).sum::<usize>()
+        );
+        assert_eq!(plan::<&str>(&[], 0), Plan::default());
+
+        // The chunks are exactly those of the chunker.
+        let parallel = plan(&strings, 100);
+        assert_eq!(parallel.parallel, cfg!(feature = "parallel"));
+        #[cfg(feature = "parallel")]
+        for (planned, chunk) in parallel
+            .chunks
+            .iter()
+            .zip(Chunker::with_length_limit(strings.into_iter(), 100))
+        {
+            assert_eq!(&strings[planned.start..planned.end], &*chunk);
+            assert_eq!(planned.bytes, total_length(chunk.iter()));
+        }
+    }
+
     #[test]
     fn empty() {
         let strings = Vec::<&str>::new();
@@ -0,0 +1,215 @@
+//! Pluggable classification of characters into mask bits.
+//!
+//! Masking is a fold over the characters of a string, each of which a
+//! [`CharClassifier`] maps to a bit, to nothing, or to an error. The free
+//! functions of this crate use [`StandardLayout`]; implement the trait to plug in
+//! any other classification, such as one based on Unicode categories:
+//!
+//! ```
+//! use alphabet_mask_core::classify::CharClassifier;
+//! use alphabet_mask_core::mask_string_by;
+//! use alphabet_mask_core::models::{LocalError, Mask};
+//!
+//! /// Whether a string has letters, digits, whitespace or anything else.
+//! struct Categories;
+//!
+//! impl CharClassifier for Categories {
+//!     type Mask = Mask;
+//!
+//!     fn classify(&self, character: char) -> Result<Option<usize>, LocalError> {
+//!         Ok(Some(match character {
+//!             c if c.is_alphabetic() => 0,
+//!             c if c.is_numeric() => 1,
+//!             c if c.is_whitespace() => 2,
+//!             _ => 3,
+//!         }))
+//!     }
+//! }
+//!
+//! assert_eq!(mask_string_by("Tür 7", &Categories).unwrap(), Mask(0b111));
+//! ```
+//!
+//! [`CharClasses`] is a built-in classification of this kind, into the
+//! [`CharClass`]es that password composition policies are written in.
+//!
+use crate::models::{char_bit, BitMask, LocalError, Mask};
+
+/// Maps characters to the bits of a mask.
+pub trait CharClassifier {
+    /// The type of the masks produced.
+    type Mask: BitMask;
+
+    /// Returns the bit set by `character`, [`None`] if it sets no bit, or an error
+    /// if it is not allowed at all.
+    ///
+    /// Every bit returned must be less than `Self::Mask::BITS`.
+    fn classify(&self, character: char) -> Result<Option<usize>, LocalError>;
+}
+
+/// The standard layout, as described in the [crate] documentation; any character
+/// outside it is an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StandardLayout;
+
+impl CharClassifier for StandardLayout {
+    type Mask = Mask;
+
+    #[inline]
+    fn classify(&self, character: char) -> Result<Option<usize>, LocalError> {
+        char_bit(character).map(|bit| Some(usize::from(bit)))
+    }
+}
+
+/// The class of a character, as used by password composition policies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum CharClass {
+    /// A lowercase letter, on bit 0.
+    Lower,
+    /// An uppercase letter, on bit 1.
+    Upper,
+    /// A digit, on bit 2.
+    Digit,
+    /// Anything else, on bit 3.
+    Symbol,
+    /// Whitespace, on bit 4.
+    Space,
+}
+
+impl CharClass {
+    /// Every class, in bit order.
+    pub const ALL: [Self; 5] = [
+        Self::Lower,
+        Self::Upper,
+        Self::Digit,
+        Self::Symbol,
+        Self::Space,
+    ];
+
+    /// The class of `character`.
+    ///
+    /// Letters without case, such as those of CJK scripts, count as lowercase.
+    pub fn of(character: char) -> Self {
+        match character {
+            c if c.is_uppercase() => Self::Upper,
+            c if c.is_alphabetic() => Self::Lower,
+            c if c.is_numeric() => Self::Digit,
+            c if c.is_whitespace() => Self::Space,
+            _ => Self::Symbol,
+        }
+    }
+
+    /// The name of this class, in lowercase.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Lower => "lower",
+            Self::Upper => "upper",
+            Self::Digit => "digit",
+            Self::Symbol => "symbol",
+            Self::Space => "space",
+        }
+    }
+
+    /// The mask with only the bit of this class set.
+    pub fn mask(self) -> Mask {
+        Mask::from_bit(self as usize)
+    }
+}
+
+/// Classifies every character into its [`CharClass`]; no character is an error.
+///
+/// ```
+/// use alphabet_mask_core::classify::{CharClass, CharClasses};
+/// use alphabet_mask_core::mask_string_by;
+///
+/// let mask = mask_string_by("hunter2!", &CharClasses).unwrap();
+/// assert_eq!(mask, CharClass::Lower.mask() | CharClass::Digit.mask() | CharClass::Symbol.mask());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CharClasses;
+
+impl CharClasses {
+    /// The mask of the classes in `string`, which unlike
+    /// [`mask_string_by`](crate::mask_string_by) cannot fail.
+    pub fn mask(string: &str) -> Mask {
+        string.chars().fold(Mask::EMPTY, |mask, character| {
+            mask | CharClass::of(character).mask()
+        })
+    }
+}
+
+impl CharClassifier for CharClasses {
+    type Mask = Mask;
+
+    #[inline]
+    fn classify(&self, character: char) -> Result<Option<usize>, LocalError> {
+        Ok(Some(CharClass::of(character) as usize))
+    }
+}
+
+impl<C: CharClassifier + ?Sized> CharClassifier for &C {
+    type Mask = C::Mask;
+
+    #[inline]
+    fn classify(&self, character: char) -> Result<Option<usize>, LocalError> {
+        (**self).classify(character)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::models::{Mask128, WideMask};
+    use crate::{conftest, find_union_mask, find_union_mask_by, mask_string, mask_string_by};
+
+    /// The standard layout on the low bits, with digits on bits 100 to 109.
+    struct WithDigits;
+
+    impl CharClassifier for WithDigits {
+        type Mask = Mask128;
+
+        fn classify(&self, character: char) -> Result<Option<usize>, LocalError> {
+            match character.to_digit(10) {
+                Some(digit) => Ok(Some(100 + digit as usize)),
+                None => StandardLayout.classify(character),
+            }
+        }
+    }
+
+    #[test]
+    fn standard_layout() {
+        for string in conftest::COLLECTION_OF_50_CHARS_STRINGS {
+            assert_eq!(
+                mask_string_by(string, &StandardLayout).unwrap(),
+                mask_string(string).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn wider_classifier() {
+        let strings = conftest::COLLECTION_OF_50_CHARS_STRINGS;
+        let standard = find_union_mask(strings.iter()).unwrap();
+
+        assert_eq!(
+            find_union_mask_by(strings.iter().chain(["7"].iter()), &WithDigits).unwrap(),
+            WideMask::from(standard) | Mask128::from_bit(107)
+        );
+        assert!(mask_string_by("a!", &WithDigits).is_err());
+    }
+
+    #[test]
+    fn char_classes() {
+        let classes = |string| CharClasses::mask(string).bits();
+
+        assert_eq!(classes(""), 0);
+        assert_eq!(classes("password"), 0b1);
+        assert_eq!(classes("Pa55 w0rd!"), 0b11111);
+        assert_eq!(classes("ÄÖÜ٣"), 0b110);
+        assert_eq!(classes("密码\t"), 0b10001);
+        assert_eq!(
+            mask_string_by("Pa55 w0rd!", &CharClasses).unwrap(),
+            CharClasses::mask("Pa55 w0rd!")
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! A simple versioned binary format for persisting masks, indexes and accumulators.
+//!
+//! Every encoded value starts with a header of two magic bytes identifying its
+//! type, followed by a format version; the rest is specific to the type, with
+//! every integer stored little-endian. Loading checks the header, so a value
+//! written by an incompatible version is rejected instead of misread.
+//!
+use alloc::{format, string::String, vec::Vec};
+
+use crate::models::LocalError;
+
+/// The header identifying one encoded type and version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Format {
+    /// What the encoded value is, as used in error messages.
+    pub kind: &'static str,
+    /// The magic bytes at the start of every encoded value.
+    pub magic: [u8; 2],
+    /// The current version of the encoding.
+    pub version: u8,
+}
+
+impl Format {
+    /// The length of the header in bytes.
+    pub const HEADER_LENGTH: usize = 3;
+
+    /// Append the header to `out`.
+    pub fn write_header(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.magic);
+        out.push(self.version);
+    }
+
+    /// Check the header of `bytes`, returning the bytes that follow it.
+    pub fn read_header<'b>(&self, bytes: &'b [u8]) -> Result<&'b [u8], LocalError> {
+        if bytes.len() < Self::HEADER_LENGTH || bytes[..2] != self.magic {
+            return Err(self.error(format!("unrecognised bytes {bytes:?}")));
+        }
+        if bytes[2] != self.version {
+            return Err(self.error(format!(
+                "unsupported version {}, expected {}",
+                bytes[2], self.version
+            )));
+        }
+
+        Ok(&bytes[Self::HEADER_LENGTH..])
+    }
+
+    /// An error for a value of this format that could not be decoded.
+    pub fn error(&self, context: String) -> LocalError {
+        LocalError::Deserialisation {
+            kind: self.kind,
+            context,
+        }
+    }
+}
+
+/// Reads little-endian integers from the body of an encoded value.
+#[derive(Debug)]
+pub struct Decoder<'b> {
+    format: Format,
+    bytes: &'b [u8],
+}
+
+impl<'b> Decoder<'b> {
+    /// Read `length` raw bytes.
+    pub fn bytes(&mut self, length: usize) -> Result<&'b [u8], LocalError> {
+        if self.bytes.len() < length {
+            return Err(self.format.error(format!(
+                "expected {length} more bytes, found {}",
+                self.bytes.len()
+            )));
+        }
+
+        let (taken, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    /// Read a `u8`.
+    pub fn u8(&mut self) -> Result<u8, LocalError> {
+        self.bytes(1).map(|bytes| bytes[0])
+    }
+
+    /// Read a little-endian `u32`.
+    pub fn u32(&mut self) -> Result<u32, LocalError> {
+        // This is safe because `bytes` returns exactly the length asked for.
+        self.bytes(4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Read a little-endian `u64`.
+    pub fn u64(&mut self) -> Result<u64, LocalError> {
+        // This is safe because `bytes` returns exactly the length asked for.
+        self.bytes(8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Read a `u64` length prefix, checking that it fits in memory.
+    pub fn length(&mut self) -> Result<usize, LocalError> {
+        let length = self.u64()?;
+        usize::try_from(length)
+            .ok()
+            .filter(|&length| length <= self.bytes.len())
+            .ok_or_else(|| self.format.error(format!("length {length} out of bounds")))
+    }
+}
+
+/// A type with a versioned binary encoding.
+pub trait Encode: Sized {
+    /// The header of the encoding.
+    const FORMAT: Format;
+
+    /// Append the body of the encoding, without the header, to `out`.
+    fn encode_body(&self, out: &mut Vec<u8>);
+
+    /// Decode the body of the encoding, without the header.
+    fn decode_body(decoder: &mut Decoder<'_>) -> Result<Self, LocalError>;
+
+    /// Encode this value, header included.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        Self::FORMAT.write_header(&mut out);
+        self.encode_body(&mut out);
+        out
+    }
+
+    /// Decode a value, checking its header and that no bytes are left over.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, LocalError> {
+        let mut decoder = Decoder {
+            format: Self::FORMAT,
+            bytes: Self::FORMAT.read_header(bytes)?,
+        };
+        let value = Self::decode_body(&mut decoder)?;
+
+        if decoder.bytes.is_empty() {
+            Ok(value)
+        } else {
+            Err(Self::FORMAT.error(format!("{} trailing bytes", decoder.bytes.len())))
+        }
+    }
+
+    /// Save this value to a file.
+    ///
+    /// The value is written to a temporary file first then renamed over `path`,
+    /// so a process killed mid-write never leaves a truncated file behind.
+    #[cfg(feature = "std")]
+    fn save(&self, path: &std::path::Path) -> Result<(), LocalError> {
+        let mut temp_path = std::path::PathBuf::from(path);
+        temp_path.as_mut_os_string().push(".tmp");
+
+        std::fs::write(&temp_path, self.to_bytes())
+            .and_then(|_| std::fs::rename(&temp_path, path))
+            .map_err(|source| LocalError::Io {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+
+    /// Load a value saved with [`Encode::save`].
+    #[cfg(feature = "std")]
+    fn load(path: &std::path::Path) -> Result<Self, LocalError> {
+        let bytes = std::fs::read(path).map_err(|source| LocalError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        Self::from_bytes(&bytes)
+    }
+}
@@ -0,0 +1,81 @@
+//! Static test data for unit tests.
+//!
+
+pub const COLLECTION_OF_50_CHARS_STRINGS: [&str; 20] = [
+    "A red pen drew her new dress with elegance.",
+    "He led them west, where trees swayed gently.",
+    "Complex tasks vex experts, yet they excel.",
+    "Velvet drapes framed a hexagonal window.",
+    "Twelve birds flew swiftly over the meadow.",
+    "Beneath the green ferns, a secret dwelled.",
+    "She felt blessed, renewed with energy.",
+    "Hexagonal patterns emerged in the twilight.",
+    "A melody weaved through the evening air.",
+    "Emerald eyes met hers, sparking interest.",
+    "We trekked through the dense, eerie forest.",
+    "In the evening, he brewed herbal tea.",
+    "The chef perfected the recipe with zest.",
+    "A gentle breeze swept through the valley.",
+    "Beneath the tree, a deer rested peacefully.",
+    "Evening descended, revealing celestial beauty.",
+    "Nebulous clouds obscured the moon's glow.",
+    "Serene landscapes emerged in the dusk.",
+    "Elegant swans gracefully swam in the pond.",
+    "Hexagons echoed in her geometric designs.",
+];
+
+/// The characters of the random strings: mostly within the alphabet, with
+/// invalid and control characters now and then, so that some inputs
+/// fail in several chunks at once.
+// Only the tests of some features use it.
+#[allow(dead_code)]
+pub const DIFFERENTIAL_CHARS: [char; 64] = {
+    let mut chars = ['e'; 64];
+    let valid = b"abcdefghijklmnopqrstuvwxyz ABCDEFGHIJKLMNOPQRSTUVWXYZ.,'-\"";
+    let mut index = 0;
+    while index < valid.len() {
+        chars[index] = valid[index] as char;
+        index += 1;
+    }
+    chars[61] = '1';
+    chars[62] = '2';
+    chars[63] = '\0';
+    chars
+};
+
+/// Returns `count` strings of up to 40 characters drawn from `chars`, the same
+/// for the same `seed`, for comparing the serial and parallel paths.
+// Only the tests of some features use it.
+#[allow(dead_code)]
+pub fn random_strings(
+    seed: u64,
+    count: usize,
+    chars: &[char],
+) -> std::vec::Vec<std::string::String> {
+    // xorshift64, which is enough to vary the inputs reproducibly.
+    let mut state = seed | 1;
+    let mut next = |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound as u64) as usize
+    };
+
+    (0..count)
+        .map(|_| (0..next(41)).map(|_| chars[next(chars.len())]).collect())
+        .collect()
+}
+
+/// Returns `count` strings within the alphabet, the same for the same `seed`,
+/// except for an invalid character in one of the first half and a control
+/// character in one of the second: inputs which fail in several chunks or
+/// blocks at once, far apart.
+// Only the tests of some features use it.
+#[allow(dead_code)]
+pub fn strings_failing_twice(seed: u64, count: usize) -> std::vec::Vec<std::string::String> {
+    let mut strings = random_strings(seed, count, &DIFFERENTIAL_CHARS[..61]);
+    let first = seed as usize % (count / 2);
+    strings[first].push('1');
+    strings[count - 1 - first].push('\0');
+    strings
+}
@@ -0,0 +1,318 @@
+//! A compact syntax for conditions on the mask of a string, compiled into
+//! [`MaskPredicate`]s, so that the same condition can be written for Python,
+//! the command line tool and the file APIs.
+//!
+//! An expression combines conditions with `and`, `or`, `not` and parentheses,
+//! with `not` binding tightest and `or` loosest. Each condition is a function of
+//! some characters:
+//!
+//! - `subset(...)`: the string only uses these characters;
+//! - `contains(...)`: the string uses every one of these characters;
+//! - `any(...)`: the string uses at least one of these characters;
+//! - `avoids(...)`: the string uses none of these characters.
+//!
+//! The characters are given as a quoted string, which may contain spaces and
+//! commas, or as bare characters separated by commas, such as `contains(a,e)`.
+//!
+//! ```
+//! use alphabet_mask_core::expression::Expression;
+//! use alphabet_mask_core::mask_string;
+//!
+//! let expression: Expression = "contains(a,e) and not contains(z) and subset(' aeglrst')"
+//!     .parse()
+//!     .unwrap();
+//!
+//! assert!(expression.matches(mask_string("great").unwrap()));
+//! assert!(!expression.matches(mask_string("tree").unwrap()));
+//! ```
+//!
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::iter::Peekable;
+use core::str::{CharIndices, FromStr};
+
+use crate::mask_string;
+use crate::models::{LocalError, Mask, MaskPredicate};
+
+/// A condition on the mask of a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression {
+    /// The mask matches the predicate against the reference mask.
+    Match(MaskPredicate, Mask),
+    /// The mask does not match the expression.
+    Not(Box<Expression>),
+    /// The mask matches every expression; without any, always true.
+    And(Vec<Expression>),
+    /// The mask matches any expression; without any, always false.
+    Or(Vec<Expression>),
+}
+
+impl Expression {
+    /// Returns whether `mask` matches this expression.
+    pub fn matches(&self, mask: Mask) -> bool {
+        match self {
+            Self::Match(predicate, reference) => predicate.matches(mask, *reference),
+            Self::Not(expression) => !expression.matches(mask),
+            Self::And(expressions) => expressions.iter().all(|expr| expr.matches(mask)),
+            Self::Or(expressions) => expressions.iter().any(|expr| expr.matches(mask)),
+        }
+    }
+}
+
+impl FromStr for Expression {
+    type Err = LocalError;
+
+    /// Parse an expression in the syntax of the [module](self).
+    fn from_str(expression: &str) -> Result<Self, LocalError> {
+        let mut parser = Parser {
+            chars: expression.char_indices().peekable(),
+            length: expression.len(),
+        };
+        let parsed = parser.or()?;
+        match parser.peek() {
+            None => Ok(parsed),
+            Some(_) => Err(parser.error("expected `and`, `or` or the end")),
+        }
+    }
+}
+
+/// A recursive descent parser of expressions.
+struct Parser<'e> {
+    chars: Peekable<CharIndices<'e>>,
+    length: usize,
+}
+
+impl Parser<'_> {
+    /// Returns the next character after any whitespace, without consuming it.
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.next_if(|&(_, c)| c.is_whitespace()).is_some() {}
+        self.chars.peek().map(|&(_, c)| c)
+    }
+
+    /// The byte position of the next character.
+    fn position(&mut self) -> usize {
+        self.chars
+            .peek()
+            .map_or(self.length, |&(position, _)| position)
+    }
+
+    fn error(&mut self, expected: &str) -> LocalError {
+        LocalError::Deserialisation {
+            kind: "predicate expression",
+            context: format!("{expected} at position {}", self.position()),
+        }
+    }
+
+    /// Consume `expected`, the next character after any whitespace.
+    fn expect(&mut self, expected: char) -> Result<(), LocalError> {
+        if self.peek() == Some(expected) {
+            self.chars.next();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{expected}`")))
+        }
+    }
+
+    /// Consume the next word after any whitespace, of letters and underscores.
+    fn word(&mut self) -> String {
+        self.peek();
+        let mut word = String::new();
+        while let Some((_, c)) = self
+            .chars
+            .next_if(|&(_, c)| c.is_ascii_alphabetic() || c == '_')
+        {
+            word.push(c);
+        }
+        word
+    }
+
+    /// Consume `keyword` if it is the next word.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        self.peek();
+        let mut ahead = self.chars.clone();
+        let matched = keyword
+            .chars()
+            .all(|k| ahead.next().is_some_and(|(_, c)| c == k))
+            && !ahead
+                .peek()
+                .is_some_and(|&(_, c)| c.is_ascii_alphanumeric() || c == '_');
+        if matched {
+            self.chars = ahead;
+        }
+        matched
+    }
+
+    fn or(&mut self) -> Result<Expression, LocalError> {
+        let mut expressions = Vec::from([self.and()?]);
+        while self.keyword("or") {
+            expressions.push(self.and()?);
+        }
+        Ok(flatten(expressions, Expression::Or))
+    }
+
+    fn and(&mut self) -> Result<Expression, LocalError> {
+        let mut expressions = Vec::from([self.not()?]);
+        while self.keyword("and") {
+            expressions.push(self.not()?);
+        }
+        Ok(flatten(expressions, Expression::And))
+    }
+
+    fn not(&mut self) -> Result<Expression, LocalError> {
+        if self.keyword("not") {
+            Ok(Expression::Not(Box::new(self.not()?)))
+        } else {
+            self.atom()
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expression, LocalError> {
+        if self.peek() == Some('(') {
+            self.chars.next();
+            let expression = self.or()?;
+            self.expect(')')?;
+            return Ok(expression);
+        }
+
+        let position = self.position();
+        let predicate = match self.word().as_str() {
+            "subset" => MaskPredicate::Subset,
+            "contains" => MaskPredicate::Superset,
+            "any" => MaskPredicate::Contains,
+            "avoids" => MaskPredicate::Avoids,
+            _ => {
+                return Err(LocalError::Deserialisation {
+                    kind: "predicate expression",
+                    context: format!(
+                        "expected `subset`, `contains`, `any`, `avoids`, `not` or `(` at \
+                         position {position}"
+                    ),
+                })
+            }
+        };
+        self.expect('(')?;
+        let reference = self.characters()?;
+        self.expect(')')?;
+        Ok(Expression::Match(predicate, reference))
+    }
+
+    /// Consume the characters of a condition, up to its closing parenthesis.
+    fn characters(&mut self) -> Result<Mask, LocalError> {
+        let mut characters = String::new();
+        loop {
+            match self.peek() {
+                Some(')') => break,
+                Some(quote @ ('\'' | '"')) => {
+                    self.chars.next();
+                    loop {
+                        match self.chars.next() {
+                            Some((_, c)) if c == quote => break,
+                            Some((_, c)) => characters.push(c),
+                            None => return Err(self.error(&format!("expected `{quote}`"))),
+                        }
+                    }
+                }
+                Some(_) => {
+                    while let Some((_, c)) = self.chars.next_if(|&(_, c)| {
+                        !c.is_whitespace() && !matches!(c, ',' | '(' | ')' | '\'' | '"')
+                    }) {
+                        characters.push(c);
+                    }
+                }
+                None => return Err(self.error("expected `)`")),
+            }
+            match self.peek() {
+                Some(',') => {
+                    self.chars.next();
+                }
+                Some(')') => break,
+                _ => return Err(self.error("expected `,` or `)`")),
+            }
+        }
+        mask_string(&characters)
+    }
+}
+
+/// Returns the only expression, or all of them combined with `combine`.
+fn flatten(
+    mut expressions: Vec<Expression>,
+    combine: fn(Vec<Expression>) -> Expression,
+) -> Expression {
+    if expressions.len() == 1 {
+        expressions.remove(0)
+    } else {
+        combine(expressions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn matches(expression: &str, string: &str) -> bool {
+        expression
+            .parse::<Expression>()
+            .unwrap()
+            .matches(mask_string(string).unwrap())
+    }
+
+    #[test]
+    fn conditions() {
+        assert!(matches("subset(' aceht')", "the cat"));
+        assert!(!matches("subset(' aceht')", "the dog"));
+        assert!(matches("contains(a, t)", "the cat"));
+        assert!(!matches("contains(a,d)", "the cat"));
+        assert!(matches("any(ad)", "the cat"));
+        assert!(matches("avoids(\"d,\")", "the cat"));
+        assert!(!matches("avoids(\"d,\")", "the cat, the dog"));
+        assert!(matches("contains()", "the cat"));
+    }
+
+    #[test]
+    fn precedence() {
+        assert!(matches("not contains(z) and contains(a)", "cat"));
+        assert!(matches("contains(z) or contains(a) and contains(t)", "cat"));
+        assert!(!matches(
+            "(contains(z) or contains(a)) and contains(d)",
+            "cat"
+        ));
+        assert!(matches("not not contains(c)", "cat"));
+        assert_eq!(
+            "any(a) or any(b) or any(c)".parse::<Expression>().unwrap(),
+            Expression::Or(
+                ["a", "b", "c"]
+                    .map(|c| Expression::Match(MaskPredicate::Contains, mask_string(c).unwrap()))
+                    .into()
+            )
+        );
+    }
+
+    #[test]
+    fn errors() {
+        for (expression, position) in [
+            ("contains(a", 10),
+            ("contains(a) and", 15),
+            ("contains(a) contains(b)", 12),
+            ("has(a)", 0),
+            ("notcontains(z)", 0),
+            ("subset('abc)", 12),
+            ("(any(a)", 7),
+        ] {
+            match expression.parse::<Expression>() {
+                Err(LocalError::Deserialisation { context, .. }) => {
+                    assert!(
+                        context.ends_with(&format!("position {position}")),
+                        "{context}"
+                    )
+                }
+                other => panic!("{expression:?} gave {other:?}"),
+            }
+        }
+        assert!(matches!(
+            "contains(1)".parse::<Expression>(),
+            Err(LocalError::InvalidCharacter { character: '1' })
+        ));
+    }
+}
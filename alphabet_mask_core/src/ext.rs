@@ -0,0 +1,169 @@
+//! Extension traits for composing masks into iterator pipelines.
+//!
+//! With the `parallel` feature, rayon pipelines get the same reductions through
+//! [`ParAlphabetMaskExt`], without going through this crate's own chunking.
+//!
+#[cfg(feature = "parallel")]
+use rayon::iter::ParallelIterator;
+
+use crate::models::{LocalError, Mask};
+use crate::{intersect_masks, mask_string, unite_masks};
+
+/// Adapters for any iterator of strings.
+///
+/// ```
+/// use alphabet_mask_core::ext::AlphabetMaskExt;
+/// use alphabet_mask_core::mask_string;
+///
+/// let words = vec![String::from("tea"), String::from("ate"), String::from("at")];
+/// assert_eq!(words.iter().common_alphabet_mask().unwrap(), mask_string("at").unwrap());
+/// assert_eq!(words.iter().alphabet_mask().unwrap(), mask_string("tea").unwrap());
+/// ```
+pub trait AlphabetMaskExt: Iterator + Sized
+where
+    Self::Item: AsRef<str>,
+{
+    /// Returns the mask of every string combined.
+    fn alphabet_mask(self) -> Result<Mask, LocalError> {
+        unite_masks(self.alphabet_masks())
+    }
+
+    /// Returns the mask of the alphabet common to every string.
+    fn common_alphabet_mask(self) -> Result<Mask, LocalError> {
+        intersect_masks(self.alphabet_masks())
+    }
+
+    /// Returns an iterator over the mask of each string.
+    fn alphabet_masks(self) -> AlphabetMasks<Self> {
+        AlphabetMasks { strings: self }
+    }
+}
+
+impl<I> AlphabetMaskExt for I
+where
+    I: Iterator,
+    I::Item: AsRef<str>,
+{
+}
+
+/// An iterator over the mask of each string of another iterator, returned by
+/// [`AlphabetMaskExt::alphabet_masks`].
+#[derive(Debug, Clone)]
+pub struct AlphabetMasks<I> {
+    strings: I,
+}
+
+impl<I> Iterator for AlphabetMasks<I>
+where
+    I: Iterator,
+    I::Item: AsRef<str>,
+{
+    type Item = Result<Mask, LocalError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.strings
+            .next()
+            .map(|string| mask_string(string.as_ref()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.strings.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for AlphabetMasks<I>
+where
+    I: ExactSizeIterator,
+    I::Item: AsRef<str>,
+{
+}
+
+/// Adapters for any rayon parallel iterator of strings.
+///
+/// ```
+/// use alphabet_mask_core::ext::ParAlphabetMaskExt;
+/// use alphabet_mask_core::mask_string;
+/// use rayon::prelude::*;
+///
+/// let words = ["tea", "ate", "at"];
+/// assert_eq!(words.par_iter().common_alphabet().unwrap(), mask_string("at").unwrap());
+/// assert_eq!(words.par_iter().union_alphabet().unwrap(), mask_string("tea").unwrap());
+/// ```
+#[cfg(feature = "parallel")]
+pub trait ParAlphabetMaskExt: ParallelIterator
+where
+    Self::Item: AsRef<str>,
+{
+    /// Returns the mask of the alphabet common to every string.
+    fn common_alphabet(self) -> Result<Mask, LocalError> {
+        self.map(|string| mask_string(string.as_ref()))
+            .try_reduce(|| Mask::ALL, |a, b| Ok(a & b))
+    }
+
+    /// Returns the mask of every string combined.
+    fn union_alphabet(self) -> Result<Mask, LocalError> {
+        self.map(|string| mask_string(string.as_ref()))
+            .try_reduce(|| Mask::EMPTY, |a, b| Ok(a | b))
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<I> ParAlphabetMaskExt for I
+where
+    I: ParallelIterator,
+    I::Item: AsRef<str>,
+{
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::conftest;
+
+    #[test]
+    fn common_and_union() {
+        let strings = conftest::COLLECTION_OF_50_CHARS_STRINGS;
+
+        assert_eq!(
+            strings.iter().common_alphabet_mask().unwrap(),
+            mask_string(" ert.").unwrap()
+        );
+        assert_eq!(
+            ["ab", "bc", ""].iter().alphabet_mask().unwrap(),
+            mask_string("abc").unwrap()
+        );
+        assert_eq!(
+            core::iter::empty::<&str>().alphabet_mask().unwrap(),
+            Mask::EMPTY
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel() {
+        use rayon::prelude::*;
+
+        let strings = conftest::COLLECTION_OF_50_CHARS_STRINGS;
+
+        assert_eq!(
+            strings.par_iter().common_alphabet().unwrap(),
+            strings.iter().common_alphabet_mask().unwrap()
+        );
+        assert_eq!(
+            strings.par_iter().union_alphabet().unwrap(),
+            strings.iter().alphabet_mask().unwrap()
+        );
+        assert!(["a", "1"].par_iter().union_alphabet().is_err());
+    }
+
+    #[test]
+    fn masks() {
+        let mut masks = ["a", "b1", "c"].into_iter().alphabet_masks();
+
+        assert_eq!(masks.len(), 3);
+        assert_eq!(masks.next().unwrap().unwrap(), Mask(0b10));
+        assert!(masks.next().unwrap().is_err());
+        assert_eq!(masks.next().unwrap().unwrap(), Mask(0b1000));
+        assert!(masks.next().is_none());
+    }
+}
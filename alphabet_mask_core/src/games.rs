@@ -0,0 +1,367 @@
+//! Word game solvers built on masks.
+//!
+//! Masks answer which letters a word contains, which is most of what a word game
+//! constraint asks; the remainder, such as the position of a letter, is checked
+//! per word only once the masks match:
+//!
+//! ```
+//! use alphabet_mask_core::games::{filter_wordle, WordleConstraints};
+//!
+//! // `c` is at position 0, `a` is in the word but not at position 2, and there
+//! // is no `t` or `s`.
+//! let constraints = WordleConstraints::new([(0, 'c')], [(2, 'a')], "ts").unwrap();
+//!
+//! let words = ["crane", "cacao", "coast", "cigar", "apple"];
+//! assert_eq!(filter_wordle(&words, &constraints).unwrap(), ["cacao", "cigar"]);
+//! ```
+//!
+//! Where the number of copies of a letter matters, a [`Signature`] counts them:
+//!
+//! ```
+//! use alphabet_mask_core::games::{playable_from_rack, Signature};
+//!
+//! let rack = Signature::new("retains").unwrap();
+//! let words = ["stainer", "tennis", "satin", "street"];
+//! assert_eq!(playable_from_rack(&words, &rack, 0).unwrap(), ["stainer", "satin"]);
+//! assert_eq!(playable_from_rack(&words, &rack, 1).unwrap(), ["stainer", "tennis", "satin"]);
+//! ```
+//!
+//! Words with the same signature are anagrams of each other:
+//!
+//! ```
+//! use alphabet_mask_core::games::group_anagrams;
+//!
+//! let words = ["listen", "google", "Silent", "enlist"];
+//! assert_eq!(
+//!     group_anagrams(&words).unwrap(),
+//!     [vec!["listen", "Silent", "enlist"], vec!["google"]]
+//! );
+//! ```
+//!
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "parallel"))]
+use alloc::collections::BTreeMap;
+
+#[cfg(feature = "parallel")]
+use dashmap::DashMap;
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+use crate::classify::{CharClassifier, StandardLayout};
+use crate::mask_string;
+use crate::models::{char_bit, BitMask, LocalError, Mask};
+
+/// The words of `words` satisfying `predicate`, in their original order, checked
+/// in parallel with the `parallel` feature.
+fn filter_words<'w, S, P>(words: &'w [S], predicate: P) -> Result<Vec<&'w str>, LocalError>
+where
+    S: AsRef<str> + Sync,
+    P: Fn(&str) -> Result<bool, LocalError> + Sync,
+{
+    let keep = |word: &'w S| match predicate(word.as_ref()) {
+        Ok(true) => Some(Ok(word.as_ref())),
+        Ok(false) => None,
+        Err(error) => Some(Err(error)),
+    };
+
+    #[cfg(feature = "parallel")]
+    return words.par_iter().filter_map(keep).collect();
+    #[cfg(not(feature = "parallel"))]
+    return words.iter().filter_map(keep).collect();
+}
+
+/// The mask of a single letter of a constraint, which must be in the alphabet.
+fn letter_mask(letter: char) -> Result<Mask, LocalError> {
+    char_bit(letter).map(|bit| Mask::from_bit(usize::from(bit)))
+}
+
+/// The feedback of a game of Wordle so far.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordleConstraints {
+    /// The letters known at each position.
+    greens: Vec<(usize, char)>,
+    /// The letters in the word, but known not to be at each position.
+    yellows: Vec<(usize, char)>,
+    /// Every letter the word must contain.
+    include: Mask,
+    /// Every letter the word must not contain.
+    exclude: Mask,
+}
+
+impl WordleConstraints {
+    /// Combine the green letters and their positions, the yellow letters and the
+    /// positions they are not at, and the gray letters.
+    ///
+    /// Positions count characters from 0. A gray letter that is also green or
+    /// yellow only rules out further copies of it, so it excludes nothing here.
+    /// Fails if any letter is outside the alphabet.
+    pub fn new(
+        greens: impl IntoIterator<Item = (usize, char)>,
+        yellows: impl IntoIterator<Item = (usize, char)>,
+        grays: &str,
+    ) -> Result<Self, LocalError> {
+        let mut constraints = Self {
+            greens: Vec::new(),
+            yellows: Vec::new(),
+            include: Mask::EMPTY,
+            exclude: Mask::EMPTY,
+        };
+        for (letters, positioned) in [
+            (
+                &mut constraints.greens,
+                greens.into_iter().collect::<Vec<_>>(),
+            ),
+            (&mut constraints.yellows, yellows.into_iter().collect()),
+        ] {
+            for (position, letter) in positioned {
+                let letter = letter.to_ascii_lowercase();
+                constraints.include |= letter_mask(letter)?;
+                letters.push((position, letter));
+            }
+        }
+        constraints.exclude = mask_string(grays)? & !constraints.include;
+
+        Ok(constraints)
+    }
+
+    /// Whether `word` satisfies every constraint.
+    ///
+    /// Fails if `word` contains a character outside the alphabet.
+    pub fn matches(&self, word: &str) -> Result<bool, LocalError> {
+        let mask = mask_string(word)?;
+        if !self.include.is_subset_of(mask) || !(mask & self.exclude).is_empty() {
+            return Ok(false);
+        }
+
+        let letter_at = |position| word.chars().nth(position).map(|c| c.to_ascii_lowercase());
+        Ok(self
+            .greens
+            .iter()
+            .all(|&(position, letter)| letter_at(position) == Some(letter))
+            && self
+                .yellows
+                .iter()
+                .all(|&(position, letter)| letter_at(position) != Some(letter)))
+    }
+}
+
+/// The words satisfying `constraints`, in their original order.
+///
+/// Fails if any word contains a character outside the alphabet.
+pub fn filter_wordle<'w, S: AsRef<str> + Sync>(
+    words: &'w [S],
+    constraints: &WordleConstraints,
+) -> Result<Vec<&'w str>, LocalError> {
+    filter_words(words, |word| constraints.matches(word))
+}
+
+/// The number of times each character of the alphabet occurs in a string, which
+/// is the same for every anagram of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Signature {
+    /// The count of each bit of the mask of the string, saturating at the maximum.
+    counts: [u16; Mask::WIDTH as usize],
+}
+
+impl Signature {
+    /// Count the characters of `string`.
+    ///
+    /// Letters are counted regardless of case, and any character outside the
+    /// alphabet gives a [`LocalError::InvalidCharacter`].
+    pub fn new(string: &str) -> Result<Self, LocalError> {
+        let mut counts = [0_u16; Mask::WIDTH as usize];
+        for character in string.chars() {
+            if let Some(bit) = StandardLayout.classify(character)? {
+                counts[bit] = counts[bit].saturating_add(1);
+            }
+        }
+
+        Ok(Self { counts })
+    }
+
+    /// The number of times each bit of the mask occurs, in bit order.
+    pub fn counts(&self) -> &[u16; Mask::WIDTH as usize] {
+        &self.counts
+    }
+
+    /// The mask of the characters counted at least once.
+    pub fn mask(&self) -> Mask {
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .fold(Mask::EMPTY, |mask, (bit, _)| mask | Mask::from_bit(bit))
+    }
+
+    /// The number of characters of this signature missing from `available`.
+    pub fn shortfall(&self, available: &Self) -> u32 {
+        self.counts
+            .iter()
+            .zip(&available.counts)
+            .map(|(&needed, &available)| u32::from(needed.saturating_sub(available)))
+            .sum()
+    }
+}
+
+/// The words that can be spelt from the tiles of `rack`, plus `blanks` tiles
+/// standing in for any character, in their original order.
+///
+/// Unless there are blanks, words using a character missing from the rack are
+/// ruled out by their masks before their characters are counted. Fails if any
+/// word contains a character outside the alphabet.
+pub fn playable_from_rack<'w, S: AsRef<str> + Sync>(
+    words: &'w [S],
+    rack: &Signature,
+    blanks: u32,
+) -> Result<Vec<&'w str>, LocalError> {
+    let available = rack.mask();
+
+    filter_words(words, |word| {
+        if blanks == 0 && !mask_string(word)?.is_subset_of(available) {
+            return Ok(false);
+        }
+        Ok(Signature::new(word)?.shortfall(rack) <= blanks)
+    })
+}
+
+/// The words of `words` grouped by their [`Signature`], so that each group holds
+/// every anagram of its first word.
+///
+/// Groups are in the order of their first word, and words in each group in
+/// their original order; words without any anagram are groups of their own.
+/// With the `parallel` feature, the words are grouped in parallel into a
+/// concurrent map. Fails if any word contains a character outside the alphabet.
+pub fn group_anagrams<S: AsRef<str> + Sync>(words: &[S]) -> Result<Vec<Vec<&str>>, LocalError> {
+    #[cfg(feature = "parallel")]
+    let mut groups: Vec<Vec<usize>> = {
+        let groups = DashMap::<Signature, Vec<usize>>::new();
+        words
+            .par_iter()
+            .enumerate()
+            .try_for_each(|(position, word)| {
+                let signature = Signature::new(word.as_ref())?;
+                groups.entry(signature).or_default().push(position);
+                Ok::<_, LocalError>(())
+            })?;
+
+        groups
+            .into_iter()
+            .map(|(_, mut positions)| {
+                positions.sort_unstable();
+                positions
+            })
+            .collect()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let mut groups: Vec<Vec<usize>> = {
+        let mut groups = BTreeMap::<Signature, Vec<usize>>::new();
+        for (position, word) in words.iter().enumerate() {
+            let signature = Signature::new(word.as_ref())?;
+            groups.entry(signature).or_default().push(position);
+        }
+
+        groups.into_values().collect()
+    };
+
+    groups.sort_unstable_by_key(|positions| positions[0]);
+    Ok(groups
+        .into_iter()
+        .map(|positions| {
+            positions
+                .into_iter()
+                .map(|position| words[position].as_ref())
+                .collect()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+
+    const WORDS: [&str; 8] = [
+        "crane", "cacao", "coast", "cigar", "apple", "Allee", "eerie", "sheep",
+    ];
+
+    #[test]
+    fn wordle() {
+        let filter = |greens: &[(usize, char)], yellows: &[(usize, char)], grays| {
+            let constraints =
+                WordleConstraints::new(greens.iter().copied(), yellows.iter().copied(), grays)
+                    .unwrap();
+            filter_wordle(&WORDS, &constraints).unwrap()
+        };
+
+        assert_eq!(filter(&[], &[], ""), WORDS);
+        assert_eq!(filter(&[(4, 'E')], &[], "c"), ["apple", "Allee", "eerie"]);
+        assert_eq!(filter(&[], &[(0, 'e')], "r"), ["apple", "Allee", "sheep"]);
+        // A gray `e` next to a green one only rules out further copies of it.
+        assert_eq!(
+            filter(&[(4, 'e')], &[], "e"),
+            ["crane", "apple", "Allee", "eerie"]
+        );
+        assert_eq!(filter(&[(5, 'e')], &[], ""), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(WordleConstraints::new([(0, '1')], [], "").is_err());
+        assert!(WordleConstraints::new([], [], "!").is_err());
+
+        let constraints = WordleConstraints::new([], [], "").unwrap();
+        assert!(matches!(
+            filter_wordle(&["ok", "n0"], &constraints),
+            Err(LocalError::InvalidCharacter { character: '0' })
+        ));
+        assert!(playable_from_rack(&["n0"], &Signature::new("n").unwrap(), 1).is_err());
+        assert!(group_anagrams(&["on", "n0"]).is_err());
+    }
+
+    #[test]
+    fn signature() {
+        let signature = Signature::new("Sheep").unwrap();
+
+        assert_eq!(signature, Signature::new("hepes").unwrap());
+        assert_ne!(signature, Signature::new("shep").unwrap());
+        assert_eq!(signature.counts()[5], 2);
+        assert_eq!(signature.mask(), mask_string("shep").unwrap());
+        assert_eq!(signature.shortfall(&Signature::new("ships").unwrap()), 2);
+        assert_eq!(Signature::new("").unwrap().mask(), Mask::EMPTY);
+    }
+
+    #[test]
+    fn rack() {
+        let rack = Signature::new("aabst").unwrap();
+        let words = ["bat", "abbas", "tabs", "stab", "sabbat", "zz", ""];
+
+        assert_eq!(
+            playable_from_rack(&words, &rack, 0).unwrap(),
+            ["bat", "tabs", "stab", ""]
+        );
+        assert_eq!(
+            playable_from_rack(&words, &rack, 1).unwrap(),
+            ["bat", "abbas", "tabs", "stab", "sabbat", ""]
+        );
+        assert_eq!(playable_from_rack(&words, &rack, 2).unwrap(), words);
+    }
+
+    #[test]
+    fn anagrams() {
+        let words = [
+            "stop", "pots", "Tops", "spot", "post", "stops", "opts", "", "top",
+        ];
+
+        assert_eq!(
+            group_anagrams(&words).unwrap(),
+            [
+                vec!["stop", "pots", "Tops", "spot", "post", "opts"],
+                vec!["stops"],
+                vec![""],
+                vec!["top"],
+            ]
+        );
+        assert!(group_anagrams::<&str>(&[]).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,334 @@
+//! An index of word masks, for answering many queries against one word list.
+//!
+//! Every word is masked once, and the masks are bucketed by the number of distinct
+//! characters they contain, so that each query only scans the buckets that can
+//! possibly match:
+//!
+//! ```
+//! use alphabet_mask_core::index::WordIndex;
+//! use alphabet_mask_core::mask_string;
+//!
+//! let index = WordIndex::new(["tea", "eat", "treat", "rat", "a"]).unwrap();
+//!
+//! let rack = mask_string("aet").unwrap();
+//! assert_eq!(index.subsets_of(rack).collect::<Vec<_>>(), ["a", "tea", "eat"]);
+//! assert_eq!(index.supersets_of(rack).collect::<Vec<_>>(), ["tea", "eat", "treat"]);
+//! ```
+//!
+//! An index is saved and loaded through [`Encode`], so that a short-lived process
+//! can skip masking the words again; with the `mmap` feature,
+//! [`WordIndex::load_mapped`] decodes a large index straight from a memory map.
+//!
+use alloc::{format, string::String, vec::Vec};
+use core::ops::Range;
+
+use crate::codec::{Decoder, Encode, Format};
+use crate::mask_string;
+use crate::models::{LocalError, Mask};
+
+/// The number of buckets, one for each possible number of distinct characters.
+const BUCKETS: usize = Mask::WIDTH as usize + 1;
+
+/// Words with their masks, bucketed by the number of distinct characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordIndex {
+    /// Every word, sorted by the number of distinct characters, then by position
+    /// in the original list.
+    words: Vec<String>,
+    /// The mask of each word of `words`.
+    masks: Vec<Mask>,
+    /// The start of each bucket in `words`, with the end of the last one at the end.
+    starts: [usize; BUCKETS + 1],
+}
+
+impl Default for WordIndex {
+    fn default() -> Self {
+        Self::from_sorted(Vec::new(), Vec::new())
+    }
+}
+
+impl WordIndex {
+    /// Mask every word and index it.
+    ///
+    /// Fails if any word contains a character outside the alphabet.
+    pub fn new<S: Into<String>>(words: impl IntoIterator<Item = S>) -> Result<Self, LocalError> {
+        let mut entries = words
+            .into_iter()
+            .map(|word| {
+                let word = word.into();
+                mask_string(&word).map(|mask| (mask, word))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|(mask, _)| mask.len());
+
+        let (masks, words) = entries.into_iter().unzip();
+        Ok(Self::from_sorted(words, masks))
+    }
+
+    /// Index words that are already sorted by the number of distinct characters
+    /// of their masks.
+    pub(crate) fn from_sorted(words: Vec<String>, masks: Vec<Mask>) -> Self {
+        let mut starts = [masks.len(); BUCKETS + 1];
+        for length in (0..BUCKETS).rev() {
+            starts[length] = masks.partition_point(|mask| (mask.len() as usize) < length);
+        }
+
+        Self {
+            words,
+            masks,
+            starts,
+        }
+    }
+
+    /// The number of words.
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Whether there are no words.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Every word with its mask, in index order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Mask)> {
+        self.words
+            .iter()
+            .map(String::as_str)
+            .zip(self.masks.iter().copied())
+    }
+
+    /// The positions of the words with between `lengths.start` and `lengths.end`
+    /// distinct characters, exclusive.
+    fn span(&self, lengths: Range<u32>) -> Range<usize> {
+        let bucket = |length: u32| (length as usize).min(BUCKETS);
+        self.starts[bucket(lengths.start)]..self.starts[bucket(lengths.end)]
+    }
+
+    /// The words within the positions `span` whose masks satisfy `predicate`.
+    fn matching(
+        &self,
+        span: Range<usize>,
+        predicate: impl Fn(Mask) -> bool,
+    ) -> impl Iterator<Item = &str> {
+        self.words[span.clone()]
+            .iter()
+            .zip(&self.masks[span])
+            .filter(move |(_, &mask)| predicate(mask))
+            .map(|(word, _)| word.as_str())
+    }
+
+    /// The words using only characters of `mask`.
+    pub fn subsets_of(&self, mask: Mask) -> impl Iterator<Item = &str> {
+        self.matching(self.span(0..mask.len() + 1), move |word| {
+            word.is_subset_of(mask)
+        })
+    }
+
+    /// The words using every character of `mask`.
+    pub fn supersets_of(&self, mask: Mask) -> impl Iterator<Item = &str> {
+        self.matching(self.span(mask.len()..BUCKETS as u32), move |word| {
+            mask.is_subset_of(word)
+        })
+    }
+
+    /// The words with exactly the characters of `mask`.
+    pub fn equal_to(&self, mask: Mask) -> impl Iterator<Item = &str> {
+        self.matching(self.span(mask.len()..mask.len() + 1), move |word| {
+            word == mask
+        })
+    }
+
+    /// The `k` words most similar to `mask` by the Jaccard index of their masks,
+    /// with their similarity, most similar first; ties keep index order.
+    ///
+    /// Words sharing no character with `mask` are never returned.
+    pub fn most_similar(&self, mask: Mask, k: usize) -> Vec<(&str, f64)> {
+        let mut best: Vec<(&str, f64)> = Vec::with_capacity(k + 1);
+
+        // Scan the buckets outwards from the length of `mask`, since a word of
+        // length `n` is at most `min(n, m) / max(n, m)` similar to one of length `m`.
+        let target = mask.len();
+        let mut order: Vec<u32> = (0..BUCKETS as u32).collect();
+        order.sort_by_key(|&length| (length.abs_diff(target), length));
+
+        for length in order {
+            let bound = f64::from(length.min(target)) / f64::from(length.max(target).max(1));
+            if best.len() == k && best.last().is_some_and(|&(_, worst)| worst >= bound) {
+                continue;
+            }
+
+            let span = self.span(length..length + 1);
+            for (word, &other) in self.words[span.clone()].iter().zip(&self.masks[span]) {
+                let shared = (mask & other).len();
+                if shared == 0 {
+                    continue;
+                }
+                let score = f64::from(shared) / f64::from((mask | other).len());
+                let position = best.partition_point(|&(_, better)| better >= score);
+                if position < k {
+                    best.insert(position, (word, score));
+                    best.truncate(k);
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Load an index saved with [`Encode::save`], decoding it from a memory map of
+    /// the file rather than reading the whole file onto the heap first.
+    #[cfg(feature = "mmap")]
+    pub fn load_mapped(path: &std::path::Path) -> Result<Self, LocalError> {
+        let io_error = |source| LocalError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let file = std::fs::File::open(path).map_err(io_error)?;
+        // This is safe as long as the file is not modified while it is mapped; the
+        // map only lives until the index is decoded, and `save` never writes to
+        // an existing file in place.
+        let map = unsafe { memmap2::Mmap::map(&file) }.map_err(io_error)?;
+
+        Self::from_bytes(&map)
+    }
+}
+
+/// The encoding is the number of words as a little-endian `u64`, then each word
+/// in index order as its mask as a `u32`, its length in bytes as a `u64` and its
+/// UTF-8 bytes.
+impl Encode for WordIndex {
+    const FORMAT: Format = Format {
+        kind: "word index",
+        magic: *b"AW",
+        version: 1,
+    };
+
+    fn encode_body(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&(self.len() as u64).to_le_bytes());
+        for (word, mask) in self.iter() {
+            out.extend_from_slice(&mask.bits().to_le_bytes());
+            out.extend_from_slice(&(word.len() as u64).to_le_bytes());
+            out.extend_from_slice(word.as_bytes());
+        }
+    }
+
+    fn decode_body(decoder: &mut Decoder<'_>) -> Result<Self, LocalError> {
+        let count = decoder.length()?;
+        let mut words = Vec::with_capacity(count);
+        let mut masks = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let mask = Mask(decoder.u32()?);
+            let length = decoder.length()?;
+            let word = core::str::from_utf8(decoder.bytes(length)?)
+                .map_err(|error| Self::FORMAT.error(format!("invalid word: {error}")))?;
+
+            if masks
+                .last()
+                .is_some_and(|last: &Mask| last.len() > mask.len())
+            {
+                return Err(Self::FORMAT.error(format!("word {word:?} out of order")));
+            }
+            masks.push(mask);
+            words.push(word.into());
+        }
+
+        Ok(Self::from_sorted(words, masks))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+
+    fn index() -> WordIndex {
+        WordIndex::new(["tea", "eat", "treat", "rat", "a", "", "zebra", "tee"]).unwrap()
+    }
+
+    #[test]
+    fn subsets_and_supersets() {
+        let index = index();
+        let mask = |string| mask_string(string).unwrap();
+
+        assert_eq!(index.len(), 8);
+        assert_eq!(
+            index.subsets_of(mask("aet")).collect::<Vec<_>>(),
+            ["", "a", "tee", "tea", "eat"]
+        );
+        assert_eq!(
+            index.supersets_of(mask("ra")).collect::<Vec<_>>(),
+            ["rat", "treat", "zebra"]
+        );
+        assert_eq!(
+            index.equal_to(mask("ate")).collect::<Vec<_>>(),
+            ["tea", "eat"]
+        );
+        assert_eq!(index.supersets_of(Mask::ALL).count(), 0);
+        assert_eq!(index.subsets_of(Mask::ALL).count(), 8);
+    }
+
+    #[test]
+    fn most_similar() {
+        let index = index();
+
+        assert_eq!(
+            index.most_similar(mask_string("tear").unwrap(), 3),
+            vec![("treat", 1.0), ("tea", 0.75), ("eat", 0.75)]
+        );
+        assert_eq!(index.most_similar(mask_string("q").unwrap(), 3), vec![]);
+        assert_eq!(index.most_similar(Mask::EMPTY, 3), vec![]);
+    }
+
+    #[test]
+    fn invalid_word() {
+        assert!(matches!(
+            WordIndex::new(["ok", "n0"]),
+            Err(LocalError::InvalidCharacter { character: '0' })
+        ));
+    }
+
+    #[test]
+    fn round_trip() {
+        let index = index();
+        let bytes = index.to_bytes();
+
+        assert_eq!(&bytes[..3], b"AW\x01");
+        assert_eq!(WordIndex::from_bytes(&bytes).unwrap(), index);
+        assert_eq!(
+            WordIndex::from_bytes(&WordIndex::default().to_bytes()).unwrap(),
+            WordIndex::default()
+        );
+    }
+
+    #[test]
+    fn corrupt_bytes() {
+        let bytes = WordIndex::new(["ab", "c"]).unwrap().to_bytes();
+        // The mask of "c", the first word, has its low byte at offset 11.
+        let mut unordered = bytes.clone();
+        unordered[11] = 0xff;
+        let mut invalid = bytes.clone();
+        *invalid.last_mut().unwrap() = 0xff;
+
+        for corrupt in [&bytes[..bytes.len() - 1], &unordered, &invalid] {
+            assert!(matches!(
+                WordIndex::from_bytes(corrupt),
+                Err(LocalError::Deserialisation { .. })
+            ));
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn save_and_load_mapped() {
+        let path = std::env::temp_dir().join("alphabet_mask_word_index");
+        let index = index();
+
+        index.save(&path).unwrap();
+        let loaded = WordIndex::load_mapped(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), index);
+    }
+}
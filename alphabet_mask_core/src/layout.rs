@@ -0,0 +1,320 @@
+//! Built-in layouts wider than the standard one.
+//!
+//! Each layout extends the standard layout without moving any of its 32 bits, so
+//! that the low bits of a wide mask of a string within the standard alphabet are
+//! its standard mask, and a character outside both fails the same way.
+//! [`ALPHANUMERIC`] adds the digits `0` to `9` on bits 32 to 41 of a [`Mask64`],
+//! and [`EXTENDED`] also adds every other ASCII punctuation mark, such as `;`,
+//! `?` and `(`, from bit 42 of a [`Mask128`]. [`CASE_SENSITIVE`] instead keeps
+//! the standard bits for lowercase letters and adds `A` to `Z` on bits 32 to 57 of
+//! a [`Mask64`]:
+//!
+//! ```
+//! use alphabet_mask_core::layout::{ALPHANUMERIC, CASE_SENSITIVE, EXTENDED};
+//! use alphabet_mask_core::models::BitMask;
+//!
+//! let mask = ALPHANUMERIC.mask_string("SKU 42-b").unwrap();
+//! assert!(mask.contains_bit(ALPHANUMERIC.bit('4').unwrap()));
+//! assert_eq!(ALPHANUMERIC.chars(mask).collect::<String>(), " bksu-24");
+//!
+//! let common = ALPHANUMERIC.find_common_mask(&["AB12", "b2"], 1024).unwrap();
+//! assert_eq!(ALPHANUMERIC.chars(common).collect::<String>(), "b2");
+//!
+//! let mask = EXTENDED.mask_string("Why? (Because.)").unwrap();
+//! assert_eq!(EXTENDED.chars(mask).collect::<String>(), " abcehsuwy.()?");
+//!
+//! let mask = CASE_SENSITIVE.mask_string("Hello World").unwrap();
+//! assert_eq!(CASE_SENSITIVE.chars(mask).collect::<String>(), " delorHW");
+//! ```
+//!
+use crate::classify::CharClassifier;
+use crate::models::bits::{
+    APOSTROPHE_BIT, COMMA_BIT, DOUBLE_QUOTE_BIT, FULL_STOP_BIT, HYPHEN_BIT, LETTER_A_BIT, SPACE_BIT,
+};
+use crate::models::{BitMask, CasePolicy, LocalError, Mask128, Mask64};
+use crate::table::ClassTable;
+use crate::{find_common_mask_dispatch_by, find_union_mask_by, mask_string_by};
+
+/// Every symbol of the standard layout with its bit, in bit order.
+const STANDARD_SYMBOLS: [(char, u8); 32] = {
+    let mut symbols = [(' ', SPACE_BIT); 32];
+    let mut letter = 0;
+    while letter < 26 {
+        symbols[letter + 1] = ((b'a' + letter as u8) as char, LETTER_A_BIT + letter as u8);
+        letter += 1;
+    }
+    symbols[27] = ('.', FULL_STOP_BIT);
+    symbols[28] = (',', COMMA_BIT);
+    symbols[29] = ('\'', APOSTROPHE_BIT);
+    symbols[30] = ('-', HYPHEN_BIT);
+    symbols[31] = ('"', DOUBLE_QUOTE_BIT);
+    symbols
+};
+
+/// Returns the symbols of the standard layout followed by `extra`, which must
+/// be `N - 32` symbols long.
+const fn extend_standard<const N: usize>(extra: &[(char, u8)]) -> [(char, u8); N] {
+    let mut symbols = [(' ', 0); N];
+    let mut index = 0;
+    while index < N {
+        symbols[index] = if index < STANDARD_SYMBOLS.len() {
+            STANDARD_SYMBOLS[index]
+        } else {
+            extra[index - STANDARD_SYMBOLS.len()]
+        };
+        index += 1;
+    }
+    symbols
+}
+
+/// Returns the digits `0` to `9` on the 10 bits from `first`.
+const fn digits(first: u8) -> [(char, u8); 10] {
+    let mut digits = [('0', first); 10];
+    let mut digit = 0;
+    while digit < 10 {
+        digits[digit as usize] = ((b'0' + digit) as char, first + digit);
+        digit += 1;
+    }
+    digits
+}
+
+/// The symbols of [`ALPHANUMERIC`].
+const ALPHANUMERIC_SYMBOLS: [(char, u8); 42] = extend_standard(&digits(32));
+
+/// The standard layout with the digits `0` to `9` on bits 32 to 41.
+pub static ALPHANUMERIC: Layout<Mask64> =
+    Layout::new(&ALPHANUMERIC_SYMBOLS, CasePolicy::Insensitive);
+
+/// The ASCII punctuation marks outside the standard layout, in code point order.
+const PUNCTUATION: &[u8; 27] = b"!#$%&()*+/:;<=>?@[\\]^_`{|}~";
+
+/// The symbols of [`EXTENDED`].
+const EXTENDED_SYMBOLS: [(char, u8); 69] = {
+    let mut extra = [(' ', 0); 37];
+    let digits = digits(32);
+    let mut index = 0;
+    while index < extra.len() {
+        extra[index] = if index < digits.len() {
+            digits[index]
+        } else {
+            let mark = index - digits.len();
+            (PUNCTUATION[mark] as char, 42 + mark as u8)
+        };
+        index += 1;
+    }
+    extend_standard(&extra)
+};
+
+/// The standard layout with the digits `0` to `9` on bits 32 to 41, as for
+/// [`ALPHANUMERIC`], and every other ASCII punctuation mark on bits 42 to 68.
+pub static EXTENDED: Layout<Mask128> = Layout::new(&EXTENDED_SYMBOLS, CasePolicy::Insensitive);
+
+/// The symbols of [`CASE_SENSITIVE`].
+const CASE_SENSITIVE_SYMBOLS: [(char, u8); 58] = {
+    let mut uppercase = [('A', 32); 26];
+    let mut letter = 0;
+    while letter < 26 {
+        uppercase[letter] = ((b'A' + letter as u8) as char, 32 + letter as u8);
+        letter += 1;
+    }
+    extend_standard(&uppercase)
+};
+
+/// The standard layout with the letters told apart by case: `a` to `z` on the
+/// standard bits 1 to 26, and `A` to `Z` on bits 32 to 57.
+pub static CASE_SENSITIVE: Layout<Mask64> =
+    Layout::new(&CASE_SENSITIVE_SYMBOLS, CasePolicy::Sensitive);
+
+/// A layout of symbols from U+0000 to U+00FF, with masks of type `M`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout<M: 'static> {
+    symbols: &'static [(char, u8)],
+    table: ClassTable<M>,
+}
+
+impl<M: BitMask> Layout<M> {
+    /// Build a layout from every symbol with its bit, with the first symbol of
+    /// each bit representing it.
+    ///
+    /// # Panics
+    ///
+    /// As for [`ClassTable::new`].
+    pub const fn new(symbols: &'static [(char, u8)], case: CasePolicy) -> Self {
+        Self {
+            symbols,
+            table: ClassTable::new(symbols, case),
+        }
+    }
+
+    /// Every symbol with its bit.
+    pub fn symbols(&self) -> &'static [(char, u8)] {
+        self.symbols
+    }
+
+    /// Returns the bit of `character`, or [`None`] if it is not in the layout.
+    pub fn bit(&self, character: char) -> Option<usize> {
+        self.table.bit(character).map(usize::from)
+    }
+
+    /// Returns the character representing `bit`, or [`None`] if no symbol sets it.
+    pub fn symbol(&self, bit: usize) -> Option<char> {
+        self.symbols
+            .iter()
+            .find(|&&(_, symbol_bit)| usize::from(symbol_bit) == bit)
+            .map(|&(symbol, _)| symbol)
+    }
+
+    /// The mask of every bit set by a symbol.
+    pub fn defined(&self) -> M {
+        self.symbols
+            .iter()
+            .fold(M::EMPTY, |mask, &(_, bit)| mask | M::from_bit(bit.into()))
+    }
+
+    /// Mask a string; any character outside the layout is an error, as for
+    /// [`mask_string`](crate::mask_string).
+    pub fn mask_string(&self, string: &str) -> Result<M, LocalError> {
+        mask_string_by(string, &self.table)
+    }
+
+    /// Returns the characters of `mask` in bit order, ignoring bits without a
+    /// symbol.
+    pub fn chars(&self, mask: M) -> impl Iterator<Item = char> + '_ {
+        mask.bits_set().filter_map(|bit| self.symbol(bit))
+    }
+
+    /// Returns the common mask of `strings`, in parallel chunks of
+    /// `length_limit` bytes if they are longer in total, as for
+    /// [`find_common_mask_dispatch`](crate::find_common_mask_dispatch).
+    pub fn find_common_mask<S: AsRef<str> + Sync>(
+        &self,
+        strings: &[S],
+        length_limit: usize,
+    ) -> Result<M, LocalError>
+    where
+        M: Send + Sync,
+    {
+        find_common_mask_dispatch_by(strings, length_limit, &self.table)
+    }
+
+    /// Returns the combined mask of `strings`.
+    pub fn find_union_mask<S: AsRef<str>>(
+        &self,
+        strings: impl Iterator<Item = S>,
+    ) -> Result<M, LocalError> {
+        find_union_mask_by(strings, &self.table)
+    }
+}
+
+impl<M: BitMask> CharClassifier for Layout<M> {
+    type Mask = M;
+
+    #[inline]
+    fn classify(&self, character: char) -> Result<Option<usize>, LocalError> {
+        self.table.classify(character)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::models::{Mask, WideMask};
+    use crate::{conftest, mask_string};
+    use alloc::string::String;
+
+    /// Checks every printable ASCII character against the standard layout.
+    fn check_printable_ascii<const WORDS: usize>(layout: &Layout<WideMask<WORDS>>) {
+        use std::format;
+
+        let mut buffer = [0; 4];
+        for byte in b' '..=b'~' {
+            let string = char::from(byte).encode_utf8(&mut buffer);
+
+            match (mask_string(string), layout.mask_string(string)) {
+                (Ok(mask), Ok(wide)) => assert_eq!(mask, Mask(wide.0[0] as u32), "{string:?}"),
+                (Ok(_), Err(err)) => panic!("{string:?} fails with {err:?}"),
+                (Err(_), Ok(_)) => {}
+                (Err(err), Err(wide_err)) => {
+                    assert_eq!(format!("{wide_err:?}"), format!("{err:?}"), "{string:?}")
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn extends_standard() {
+        for string in conftest::COLLECTION_OF_50_CHARS_STRINGS {
+            assert_eq!(
+                ALPHANUMERIC.mask_string(string).unwrap(),
+                WideMask::from(mask_string(string).unwrap())
+            );
+        }
+        check_printable_ascii(&ALPHANUMERIC);
+        check_printable_ascii(&EXTENDED);
+        assert_eq!(
+            ALPHANUMERIC.defined(),
+            WideMask::from(Mask::ALL) | Mask64::from((1 << 42) - (1 << 32))
+        );
+    }
+
+    #[test]
+    fn alphanumeric() {
+        let mask = ALPHANUMERIC.mask_string("Route 66").unwrap();
+
+        assert_eq!(ALPHANUMERIC.bit('0'), Some(32));
+        assert_eq!(ALPHANUMERIC.symbol(41), Some('9'));
+        assert_eq!(ALPHANUMERIC.chars(mask).collect::<String>(), " eortu6");
+        assert!(matches!(
+            ALPHANUMERIC.mask_string("a;b"),
+            Err(LocalError::InvalidCharacter { character: ';' })
+        ));
+    }
+
+    #[test]
+    fn extended() {
+        let mask = EXTENDED.mask_string("a;b: c/d [1]").unwrap();
+
+        assert_eq!(EXTENDED.chars(mask).collect::<String>(), " abcd1/:;[]");
+        assert_eq!(EXTENDED.symbol(68), Some('~'));
+        assert_eq!(EXTENDED.defined().len(), 69);
+        assert!(
+            (0x21..0x7f_u8).all(|code| EXTENDED.bit(code as char).is_some()),
+            "every printable ASCII character"
+        );
+        assert_eq!(
+            EXTENDED.mask_string("Tea, 42.").unwrap(),
+            Mask128::from(u128::from(u64::from(
+                ALPHANUMERIC.mask_string("Tea, 42.").unwrap()
+            )))
+        );
+    }
+
+    #[test]
+    fn case_sensitive() {
+        let mask = CASE_SENSITIVE.mask_string("Zebra, ZEBRA").unwrap();
+
+        assert_eq!(CASE_SENSITIVE.bit('A'), Some(32));
+        assert_eq!(CASE_SENSITIVE.bit('z'), Some(26));
+        assert_eq!(
+            CASE_SENSITIVE.chars(mask).collect::<String>(),
+            " aber,ABERZ"
+        );
+        assert_eq!(
+            CASE_SENSITIVE
+                .find_common_mask(&["Ab", "bA"], 1024)
+                .unwrap(),
+            CASE_SENSITIVE.mask_string("bA").unwrap()
+        );
+        assert!(CASE_SENSITIVE.mask_string("Å").is_err());
+    }
+
+    #[test]
+    fn common_in_parallel() {
+        let strings = ["abc123"; 1000];
+
+        assert_eq!(
+            ALPHANUMERIC.find_common_mask(&strings, 64).unwrap(),
+            ALPHANUMERIC.mask_string("321cba").unwrap()
+        );
+    }
+}
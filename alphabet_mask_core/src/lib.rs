@@ -0,0 +1,1008 @@
+//! The alphabet masking algorithm, without any bindings.
+//!
+//! Every string is reduced to a [`Mask`] with one bit per character it contains:
+//! bit 0 for space, bits 1 to 26 for the letters `a` to `z` regardless of case, and
+//! bits 27 to 31 for `.`, `,`, `'`, `-` and `"`. Common alphabets are the bitwise
+//! AND of these masks, and combined alphabets their bitwise OR.
+//!
+//! ```
+//! use alphabet_mask_core::{find_common_mask, mask_string, mask_to_chars};
+//!
+//! let mask = mask_string("Hello, world.").unwrap();
+//! assert_eq!(mask_to_chars(mask), " dehlorw.,");
+//!
+//! let common = find_common_mask(["the cat", "a hat"].into_iter()).unwrap();
+//! assert_eq!(mask_to_chars(common), " aht");
+//! ```
+//!
+//! Any character outside the alphabet is an error:
+//!
+//! ```
+//! use alphabet_mask_core::{mask_string, LocalError};
+//!
+//! assert!(matches!(
+//!     mask_string("a1"),
+//!     Err(LocalError::InvalidCharacter { character: '1' })
+//! ));
+//! ```
+//!
+//! Large collections of strings are masked in parallel with rayon when the
+//! `parallel` feature is enabled, which it is by default; ASCII strings take a
+//! table-driven fast path when the `simd` feature is enabled.
+//!
+//! Parallelism never changes the outcome: for the same strings and options, the
+//! parallel paths return the same mask as the serial ones, bit for bit, and fail
+//! with the same error, that of the first string outside the alphabet, however
+//! the chunks are scheduled.
+//!
+//! The crate is `no_std`. The mask algebra works without an allocator; anything
+//! that builds strings or chunks needs the `alloc` feature, and parallelism needs
+//! `std`, which `parallel` implies. The `trace` feature adds `tracing` spans
+//! around chunking, masking each chunk and reducing the results, and the
+//! `scripts` feature counts the Unicode scripts of text outside the alphabet,
+//! the `normalize` feature lets a masker fold diacritics, and the `unicode`
+//! feature masks any text into sets of Unicode scalar values.
+//!
+#![no_std]
+#![warn(missing_docs)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, string::String};
+
+#[macro_use]
+mod trace;
+
+#[cfg(feature = "alloc")]
+pub mod alphabet;
+#[cfg(feature = "alloc")]
+pub mod bio;
+#[cfg(feature = "alloc")]
+pub mod chunks;
+pub mod classify;
+#[cfg(feature = "alloc")]
+pub mod codec;
+#[cfg(feature = "alloc")]
+pub mod expression;
+pub mod ext;
+#[cfg(feature = "alloc")]
+pub mod games;
+#[cfg(feature = "alloc")]
+pub mod index;
+#[cfg(feature = "alloc")]
+pub mod layout;
+#[cfg(feature = "alloc")]
+pub mod masker;
+#[cfg(feature = "alloc")]
+pub mod pipeline;
+#[cfg(feature = "alloc")]
+pub mod profile;
+#[cfg(feature = "alloc")]
+pub mod sample;
+#[cfg(feature = "scripts")]
+pub mod scripts;
+#[cfg(feature = "alloc")]
+pub mod segment;
+pub mod stats;
+#[cfg(feature = "alloc")]
+use chunks::Chunker;
+#[cfg(feature = "simd")]
+mod simd;
+pub mod table;
+#[cfg(feature = "alloc")]
+pub mod transform;
+#[cfg(feature = "unicode")]
+pub mod unicode;
+#[cfg(feature = "alloc")]
+pub mod validate;
+
+pub use alphabet_mask_models as models;
+use classify::{CharClassifier, StandardLayout};
+#[cfg(feature = "alloc")]
+use models::AlphabetSpec;
+use models::BitMask;
+pub use models::{LocalError, Mask};
+#[cfg(feature = "parallel")]
+use rayon::iter::ParallelBridge;
+#[cfg(feature = "parallel")]
+use rayon::iter::ParallelIterator;
+
+#[cfg(test)]
+pub(crate) mod conftest;
+
+/// Mask a string.
+///
+/// Letters are masked regardless of case, and any character outside the alphabet
+/// gives a [`LocalError::InvalidCharacter`], or a [`LocalError::ControlCharacter`]
+/// with its position if it is a control character.
+///
+/// ```
+/// use alphabet_mask_core::{mask_string, Mask};
+///
+/// assert_eq!(mask_string("Cab").unwrap(), Mask(0b1110));
+/// assert_eq!(mask_string("").unwrap(), Mask::EMPTY);
+/// assert!(mask_string("café").is_err());
+/// ```
+pub fn mask_string(string: &str) -> Result<Mask, LocalError> {
+    #[cfg(feature = "simd")]
+    if string.is_ascii() {
+        return simd::mask_ascii(string);
+    }
+
+    mask_chars(string)
+}
+
+/// Mask a string one character at a time.
+fn mask_chars(string: &str) -> Result<Mask, LocalError> {
+    mask_string_by(string, &StandardLayout)
+}
+
+/// Mask a string, classifying each character with `classifier`.
+///
+/// [`mask_string`] is the same as this with [`StandardLayout`], except that it may
+/// take faster paths.
+///
+/// ```
+/// use alphabet_mask_core::classify::StandardLayout;
+/// use alphabet_mask_core::{mask_string, mask_string_by};
+///
+/// assert_eq!(mask_string_by("Cab", &StandardLayout).unwrap(), mask_string("abc").unwrap());
+/// ```
+pub fn mask_string_by<C: CharClassifier>(
+    string: &str,
+    classifier: &C,
+) -> Result<C::Mask, LocalError> {
+    string
+        .chars()
+        .enumerate()
+        .try_fold(C::Mask::EMPTY, |acc, (position, character)| {
+            Ok(
+                match classifier
+                    .classify(character)
+                    .map_err(|err| err.at(position))?
+                {
+                    Some(bit) => acc | C::Mask::from_bit(bit),
+                    None => acc,
+                },
+            )
+        })
+}
+
+/// Convert a mask created from `mask_string` to a string of characters.
+///
+/// The characters are always in bit order: space, the letters from `a` to `z` in
+/// lowercase, then `.`, `,`, `'`, `-` and `"`. See [`mask_to_chars_ordered`] for
+/// other orders.
+///
+/// ```
+/// use alphabet_mask_core::{mask_string, mask_to_chars, Mask};
+///
+/// assert_eq!(mask_to_chars(Mask(0b1110)), "abc");
+/// assert_eq!(mask_to_chars(mask_string("Tea.").unwrap()), "aet.");
+/// ```
+#[cfg(feature = "alloc")]
+pub fn mask_to_chars(mask: Mask) -> String {
+    mask.chars().collect()
+}
+
+/// The order of the characters returned by [`mask_to_chars_ordered`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CharOrder<'a> {
+    /// Bit order, as returned by [`mask_to_chars`].
+    #[default]
+    Bit,
+    /// Code point order: space and punctuation, then the letters.
+    Alphabetical,
+    /// The order of the characters of the given string, regardless of case; the
+    /// characters it leaves out follow in bit order, and any character outside
+    /// the alphabet is ignored.
+    Custom(&'a str),
+}
+
+/// Convert a mask created from `mask_string` to a string of characters in the
+/// given order.
+///
+/// ```
+/// use alphabet_mask_core::{mask_string, mask_to_chars_ordered, CharOrder};
+///
+/// let mask = mask_string("Hello, world.").unwrap();
+/// assert_eq!(mask_to_chars_ordered(mask, CharOrder::Bit), " dehlorw.,");
+/// assert_eq!(mask_to_chars_ordered(mask, CharOrder::Alphabetical), " ,.dehlorw");
+/// assert_eq!(mask_to_chars_ordered(mask, CharOrder::Custom("World")), "world eh.,");
+/// ```
+#[cfg(feature = "alloc")]
+pub fn mask_to_chars_ordered(mask: Mask, order: CharOrder<'_>) -> String {
+    match order {
+        CharOrder::Bit => mask_to_chars(mask),
+        CharOrder::Alphabetical => {
+            let mut chars: alloc::vec::Vec<char> = mask.chars().collect();
+            chars.sort_unstable();
+            chars.into_iter().collect()
+        }
+        CharOrder::Custom(order) => {
+            let mut seen = Mask::EMPTY;
+            let mut chars = String::new();
+            for character in order.chars() {
+                if let Ok(Some(bit)) = StandardLayout.classify(character) {
+                    let bit = Mask::from_bit(bit);
+                    if mask & !seen & bit != Mask::EMPTY {
+                        seen |= bit;
+                        chars.extend(bit.chars());
+                    }
+                }
+            }
+            chars.extend((mask & !seen).chars());
+            chars
+        }
+    }
+}
+
+/// Mask a string according to the bit layout of `spec`.
+///
+/// [`mask_string`] is the same as this with [`AlphabetSpec::standard`], except that
+/// it is much faster.
+#[cfg(feature = "alloc")]
+pub fn mask_string_with(string: &str, spec: &AlphabetSpec) -> Result<Mask, LocalError> {
+    spec.validate()?;
+
+    string
+        .chars()
+        .enumerate()
+        .try_fold(0_u32, |acc, (position, character)| {
+            spec.mask_bit(character)
+                .map(|bit| acc | 1 << bit)
+                .ok_or_else(|| LocalError::outside_alphabet(character).at(position))
+        })
+        .map(Mask)
+}
+
+/// Convert a mask created from `mask_string_with` to a string of characters,
+/// according to the bit layout of `spec`.
+///
+/// Bits without a symbol are ignored, and the `other` bit of `spec` is
+/// represented by [`OTHER_SYMBOL`](models::OTHER_SYMBOL).
+#[cfg(feature = "alloc")]
+pub fn mask_to_chars_with(mask: Mask, spec: &AlphabetSpec) -> String {
+    (0..spec.width.min(Mask::WIDTH))
+        .filter(|&bit| mask.contains_bit(bit))
+        .filter_map(|bit| spec.symbol(bit))
+        .collect()
+}
+
+/// Returns `mask` with every reserved bit of the standard layout cleared, so
+/// that masks persisted by any version compare equal to freshly computed ones.
+///
+/// For any mask, `mask_string(&mask_to_chars(mask))` is `normalize_mask(mask)`.
+///
+/// ```
+/// use alphabet_mask_core::{mask_string, mask_to_chars, normalize_mask, Mask};
+///
+/// let mask = Mask(0b1110);
+/// assert_eq!(normalize_mask(mask), mask);
+/// assert_eq!(mask_string(&mask_to_chars(mask)).unwrap(), normalize_mask(mask));
+/// ```
+pub fn normalize_mask(mask: Mask) -> Mask {
+    mask & models::bits::DEFINED_MASK
+}
+
+/// Returns `bits` as a mask if it only sets bits defined by the standard layout,
+/// or a descriptive error otherwise.
+///
+/// Use this on integers from outside this crate, such as a database column;
+/// [`normalize_mask`] clears the undefined bits instead.
+///
+/// ```
+/// use alphabet_mask_core::{validate_mask, Mask};
+///
+/// assert_eq!(validate_mask(0b1110).unwrap(), Mask(0b1110));
+/// assert!(validate_mask(1 << 32).is_err());
+/// assert!(validate_mask(-1).is_err());
+/// ```
+#[cfg(feature = "alloc")]
+pub fn validate_mask(bits: i128) -> Result<Mask, LocalError> {
+    check_mask(bits, models::bits::DEFINED_MASK)
+}
+
+/// Returns `bits` as a mask if it only sets bits with a symbol in `spec`, or a
+/// descriptive error otherwise.
+#[cfg(feature = "alloc")]
+pub fn validate_mask_with(bits: i128, spec: &AlphabetSpec) -> Result<Mask, LocalError> {
+    check_mask(bits, normalize_mask_with(Mask::ALL, spec))
+}
+
+/// Returns `bits` as a mask if it only sets bits of `defined`.
+#[cfg(feature = "alloc")]
+fn check_mask(bits: i128, defined: Mask) -> Result<Mask, LocalError> {
+    if bits < 0 {
+        return Err(LocalError::InvalidMask {
+            context: alloc::format!("{bits} is negative"),
+        });
+    }
+
+    let undefined = bits & !i128::from(defined.0);
+    if undefined != 0 {
+        let positions: alloc::vec::Vec<u32> = (0..i128::BITS)
+            .filter(|bit| undefined >> bit & 1 == 1)
+            .collect();
+        return Err(LocalError::InvalidMask {
+            context: alloc::format!("{bits:#x} sets bits {positions:?} undefined by the layout"),
+        });
+    }
+
+    Ok(Mask(bits as u32))
+}
+
+/// Returns `mask` with every bit without a symbol in `spec` cleared, including
+/// its reserved bits and any bit beyond its width.
+///
+/// For any mask, `mask_string_with(&mask_to_chars_with(mask, spec), spec)` is
+/// `normalize_mask_with(mask, spec)`.
+#[cfg(feature = "alloc")]
+pub fn normalize_mask_with(mask: Mask, spec: &AlphabetSpec) -> Mask {
+    (0..spec.width.min(Mask::WIDTH))
+        .filter(|&bit| mask.contains_bit(bit) && spec.symbol(bit).is_some())
+        .fold(Mask::EMPTY, |normalized, bit| normalized | Mask(1 << bit))
+}
+
+/// Aggregate the results of a mask iterator by performing a bitwise AND on each result.
+///
+/// If any of the results are errors, the first error is returned. An empty iterator
+/// gives [`Mask::ALL`], or the equivalent for masks of any other width.
+///
+/// ```
+/// use alphabet_mask_core::{intersect_masks, Mask};
+///
+/// let masks = [Ok::<_, ()>(Mask(0b1110)), Ok(Mask(0b0111))];
+/// assert_eq!(intersect_masks(masks.into_iter()), Ok(Mask(0b0110)));
+/// assert_eq!(intersect_masks(core::iter::empty::<Result<Mask, ()>>()), Ok(Mask::ALL));
+/// assert_eq!(intersect_masks([Ok(Mask(1)), Err("bad")].into_iter()), Err("bad"));
+///
+/// // Masks of any width reduce the same way.
+/// use alphabet_mask_core::models::{BitMask, Mask128};
+///
+/// let wide = [Ok::<_, ()>(Mask128::from_bit(100)), Ok(Mask128::ALL)];
+/// assert_eq!(intersect_masks(wide.into_iter()), Ok(Mask128::from_bit(100)));
+/// ```
+pub fn intersect_masks<M: BitMask, E>(
+    mut masks: impl Iterator<Item = Result<M, E>>,
+) -> Result<M, E> {
+    masks.try_fold(M::ALL, |acc, result| {
+        if let Ok(mask) = result {
+            Ok(acc & mask)
+        } else {
+            result
+        }
+    })
+}
+
+/// Aggregate the results of a mask iterator by performing a bitwise OR on each result.
+///
+/// If any of the results are errors, the first error is returned. An empty iterator
+/// gives [`Mask::EMPTY`].
+///
+/// ```
+/// use alphabet_mask_core::{unite_masks, Mask};
+///
+/// let masks = [Ok::<_, ()>(Mask(0b1110)), Ok(Mask(0b0111))];
+/// assert_eq!(unite_masks(masks.into_iter()), Ok(Mask(0b1111)));
+/// ```
+pub fn unite_masks<M: BitMask, E>(mut masks: impl Iterator<Item = Result<M, E>>) -> Result<M, E> {
+    masks.try_fold(M::EMPTY, |acc, result| result.map(|mask| acc | mask))
+}
+
+/// Returns a bit mask representing the combined alphabet of the given strings.
+///
+/// The strings can be of any type that borrows as a `str`, such as `&str`,
+/// `String` or `Cow<str>`.
+///
+/// ```
+/// use alphabet_mask_core::{find_union_mask, mask_to_chars};
+///
+/// let mask = find_union_mask(["ab", "bc"].into_iter()).unwrap();
+/// assert_eq!(mask_to_chars(mask), "abc");
+/// ```
+pub fn find_union_mask<S: AsRef<str>>(
+    strings: impl Iterator<Item = S>,
+) -> Result<Mask, LocalError> {
+    unite_masks(strings.map(|string| mask_string(string.as_ref())))
+}
+
+/// Returns a bit mask representing the common alphabet of the given strings.
+///
+/// The strings can be of any type that borrows as a `str`, such as `&str`,
+/// `String` or `Cow<str>`. See [`find_common_mask_dispatch`] to parallelise large
+/// inputs.
+///
+/// ```
+/// use alphabet_mask_core::{find_common_mask, mask_to_chars};
+///
+/// let strings = vec![String::from("ab"), String::from("bc")];
+/// let mask = find_common_mask(strings.iter()).unwrap();
+/// assert_eq!(mask_to_chars(mask), "b");
+/// ```
+pub fn find_common_mask<S: AsRef<str>>(
+    strings: impl Iterator<Item = S>,
+) -> Result<Mask, LocalError> {
+    intersect_masks(strings.map(|string| mask_string(string.as_ref())))
+}
+
+/// Returns the common mask of the given strings, classifying each character with
+/// `classifier`.
+pub fn find_common_mask_by<C: CharClassifier, S: AsRef<str>>(
+    strings: impl Iterator<Item = S>,
+    classifier: &C,
+) -> Result<C::Mask, LocalError> {
+    intersect_masks(strings.map(|string| mask_string_by(string.as_ref(), classifier)))
+}
+
+/// Returns the combined mask of the given strings, classifying each character with
+/// `classifier`.
+pub fn find_union_mask_by<C: CharClassifier, S: AsRef<str>>(
+    strings: impl Iterator<Item = S>,
+    classifier: &C,
+) -> Result<C::Mask, LocalError> {
+    unite_masks(strings.map(|string| mask_string_by(string.as_ref(), classifier)))
+}
+
+/// Chunk the given string iterator into chunks of at most `LENGTH_LIMIT_PER_CHUNK` bytes,
+/// or a chunk of a single string if it is larger than `LENGTH_LIMIT_PER_CHUNK`.
+///
+/// `length_limit` overrides [`chunks::LENGTH_LIMIT_PER_CHUNK`] if given.
+///
+/// ```
+/// use alphabet_mask_core::chunk_strings_by;
+///
+/// let chunks: Vec<_> = chunk_strings_by(["ab", "cd", "efgh"].into_iter(), Some(4)).collect();
+/// assert_eq!(chunks, [vec!["ab", "cd"].into(), vec!["efgh"].into()]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn chunk_strings_by<S: AsRef<str>>(
+    strings: impl Iterator<Item = S>,
+    length_limit: Option<usize>,
+) -> impl Iterator<Item = Box<[S]>> {
+    if let Some(length_limit) = length_limit {
+        Chunker::with_length_limit(strings, length_limit)
+    } else {
+        Chunker::new(strings)
+    }
+}
+
+/// Returns a bit mask representing the common alphabet of the given strings,
+/// using parallel processing.
+///
+/// The strings are chunked by [`chunk_strings_by`], and the chunks masked on the
+/// rayon thread pool.
+///
+/// ```
+/// use alphabet_mask_core::{find_common_mask_parallel, mask_to_chars};
+///
+/// let strings = vec!["the cat"; 1000];
+/// let mask = find_common_mask_parallel(strings.into_iter(), Some(100)).unwrap();
+/// assert_eq!(mask_to_chars(mask), " aceht");
+/// ```
+#[cfg(feature = "parallel")]
+pub fn find_common_mask_parallel<S, T>(
+    strings: T,
+    length_limit: Option<usize>,
+) -> Result<Mask, LocalError>
+where
+    S: AsRef<str> + Send,
+    T: ExactSizeIterator<Item = S> + Send + Sync,
+{
+    find_common_mask_parallel_with(strings, length_limit, |_, _| ())
+}
+
+/// The same as [`find_common_mask_parallel`], calling `on_chunk` with the number
+/// of strings and bytes of each chunk on the thread which masks it.
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use alphabet_mask_core::find_common_mask_parallel_with;
+///
+/// let chunks = AtomicUsize::new(0);
+/// let strings = vec!["the cat"; 1000];
+/// find_common_mask_parallel_with(strings.into_iter(), Some(700), |strings, bytes| {
+///     assert_eq!((strings, bytes), (100, 700));
+///     chunks.fetch_add(1, Ordering::Relaxed);
+/// })
+/// .unwrap();
+/// assert_eq!(chunks.into_inner(), 10);
+/// ```
+#[cfg(feature = "parallel")]
+pub fn find_common_mask_parallel_with<S, T>(
+    strings: T,
+    length_limit: Option<usize>,
+    on_chunk: impl Fn(usize, usize) + Sync,
+) -> Result<Mask, LocalError>
+where
+    S: AsRef<str> + Send,
+    T: ExactSizeIterator<Item = S> + Send + Sync,
+{
+    span!(DEBUG, "find_common_mask_parallel", strings = strings.len());
+
+    reduce_in_order(
+        chunk_strings_by(strings, length_limit)
+            .enumerate()
+            .par_bridge(),
+        Mask::ALL,
+        |chunk| {
+            let bytes = chunks::total_length(chunk.iter());
+            span!(TRACE, "mask_chunk", strings = chunk.len(), bytes);
+            on_chunk(chunk.len(), bytes);
+            // `into_vec()` should be fine here - there's no memcpy or allocation.
+            find_common_mask(chunk.into_vec().into_iter())
+        },
+        |a, b| a & b,
+    )
+}
+
+/// Reduce the results of `mask_chunk` for each of the numbered `chunks` with
+/// `reduce` from `identity`, failing with the error of the lowest numbered chunk
+/// which fails, as masking them in order would.
+///
+/// Chunks numbered above one already known to fail are skipped, while those
+/// below it are still masked in case one of them fails first. The bindings
+/// reduce blocks of lines read from files with it too.
+#[cfg(feature = "parallel")]
+pub fn reduce_in_order<C, M>(
+    chunks: impl ParallelIterator<Item = (usize, C)>,
+    identity: M,
+    mask_chunk: impl Fn(C) -> Result<M, LocalError> + Sync,
+    reduce: impl Fn(M, M) -> M + Sync,
+) -> Result<M, LocalError>
+where
+    C: Send,
+    M: Clone + Send + Sync,
+{
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    let first_failure = AtomicUsize::new(usize::MAX);
+
+    chunks
+        .map(|(index, chunk)| {
+            if index > first_failure.load(Ordering::Relaxed) {
+                return Ok(identity.clone());
+            }
+            mask_chunk(chunk).map_err(|err| {
+                first_failure.fetch_min(index, Ordering::Relaxed);
+                (index, err)
+            })
+        })
+        .reduce(
+            || Ok(identity.clone()),
+            |a, b| {
+                span!(TRACE, "reduce");
+                match (a, b) {
+                    (Ok(a), Ok(b)) => Ok(reduce(a, b)),
+                    (Err(a), Err(b)) => Err(if a.0 < b.0 { a } else { b }),
+                    (Err(err), Ok(_)) | (Ok(_), Err(err)) => Err(err),
+                }
+            },
+        )
+        .map_err(|(_, err)| err)
+}
+
+/// Without the `parallel` feature, the same as [`find_common_mask`].
+#[cfg(not(feature = "parallel"))]
+fn find_common_mask_parallel<S, T>(
+    strings: T,
+    _length_limit: Option<usize>,
+) -> Result<Mask, LocalError>
+where
+    S: AsRef<str>,
+    T: ExactSizeIterator<Item = S>,
+{
+    find_common_mask(strings)
+}
+
+/// Without the `parallel` feature, the same as [`find_common_mask`], calling
+/// `on_chunk` once with every string.
+#[cfg(not(feature = "parallel"))]
+fn find_common_mask_parallel_with<S, T>(
+    strings: T,
+    _length_limit: Option<usize>,
+    on_chunk: impl Fn(usize, usize),
+) -> Result<Mask, LocalError>
+where
+    S: AsRef<str>,
+    T: ExactSizeIterator<Item = S>,
+{
+    let (mut count, mut bytes) = (0, 0);
+    let mask = find_common_mask(strings.inspect(|string| {
+        count += 1;
+        bytes += string.as_ref().len();
+    }));
+    on_chunk(count, bytes);
+    mask
+}
+
+/// Returns a bit mask representing the common alphabet of the given strings,
+/// using parallel processing only if their total length exceeds `length_limit`
+/// and the `parallel` feature is enabled.
+///
+/// The result is the same either way, including the error if any string is
+/// outside the alphabet, so this is the function to use unless the size of the
+/// input is known in advance.
+///
+/// ```
+/// use alphabet_mask_core::{chunks, find_common_mask_dispatch, mask_to_chars};
+///
+/// let strings = ["the cat", "a hat"];
+/// let mask = find_common_mask_dispatch(&strings, chunks::LENGTH_LIMIT_PER_CHUNK).unwrap();
+/// assert_eq!(mask_to_chars(mask), " aht");
+/// ```
+pub fn find_common_mask_dispatch<S>(strings: &[S], length_limit: usize) -> Result<Mask, LocalError>
+where
+    S: AsRef<str> + Sync,
+{
+    if exceeds_length_limit(strings, length_limit) {
+        find_common_mask_parallel(strings.iter(), Some(length_limit))
+    } else {
+        find_common_mask(strings.iter())
+    }
+}
+
+/// The same as [`find_common_mask_dispatch`], calling `on_chunk` with the number
+/// of strings and bytes of each chunk on the thread which masks it; without
+/// parallelism, once with every string on the current thread.
+pub fn find_common_mask_dispatch_with<S>(
+    strings: &[S],
+    length_limit: usize,
+    on_chunk: impl Fn(usize, usize) + Sync,
+) -> Result<Mask, LocalError>
+where
+    S: AsRef<str> + Sync,
+{
+    if exceeds_length_limit(strings, length_limit) {
+        find_common_mask_parallel_with(strings.iter(), Some(length_limit), on_chunk)
+    } else {
+        let mask = find_common_mask(strings.iter());
+        let bytes = strings.iter().fold(0, |bytes: usize, string| {
+            bytes.saturating_add(string.as_ref().len())
+        });
+        on_chunk(strings.len(), bytes);
+        mask
+    }
+}
+
+/// The same as [`find_common_mask_dispatch`], classifying each character with
+/// `classifier`, such as a [`Layout`](layout::Layout) wider than the standard one.
+#[cfg(feature = "alloc")]
+pub fn find_common_mask_dispatch_by<C, S>(
+    strings: &[S],
+    length_limit: usize,
+    classifier: &C,
+) -> Result<C::Mask, LocalError>
+where
+    C: CharClassifier + Sync,
+    C::Mask: Send + Sync,
+    S: AsRef<str> + Sync,
+{
+    #[cfg(feature = "parallel")]
+    if exceeds_length_limit(strings, length_limit) {
+        span!(DEBUG, "find_common_mask_parallel", strings = strings.len());
+        return reduce_in_order(
+            chunk_strings_by(strings.iter(), Some(length_limit))
+                .enumerate()
+                .par_bridge(),
+            C::Mask::ALL,
+            |chunk| find_common_mask_by(chunk.iter(), classifier),
+            |a, b| a & b,
+        );
+    }
+    #[cfg(not(feature = "parallel"))]
+    let _ = length_limit;
+
+    find_common_mask_by(strings.iter(), classifier)
+}
+
+/// Returns the intersection of the common alphabet of `strings` with each of
+/// `references`, such as the alphabets of several languages, masking the
+/// strings only once.
+///
+/// ```
+/// use alphabet_mask_core::{chunks, intersect_with, mask_string};
+///
+/// let references = [mask_string("abc").unwrap(), mask_string("the").unwrap()];
+/// let masks = intersect_with(&["the cat", "a hat"], &references, chunks::LENGTH_LIMIT_PER_CHUNK);
+///
+/// assert_eq!(masks.unwrap(), [mask_string("a").unwrap(), mask_string("th").unwrap()]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn intersect_with<S>(
+    strings: &[S],
+    references: &[Mask],
+    length_limit: usize,
+) -> Result<alloc::vec::Vec<Mask>, LocalError>
+where
+    S: AsRef<str> + Sync,
+{
+    let common = find_common_mask_dispatch(strings, length_limit)?;
+    Ok(references
+        .iter()
+        .map(|&reference| common & reference)
+        .collect())
+}
+
+/// Whether the total length of `strings` exceeds `length_limit`, so that
+/// [`find_common_mask_dispatch`] would parallelise.
+pub(crate) fn exceeds_length_limit<S: AsRef<str>>(strings: &[S], length_limit: usize) -> bool {
+    strings
+        .iter()
+        .try_fold(0_usize, |acc, s| {
+            // An overflow certainly exceeds the limit.
+            acc.checked_add(s.as_ref().len())
+                .filter(|&new_len| new_len <= length_limit)
+        })
+        .is_none()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn common_mask_without_alloc() {
+        let strings = conftest::COLLECTION_OF_50_CHARS_STRINGS;
+
+        let mask = find_common_mask(strings.into_iter()).unwrap();
+
+        assert_eq!(mask, mask_string(" ert.").unwrap());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_matches_serial() {
+        use std::format;
+
+        for seed in 0..100 {
+            let strings = conftest::random_strings(seed, 200, &conftest::DIFFERENTIAL_CHARS);
+            let serial = find_common_mask(strings.iter());
+
+            for length_limit in [1, 40, 400, 4000] {
+                let parallel = find_common_mask_parallel(strings.iter(), Some(length_limit));
+                // Errors are compared by their message, which names the character
+                // and position of the first failure.
+                assert_eq!(
+                    format!("{parallel:?}"),
+                    format!("{serial:?}"),
+                    "seed {seed}, length limit {length_limit}"
+                );
+                assert_eq!(
+                    format!("{:?}", find_common_mask_dispatch(&strings, length_limit)),
+                    format!("{serial:?}")
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn control_character_positions() {
+        // The position is counted in characters, on both the ASCII and the
+        // Unicode path.
+        for (string, position) in [("ab\0", 2), ("\x7f", 0), ("a\tж", 1), ("ab\u{85}", 2)] {
+            assert!(
+                matches!(
+                    mask_string(string),
+                    Err(LocalError::ControlCharacter { position: p, .. }) if p == position
+                ),
+                "{string:?}"
+            );
+        }
+        assert!(matches!(
+            mask_string("a\u{141}"),
+            Err(LocalError::InvalidCharacter { .. })
+        ));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn standard_spec() {
+        let spec = AlphabetSpec::standard();
+
+        for string in conftest::COLLECTION_OF_50_CHARS_STRINGS {
+            assert_eq!(
+                mask_string_with(string, &spec).unwrap(),
+                mask_string(string).unwrap()
+            );
+        }
+        for bit in 0..32 {
+            assert_eq!(
+                mask_to_chars_with(Mask(1 << bit), &spec),
+                mask_to_chars(Mask(1 << bit))
+            );
+        }
+        assert!(mask_string_with("a1", &spec).is_err());
+        assert!(matches!(
+            mask_string_with("a\n", &spec),
+            Err(LocalError::ControlCharacter { position: 1, .. })
+        ));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn standard_spec_every_ascii_char() {
+        use std::format;
+
+        let spec = AlphabetSpec::standard();
+        let mut buffer = [0; 4];
+        for byte in 0..=0x7f_u8 {
+            let string = char::from(byte).encode_utf8(&mut buffer);
+
+            assert_eq!(
+                format!("{:?}", mask_string(string)),
+                format!("{:?}", mask_string_with(string, &spec)),
+                "{byte:#04x} differs"
+            );
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn ordered_chars() {
+        let mask = Mask::ALL;
+
+        assert_eq!(
+            mask_to_chars_ordered(mask, CharOrder::Alphabetical),
+            " \"',-.abcdefghijklmnopqrstuvwxyz"
+        );
+        // Repeated, uppercase and unknown characters in a custom order.
+        assert_eq!(
+            mask_to_chars_ordered(mask_string("abc.").unwrap(), CharOrder::Custom("CcX.1")),
+            "c.ab"
+        );
+        assert_eq!(
+            mask_to_chars_ordered(Mask::EMPTY, CharOrder::Custom("abc")),
+            ""
+        );
+        for order in [
+            CharOrder::Bit,
+            CharOrder::Alphabetical,
+            CharOrder::Custom("zyx"),
+        ] {
+            let chars = mask_to_chars_ordered(mask, order);
+            assert_eq!(mask_string(&chars).unwrap(), mask);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn normalize_round_trip() {
+        use alloc::vec;
+
+        let spec = AlphabetSpec {
+            width: 4,
+            case: models::CasePolicy::Sensitive,
+            symbols: vec![('a', 0), ('b', 1), ('A', 3)],
+            reserved: vec![2],
+            other: None,
+            fallbacks: vec![],
+        };
+        let with_other = AlphabetSpec {
+            reserved: vec![],
+            other: Some(2),
+            ..spec.clone()
+        };
+        // Every mask of the spec, and a spread of masks of the standard layout.
+        let masks = (0..1 << 6)
+            .chain((0..32).map(|bit| 1 << bit))
+            .chain((0..1000_u32).map(|seed| seed.wrapping_mul(0x9E37_79B9)))
+            .chain([u32::MAX])
+            .map(Mask);
+
+        for mask in masks {
+            assert_eq!(
+                mask_string(&mask_to_chars(mask)).unwrap(),
+                normalize_mask(mask)
+            );
+            assert_eq!(
+                mask_string_with(&mask_to_chars_with(mask, &spec), &spec).unwrap(),
+                normalize_mask_with(mask, &spec)
+            );
+            assert_eq!(
+                mask_string_with(&mask_to_chars_with(mask, &with_other), &with_other).unwrap(),
+                normalize_mask_with(mask, &with_other)
+            );
+        }
+        assert_eq!(normalize_mask_with(Mask(0b11_1111), &spec), Mask(0b1011));
+        assert_eq!(mask_string_with("a?B", &with_other).unwrap(), Mask(0b101));
+        assert_eq!(mask_to_chars_with(Mask(0b101), &with_other), "a\u{FFFD}");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn validate() {
+        use alloc::{string::ToString, vec};
+
+        assert_eq!(validate_mask(u32::MAX.into()).unwrap(), Mask::ALL);
+        assert_eq!(
+            validate_mask(1 << 40 | 1 << 33 | 1)
+                .unwrap_err()
+                .to_string(),
+            "invalid mask: 0x10200000001 sets bits [33, 40] undefined by the layout"
+        );
+        assert_eq!(
+            validate_mask(-2).unwrap_err().to_string(),
+            "invalid mask: -2 is negative"
+        );
+
+        let spec = AlphabetSpec {
+            width: 4,
+            case: models::CasePolicy::Sensitive,
+            symbols: vec![('a', 0), ('b', 1), ('A', 3)],
+            reserved: vec![2],
+            other: None,
+            fallbacks: vec![],
+        };
+        assert_eq!(validate_mask_with(0b1011, &spec).unwrap(), Mask(0b1011));
+        assert!(validate_mask_with(0b100, &spec).is_err());
+        assert!(validate_mask_with(0b10000, &spec).is_err());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn owned_and_borrowed_strings() {
+        use alloc::{borrow::Cow, string::ToString, vec::Vec};
+
+        let strings: Vec<_> = conftest::COLLECTION_OF_50_CHARS_STRINGS
+            .iter()
+            .map(|string| string.to_string())
+            .collect();
+        let cows = [Cow::Borrowed("abc"), Cow::Owned("bcd".to_string())];
+
+        assert_eq!(
+            find_common_mask_dispatch(&strings, 100).unwrap(),
+            mask_string(" ert.").unwrap()
+        );
+        assert_eq!(
+            find_common_mask(cows.iter()).unwrap(),
+            mask_string("bc").unwrap()
+        );
+        assert_eq!(
+            find_union_mask(cows.into_iter()).unwrap(),
+            mask_string("abcd").unwrap()
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    macro_rules! expand_tests {
+        (
+            $((
+                $name:ident,
+                $max:literal,
+                $expected:expr
+            ),)*$(,)?
+        ) => {
+            $(
+                #[test]
+                fn $name() {
+                    let texts = conftest::COLLECTION_OF_50_CHARS_STRINGS[0..$max].to_vec();
+
+                    let mask = find_common_mask_parallel(texts.into_iter(), Some(100)).unwrap();
+
+                    assert_eq!(&mask_to_chars(mask), $expected);
+                }
+            )*
+        };
+    }
+
+    #[cfg(feature = "parallel")]
+    expand_tests!(
+        (test_1, 1, " acdeghilnprstw."),
+        (test_2, 2, " adeghlnrstw."),
+        (test_3, 3, " aehlrst."),
+        (test_6, 6, " aehlrst."),
+        (test_7, 7, " ehlrst."),
+        (test_8, 8, " ehlrst."),
+        (test_9, 9, " ehlrt."),
+        (test_11, 11, " ehrt."),
+        (test_15, 15, " ehrt."),
+        (test_16, 16, " ert."),
+        (test_19, 19, " ert."),
+    );
+}
@@ -0,0 +1,630 @@
+//! A reusable, configurable masker for any [`AlphabetSpec`].
+//!
+//! The free functions of this crate always use the standard layout; a [`Masker`]
+//! compiles its spec into a lookup table once, then masks any number of strings
+//! with it:
+//!
+//! ```
+//! use alphabet_mask_core::masker::Masker;
+//! use alphabet_mask_core::models::{AlphabetSpec, InvalidCharPolicy};
+//!
+//! let masker = Masker::builder()
+//!     .alphabet(AlphabetSpec::standard())
+//!     .case_insensitive()
+//!     .on_invalid(InvalidCharPolicy::Skip)
+//!     .build()
+//!     .unwrap();
+//!
+//! let mask = masker.common_mask(&["Route 66", "route 1"]).unwrap();
+//! assert_eq!(masker.mask_to_chars(mask), " eortu");
+//! ```
+//!
+use alloc::{boxed::Box, string::String, vec::Vec};
+#[cfg(feature = "parallel")]
+use std::sync::Arc;
+
+#[cfg(feature = "parallel")]
+use rayon::iter::ParallelBridge;
+
+use crate::classify::CharClassifier;
+use crate::models::{
+    AlphabetSpec, CasePolicy, ControlCharPolicy, InvalidCharPolicy, LocalError, Mask,
+};
+use crate::transform::{apply_all, Transform};
+#[cfg(feature = "parallel")]
+use crate::{chunk_strings_by, chunks, reduce_in_order};
+use crate::{
+    find_common_mask_by, find_union_mask_by, mask_string_by, mask_to_chars_with,
+    normalize_mask_with, validate_mask_with,
+};
+
+/// The configuration of a [`Masker`], returned by [`Masker::builder`].
+#[derive(Debug, Clone, Default)]
+pub struct MaskerBuilder {
+    spec: AlphabetSpec,
+    case: Option<CasePolicy>,
+    on_invalid: InvalidCharPolicy,
+    on_control: ControlCharPolicy,
+    transforms: Vec<Transform>,
+    #[cfg(feature = "parallel")]
+    threads: Option<usize>,
+}
+
+impl MaskerBuilder {
+    /// Use the bit layout of `spec`, instead of the standard layout.
+    pub fn alphabet(mut self, spec: AlphabetSpec) -> Self {
+        self.spec = spec;
+        self
+    }
+
+    /// Mask letters regardless of case, overriding the case policy of the spec.
+    pub fn case_insensitive(mut self) -> Self {
+        self.case = Some(CasePolicy::Insensitive);
+        self
+    }
+
+    /// Mask characters exactly as given, overriding the case policy of the spec.
+    pub fn case_sensitive(mut self) -> Self {
+        self.case = Some(CasePolicy::Sensitive);
+        self
+    }
+
+    /// Let each character `from` set the bit of the symbol `to`, in addition to
+    /// the fallbacks of the spec.
+    pub fn fallbacks(mut self, fallbacks: impl IntoIterator<Item = (char, char)>) -> Self {
+        self.spec.fallbacks.extend(fallbacks);
+        self
+    }
+
+    /// What to do with characters outside the alphabet; an error by default.
+    ///
+    /// This has no effect if the spec has an `other` bit, which every such
+    /// character sets instead.
+    pub fn on_invalid(mut self, policy: InvalidCharPolicy) -> Self {
+        self.on_invalid = policy;
+        self
+    }
+
+    /// What to do with control characters that are not symbols of the spec; an
+    /// error giving their position by default.
+    ///
+    /// This takes precedence over both `on_invalid` and the `other` bit.
+    pub fn on_control(mut self, policy: ControlCharPolicy) -> Self {
+        self.on_control = policy;
+        self
+    }
+
+    /// Apply `transforms` in order to each character before classifying it,
+    /// after any transforms already added.
+    ///
+    /// On ASCII characters, they are composed into the lookup table.
+    pub fn transforms(mut self, transforms: impl IntoIterator<Item = Transform>) -> Self {
+        self.transforms.extend(transforms);
+        self
+    }
+
+    /// Mask collections of strings in parallel, on a dedicated pool of `threads`
+    /// threads, or on as many threads as there are CPUs if `threads` is `0`.
+    ///
+    /// Without this, every string is masked on the calling thread.
+    #[cfg(feature = "parallel")]
+    pub fn parallel(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Validate the spec and compile it into a [`Masker`].
+    pub fn build(self) -> Result<Masker, LocalError> {
+        let spec = AlphabetSpec {
+            case: self.case.unwrap_or(self.spec.case),
+            ..self.spec
+        };
+        spec.validate()?;
+
+        let mut ascii = [None; 128];
+        for (byte, bit) in ascii.iter_mut().enumerate() {
+            *bit = spec.bit(char::from(byte as u8));
+        }
+
+        let mut symbols: Vec<(char, u8)> = spec
+            .symbols
+            .iter()
+            .map(|&(symbol, bit)| (spec.fold(symbol), bit))
+            .chain(spec.fallbacks.iter().filter_map(|&(from, _)| {
+                let from = spec.fold(from);
+                spec.bit(from).map(|bit| (from, bit))
+            }))
+            .filter(|(symbol, _)| !symbol.is_ascii())
+            .collect();
+        symbols.sort_unstable();
+
+        #[cfg(feature = "parallel")]
+        let pool = self
+            .threads
+            .map(|threads| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .map(Arc::new)
+                    .map_err(|err| LocalError::Unknown {
+                        context: alloc::format!("could not start a thread pool: {err}"),
+                    })
+            })
+            .transpose()?;
+
+        let mut masker = Masker {
+            spec,
+            ascii,
+            symbols: symbols.into_boxed_slice(),
+            transforms: self.transforms.into_boxed_slice(),
+            transformed: [Lookup::Outside; 128],
+            on_invalid: self.on_invalid,
+            on_control: self.on_control,
+            #[cfg(feature = "parallel")]
+            pool,
+        };
+        for byte in 0..128_u8 {
+            masker.transformed[usize::from(byte)] = masker.lookup_transformed(char::from(byte));
+        }
+        Ok(masker)
+    }
+}
+
+/// How a character is classified once transformed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lookup {
+    /// The character sets this bit.
+    Bit(u8),
+    /// A transform removed the character.
+    Removed,
+    /// The character is outside the alphabet.
+    Outside,
+}
+
+/// Masks strings according to an [`AlphabetSpec`], built by [`Masker::builder`].
+///
+/// Cloning a masker is cheap, and clones share the same thread pool.
+#[derive(Debug, Clone)]
+pub struct Masker {
+    spec: AlphabetSpec,
+    /// The bit of every ASCII character, if it is in the alphabet.
+    ascii: [Option<u8>; 128],
+    /// Every other symbol or fallback after case folding, sorted for binary search.
+    symbols: Box<[(char, u8)]>,
+    transforms: Box<[Transform]>,
+    /// The lookup of every ASCII character after the transforms.
+    transformed: [Lookup; 128],
+    on_invalid: InvalidCharPolicy,
+    on_control: ControlCharPolicy,
+    #[cfg(feature = "parallel")]
+    pool: Option<Arc<rayon::ThreadPool>>,
+}
+
+impl Default for Masker {
+    /// A masker for the standard layout, failing on invalid characters.
+    fn default() -> Self {
+        // This is safe because the standard spec is always valid.
+        Self::builder().build().unwrap()
+    }
+}
+
+impl Masker {
+    /// Returns a builder for the standard layout, failing on invalid characters
+    /// and masking on the calling thread.
+    pub fn builder() -> MaskerBuilder {
+        MaskerBuilder::default()
+    }
+
+    /// The spec this masker was built from, with the case policy in effect.
+    pub fn spec(&self) -> &AlphabetSpec {
+        &self.spec
+    }
+
+    /// What this masker does with characters outside the alphabet.
+    pub fn on_invalid(&self) -> InvalidCharPolicy {
+        self.on_invalid
+    }
+
+    /// The transforms applied to each character before it is classified.
+    pub fn transforms(&self) -> &[Transform] {
+        &self.transforms
+    }
+
+    /// Returns the lookup of a single character after the transforms.
+    fn lookup(&self, character: char) -> Lookup {
+        match self.transformed.get(character as usize) {
+            Some(&lookup) => lookup,
+            None => self.lookup_transformed(character),
+        }
+    }
+
+    /// Returns the lookup of a single character, applying the transforms.
+    fn lookup_transformed(&self, character: char) -> Lookup {
+        match apply_all(&self.transforms, character) {
+            Some(transformed) => self
+                .char_bit(transformed)
+                .map_or(Lookup::Outside, Lookup::Bit),
+            None => Lookup::Removed,
+        }
+    }
+
+    /// Returns the bit of a single character, or `None` if it is not in the alphabet.
+    fn char_bit(&self, character: char) -> Option<u8> {
+        if let Some(&bit) = self.ascii.get(character as usize) {
+            return bit;
+        }
+
+        match self.spec.fold(character) {
+            // Some characters, such as the Kelvin sign, fold into ASCII.
+            folded if folded.is_ascii() => self.char_bit(folded),
+            folded => self
+                .symbols
+                .binary_search_by_key(&folded, |&(symbol, _)| symbol)
+                .ok()
+                .map(|index| self.symbols[index].1),
+        }
+    }
+
+    /// Mask a string.
+    pub fn mask(&self, string: &str) -> Result<Mask, LocalError> {
+        mask_string_by(string, self)
+    }
+
+    /// Convert a mask created by this masker to a string of characters.
+    pub fn mask_to_chars(&self, mask: Mask) -> String {
+        mask_to_chars_with(mask, &self.spec)
+    }
+
+    /// Returns `mask` of this masker converted to the layout of `to`; see
+    /// [`AlphabetSpec::convert_mask`].
+    pub fn convert_mask(&self, mask: Mask, to: &Masker) -> Result<Mask, LocalError> {
+        self.spec.convert_mask(mask, &to.spec)
+    }
+
+    /// Returns `bits` as a mask if it only sets bits with a symbol in the spec, or
+    /// a descriptive error otherwise.
+    pub fn validate_mask(&self, bits: i128) -> Result<Mask, LocalError> {
+        validate_mask_with(bits, &self.spec)
+    }
+
+    /// Returns `mask` with every bit without a symbol in the spec cleared; the
+    /// characters of a mask always mask back to its normalized mask.
+    pub fn normalize_mask(&self, mask: Mask) -> Mask {
+        normalize_mask_with(mask, &self.spec)
+    }
+
+    /// Returns the mask of the alphabet common to every string.
+    pub fn common_mask<S: AsRef<str> + Sync>(&self, strings: &[S]) -> Result<Mask, LocalError> {
+        #[cfg(feature = "parallel")]
+        if let Some(pool) = &self.pool {
+            return pool.install(|| {
+                reduce_in_order(
+                    chunk_strings_by(strings.iter(), Some(chunks::LENGTH_LIMIT_PER_CHUNK))
+                        .enumerate()
+                        .par_bridge(),
+                    Mask::ALL,
+                    |chunk| {
+                        span!(TRACE, "mask_chunk", strings = chunk.len());
+                        find_common_mask_by(chunk.iter(), self)
+                    },
+                    |a, b| a & b,
+                )
+            });
+        }
+
+        find_common_mask_by(strings.iter(), self)
+    }
+
+    /// Returns the mask of every string combined.
+    pub fn union_mask<S: AsRef<str> + Sync>(&self, strings: &[S]) -> Result<Mask, LocalError> {
+        #[cfg(feature = "parallel")]
+        if let Some(pool) = &self.pool {
+            return pool.install(|| {
+                reduce_in_order(
+                    chunk_strings_by(strings.iter(), Some(chunks::LENGTH_LIMIT_PER_CHUNK))
+                        .enumerate()
+                        .par_bridge(),
+                    Mask::EMPTY,
+                    |chunk| {
+                        span!(TRACE, "mask_chunk", strings = chunk.len());
+                        find_union_mask_by(chunk.iter(), self)
+                    },
+                    |a, b| a | b,
+                )
+            });
+        }
+
+        find_union_mask_by(strings.iter(), self)
+    }
+}
+
+impl CharClassifier for Masker {
+    type Mask = Mask;
+
+    fn classify(&self, character: char) -> Result<Option<usize>, LocalError> {
+        match self.lookup(character) {
+            Lookup::Bit(bit) => return Ok(Some(usize::from(bit))),
+            Lookup::Removed => return Ok(None),
+            Lookup::Outside => {}
+        }
+
+        if character.is_control() {
+            return match self.on_control {
+                ControlCharPolicy::Skip => Ok(None),
+                ControlCharPolicy::Space => match self.char_bit(' ') {
+                    Some(bit) => Ok(Some(usize::from(bit))),
+                    None => Err(LocalError::outside_alphabet(character)),
+                },
+                ControlCharPolicy::Error => Err(LocalError::outside_alphabet(character)),
+            };
+        }
+
+        match self.spec.other {
+            Some(bit) => Ok(Some(usize::from(bit))),
+            None if self.on_invalid == InvalidCharPolicy::Skip => Ok(None),
+            None => Err(LocalError::InvalidCharacter { character }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::models::TYPOGRAPHIC_FALLBACKS;
+    use crate::{conftest, mask_string};
+
+    #[test]
+    fn standard_layout() {
+        let masker = Masker::default();
+
+        for string in conftest::COLLECTION_OF_50_CHARS_STRINGS {
+            assert_eq!(masker.mask(string).unwrap(), mask_string(string).unwrap());
+        }
+        assert_eq!(
+            masker
+                .common_mask(&conftest::COLLECTION_OF_50_CHARS_STRINGS)
+                .unwrap(),
+            mask_string(" ert.").unwrap()
+        );
+        assert!(matches!(
+            masker.mask("a1"),
+            Err(LocalError::InvalidCharacter { character: '1' })
+        ));
+    }
+
+    #[test]
+    fn custom_alphabet() {
+        let spec = AlphabetSpec {
+            width: 4,
+            case: CasePolicy::Insensitive,
+            symbols: alloc::vec![('a', 0), ('ä', 1), ('ß', 2), ('Ω', 3)],
+            reserved: Vec::new(),
+            other: None,
+            fallbacks: Vec::new(),
+        };
+        let masker = Masker::builder()
+            .alphabet(spec.clone())
+            .on_invalid(InvalidCharPolicy::Skip)
+            .build()
+            .unwrap();
+
+        assert_eq!(masker.mask("AÄb ω").unwrap(), Mask(0b1011));
+        assert_eq!(masker.mask_to_chars(Mask(0b1011)), "aäΩ");
+        assert_eq!(masker.union_mask(&["a", "ß"]).unwrap(), Mask(0b0101));
+
+        let masker = Masker::builder()
+            .alphabet(spec)
+            .case_sensitive()
+            .build()
+            .unwrap();
+
+        assert!(masker.mask("Ä").is_err());
+        assert_eq!(masker.mask("ä").unwrap(), Mask(0b0010));
+    }
+
+    #[test]
+    fn custom_order() {
+        let spec = AlphabetSpec::from_order(".,etaoin ").unwrap();
+        let masker = Masker::builder().alphabet(spec).build().unwrap();
+        let mask = masker.mask("Tea, no.").unwrap();
+
+        assert_eq!(mask, Mask(0b1_1011_1111));
+        assert_eq!(masker.mask_to_chars(mask), ".,etaon ");
+        assert_eq!(
+            masker.convert_mask(mask, &Masker::default()).unwrap(),
+            crate::mask_string("tea, no.").unwrap()
+        );
+    }
+
+    #[test]
+    fn other_bit() {
+        let spec = AlphabetSpec {
+            width: 4,
+            case: CasePolicy::Insensitive,
+            symbols: alloc::vec![('a', 0), ('b', 1), ('ß', 2)],
+            reserved: Vec::new(),
+            other: Some(3),
+            fallbacks: Vec::new(),
+        };
+        let masker = Masker::builder().alphabet(spec.clone()).build().unwrap();
+
+        assert_eq!(masker.mask("AB").unwrap(), Mask(0b0011));
+        assert_eq!(masker.mask("a1").unwrap(), Mask(0b1001));
+        assert_eq!(masker.mask("ßΩ").unwrap(), Mask(0b1100));
+        assert_eq!(masker.common_mask(&["a?", "ab!"]).unwrap(), Mask(0b1001));
+        assert_eq!(masker.mask_to_chars(Mask(0b1001)), "a\u{FFFD}");
+        assert_eq!(masker.validate_mask(0b1000).unwrap(), Mask(0b1000));
+        assert_eq!(
+            masker.mask("a1").unwrap(),
+            crate::mask_string_with("a1", &spec).unwrap()
+        );
+    }
+
+    #[test]
+    fn control_characters() {
+        let strict = Masker::builder()
+            .on_invalid(InvalidCharPolicy::Skip)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            strict.mask("ab\0c"),
+            Err(LocalError::ControlCharacter {
+                character: '\0',
+                position: 2
+            })
+        ));
+
+        for (policy, expected) in [
+            (ControlCharPolicy::Skip, "abc"),
+            (ControlCharPolicy::Space, " abc"),
+        ] {
+            let masker = Masker::builder().on_control(policy).build().unwrap();
+
+            assert_eq!(
+                masker.mask("a\tb\x7fc\u{85}").unwrap(),
+                mask_string(expected).unwrap()
+            );
+            assert!(masker.mask("a1").is_err());
+        }
+
+        let spec = AlphabetSpec {
+            width: 2,
+            case: CasePolicy::Insensitive,
+            symbols: alloc::vec![('a', 0)],
+            reserved: Vec::new(),
+            other: Some(1),
+            fallbacks: Vec::new(),
+        };
+        let masker = Masker::builder().alphabet(spec).build().unwrap();
+        assert_eq!(masker.mask("a?").unwrap(), Mask(0b11));
+        assert!(matches!(
+            masker.mask("a\r"),
+            Err(LocalError::ControlCharacter { position: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn fallbacks() {
+        let masker = Masker::builder()
+            .fallbacks(TYPOGRAPHIC_FALLBACKS)
+            .fallbacks([('Æ', 'e')])
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            masker
+                .mask("It\u{2019}s \u{201C}done\u{201D}\u{2026}")
+                .unwrap(),
+            mask_string("It's \"done\".").unwrap()
+        );
+        assert_eq!(masker.mask("æ").unwrap(), mask_string("e").unwrap());
+        assert!(Masker::default().mask("\u{2019}").is_err());
+        assert!(matches!(
+            Masker::builder().fallbacks([('!', '?')]).build(),
+            Err(LocalError::InvalidSpec { .. })
+        ));
+    }
+
+    #[test]
+    fn invalid_spec() {
+        let spec = AlphabetSpec {
+            width: 2,
+            ..AlphabetSpec::standard()
+        };
+
+        assert!(matches!(
+            Masker::builder().alphabet(spec).build(),
+            Err(LocalError::InvalidSpec { .. })
+        ));
+    }
+
+    #[test]
+    fn transforms() {
+        let masker = Masker::builder()
+            .case_sensitive()
+            .transforms([
+                Transform::map([('ß', Some('s')), ('#', None)]),
+                Transform::Lowercase,
+                Transform::FoldWhitespace,
+            ])
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            masker.mask("Straße\u{a0}#").unwrap(),
+            mask_string("strae ").unwrap()
+        );
+        assert!(masker.mask("a!").is_err());
+        // The original character is reported, not the transformed one.
+        assert!(matches!(
+            masker.mask("é"),
+            Err(LocalError::InvalidCharacter { character: 'é' })
+        ));
+    }
+
+    #[cfg(feature = "normalize")]
+    #[test]
+    fn fold_diacritics() {
+        let masker = Masker::builder()
+            .transforms([Transform::FoldDiacritics])
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            masker.mask("Crème brûlée").unwrap(),
+            mask_string("creme brulee").unwrap()
+        );
+        assert!(masker.mask("ﬁ").is_err());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel() {
+        let masker = Masker::builder().parallel(2).build().unwrap();
+        let strings = conftest::COLLECTION_OF_50_CHARS_STRINGS.repeat(10_000);
+
+        assert_eq!(
+            masker.common_mask(&strings).unwrap(),
+            mask_string(" ert.").unwrap()
+        );
+        assert_eq!(
+            masker.union_mask(&strings).unwrap(),
+            crate::find_union_mask(strings.iter()).unwrap()
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_matches_serial() {
+        use alloc::format;
+
+        let serial = Masker::builder()
+            .on_invalid(InvalidCharPolicy::Error)
+            .build()
+            .unwrap();
+        let parallel = Masker::builder()
+            .on_invalid(InvalidCharPolicy::Error)
+            .parallel(4)
+            .build()
+            .unwrap();
+        // Each invalid digit makes for a different error.
+        let chars: Vec<char> = "abcdefghijklmnopqrstuvwxyz .,"
+            .repeat(20)
+            .chars()
+            .chain('1'..='9')
+            .collect();
+
+        for seed in 0..4 {
+            // Over a chunk of a MiB, so that the strings are masked in parallel.
+            let strings = conftest::random_strings(seed, 100_000, &chars);
+            assert_eq!(
+                format!("{:?}", parallel.common_mask(&strings)),
+                format!("{:?}", serial.common_mask(&strings)),
+            );
+            assert_eq!(
+                format!("{:?}", parallel.union_mask(&strings)),
+                format!("{:?}", serial.union_mask(&strings)),
+            );
+        }
+    }
+}
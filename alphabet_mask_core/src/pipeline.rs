@@ -0,0 +1,281 @@
+//! A pipeline of masking, filtering and aggregation, fused into a single pass
+//! over a corpus, so that only the final aggregate is kept.
+//!
+//! ```
+//! use alphabet_mask_core::mask_to_chars;
+//! use alphabet_mask_core::pipeline::{Aggregate, Aggregated, Pipeline, PipelineResult};
+//!
+//! let pipeline = Pipeline {
+//!     filters: vec!["subset(' aceht')".parse().unwrap()],
+//!     group_by_mask: false,
+//!     aggregate: Aggregate::Union,
+//! };
+//!
+//! let PipelineResult::Total(Aggregated::Union(union)) =
+//!     pipeline.run(&["the cat", "a hat", "the dog"]).unwrap()
+//! else {
+//!     unreachable!()
+//! };
+//! assert_eq!(mask_to_chars(union), " aceht");
+//! ```
+//!
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+#[cfg(feature = "parallel")]
+use rayon::slice::ParallelSlice;
+
+use crate::expression::Expression;
+use crate::mask_string;
+use crate::models::{LocalError, Mask};
+
+/// The number of strings per chunk run in parallel.
+const CHUNK_LENGTH: usize = 4096;
+
+/// What a pipeline keeps of the strings which pass its filters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    /// The number of strings.
+    Count,
+    /// The common mask of the strings.
+    Common,
+    /// The union of the masks of the strings.
+    Union,
+    /// The position of each string in the corpus, in order.
+    Positions,
+}
+
+/// The aggregate of the strings which passed the filters of a pipeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Aggregated {
+    /// The number of strings.
+    Count(u64),
+    /// The common mask of the strings; without strings, every bit is set.
+    Common(Mask),
+    /// The union of the masks of the strings.
+    Union(Mask),
+    /// The position of each string, in order.
+    Positions(Vec<usize>),
+}
+
+impl Aggregated {
+    /// The aggregate of no strings.
+    pub fn empty(aggregate: Aggregate) -> Self {
+        match aggregate {
+            Aggregate::Count => Self::Count(0),
+            Aggregate::Common => Self::Common(Mask::ALL),
+            Aggregate::Union => Self::Union(Mask::EMPTY),
+            Aggregate::Positions => Self::Positions(Vec::new()),
+        }
+    }
+
+    /// Add the string at `position`, of `mask`.
+    fn add(&mut self, position: usize, mask: Mask) {
+        match self {
+            Self::Count(count) => *count += 1,
+            Self::Common(common) => *common &= mask,
+            Self::Union(union) => *union |= mask,
+            Self::Positions(positions) => positions.push(position),
+        }
+    }
+
+    /// Merge two aggregates of the same kind, `other` following `self` in the
+    /// corpus.
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Count(a), Self::Count(b)) => Self::Count(a + b),
+            (Self::Common(a), Self::Common(b)) => Self::Common(a & b),
+            (Self::Union(a), Self::Union(b)) => Self::Union(a | b),
+            (Self::Positions(mut a), Self::Positions(b)) => {
+                a.extend(b);
+                Self::Positions(a)
+            }
+            _ => unreachable!("aggregates of a pipeline are all of the same kind"),
+        }
+    }
+}
+
+/// The result of a pipeline over a corpus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PipelineResult {
+    /// The aggregate of every string which passed the filters.
+    Total(Aggregated),
+    /// The aggregate of the strings of each mask which passed the filters.
+    Groups(BTreeMap<Mask, Aggregated>),
+}
+
+impl PipelineResult {
+    fn add(&mut self, aggregate: Aggregate, position: usize, mask: Mask) {
+        match self {
+            Self::Total(total) => total.add(position, mask),
+            Self::Groups(groups) => groups
+                .entry(mask)
+                .or_insert_with(|| Aggregated::empty(aggregate))
+                .add(position, mask),
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Total(a), Self::Total(b)) => Self::Total(a.merge(b)),
+            (Self::Groups(mut a), Self::Groups(b)) => {
+                for (mask, b) in b {
+                    let merged = match a.remove(&mask) {
+                        Some(a) => a.merge(b),
+                        None => b,
+                    };
+                    a.insert(mask, merged);
+                }
+                Self::Groups(a)
+            }
+            _ => unreachable!("results of a pipeline are all of the same kind"),
+        }
+    }
+}
+
+/// Masks each string of a corpus, keeps those whose mask matches every filter,
+/// then aggregates them, either in total or per mask, in a single pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pipeline {
+    /// The expressions a mask must match.
+    pub filters: Vec<Expression>,
+    /// Whether to aggregate the strings of each mask separately.
+    pub group_by_mask: bool,
+    /// What to keep of the strings.
+    pub aggregate: Aggregate,
+}
+
+impl Pipeline {
+    /// Returns whether `mask` matches every filter.
+    pub fn matches(&self, mask: Mask) -> bool {
+        self.filters.iter().all(|filter| filter.matches(mask))
+    }
+
+    /// The result of no strings.
+    fn empty(&self) -> PipelineResult {
+        if self.group_by_mask {
+            PipelineResult::Groups(BTreeMap::new())
+        } else {
+            PipelineResult::Total(Aggregated::empty(self.aggregate))
+        }
+    }
+
+    /// Run the pipeline over `strings`, in parallel chunks with the `parallel`
+    /// feature; a string outside the alphabet fails the whole run, with the
+    /// error of the first such string.
+    pub fn run<S: AsRef<str> + Sync>(&self, strings: &[S]) -> Result<PipelineResult, LocalError> {
+        span!(DEBUG, "run_pipeline", strings = strings.len());
+        let run = |(index, chunk): (usize, &[S])| {
+            chunk
+                .iter()
+                .enumerate()
+                .try_fold(self.empty(), |mut result, (offset, string)| {
+                    let mask = mask_string(string.as_ref())?;
+                    if self.matches(mask) {
+                        result.add(self.aggregate, index * CHUNK_LENGTH + offset, mask);
+                    }
+                    Ok(result)
+                })
+                .map_err(|err| (index, err))
+        };
+        let merge = |a: Result<_, (usize, _)>, b: Result<_, (usize, _)>| match (a, b) {
+            (Ok(a), Ok(b)) => Ok(PipelineResult::merge(a, b)),
+            (Err(a), Err(b)) => Err(if a.0 < b.0 { a } else { b }),
+            (Err(err), Ok(_)) | (Ok(_), Err(err)) => Err(err),
+        };
+
+        #[cfg(feature = "parallel")]
+        let result = strings
+            .par_chunks(CHUNK_LENGTH)
+            .enumerate()
+            .map(run)
+            .reduce(|| Ok(self.empty()), merge);
+        #[cfg(not(feature = "parallel"))]
+        let result = strings
+            .chunks(CHUNK_LENGTH)
+            .enumerate()
+            .map(run)
+            .fold(Ok(self.empty()), merge);
+
+        result.map_err(|(_, err)| err)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::conftest;
+    use alloc::vec;
+
+    fn pipeline(group_by_mask: bool, aggregate: Aggregate) -> Pipeline {
+        Pipeline {
+            filters: vec!["any(e)".parse().unwrap(), "avoids(z)".parse().unwrap()],
+            group_by_mask,
+            aggregate,
+        }
+    }
+
+    #[test]
+    fn totals() {
+        let strings = conftest::COLLECTION_OF_50_CHARS_STRINGS;
+        let masks: Vec<Mask> = strings
+            .iter()
+            .map(|string| mask_string(string).unwrap())
+            .collect();
+        let kept: Vec<usize> = (0..masks.len())
+            .filter(|&index| pipeline(false, Aggregate::Count).matches(masks[index]))
+            .collect();
+
+        let run = |aggregate| match pipeline(false, aggregate).run(&strings).unwrap() {
+            PipelineResult::Total(total) => total,
+            PipelineResult::Groups(_) => panic!("expected a total"),
+        };
+        assert_eq!(run(Aggregate::Count), Aggregated::Count(kept.len() as u64));
+        assert_eq!(
+            run(Aggregate::Common),
+            Aggregated::Common(
+                kept.iter()
+                    .fold(Mask::ALL, |mask, &index| mask & masks[index])
+            )
+        );
+        assert_eq!(run(Aggregate::Positions), Aggregated::Positions(kept));
+    }
+
+    #[test]
+    fn groups() {
+        let strings = ["the cat", "tac the", "hat", "zest", "eat", "tea"];
+        let PipelineResult::Groups(groups) =
+            pipeline(true, Aggregate::Positions).run(&strings).unwrap()
+        else {
+            panic!("expected groups");
+        };
+
+        assert_eq!(
+            groups,
+            BTreeMap::from([
+                (
+                    mask_string("the cat").unwrap(),
+                    Aggregated::Positions(vec![0, 1])
+                ),
+                (
+                    mask_string("eat").unwrap(),
+                    Aggregated::Positions(vec![4, 5])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn first_error() {
+        let mut strings = vec!["abc"; CHUNK_LENGTH * 3];
+        strings[CHUNK_LENGTH * 2] = "2";
+        strings[CHUNK_LENGTH + 1] = "1";
+
+        assert!(matches!(
+            pipeline(false, Aggregate::Count).run(&strings),
+            Err(LocalError::InvalidCharacter { character: '1' })
+        ));
+    }
+}
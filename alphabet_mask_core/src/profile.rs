@@ -0,0 +1,144 @@
+//! Profiles of the alphabets of documents, gathered in a single pass.
+//!
+//! Profiling is lenient: characters outside the alphabet, such as digits, are
+//! skipped rather than rejected, and flagged instead where they matter.
+//!
+//! ```
+//! use alphabet_mask_core::profile::profile;
+//!
+//! let profiles = profile(&["The quick brown fox jumps over the lazy dog", "Take 2!", "..."]);
+//!
+//! assert_eq!(profiles.pangram, [true, false, false]);
+//! assert_eq!(profiles.has_digits, [false, true, false]);
+//! assert_eq!(profiles.punctuation_only, [false, false, true]);
+//! ```
+//!
+use alloc::vec::Vec;
+
+#[cfg(feature = "parallel")]
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::classify::{CharClassifier, StandardLayout};
+use crate::models::bits::LETTERS_MASK;
+use crate::models::{BitMask, Mask};
+
+/// The profile of one document.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Profile {
+    /// The mask of the characters in the alphabet.
+    pub mask: Mask,
+    /// The proportion of the 26 letters used.
+    pub coverage: f64,
+    /// Whether every letter is used.
+    pub pangram: bool,
+    /// Whether there are any ASCII digits, which the mask skips.
+    pub has_digits: bool,
+    /// Whether there is ASCII punctuation and nothing else besides whitespace.
+    pub punctuation_only: bool,
+}
+
+impl Profile {
+    /// Profile one document.
+    pub fn of(string: &str) -> Self {
+        let mut mask = Mask::EMPTY;
+        let (mut has_digits, mut has_punctuation, mut has_other) = (false, false, false);
+        for character in string.chars() {
+            if let Ok(Some(bit)) = StandardLayout.classify(character) {
+                mask |= Mask::from_bit(bit);
+            }
+            has_digits |= character.is_ascii_digit();
+            has_punctuation |= character.is_ascii_punctuation();
+            has_other |= !(character.is_ascii_punctuation() || character.is_whitespace());
+        }
+
+        let letters = (mask & LETTERS_MASK).len();
+        Self {
+            mask,
+            coverage: f64::from(letters) / f64::from(LETTERS_MASK.len()),
+            pangram: letters == LETTERS_MASK.len(),
+            has_digits,
+            punctuation_only: has_punctuation && !has_other,
+        }
+    }
+}
+
+/// The profiles of many documents, one column per field of [`Profile`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Profiles {
+    /// The mask of each document.
+    pub masks: Vec<Mask>,
+    /// The coverage of each document.
+    pub coverage: Vec<f64>,
+    /// Whether each document is a pangram.
+    pub pangram: Vec<bool>,
+    /// Whether each document has digits.
+    pub has_digits: Vec<bool>,
+    /// Whether each document is punctuation only.
+    pub punctuation_only: Vec<bool>,
+}
+
+impl Profiles {
+    /// Add the profile of one more document.
+    pub fn push(&mut self, profile: Profile) {
+        self.masks.push(profile.mask);
+        self.coverage.push(profile.coverage);
+        self.pangram.push(profile.pangram);
+        self.has_digits.push(profile.has_digits);
+        self.punctuation_only.push(profile.punctuation_only);
+    }
+}
+
+/// Profile every document, in parallel with the `parallel` feature.
+pub fn profile<S: AsRef<str> + Sync>(strings: &[S]) -> Profiles {
+    let of = |string: &S| Profile::of(string.as_ref());
+    #[cfg(feature = "parallel")]
+    let rows: Vec<Profile> = strings.par_iter().map(of).collect();
+    #[cfg(not(feature = "parallel"))]
+    let rows: Vec<Profile> = strings.iter().map(of).collect();
+
+    let mut profiles = Profiles::default();
+    for row in rows {
+        profiles.push(row);
+    }
+    profiles
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mask_string;
+
+    #[test]
+    fn profile_of() {
+        let profile = Profile::of("Pack my box with 5 dozen liquor jugs, five!");
+        assert_eq!(
+            profile.mask,
+            mask_string("Pack my box with dozen liquor jugs, five").unwrap()
+        );
+        assert_eq!(profile.coverage, 1.0);
+        assert!(profile.pangram && profile.has_digits && !profile.punctuation_only);
+
+        let profile = Profile::of("abcd");
+        assert_eq!(profile.coverage, 4.0 / 26.0);
+        assert!(!profile.pangram && !profile.has_digits);
+
+        assert!(Profile::of("?! ...").punctuation_only);
+        assert!(!Profile::of("").punctuation_only);
+        assert!(!Profile::of("   ").punctuation_only);
+        assert!(!Profile::of("1.").punctuation_only);
+        assert!(!Profile::of("…").punctuation_only);
+    }
+
+    #[test]
+    fn columns() {
+        let strings = ["abc", "", "1"];
+        let profiles = profile(&strings);
+
+        assert_eq!(profiles.masks.len(), 3);
+        for (position, string) in strings.iter().enumerate() {
+            let profile = Profile::of(string);
+            assert_eq!(profiles.masks[position], profile.mask);
+            assert_eq!(profiles.has_digits[position], profile.has_digits);
+        }
+    }
+}
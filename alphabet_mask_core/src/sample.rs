@@ -0,0 +1,277 @@
+//! Reproducible random samples of a corpus, for estimating its alphabets
+//! without masking every string, and reservoir samples of streams, for
+//! estimating them in constant memory.
+//!
+//! ```
+//! use alphabet_mask_core::sample::sample_indices;
+//!
+//! let indices = sample_indices(1000, 10, 42);
+//!
+//! assert_eq!(indices.len(), 10);
+//! assert!(indices.windows(2).all(|pair| pair[0] < pair[1]));
+//! assert_eq!(indices, sample_indices(1000, 10, 42));
+//! ```
+//!
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use crate::models::Mask;
+
+/// The number of strings sampled for an estimate, if not specified.
+pub const DEFAULT_SAMPLE_SIZE: usize = 10_000;
+
+/// The z-score of a 95% confidence interval.
+pub const Z_95: f64 = 1.959_963_984_540_054;
+
+/// A small, fast pseudo-random generator, SplitMix64, which gives the same
+/// sequence for the same seed on every platform.
+#[derive(Debug, Clone)]
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    /// A generator starting from `seed`.
+    pub const fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// The next number of the sequence.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number from `0` up to but excluding `bound`, which must be positive.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        // Multiply-shift, whose bias is negligible for the sizes sampled here.
+        ((u128::from(self.next_u64()) * u128::from(bound)) >> 64) as u64
+    }
+}
+
+/// Returns `size` distinct positions from `0` up to but excluding `length`, in
+/// ascending order, chosen uniformly at random from `seed`; every position if
+/// `size` is at least `length`.
+///
+/// The positions are selected in a single pass, keeping only those chosen.
+pub fn sample_indices(length: usize, size: usize, seed: u64) -> Vec<usize> {
+    if size >= length {
+        return (0..length).collect();
+    }
+
+    // Selection sampling: keep each position with the probability of it being
+    // one of the positions still needed among those left.
+    let mut rng = SplitMix64::new(seed);
+    let mut indices = Vec::with_capacity(size);
+    for index in 0..length {
+        let (needed, left) = (size - indices.len(), length - index);
+        if needed == 0 {
+            break;
+        }
+        if rng.below(left as u64) < needed as u64 {
+            indices.push(index);
+        }
+    }
+    indices
+}
+
+/// A uniform random sample of at most `capacity` items of a stream of unknown
+/// length, in constant memory.
+///
+/// Whether an item is sampled is decided before it is seen, so that the items
+/// left out need not be computed at all:
+///
+/// ```
+/// use alphabet_mask_core::sample::Reservoir;
+///
+/// let mut reservoir = Reservoir::new(10, 42);
+/// for item in 0..1000 {
+///     if let Some(slot) = reservoir.offer() {
+///         reservoir.store(slot, item * 2);
+///     }
+/// }
+///
+/// assert_eq!(reservoir.seen(), 1000);
+/// assert_eq!(reservoir.items().len(), 10);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Reservoir<T> {
+    capacity: usize,
+    seen: u64,
+    items: Vec<T>,
+    rng: SplitMix64,
+}
+
+impl<T> Reservoir<T> {
+    /// An empty reservoir of at most `capacity` items, sampling from `seed`.
+    pub fn new(capacity: usize, seed: u64) -> Self {
+        Self {
+            capacity,
+            seen: 0,
+            items: Vec::new(),
+            rng: SplitMix64::new(seed),
+        }
+    }
+
+    /// Count the next item of the stream, returning the slot to [`store`] it in
+    /// if it is sampled.
+    ///
+    /// [`store`]: Self::store
+    pub fn offer(&mut self) -> Option<usize> {
+        self.seen += 1;
+        if self.items.len() < self.capacity {
+            return Some(self.items.len());
+        }
+        let slot = self.rng.below(self.seen);
+        (slot < self.capacity as u64).then_some(slot as usize)
+    }
+
+    /// Store a sampled item in the slot returned by [`offer`](Self::offer).
+    pub fn store(&mut self, slot: usize, item: T) {
+        match self.items.get_mut(slot) {
+            Some(stored) => *stored = item,
+            None => self.items.push(item),
+        }
+    }
+
+    /// Count and maybe sample the next item of the stream.
+    pub fn add(&mut self, item: T) {
+        if let Some(slot) = self.offer() {
+            self.store(slot, item);
+        }
+    }
+
+    /// The number of items counted so far.
+    pub fn seen(&self) -> u64 {
+        self.seen
+    }
+
+    /// The sampled items, in no particular order.
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+}
+
+/// An estimate of a proportion from a sample, with the bounds of its confidence
+/// interval.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    /// The proportion of the sample.
+    pub ratio: f64,
+    /// The lower bound of the interval.
+    pub low: f64,
+    /// The upper bound of the interval.
+    pub high: f64,
+}
+
+#[cfg(feature = "std")]
+impl Estimate {
+    /// The Wilson score interval of `successes` out of `trials`, for the
+    /// z-score `z`, such as [`Z_95`]; unlike the normal approximation, it stays
+    /// within `0` and `1` and is meaningful for proportions close to either.
+    /// Without trials, the interval is from `0` to `1`.
+    pub fn wilson(successes: u64, trials: u64, z: f64) -> Self {
+        if trials == 0 {
+            return Self {
+                ratio: 0.0,
+                low: 0.0,
+                high: 1.0,
+            };
+        }
+
+        let (n, ratio) = (trials as f64, successes as f64 / trials as f64);
+        let z2 = z * z;
+        let centre = (ratio + z2 / (2.0 * n)) / (1.0 + z2 / n);
+        let margin = z * (ratio * (1.0 - ratio) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+        Self {
+            ratio,
+            low: (centre - margin).max(0.0),
+            high: (centre + margin).min(1.0),
+        }
+    }
+}
+
+/// Estimate the proportion of the strings of a population containing each bit
+/// of the mask from the masks of a sample of them, with intervals for `z`.
+#[cfg(feature = "std")]
+pub fn coverage_estimates(masks: &[Mask], z: f64) -> [Estimate; 32] {
+    let mut counts = [0_u64; 32];
+    for mask in masks {
+        for (bit, count) in counts.iter_mut().enumerate() {
+            *count += u64::from(mask.bits() >> bit & 1);
+        }
+    }
+    counts.map(|count| Estimate::wilson(count, masks.len() as u64, z))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sizes() {
+        assert_eq!(sample_indices(5, 10, 0), [0, 1, 2, 3, 4]);
+        assert!(sample_indices(5, 0, 0).is_empty());
+        for seed in 0..100 {
+            let indices = sample_indices(100, 7, seed);
+            assert_eq!(indices.len(), 7);
+            assert!(indices.iter().all(|&index| index < 100));
+        }
+        assert_ne!(sample_indices(1000, 10, 1), sample_indices(1000, 10, 2));
+    }
+
+    #[test]
+    fn uniform() {
+        // Each position is chosen about as often as any other.
+        let mut counts = [0_u32; 10];
+        for seed in 0..10_000 {
+            for index in sample_indices(10, 3, seed) {
+                counts[index] += 1;
+            }
+        }
+        assert!(
+            counts.iter().all(|&count| (2700..3300).contains(&count)),
+            "{counts:?}"
+        );
+    }
+
+    #[test]
+    fn reservoir() {
+        let mut reservoir = Reservoir::new(3, 0);
+        for item in 0..2 {
+            reservoir.add(item);
+        }
+        assert_eq!(reservoir.items(), [0, 1]);
+
+        // Each item of a stream is sampled about as often as any other.
+        let mut counts = [0_u32; 10];
+        for seed in 0..10_000 {
+            let mut reservoir = Reservoir::new(3, seed);
+            for item in 0..10 {
+                reservoir.add(item);
+            }
+            for &item in reservoir.items() {
+                counts[item] += 1;
+            }
+        }
+        assert!(
+            counts.iter().all(|&count| (2700..3300).contains(&count)),
+            "{counts:?}"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn wilson() {
+        let estimate = Estimate::wilson(50, 100, Z_95);
+        assert_eq!(estimate.ratio, 0.5);
+        assert!((estimate.low - 0.4038).abs() < 1e-4 && (estimate.high - 0.5962).abs() < 1e-4);
+
+        let none = Estimate::wilson(0, 100, Z_95);
+        assert!(none.low < 1e-12);
+        assert!(none.high > 0.0 && none.high < 0.05);
+        assert_eq!(Estimate::wilson(0, 0, Z_95).high, 1.0);
+    }
+}
@@ -0,0 +1,143 @@
+//! The Unicode scripts of text, for what a corpus actually contains beyond the
+//! alphabet, such as mixed-language data.
+//!
+//! Unlike masking, no character is an error: each is counted under its script,
+//! such as `Latin`, `Greek` or `Han`, with digits, spaces and most punctuation
+//! under `Common`, and combining marks under `Inherited`.
+//!
+//! ```
+//! use alphabet_mask_core::mask_to_chars;
+//! use alphabet_mask_core::scripts::ScriptCoverage;
+//!
+//! let coverage = ScriptCoverage::gather(&["the cat", "καλή νύχτα", "café 42"]);
+//!
+//! assert_eq!(coverage.scripts["Greek"].strings, 1);
+//! assert_eq!(coverage.scripts["Latin"].characters, 10);
+//! assert_eq!(mask_to_chars(coverage.union), " acefht");
+//! ```
+//!
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+#[cfg(feature = "parallel")]
+use rayon::iter::ParallelIterator;
+#[cfg(feature = "parallel")]
+use rayon::slice::ParallelSlice;
+use unicode_script::{Script, UnicodeScript};
+
+use crate::models::{char_bit, Mask};
+
+/// The number of strings per chunk gathered in parallel.
+#[cfg(feature = "parallel")]
+const CHUNK_LENGTH: usize = 4096;
+
+/// The occurrences of one script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ScriptCount {
+    /// The number of strings with any character of the script.
+    pub strings: u64,
+    /// The number of characters of the script in every string.
+    pub characters: u64,
+}
+
+/// The scripts of a collection of strings, with the combined mask of their
+/// characters within the alphabet.
+///
+/// Like [`CorpusStats`](crate::stats::CorpusStats), two can be merged, so they
+/// are gathered per chunk in parallel then reduced.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ScriptCoverage {
+    /// The number of strings.
+    pub strings: u64,
+    /// The union of the masks of every string, ignoring the characters outside
+    /// the alphabet.
+    pub union: Mask,
+    /// The occurrences of each script found, by its full name.
+    pub scripts: BTreeMap<&'static str, ScriptCount>,
+}
+
+impl ScriptCoverage {
+    /// Add a string to these counts.
+    pub fn add(&mut self, string: &str) {
+        let mut scripts: Vec<(Script, u64)> = Vec::new();
+        for character in string.chars() {
+            if let Ok(bit) = char_bit(character) {
+                self.union |= Mask(1 << bit);
+            }
+            let script = character.script();
+            match scripts.iter_mut().find(|(found, _)| *found == script) {
+                Some((_, count)) => *count += 1,
+                None => scripts.push((script, 1)),
+            }
+        }
+
+        self.strings += 1;
+        for (script, characters) in scripts {
+            let count = self.scripts.entry(script.full_name()).or_default();
+            count.strings += 1;
+            count.characters += characters;
+        }
+    }
+
+    /// Merge two sets of counts, as if all their strings were added to one.
+    pub fn merge(mut self, other: Self) -> Self {
+        self.strings += other.strings;
+        self.union |= other.union;
+        for (script, other) in other.scripts {
+            let count = self.scripts.entry(script).or_default();
+            count.strings += other.strings;
+            count.characters += other.characters;
+        }
+        self
+    }
+
+    /// Count the scripts of a slice of strings, in parallel chunks with the
+    /// `parallel` feature.
+    pub fn gather<S: AsRef<str> + Sync>(strings: &[S]) -> Self {
+        let gather = |chunk: &[S]| {
+            chunk.iter().fold(Self::default(), |mut coverage, string| {
+                coverage.add(string.as_ref());
+                coverage
+            })
+        };
+
+        #[cfg(feature = "parallel")]
+        return strings
+            .par_chunks(CHUNK_LENGTH)
+            .map(gather)
+            .reduce(Self::default, Self::merge);
+        #[cfg(not(feature = "parallel"))]
+        return gather(strings);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::conftest;
+
+    #[test]
+    fn merge_equals_gather() {
+        let strings = ["Привет, мир", "hello", "日本語", "e\u{301}"];
+        let merged =
+            ScriptCoverage::gather(&strings[..2]).merge(ScriptCoverage::gather(&strings[2..]));
+
+        assert_eq!(merged, ScriptCoverage::gather(&strings));
+        assert_eq!(merged.scripts["Cyrillic"].characters, 9);
+        assert_eq!(merged.scripts["Common"].strings, 1);
+        assert_eq!(merged.scripts["Inherited"].characters, 1);
+        assert_eq!(merged.scripts["Han"].characters, 3);
+    }
+
+    #[test]
+    fn latin_union() {
+        let strings = conftest::COLLECTION_OF_50_CHARS_STRINGS;
+        let coverage = ScriptCoverage::gather(&strings);
+
+        assert_eq!(
+            coverage.union,
+            crate::find_union_mask(strings.iter()).unwrap()
+        );
+        assert_eq!(coverage.strings as usize, strings.len());
+    }
+}
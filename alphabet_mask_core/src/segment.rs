@@ -0,0 +1,95 @@
+//! Splitting a sequence of strings into contiguous runs which share enough of
+//! their alphabet, such as the stylistically consistent blocks of a transcript.
+//!
+//! ```
+//! use alphabet_mask_core::mask_string;
+//! use alphabet_mask_core::segment::{segment_by_common_mask, Segment};
+//!
+//! let masks = ["abcd", "abce", "xyz"].map(|string| mask_string(string).unwrap());
+//!
+//! assert_eq!(
+//!     segment_by_common_mask(&masks, 3),
+//!     [
+//!         Segment { start: 0, end: 2, common: mask_string("abc").unwrap() },
+//!         Segment { start: 2, end: 3, common: mask_string("xyz").unwrap() },
+//!     ]
+//! );
+//! ```
+//!
+use alloc::vec::Vec;
+
+use crate::models::Mask;
+
+/// A run of strings, from `start` up to but excluding `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    /// The position of the first string of the run.
+    pub start: usize,
+    /// The position after the last string of the run.
+    pub end: usize,
+    /// The common mask of the strings of the run.
+    pub common: Mask,
+}
+
+/// Split `masks` into maximal contiguous runs whose common mask keeps at least
+/// `min_letters` letters, walking them in order.
+///
+/// Each run is extended for as long as the next mask keeps enough letters in
+/// common, then the next run starts from that mask. A mask with fewer than
+/// `min_letters` letters of its own is a run by itself, with the same common
+/// mask. No masks give no runs.
+pub fn segment_by_common_mask(masks: &[Mask], min_letters: u32) -> Vec<Segment> {
+    span!(
+        DEBUG,
+        "segment_by_common_mask",
+        masks = masks.len(),
+        min_letters
+    );
+    let mut segments: Vec<Segment> = Vec::new();
+
+    for (index, &mask) in masks.iter().enumerate() {
+        match segments.last_mut() {
+            Some(segment)
+                if (segment.common & mask).letter_count(false) >= min_letters
+                    && segment.common.letter_count(false) >= min_letters =>
+            {
+                segment.end = index + 1;
+                segment.common &= mask;
+            }
+            _ => segments.push(Segment {
+                start: index,
+                end: index + 1,
+                common: mask,
+            }),
+        }
+    }
+    segments
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mask_string;
+
+    fn segments(strings: &[&str], min_letters: u32) -> Vec<(usize, usize)> {
+        let masks: Vec<Mask> = strings
+            .iter()
+            .map(|string| mask_string(string).unwrap())
+            .collect();
+        segment_by_common_mask(&masks, min_letters)
+            .into_iter()
+            .map(|segment| (segment.start, segment.end))
+            .collect()
+    }
+
+    #[test]
+    fn runs() {
+        let strings = ["the cat", "a hat", "that", "xyz", "zyx", "y"];
+
+        assert_eq!(segments(&strings, 2), [(0, 3), (3, 5), (5, 6)]);
+        assert_eq!(segments(&strings, 0), [(0, 6)]);
+        // A string short of letters stands alone.
+        assert_eq!(segments(&["ab", "a", "ab"], 2), [(0, 1), (1, 2), (2, 3)]);
+        assert_eq!(segments(&[], 1), []);
+    }
+}
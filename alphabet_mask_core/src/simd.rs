@@ -0,0 +1,115 @@
+//! A fast path for masking ASCII strings, enabled by the `simd` feature.
+//!
+//! Every byte is classified through a lookup table, in fixed-size blocks without
+//! any early exit, so that the compiler can unroll and vectorise the loop where
+//! the target allows; the first invalid character is only searched for once a
+//! block is known to contain one.
+//!
+use crate::models::bits::{
+    APOSTROPHE_BIT, COMMA_BIT, DOUBLE_QUOTE_BIT, FULL_STOP_BIT, HYPHEN_BIT, SPACE_BIT,
+};
+use crate::models::{LocalError, Mask};
+
+/// The number of bytes classified together before checking for invalid characters.
+const BLOCK_SIZE: usize = 16;
+
+/// The mask bit of every byte, or `0` if the byte is not a valid character.
+///
+/// This mirrors `char_bit` for every ASCII byte; bytes above 127 are all invalid.
+const TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut byte = 0;
+    while byte < 128 {
+        table[byte] = match byte as u8 {
+            b' ' => 1 << SPACE_BIT,
+            b'.' => 1 << FULL_STOP_BIT,
+            b',' => 1 << COMMA_BIT,
+            b'\'' => 1 << APOSTROPHE_BIT,
+            b'-' => 1 << HYPHEN_BIT,
+            b'"' => 1 << DOUBLE_QUOTE_BIT,
+            v if v & 64 == 0 || v == b'@' || (v >= b'[' && v <= b'`') || v > b'z' => 0,
+            v => 1 << (v & 31),
+        };
+        byte += 1;
+    }
+    table
+};
+
+/// Mask a block of bytes, returning the mask and whether any byte was invalid.
+#[inline(always)]
+fn mask_block(block: &[u8]) -> (u32, bool) {
+    block.iter().fold((0, false), |(mask, invalid), &byte| {
+        let bit = TABLE[byte as usize];
+        (mask | bit, invalid | (bit == 0))
+    })
+}
+
+/// Mask a string consisting only of ASCII characters.
+pub(crate) fn mask_ascii(string: &str) -> Result<Mask, LocalError> {
+    let bytes = string.as_bytes();
+    let blocks = bytes.chunks_exact(BLOCK_SIZE);
+    let remainder = blocks.remainder();
+
+    let (mask, invalid) =
+        blocks
+            .chain(core::iter::once(remainder))
+            .fold((0, false), |(mask, invalid), block| {
+                let (block_mask, block_invalid) = mask_block(block);
+                (mask | block_mask, invalid | block_invalid)
+            });
+
+    if invalid {
+        // This is safe because at least one byte was found to be invalid.
+        let position = bytes
+            .iter()
+            .position(|&byte| TABLE[byte as usize] == 0)
+            .unwrap();
+        Err(LocalError::outside_alphabet(char::from(bytes[position])).at(position))
+    } else {
+        Ok(Mask(mask))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{conftest, mask_chars};
+
+    #[test]
+    fn every_ascii_char() {
+        let mut buffer = [0; 4];
+        for byte in 0..128_u8 {
+            let string = (byte as char).encode_utf8(&mut buffer);
+
+            assert_eq!(
+                mask_ascii(string).ok(),
+                mask_chars(string).ok(),
+                "{byte} differs"
+            );
+        }
+    }
+
+    #[test]
+    fn strings() {
+        for string in conftest::COLLECTION_OF_50_CHARS_STRINGS {
+            assert_eq!(mask_ascii(string).unwrap(), mask_chars(string).unwrap());
+        }
+    }
+
+    #[test]
+    fn first_invalid_char() {
+        let result = mask_ascii("the quick brown fox jumps over 1 lazy dog 2");
+
+        assert!(matches!(
+            result,
+            Err(LocalError::InvalidCharacter { character: '1' })
+        ));
+        assert!(matches!(
+            mask_ascii("abc\0"),
+            Err(LocalError::ControlCharacter {
+                character: '\0',
+                position: 3
+            })
+        ));
+    }
+}
@@ -0,0 +1,915 @@
+//! Corpus-level statistics, gathered in a single pass.
+//!
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+#[cfg(feature = "parallel")]
+use rayon::slice::ParallelSlice;
+
+use crate::classify::{CharClass, CharClasses};
+#[cfg(feature = "alloc")]
+use crate::codec::{Decoder, Encode, Format};
+use crate::mask_string;
+#[cfg(feature = "alloc")]
+use crate::models::{bit_char, bits::LETTERS_MASK};
+use crate::models::{char_bit, LocalError, Mask};
+
+/// Statistics about a collection of strings.
+///
+/// Two sets of statistics can be merged, so they can be gathered per chunk in
+/// parallel then reduced; with the `alloc` feature they can also be encoded, so
+/// that an accumulation can be persisted and resumed later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CorpusStats {
+    /// The number of strings.
+    pub strings: u64,
+    /// The total length of the strings in bytes.
+    pub bytes: u64,
+    /// The common mask of every string.
+    pub common: Mask,
+    /// The union of the masks of every string.
+    pub union: Mask,
+    /// The number of strings containing each bit of the mask.
+    pub coverage: [u64; 32],
+}
+
+impl Default for CorpusStats {
+    fn default() -> Self {
+        Self {
+            strings: 0,
+            bytes: 0,
+            common: Mask::ALL,
+            union: Mask::EMPTY,
+            coverage: [0; 32],
+        }
+    }
+}
+
+impl CorpusStats {
+    /// Add one string to these statistics.
+    pub fn add(&mut self, string: &str) -> Result<(), LocalError> {
+        let mask = mask_string(string)?;
+
+        self.strings += 1;
+        self.bytes += string.len() as u64;
+        self.common &= mask;
+        self.union |= mask;
+        for (bit, count) in self.coverage.iter_mut().enumerate() {
+            *count += u64::from(mask.bits() >> bit & 1);
+        }
+
+        Ok(())
+    }
+
+    /// Add every string of an iterator to these statistics.
+    pub fn extend<'s>(
+        mut self,
+        strings: impl Iterator<Item = &'s str>,
+    ) -> Result<Self, LocalError> {
+        for string in strings {
+            self.add(string)?;
+        }
+
+        Ok(self)
+    }
+
+    /// Merge two sets of statistics, as if all their strings were added to one.
+    pub fn merge(mut self, other: Self) -> Self {
+        self.strings += other.strings;
+        self.bytes += other.bytes;
+        self.common &= other.common;
+        self.union |= other.union;
+        for (count, other) in self.coverage.iter_mut().zip(other.coverage) {
+            *count += other;
+        }
+
+        self
+    }
+}
+
+impl CorpusStats {
+    /// Gather the statistics of a slice of strings, in parallel chunks with the
+    /// `parallel` feature.
+    pub fn gather<S: AsRef<str> + Sync>(strings: &[S]) -> Result<Self, LocalError> {
+        let gather = |chunk: &[S]| Self::default().extend(chunk.iter().map(S::as_ref));
+
+        #[cfg(feature = "parallel")]
+        return crate::reduce_in_order(
+            strings.par_chunks(CHUNK_LENGTH).enumerate(),
+            Self::default(),
+            gather,
+            Self::merge,
+        );
+        #[cfg(not(feature = "parallel"))]
+        return gather(strings);
+    }
+
+    /// The proportion of strings containing each bit of the mask, or zero if
+    /// there are no strings.
+    pub fn coverage_ratios(&self) -> [f64; 32] {
+        let strings = self.strings.max(1) as f64;
+        self.coverage.map(|count| count as f64 / strings)
+    }
+
+    /// The mask of the characters contained in at least `threshold` of the
+    /// strings, a proportion from `0` to `1`; with `1`, the same as `common`.
+    ///
+    /// Unlike the common mask, a few noisy strings lacking a character do not
+    /// remove it. Without strings, every bit is set, as for the common mask.
+    ///
+    /// ```
+    /// use alphabet_mask_core::mask_string;
+    /// use alphabet_mask_core::stats::CorpusStats;
+    ///
+    /// let stats = CorpusStats::gather(&["the cat", "the hat", "a bat", "xyz"]).unwrap();
+    ///
+    /// assert_eq!(stats.threshold_mask(0.75), mask_string(" at").unwrap());
+    /// assert_eq!(stats.threshold_mask(1.0), stats.common);
+    /// ```
+    pub fn threshold_mask(&self, threshold: f64) -> Mask {
+        if self.strings == 0 {
+            return Mask::ALL;
+        }
+        mask_at_least(self.coverage_ratios(), threshold)
+    }
+
+    /// The `k` letters contained in the most strings, with their counts; see
+    /// [`top_letters`].
+    #[cfg(feature = "alloc")]
+    pub fn top_letters(&self, k: usize) -> Vec<(char, u64)> {
+        top_letters(&self.coverage, k)
+    }
+}
+
+/// The `k` letters with the largest counts in `coverage`, indexed by bit, from
+/// the most to the least; ties are alphabetical, and letters in no string are
+/// left out.
+///
+/// ```
+/// use alphabet_mask_core::stats::CorpusStats;
+///
+/// let stats = CorpusStats::gather(&["the cat", "the hat", "a bat"]).unwrap();
+///
+/// assert_eq!(stats.top_letters(3), [('a', 3), ('t', 3), ('e', 2)]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn top_letters(coverage: &[u64; 32], k: usize) -> Vec<(char, u64)> {
+    let mut letters: Vec<(char, u64)> = (0..Mask::WIDTH)
+        .filter(|&bit| LETTERS_MASK.contains_bit(bit) && coverage[usize::from(bit)] > 0)
+        .map(|bit| (bit_char(bit), coverage[usize::from(bit)]))
+        .collect();
+    letters.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    letters.truncate(k);
+    letters
+}
+
+/// The mask of the bits whose ratio is at least `threshold`.
+fn mask_at_least(ratios: [f64; 32], threshold: f64) -> Mask {
+    ratios
+        .iter()
+        .enumerate()
+        .filter(|(_, &ratio)| ratio >= threshold)
+        .fold(Mask::EMPTY, |mask, (bit, _)| mask | Mask(1 << bit))
+}
+
+/// The coverage of each bit of the mask, weighting each string, such as by its
+/// length or importance, so that documents of very different sizes can be
+/// aggregated.
+///
+/// Like [`CorpusStats`], two can be merged, so they are gathered per chunk in
+/// parallel then reduced.
+///
+/// ```
+/// use alphabet_mask_core::mask_string;
+/// use alphabet_mask_core::stats::WeightedCoverage;
+///
+/// let coverage = WeightedCoverage::gather(&["the cat", "xyz"], &[3.0, 1.0]).unwrap();
+///
+/// assert_eq!(coverage.ratios()[1], 0.75);
+/// assert_eq!(coverage.threshold_mask(0.5), mask_string("the cat").unwrap());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct WeightedCoverage {
+    /// The total weight of the strings.
+    pub total: f64,
+    /// The total weight of the strings containing each bit of the mask.
+    pub coverage: [f64; 32],
+}
+
+impl WeightedCoverage {
+    /// Add one string of `weight` to the coverage.
+    pub fn add(&mut self, string: &str, weight: f64) -> Result<(), LocalError> {
+        let mask = mask_string(string)?;
+
+        self.total += weight;
+        for (bit, total) in self.coverage.iter_mut().enumerate() {
+            if mask.bits() >> bit & 1 == 1 {
+                *total += weight;
+            }
+        }
+        Ok(())
+    }
+
+    /// Merge two coverages, as if all their strings were added to one.
+    pub fn merge(mut self, other: Self) -> Self {
+        self.total += other.total;
+        for (total, other) in self.coverage.iter_mut().zip(other.coverage) {
+            *total += other;
+        }
+        self
+    }
+
+    /// Gather the coverage of `strings`, each weighted by the weight at the same
+    /// position of `weights`, in parallel chunks with the `parallel` feature; any
+    /// string or weight beyond the end of the other is ignored.
+    pub fn gather<S: AsRef<str> + Sync>(
+        strings: &[S],
+        weights: &[f64],
+    ) -> Result<Self, LocalError> {
+        let length = strings.len().min(weights.len());
+        let gather = |(strings, weights): (&[S], &[f64])| {
+            let mut coverage = Self::default();
+            for (string, &weight) in strings.iter().zip(weights) {
+                coverage.add(string.as_ref(), weight)?;
+            }
+            Ok(coverage)
+        };
+
+        #[cfg(feature = "parallel")]
+        return crate::reduce_in_order(
+            strings[..length]
+                .par_chunks(CHUNK_LENGTH)
+                .zip(weights[..length].par_chunks(CHUNK_LENGTH))
+                .enumerate(),
+            Self::default(),
+            gather,
+            Self::merge,
+        );
+        #[cfg(not(feature = "parallel"))]
+        return gather((&strings[..length], &weights[..length]));
+    }
+
+    /// The proportion of the total weight held by the strings containing each
+    /// bit of the mask, or zero if the total weight is zero.
+    pub fn ratios(&self) -> [f64; 32] {
+        if self.total == 0.0 {
+            return [0.0; 32];
+        }
+        self.coverage.map(|total| total / self.total)
+    }
+
+    /// The mask of the characters contained in strings holding at least
+    /// `threshold` of the total weight, as [`CorpusStats::threshold_mask`]; with
+    /// a total weight of zero, every bit is set.
+    pub fn threshold_mask(&self, threshold: f64) -> Mask {
+        if self.total == 0.0 {
+            return Mask::ALL;
+        }
+        mask_at_least(self.ratios(), threshold)
+    }
+}
+
+/// The number of strings containing each bit of the mask, so that strings can
+/// be removed as well as added, such as to keep the alphabets of a sliding
+/// window of records.
+///
+/// ```
+/// use alphabet_mask_core::mask_to_chars;
+/// use alphabet_mask_core::stats::CountedAlphabet;
+///
+/// let mut counted = CountedAlphabet::default();
+/// for string in ["the cat", "the hat", "xyz"] {
+///     counted.add(string).unwrap();
+/// }
+/// assert_eq!(mask_to_chars(counted.common()), "");
+///
+/// assert!(counted.remove("xyz").unwrap());
+/// assert_eq!(mask_to_chars(counted.common()), " aeht");
+/// assert_eq!(mask_to_chars(counted.union()), " aceht");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CountedAlphabet {
+    /// The number of strings.
+    pub strings: u64,
+    /// The number of strings containing each bit of the mask.
+    pub counts: [u64; 32],
+}
+
+impl CountedAlphabet {
+    /// Add the mask of a string.
+    pub fn add_mask(&mut self, mask: Mask) {
+        self.strings += 1;
+        for (bit, count) in self.counts.iter_mut().enumerate() {
+            *count += u64::from(mask.bits() >> bit & 1);
+        }
+    }
+
+    /// Remove the mask of a string added before, returning `false` and leaving
+    /// the counts unchanged if no such string can have been added.
+    pub fn remove_mask(&mut self, mask: Mask) -> bool {
+        let removable = self.strings > 0
+            && self
+                .counts
+                .iter()
+                .enumerate()
+                .all(|(bit, &count)| count > 0 || mask.bits() >> bit & 1 == 0);
+        if removable {
+            self.strings -= 1;
+            for (bit, count) in self.counts.iter_mut().enumerate() {
+                *count -= u64::from(mask.bits() >> bit & 1);
+            }
+        }
+        removable
+    }
+
+    /// Add a string.
+    pub fn add(&mut self, string: &str) -> Result<(), LocalError> {
+        self.add_mask(mask_string(string)?);
+        Ok(())
+    }
+
+    /// Remove a string added before; see [`remove_mask`](Self::remove_mask).
+    pub fn remove(&mut self, string: &str) -> Result<bool, LocalError> {
+        Ok(self.remove_mask(mask_string(string)?))
+    }
+
+    /// The characters contained in every string; without strings, every bit is
+    /// set, as for the common mask.
+    pub fn common(&self) -> Mask {
+        self.mask_where(|count| count == self.strings)
+    }
+
+    /// The characters contained in any string.
+    pub fn union(&self) -> Mask {
+        self.mask_where(|count| count > 0)
+    }
+
+    fn mask_where(&self, f: impl Fn(u64) -> bool) -> Mask {
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| f(count))
+            .fold(Mask::EMPTY, |mask, (bit, _)| mask | Mask(1 << bit))
+    }
+}
+
+/// The number of times each character is directly followed by each other in a
+/// collection of strings, as a cheap stylometric fingerprint to go with their
+/// masks.
+///
+/// `counts[a][b]` is the number of times the character of bit `a` is followed
+/// by that of bit `b`; letters are counted regardless of case, and pairs are
+/// only counted within a string. Like [`CorpusStats`], two can be merged, so
+/// they are gathered per chunk in parallel then reduced.
+///
+/// ```
+/// use alphabet_mask_core::models::char_bit;
+/// use alphabet_mask_core::stats::Transitions;
+///
+/// let transitions = Transitions::gather(&["The cat", "the hat"]).unwrap();
+/// let (t, h) = (char_bit('t').unwrap(), char_bit('h').unwrap());
+///
+/// assert_eq!(transitions.counts[usize::from(t)][usize::from(h)], 2);
+/// assert_eq!(transitions.pairs(), 12);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Transitions {
+    /// The number of times each bit is followed by each other.
+    pub counts: [[u64; 32]; 32],
+}
+
+impl Transitions {
+    /// Count the pairs of adjacent characters of a string; a string with a
+    /// character outside the alphabet is not counted at all.
+    pub fn add(&mut self, string: &str) -> Result<(), LocalError> {
+        mask_string(string)?;
+
+        let mut bits = string
+            .chars()
+            .filter_map(|character| char_bit(character).ok().map(usize::from));
+        if let Some(mut previous) = bits.next() {
+            for bit in bits {
+                self.counts[previous][bit] += 1;
+                previous = bit;
+            }
+        }
+        Ok(())
+    }
+
+    /// Merge two sets of counts, as if all their strings were added to one.
+    pub fn merge(mut self, other: Self) -> Self {
+        for (row, other) in self.counts.iter_mut().zip(other.counts) {
+            for (count, other) in row.iter_mut().zip(other) {
+                *count += other;
+            }
+        }
+        self
+    }
+
+    /// Count the pairs of a slice of strings, in parallel chunks with the
+    /// `parallel` feature.
+    pub fn gather<S: AsRef<str> + Sync>(strings: &[S]) -> Result<Self, LocalError> {
+        let gather = |chunk: &[S]| {
+            let mut transitions = Self::default();
+            for string in chunk {
+                transitions.add(string.as_ref())?;
+            }
+            Ok(transitions)
+        };
+
+        #[cfg(feature = "parallel")]
+        return crate::reduce_in_order(
+            strings.par_chunks(CHUNK_LENGTH).enumerate(),
+            Self::default(),
+            gather,
+            Self::merge,
+        );
+        #[cfg(not(feature = "parallel"))]
+        return gather(strings);
+    }
+
+    /// The total number of pairs counted.
+    pub fn pairs(&self) -> u64 {
+        self.counts.iter().flatten().sum()
+    }
+}
+
+/// The masks of the parts of a single text, such as its tokens or lines, with their
+/// common and combined masks.
+///
+/// ```
+/// use alphabet_mask_core::mask_to_chars;
+/// use alphabet_mask_core::stats::PartMasks;
+///
+/// let tokens = PartMasks::tokens("the  cat\tsat").unwrap();
+///
+/// assert_eq!(tokens.masks.len(), 3);
+/// assert_eq!(mask_to_chars(tokens.common), "t");
+/// assert_eq!(mask_to_chars(tokens.union), "acehst");
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartMasks {
+    /// The mask of each part, in order.
+    pub masks: Vec<Mask>,
+    /// The characters contained in every part; without parts, every bit is set.
+    pub common: Mask,
+    /// The characters contained in any part.
+    pub union: Mask,
+}
+
+#[cfg(feature = "alloc")]
+impl PartMasks {
+    /// Mask each of `parts`, in parallel with the `parallel` feature.
+    pub fn gather(parts: &[&str]) -> Result<Self, LocalError> {
+        span!(DEBUG, "gather_part_masks", parts = parts.len());
+        #[cfg(feature = "parallel")]
+        let masks: Result<Vec<_>, _> = parts.par_iter().map(|part| mask_string(part)).collect();
+        #[cfg(not(feature = "parallel"))]
+        let masks: Result<Vec<_>, _> = parts.iter().map(|part| mask_string(part)).collect();
+
+        Ok(Self::from_masks(masks?))
+    }
+
+    /// Mask each token of `text`, split on any whitespace as by
+    /// [`str::split_whitespace`].
+    pub fn tokens(text: &str) -> Result<Self, LocalError> {
+        Self::gather(&text.split_whitespace().collect::<Vec<_>>())
+    }
+
+    /// Mask each line of `text`, split on line endings of `\n` or `\r\n` as by
+    /// [`str::lines`], so that a final line ending does not add an empty line.
+    pub fn lines(text: &str) -> Result<Self, LocalError> {
+        Self::gather(&text.lines().collect::<Vec<_>>())
+    }
+
+    fn from_masks(masks: Vec<Mask>) -> Self {
+        let (common, union) = masks
+            .iter()
+            .fold((Mask::ALL, Mask::EMPTY), |(common, union), &mask| {
+                (common & mask, union | mask)
+            });
+        Self {
+            masks,
+            common,
+            union,
+        }
+    }
+}
+
+/// The number of strings per chunk gathered in parallel.
+#[cfg(feature = "parallel")]
+const CHUNK_LENGTH: usize = 4096;
+
+/// A comparison of the statistics of two collections of strings, such as a
+/// corpus before and after cleaning.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CorpusComparison {
+    /// The statistics of the first collection.
+    pub a: CorpusStats,
+    /// The statistics of the second collection.
+    pub b: CorpusStats,
+}
+
+impl CorpusComparison {
+    /// The characters used only by the first collection.
+    pub fn only_a(&self) -> Mask {
+        self.a.union & !self.b.union
+    }
+
+    /// The characters used only by the second collection.
+    pub fn only_b(&self) -> Mask {
+        self.b.union & !self.a.union
+    }
+
+    /// The change in the proportion of strings containing each bit of the mask,
+    /// from the first collection to the second.
+    pub fn coverage_deltas(&self) -> [f64; 32] {
+        let (a, b) = (self.a.coverage_ratios(), self.b.coverage_ratios());
+        core::array::from_fn(|bit| b[bit] - a[bit])
+    }
+}
+
+/// The characters gained and lost by the common and combined alphabets of a
+/// corpus from one version to the next, such as between dataset releases.
+///
+/// ```
+/// use alphabet_mask_core::mask_to_chars;
+/// use alphabet_mask_core::stats::{AlphabetDiff, CorpusStats};
+///
+/// let old = CorpusStats::gather(&["cafe", "face"]).unwrap();
+/// let new = CorpusStats::gather(&["cafes", "faces", "ace"]).unwrap();
+/// let diff = AlphabetDiff::between((old.common, old.union), (new.common, new.union));
+///
+/// assert_eq!(mask_to_chars(diff.union_gained), "s");
+/// assert_eq!(mask_to_chars(diff.common_lost), "f");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlphabetDiff {
+    /// The characters of the new combined alphabet but not the old.
+    pub union_gained: Mask,
+    /// The characters of the old combined alphabet but not the new.
+    pub union_lost: Mask,
+    /// The characters of the new common alphabet but not the old.
+    pub common_gained: Mask,
+    /// The characters of the old common alphabet but not the new.
+    pub common_lost: Mask,
+}
+
+impl AlphabetDiff {
+    /// The changes from the alphabets `old` to `new`, each given as
+    /// `(common, union)`.
+    pub fn between(old: (Mask, Mask), new: (Mask, Mask)) -> Self {
+        let ((old_common, old_union), (new_common, new_union)) = (old, new);
+        Self {
+            union_gained: new_union & !old_union,
+            union_lost: old_union & !new_union,
+            common_gained: new_common & !old_common,
+            common_lost: old_common & !new_common,
+        }
+    }
+}
+
+/// Compare two collections of strings.
+///
+/// ```
+/// use alphabet_mask_core::stats::compare_corpora;
+/// use alphabet_mask_core::mask_to_chars;
+///
+/// let comparison = compare_corpora(&["cafe", "face"], &["cafe", "cafes"]).unwrap();
+/// assert_eq!(mask_to_chars(comparison.only_b()), "s");
+/// assert_eq!(comparison.coverage_deltas()[19], 0.5);
+/// ```
+pub fn compare_corpora<S: AsRef<str> + Sync>(
+    a: &[S],
+    b: &[S],
+) -> Result<CorpusComparison, LocalError> {
+    Ok(CorpusComparison {
+        a: CorpusStats::gather(a)?,
+        b: CorpusStats::gather(b)?,
+    })
+}
+
+/// The number of character classes.
+const CLASSES: usize = CharClass::ALL.len();
+
+/// Statistics about the [`CharClass`]es used by a collection of strings, such as
+/// passwords audited against a composition policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassStats {
+    /// The number of strings.
+    pub strings: u64,
+    /// The classes used by every string.
+    pub common: Mask,
+    /// The classes used by any string.
+    pub union: Mask,
+    /// The number of strings using each class, in bit order.
+    pub coverage: [u64; CLASSES],
+    /// The number of strings using exactly `n` classes, at index `n`.
+    pub class_counts: [u64; CLASSES + 1],
+}
+
+impl Default for ClassStats {
+    fn default() -> Self {
+        Self {
+            strings: 0,
+            common: Mask::ALL,
+            union: Mask::EMPTY,
+            coverage: [0; CLASSES],
+            class_counts: [0; CLASSES + 1],
+        }
+    }
+}
+
+impl ClassStats {
+    /// Add the class mask of one string to these statistics.
+    pub fn add_mask(&mut self, mask: Mask) {
+        self.strings += 1;
+        self.common &= mask;
+        self.union |= mask;
+        for (bit, count) in self.coverage.iter_mut().enumerate() {
+            *count += u64::from(mask.bits() >> bit & 1);
+        }
+        self.class_counts[mask.len() as usize] += 1;
+    }
+
+    /// Add one string to these statistics.
+    pub fn add(&mut self, string: &str) {
+        self.add_mask(CharClasses::mask(string));
+    }
+
+    /// Merge two sets of statistics, as if all their strings were added to one.
+    pub fn merge(mut self, other: Self) -> Self {
+        self.strings += other.strings;
+        self.common &= other.common;
+        self.union |= other.union;
+        for (count, other) in self.coverage.iter_mut().zip(other.coverage) {
+            *count += other;
+        }
+        for (count, other) in self.class_counts.iter_mut().zip(other.class_counts) {
+            *count += other;
+        }
+
+        self
+    }
+}
+
+/// Returns the class mask of each string, with the statistics of all of them;
+/// the masks are computed in parallel with the `parallel` feature.
+///
+/// ```
+/// use alphabet_mask_core::classify::CharClass;
+/// use alphabet_mask_core::stats::analyse_classes;
+///
+/// let (masks, stats) = analyse_classes(&["hunter2", "Tr0ub4dor&3", "letmein"]);
+/// assert_eq!(masks[0], CharClass::Lower.mask() | CharClass::Digit.mask());
+/// assert_eq!(stats.coverage[CharClass::Upper as usize], 1);
+/// assert_eq!(stats.class_counts, [0, 1, 1, 0, 1, 0]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn analyse_classes<S: AsRef<str> + Sync>(strings: &[S]) -> (Vec<Mask>, ClassStats) {
+    let mask = |string: &S| CharClasses::mask(string.as_ref());
+    #[cfg(feature = "parallel")]
+    let masks: Vec<Mask> = strings.par_iter().map(mask).collect();
+    #[cfg(not(feature = "parallel"))]
+    let masks: Vec<Mask> = strings.iter().map(mask).collect();
+
+    let mut stats = ClassStats::default();
+    for &mask in &masks {
+        stats.add_mask(mask);
+    }
+
+    (masks, stats)
+}
+
+#[cfg(feature = "alloc")]
+impl Encode for CorpusStats {
+    const FORMAT: Format = Format {
+        kind: "corpus statistics",
+        magic: *b"AS",
+        version: 1,
+    };
+
+    fn encode_body(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.strings.to_le_bytes());
+        out.extend_from_slice(&self.bytes.to_le_bytes());
+        out.extend_from_slice(&self.common.bits().to_le_bytes());
+        out.extend_from_slice(&self.union.bits().to_le_bytes());
+        for count in self.coverage {
+            out.extend_from_slice(&count.to_le_bytes());
+        }
+    }
+
+    fn decode_body(decoder: &mut Decoder<'_>) -> Result<Self, LocalError> {
+        let mut stats = Self {
+            strings: decoder.u64()?,
+            bytes: decoder.u64()?,
+            common: Mask(decoder.u32()?),
+            union: Mask(decoder.u32()?),
+            coverage: [0; 32],
+        };
+        for count in stats.coverage.iter_mut() {
+            *count = decoder.u64()?;
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::conftest;
+
+    #[test]
+    fn counted_alphabet_matches_stats() {
+        let strings = conftest::COLLECTION_OF_50_CHARS_STRINGS;
+        let mut counted = CountedAlphabet::default();
+        for string in strings {
+            counted.add(string).unwrap();
+        }
+        for string in &strings[..5] {
+            assert!(counted.remove(string).unwrap());
+        }
+
+        let stats = CorpusStats::default()
+            .extend(strings[5..].iter().copied())
+            .unwrap();
+        assert_eq!(counted.common(), stats.common);
+        assert_eq!(counted.union(), stats.union);
+        assert_eq!(
+            (counted.strings, counted.counts),
+            (stats.strings, stats.coverage)
+        );
+    }
+
+    #[test]
+    fn transitions_merge_equals_gather() {
+        let strings = conftest::COLLECTION_OF_50_CHARS_STRINGS;
+        let (left, right) = strings.split_at(7);
+        let merged = Transitions::gather(left)
+            .unwrap()
+            .merge(Transitions::gather(right).unwrap());
+
+        assert_eq!(merged, Transitions::gather(&strings).unwrap());
+        let characters: usize = strings.iter().map(|string| string.chars().count()).sum();
+        assert_eq!(merged.pairs() as usize, characters - strings.len());
+        assert!(Transitions::gather(&["ab", "a1"]).is_err());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn part_masks_of_tokens() {
+        let tokens = PartMasks::tokens(" the\n cat,\u{a0}sat ").unwrap();
+        assert_eq!(
+            tokens.masks,
+            ["the", "cat,", "sat"].map(|token| mask_string(token).unwrap())
+        );
+        assert_eq!(tokens.common, mask_string("t").unwrap());
+
+        let empty = PartMasks::tokens(" \t").unwrap();
+        assert!(empty.masks.is_empty());
+        assert_eq!((empty.common, empty.union), (Mask::ALL, Mask::EMPTY));
+        assert!(PartMasks::tokens("a b1").is_err());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn part_masks_of_lines() {
+        let lines = PartMasks::lines("the cat\r\nsat\n\nhat\n").unwrap();
+        assert_eq!(
+            lines.masks,
+            ["the cat", "sat", "", "hat"].map(|line| mask_string(line).unwrap())
+        );
+        assert_eq!(lines.common, Mask::EMPTY);
+        assert_eq!(lines.union, mask_string(" acehst").unwrap());
+        assert!(PartMasks::lines("").unwrap().masks.is_empty());
+    }
+
+    #[test]
+    fn counted_alphabet_remove_unknown() {
+        let mut counted = CountedAlphabet::default();
+        assert!(!counted.remove("a").unwrap());
+        counted.add("a").unwrap();
+        assert!(!counted.remove("b").unwrap());
+        assert_eq!(counted.strings, 1);
+        assert!(counted.remove("a").unwrap());
+        assert_eq!(counted.common(), Mask::ALL);
+    }
+
+    #[test]
+    fn merge_equals_extend() {
+        let strings = conftest::COLLECTION_OF_50_CHARS_STRINGS;
+        let whole = CorpusStats::default().extend(strings.into_iter()).unwrap();
+        let (left, right) = strings.split_at(7);
+        let merged = CorpusStats::default()
+            .extend(left.iter().copied())
+            .unwrap()
+            .merge(
+                CorpusStats::default()
+                    .extend(right.iter().copied())
+                    .unwrap(),
+            );
+
+        assert_eq!(whole, merged);
+        assert_eq!(whole.strings, 20);
+        assert_eq!(whole.common, mask_string(" ert.").unwrap());
+        // Every string contains a space.
+        assert_eq!(whole.coverage[0], 20);
+    }
+
+    #[test]
+    fn gather_and_compare() {
+        let strings = conftest::COLLECTION_OF_50_CHARS_STRINGS;
+        assert_eq!(
+            CorpusStats::gather(&strings).unwrap(),
+            CorpusStats::default().extend(strings.into_iter()).unwrap()
+        );
+        assert_eq!(
+            CorpusStats::gather::<&str>(&[]).unwrap(),
+            CorpusStats::default()
+        );
+        assert!(CorpusStats::gather(&["a", "1"]).is_err());
+
+        let comparison = compare_corpora(&["abc", "ab"], &["bcd", "bd", "b", "x"]).unwrap();
+        assert_eq!(comparison.only_a(), mask_string("a").unwrap());
+        assert_eq!(comparison.only_b(), mask_string("dx").unwrap());
+        let deltas = comparison.coverage_deltas();
+        // `a` is in every string of the first collection and none of the second.
+        assert_eq!(deltas[1], -1.0);
+        assert_eq!(deltas[2], -0.25);
+        assert_eq!(deltas[3], -0.25);
+        assert_eq!(deltas[4], 0.5);
+        assert_eq!(deltas[0], 0.0);
+    }
+
+    #[test]
+    fn class_stats() {
+        let mut left = ClassStats::default();
+        left.add("password");
+        left.add("Pa55 w0rd!");
+        let mut right = ClassStats::default();
+        right.add("");
+        right.add("PASSWORD1");
+        let stats = left.merge(right);
+
+        assert_eq!(stats.strings, 4);
+        assert_eq!(stats.common, Mask::EMPTY);
+        assert_eq!(stats.union, Mask(0b11111));
+        assert_eq!(stats.coverage, [2, 2, 2, 1, 1]);
+        assert_eq!(stats.class_counts, [1, 1, 1, 0, 0, 1]);
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test_codec {
+    use super::*;
+    use crate::conftest;
+
+    #[test]
+    fn round_trip() {
+        let stats = CorpusStats::default()
+            .extend(conftest::COLLECTION_OF_50_CHARS_STRINGS.into_iter())
+            .unwrap();
+        let bytes = stats.to_bytes();
+
+        assert_eq!(&bytes[..3], b"AS\x01");
+        assert_eq!(CorpusStats::from_bytes(&bytes).unwrap(), stats);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join("alphabet_mask_corpus_stats");
+        let stats = CorpusStats::default()
+            .extend(["ab", "bc"].into_iter())
+            .unwrap();
+
+        stats.save(&path).unwrap();
+        let loaded = CorpusStats::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), stats);
+    }
+
+    #[test]
+    fn bad_version() {
+        let mut bytes = CorpusStats::default().to_bytes();
+        bytes[2] += 1;
+
+        assert!(matches!(
+            CorpusStats::from_bytes(&bytes),
+            Err(LocalError::Deserialisation { .. })
+        ));
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = CorpusStats::default().to_bytes();
+
+        assert!(CorpusStats::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(CorpusStats::from_bytes(&[bytes.as_slice(), &[0]].concat()).is_err());
+    }
+}
@@ -0,0 +1,214 @@
+//! Classification tables built at compile time.
+//!
+//! A [`ClassTable`] maps every character from U+0000 to U+00FF to its bit with a
+//! single lookup. It is built by a `const fn`, so an alphabet known when the binary
+//! is compiled needs no setup at runtime, and a mistake in it fails the build:
+//!
+//! ```
+//! use alphabet_mask_core::mask_string_by;
+//! use alphabet_mask_core::models::{CasePolicy, Mask};
+//! use alphabet_mask_core::table::ClassTable;
+//!
+//! static DNA: ClassTable = ClassTable::new(
+//!     &[('a', 0), ('c', 1), ('g', 2), ('t', 3)],
+//!     CasePolicy::Insensitive,
+//! );
+//!
+//! assert_eq!(mask_string_by("GATTACA", &DNA).unwrap(), Mask(0b1111));
+//! assert_eq!(mask_string_by("tact", &DNA).unwrap(), Mask(0b1011));
+//! assert!(mask_string_by("GATTACU", &DNA).is_err());
+//! ```
+//!
+//! ```compile_fail
+//! use alphabet_mask_core::models::CasePolicy;
+//! use alphabet_mask_core::table::ClassTable;
+//!
+//! // `A` is the same symbol as `a` regardless of case.
+//! static BROKEN: ClassTable = ClassTable::new(&[('a', 0), ('A', 1)], CasePolicy::Insensitive);
+//! ```
+//!
+use core::marker::PhantomData;
+
+use crate::classify::CharClassifier;
+use crate::models::{BitMask, CasePolicy, LocalError, Mask};
+
+/// The number of characters covered by a table.
+const TABLE_SIZE: usize = 256;
+
+/// The entry of a character that is not in the alphabet.
+const NONE: u8 = u8::MAX;
+
+/// Fold a character covered by a table according to `case`, in the same way as
+/// [`AlphabetSpec::fold`](crate::models::AlphabetSpec::fold) does.
+const fn fold(character: char, case: CasePolicy) -> char {
+    match case {
+        CasePolicy::Sensitive => character,
+        CasePolicy::Insensitive => match character {
+            // These ranges are 32 code points below their lowercase forms.
+            'A'..='Z' | 'À'..='Ö' | 'Ø'..='Þ' => match char::from_u32(character as u32 + 32) {
+                Some(lowercase) => lowercase,
+                None => character,
+            },
+            _ => character,
+        },
+    }
+}
+
+/// The bit of every character from U+0000 to U+00FF, for masks of type `M`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassTable<M = Mask> {
+    bits: [u8; TABLE_SIZE],
+    case: CasePolicy,
+    mask: PhantomData<M>,
+}
+
+impl<M: BitMask> ClassTable<M> {
+    /// Build a table from every symbol of an alphabet with its bit.
+    ///
+    /// # Panics
+    ///
+    /// If a symbol is above U+00FF, a bit does not fit in `M`, or a symbol is
+    /// listed more than once after case folding; at compile time if the table is
+    /// built in a `const` or `static`.
+    pub const fn new(symbols: &[(char, u8)], case: CasePolicy) -> Self {
+        let mut bits = [NONE; TABLE_SIZE];
+
+        let mut index = 0;
+        while index < symbols.len() {
+            let (symbol, bit) = symbols[index];
+            if symbol as usize >= TABLE_SIZE {
+                panic!("a class table only covers U+0000 to U+00FF");
+            }
+            if bit as usize >= M::BITS {
+                panic!("a bit does not fit in the mask");
+            }
+            let folded = fold(symbol, case) as usize;
+            if bits[folded] != NONE {
+                panic!("a symbol is listed more than once");
+            }
+            bits[folded] = bit;
+            index += 1;
+        }
+
+        // Every character maps to the entry of its folded form.
+        let mut character = 0;
+        while character < TABLE_SIZE {
+            let folded = match char::from_u32(character as u32) {
+                Some(character) => fold(character, case) as usize,
+                None => character,
+            };
+            bits[character] = bits[folded];
+            character += 1;
+        }
+
+        Self {
+            bits,
+            case,
+            mask: PhantomData,
+        }
+    }
+
+    /// Returns the bit of `character`, or [`None`] if it is not in the alphabet.
+    #[inline]
+    pub fn bit(&self, character: char) -> Option<u8> {
+        match self.bits.get(character as usize) {
+            Some(&NONE) => None,
+            Some(&bit) => Some(bit),
+            // Some characters, such as the Kelvin sign, fold into the table.
+            None if self.case == CasePolicy::Insensitive => {
+                let mut lowercase = character.to_lowercase();
+                match (lowercase.next(), lowercase.next()) {
+                    (Some(folded), None) if (folded as usize) < TABLE_SIZE => self.bit(folded),
+                    _ => None,
+                }
+            }
+            None => None,
+        }
+    }
+}
+
+impl<M: BitMask> CharClassifier for ClassTable<M> {
+    type Mask = M;
+
+    #[inline]
+    fn classify(&self, character: char) -> Result<Option<usize>, LocalError> {
+        self.bit(character)
+            .map(|bit| Some(usize::from(bit)))
+            .ok_or_else(|| LocalError::outside_alphabet(character))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::models::Mask64;
+    use crate::{conftest, mask_string, mask_string_by};
+
+    /// The standard layout, as a table.
+    static STANDARD: ClassTable = ClassTable::new(
+        &[
+            (' ', 0),
+            ('a', 1),
+            ('b', 2),
+            ('c', 3),
+            ('d', 4),
+            ('e', 5),
+            ('f', 6),
+            ('g', 7),
+            ('h', 8),
+            ('i', 9),
+            ('j', 10),
+            ('k', 11),
+            ('l', 12),
+            ('m', 13),
+            ('n', 14),
+            ('o', 15),
+            ('p', 16),
+            ('q', 17),
+            ('r', 18),
+            ('s', 19),
+            ('t', 20),
+            ('u', 21),
+            ('v', 22),
+            ('w', 23),
+            ('x', 24),
+            ('y', 25),
+            ('z', 26),
+            ('.', 27),
+            (',', 28),
+            ('\'', 29),
+            ('-', 30),
+            ('"', 31),
+        ],
+        CasePolicy::Insensitive,
+    );
+
+    #[test]
+    fn standard_layout() {
+        for string in conftest::COLLECTION_OF_50_CHARS_STRINGS {
+            assert_eq!(
+                mask_string_by(string, &STANDARD).unwrap(),
+                mask_string(string).unwrap()
+            );
+        }
+        assert!(mask_string_by("a1", &STANDARD).is_err());
+    }
+
+    #[test]
+    fn latin_1_and_wide_masks() {
+        const TABLE: ClassTable<Mask64> =
+            ClassTable::new(&[('é', 40), ('ß', 63)], CasePolicy::Insensitive);
+
+        assert_eq!(TABLE.bit('É'), Some(40));
+        assert_eq!(TABLE.bit('ß'), Some(63));
+        assert_eq!(TABLE.bit('×'), None);
+        assert_eq!(
+            mask_string_by("Éß", &TABLE).unwrap(),
+            Mask64::from_bit(40) | Mask64::from_bit(63)
+        );
+
+        const SENSITIVE: ClassTable = ClassTable::new(&[('k', 0)], CasePolicy::Sensitive);
+        assert_eq!(SENSITIVE.bit('K'), None);
+        assert_eq!(STANDARD.bit('\u{212A}'), Some(11)); // Kelvin sign
+    }
+}
@@ -0,0 +1,13 @@
+//! Instrumentation with `tracing` spans, compiled out without the `trace` feature.
+//!
+
+/// Enter a `tracing` span at `$level` until the end of the enclosing scope with
+/// the `trace` feature, or do nothing without it.
+// Some combinations of features compile out every span.
+#[allow(unused_macros)]
+macro_rules! span {
+    ($level:ident, $name:literal $(, $($fields:tt)*)?) => {
+        #[cfg(feature = "trace")]
+        let _span = tracing::span!(tracing::Level::$level, $name $(, $($fields)*)?).entered();
+    };
+}
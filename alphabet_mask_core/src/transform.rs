@@ -0,0 +1,229 @@
+//! Transforms applied to each character before it is classified, so that text
+//! need not be normalised before masking.
+//!
+//! A [`Masker`](crate::masker::Masker) applies its transforms in order, and
+//! composes them into its lookup table for ASCII characters, so that they cost
+//! nothing on ASCII text:
+//!
+//! ```
+//! use alphabet_mask_core::masker::Masker;
+//! use alphabet_mask_core::transform::Transform;
+//!
+//! let masker = Masker::builder()
+//!     .transforms([Transform::StripPunctuation, Transform::FoldWhitespace])
+//!     .build()
+//!     .unwrap();
+//!
+//! assert_eq!(masker.mask_to_chars(masker.mask("Hi!\tyou?").unwrap()), " hiouy");
+//! ```
+//!
+use alloc::vec::Vec;
+#[cfg(feature = "normalize")]
+use alloc::{borrow::Cow, string::String};
+use core::str::FromStr;
+
+use crate::models::LocalError;
+
+/// A transform of each character of a string, before it is classified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Transform {
+    /// Replace each character with its lowercase form, if that is a single
+    /// character.
+    Lowercase,
+    /// Remove ASCII punctuation, the inverted marks and angle quotes of Latin-1,
+    /// and the punctuation of the General Punctuation block, such as dashes and
+    /// curly quotes.
+    StripPunctuation,
+    /// Replace any whitespace, such as a tab, a line ending or a no-break space,
+    /// with a space.
+    FoldWhitespace,
+    /// Replace each character with its compatibility decomposition without
+    /// combining marks, such as `é` with `e`, if that is a single character; a
+    /// character decomposing into several, such as a ligature, is left as it is.
+    #[cfg(feature = "normalize")]
+    FoldDiacritics,
+    /// Replace each character `from` with `to`, or remove it if `to` is
+    /// [`None`]; the pairs are sorted by [`Transform::map`].
+    Map(Vec<(char, Option<char>)>),
+}
+
+impl Transform {
+    /// A transform replacing or removing each character of `pairs`; the last of
+    /// several pairs for the same character is used.
+    pub fn map(pairs: impl IntoIterator<Item = (char, Option<char>)>) -> Self {
+        let mut pairs: Vec<_> = pairs.into_iter().collect();
+        pairs.reverse();
+        pairs.sort_by_key(|&(from, _)| from);
+        pairs.dedup_by_key(|&mut (from, _)| from);
+        Self::Map(pairs)
+    }
+
+    /// Returns `character` transformed, or [`None`] if it is removed.
+    pub fn apply(&self, character: char) -> Option<char> {
+        match self {
+            Self::Lowercase => {
+                let mut lowercase = character.to_lowercase();
+                match (lowercase.next(), lowercase.next()) {
+                    (Some(lowercase), None) => Some(lowercase),
+                    _ => Some(character),
+                }
+            }
+            Self::StripPunctuation => (!is_punctuation(character)).then_some(character),
+            Self::FoldWhitespace => Some(if character.is_whitespace() {
+                ' '
+            } else {
+                character
+            }),
+            #[cfg(feature = "normalize")]
+            Self::FoldDiacritics => Some(fold_diacritics(character)),
+            Self::Map(pairs) => match pairs.binary_search_by_key(&character, |&(from, _)| from) {
+                Ok(index) => pairs[index].1,
+                Err(_) => Some(character),
+            },
+        }
+    }
+}
+
+impl FromStr for Transform {
+    type Err = LocalError;
+
+    /// Parses the name of a transform without arguments: `lowercase`,
+    /// `strip_punctuation`, `fold_whitespace` or `fold_diacritics`.
+    fn from_str(name: &str) -> Result<Self, LocalError> {
+        match name {
+            "lowercase" => Ok(Self::Lowercase),
+            "strip_punctuation" => Ok(Self::StripPunctuation),
+            "fold_whitespace" => Ok(Self::FoldWhitespace),
+            #[cfg(feature = "normalize")]
+            "fold_diacritics" => Ok(Self::FoldDiacritics),
+            _ => Err(LocalError::InvalidArgument {
+                name: "transforms",
+                value: name.into(),
+            }),
+        }
+    }
+}
+
+/// Returns `character` after every one of `transforms` in order, or [`None`] if
+/// any removes it.
+pub fn apply_all(transforms: &[Transform], character: char) -> Option<char> {
+    transforms
+        .iter()
+        .try_fold(character, |character, transform| transform.apply(character))
+}
+
+/// Whether `character` is removed by [`Transform::StripPunctuation`].
+fn is_punctuation(character: char) -> bool {
+    character.is_ascii_punctuation()
+        || matches!(
+            character,
+            '¡' | '«' | '»' | '¿' | '\u{2010}'..='\u{2027}' | '\u{2030}'..='\u{205E}'
+        )
+}
+
+/// Returns the compatibility decomposition of `character` without combining
+/// marks, if it is a single character, or `character` otherwise.
+#[cfg(feature = "normalize")]
+fn fold_diacritics(character: char) -> char {
+    use unicode_normalization::char::{decompose_compatible, is_combining_mark};
+
+    let (mut folded, mut count) = (character, 0);
+    decompose_compatible(character, |decomposed| {
+        if !is_combining_mark(decomposed) {
+            folded = decomposed;
+            count += 1;
+        }
+    });
+    if count == 1 {
+        folded
+    } else {
+        character
+    }
+}
+
+/// Returns `string` in its compatibility decomposition (NFKD) without combining
+/// marks, such as `Café` as `Cafe`; unlike [`Transform::FoldDiacritics`], a
+/// character decomposing into several, such as `ﬁ`, is replaced by all of them,
+/// and combining marks already decomposed are removed too. ASCII strings are
+/// returned as they are.
+///
+/// ```
+/// use alphabet_mask_core::transform::strip_diacritics;
+///
+/// assert_eq!(strip_diacritics("Crème brûlée"), "Creme brulee");
+/// assert_eq!(strip_diacritics("ﬁance\u{301}"), "fiance");
+/// ```
+#[cfg(feature = "normalize")]
+pub fn strip_diacritics(string: &str) -> Cow<'_, str> {
+    use unicode_normalization::char::is_combining_mark;
+    use unicode_normalization::UnicodeNormalization;
+
+    if string.is_ascii() {
+        return Cow::Borrowed(string);
+    }
+    Cow::Owned(
+        string
+            .nfkd()
+            .filter(|&character| !is_combining_mark(character))
+            .collect::<String>(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn transforms() {
+        assert_eq!(Transform::Lowercase.apply('Q'), Some('q'));
+        assert_eq!(Transform::Lowercase.apply('İ'), Some('İ'));
+        assert_eq!(Transform::StripPunctuation.apply('!'), None);
+        assert_eq!(Transform::StripPunctuation.apply('\u{201C}'), None);
+        assert_eq!(Transform::StripPunctuation.apply('a'), Some('a'));
+        assert_eq!(Transform::FoldWhitespace.apply('\u{a0}'), Some(' '));
+        assert_eq!(Transform::FoldWhitespace.apply('\n'), Some(' '));
+
+        let map = Transform::map([('ß', Some('s')), ('€', None), ('ß', Some('z'))]);
+        assert_eq!(map.apply('ß'), Some('z'));
+        assert_eq!(map.apply('€'), None);
+        assert_eq!(map.apply('a'), Some('a'));
+    }
+
+    #[cfg(feature = "normalize")]
+    #[test]
+    fn fold_diacritics() {
+        for (from, to) in [('é', 'e'), ('Å', 'A'), ('ñ', 'n'), ('ﬁ', 'ﬁ'), ('a', 'a')] {
+            assert_eq!(Transform::FoldDiacritics.apply(from), Some(to), "{from}");
+        }
+    }
+
+    #[cfg(feature = "normalize")]
+    #[test]
+    fn strip_diacritics() {
+        use super::strip_diacritics;
+
+        assert!(matches!(strip_diacritics("cafe"), Cow::Borrowed("cafe")));
+        assert_eq!(
+            strip_diacritics("Ångström, São Paulo"),
+            "Angstrom, Sao Paulo"
+        );
+        assert_eq!(strip_diacritics("½ ﬂoor"), "1\u{2044}2 floor");
+        assert_eq!(strip_diacritics("ø, ß"), "ø, ß");
+    }
+
+    #[test]
+    fn in_order() {
+        let transforms = [
+            Transform::map([('!', Some('.'))]),
+            Transform::StripPunctuation,
+        ];
+        assert_eq!(apply_all(&transforms, '!'), None);
+        assert_eq!(apply_all(&transforms[..1], '!'), Some('.'));
+        assert_eq!("Lowercase".parse::<Transform>().ok(), None);
+        assert_eq!(
+            "lowercase".parse::<Transform>().ok(),
+            Some(Transform::Lowercase)
+        );
+    }
+}
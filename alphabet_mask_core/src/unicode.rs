@@ -0,0 +1,303 @@
+//! Masking any Unicode text, into compressed sets of scalar values rather than
+//! bit masks.
+//!
+//! A [`CharSet`] plays the part of a mask for text outside any layout, such as
+//! multilingual text: no character is an error, and the sets of many strings
+//! intersect into their common alphabet, in parallel chunks as for
+//! [`find_common_mask_dispatch`](crate::find_common_mask_dispatch).
+//!
+//! ```
+//! use alphabet_mask_core::models::CasePolicy;
+//! use alphabet_mask_core::unicode::{find_common_char_set, CharSet};
+//!
+//! let set = CharSet::of("Καλή νύχτα", CasePolicy::Insensitive);
+//! assert!(set.contains('κ') && !set.contains('Κ'));
+//!
+//! let common = find_common_char_set(&["καλή", "νύχτα", "καφές"], 1024, CasePolicy::Sensitive);
+//! assert_eq!(common.chars().collect::<String>(), "α");
+//! ```
+//!
+//! Sets are stored as roaring bitmaps, so that a set of a whole script is as
+//! small as a few ranges.
+//!
+use alloc::string::ToString;
+use alloc::{format, vec::Vec};
+use core::ops::{BitAnd, BitOr, BitXor, RangeInclusive, Sub};
+
+#[cfg(feature = "parallel")]
+use rayon::iter::ParallelBridge;
+use roaring::RoaringBitmap;
+
+use crate::models::{CasePolicy, LocalError};
+#[cfg(feature = "parallel")]
+use crate::{chunk_strings_by, exceeds_length_limit, reduce_in_order};
+
+/// One past the highest Unicode scalar value.
+const END: u32 = 0x11_0000;
+
+/// The surrogate code points, which are not scalar values.
+const SURROGATES: core::ops::Range<u32> = 0xD800..0xE000;
+
+/// Returns the form of `character` that is added to a set under `case`, as
+/// [`AlphabetSpec::fold`](crate::models::AlphabetSpec::fold) does.
+fn fold(character: char, case: CasePolicy) -> char {
+    match case {
+        CasePolicy::Sensitive => character,
+        CasePolicy::Insensitive => {
+            let mut lowercase = character.to_lowercase();
+            match (lowercase.next(), lowercase.next()) {
+                (Some(folded), None) => folded,
+                _ => character,
+            }
+        }
+    }
+}
+
+/// A set of Unicode scalar values, the mask of a string in Unicode mode.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CharSet(RoaringBitmap);
+
+impl CharSet {
+    /// The empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The set of every Unicode scalar value, the common alphabet of no strings.
+    pub fn all() -> Self {
+        let mut set = RoaringBitmap::new();
+        set.insert_range(0..END);
+        set.remove_range(SURROGATES);
+        Self(set)
+    }
+
+    /// The set of the characters of `string`, folded according to `case`.
+    pub fn of(string: &str, case: CasePolicy) -> Self {
+        string
+            .chars()
+            .map(|character| fold(character, case))
+            .collect()
+    }
+
+    /// Add `character`, returning whether it was not in the set yet.
+    pub fn insert(&mut self, character: char) -> bool {
+        self.0.insert(character.into())
+    }
+
+    /// Returns whether `character` is in the set.
+    pub fn contains(&self, character: char) -> bool {
+        self.0.contains(character.into())
+    }
+
+    /// The number of characters in the set.
+    pub fn len(&self) -> u64 {
+        self.0.len()
+    }
+
+    /// Returns whether the set has no characters.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the characters of the set in code point order.
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.0.iter().filter_map(char::from_u32)
+    }
+
+    /// Returns the runs of consecutive code points in the set, in order.
+    pub fn ranges(&self) -> Vec<RangeInclusive<char>> {
+        let mut ranges: Vec<RangeInclusive<char>> = Vec::new();
+        for character in self.chars() {
+            match ranges.last_mut() {
+                Some(range) if u32::from(*range.end()) + 1 == u32::from(character) => {
+                    *range = *range.start()..=character;
+                }
+                _ => ranges.push(character..=character),
+            }
+        }
+        ranges
+    }
+
+    /// Returns the set in the portable roaring bitmap format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.0.serialized_size());
+        self.0
+            .serialize_into(&mut bytes)
+            .expect("writing to a `Vec` never fails");
+        bytes
+    }
+
+    /// Returns the set serialised by [`to_bytes`](Self::to_bytes), or an error
+    /// if `bytes` is not a roaring bitmap of scalar values.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LocalError> {
+        let invalid = |context| LocalError::Deserialisation {
+            kind: "character set",
+            context,
+        };
+        let set = RoaringBitmap::deserialize_from(bytes).map_err(|e| invalid(e.to_string()))?;
+        match set.iter().find(|&value| char::from_u32(value).is_none()) {
+            Some(value) => Err(invalid(format!("{value:#x} is not a scalar value"))),
+            None => Ok(Self(set)),
+        }
+    }
+}
+
+impl FromIterator<char> for CharSet {
+    fn from_iter<T: IntoIterator<Item = char>>(characters: T) -> Self {
+        Self(characters.into_iter().map(u32::from).collect())
+    }
+}
+
+/// Implement a set operation for sets and references to sets.
+macro_rules! impl_set_operation {
+    ($($trait:ident $method:ident),* $(,)?) => {$(
+        impl $trait for CharSet {
+            type Output = Self;
+
+            fn $method(self, other: Self) -> Self {
+                Self(self.0.$method(other.0))
+            }
+        }
+
+        impl $trait for &CharSet {
+            type Output = CharSet;
+
+            fn $method(self, other: Self) -> CharSet {
+                CharSet((&self.0).$method(&other.0))
+            }
+        }
+    )*};
+}
+
+impl_set_operation!(BitAnd bitand, BitOr bitor, BitXor bitxor, Sub sub);
+
+/// Returns the common alphabet of `strings` as a set, its identity [`None`]
+/// standing for every character.
+fn find_common_set<S: AsRef<str>>(
+    strings: impl Iterator<Item = S>,
+    case: CasePolicy,
+) -> Option<CharSet> {
+    strings.fold(None, |common, string| {
+        let set = CharSet::of(string.as_ref(), case);
+        Some(match common {
+            Some(common) => common & set,
+            None => set,
+        })
+    })
+}
+
+/// Returns the common alphabet of `strings` as a set, in parallel chunks of
+/// `length_limit` bytes if they are longer in total; without strings, every
+/// character.
+pub fn find_common_char_set<S>(strings: &[S], length_limit: usize, case: CasePolicy) -> CharSet
+where
+    S: AsRef<str> + Sync,
+{
+    #[cfg(feature = "parallel")]
+    if exceeds_length_limit(strings, length_limit) {
+        span!(DEBUG, "find_common_char_set", strings = strings.len());
+        let common = reduce_in_order(
+            chunk_strings_by(strings.iter(), Some(length_limit))
+                .enumerate()
+                .par_bridge(),
+            None,
+            |chunk| Ok(find_common_set(chunk.iter(), case)),
+            |a, b| match (a, b) {
+                (Some(a), Some(b)) => Some(a & b),
+                (a, b) => a.or(b),
+            },
+        );
+        let Ok(common) = common else {
+            unreachable!("masking into a set never fails")
+        };
+        return common.unwrap_or_else(CharSet::all);
+    }
+    #[cfg(not(feature = "parallel"))]
+    let _ = length_limit;
+
+    find_common_set(strings.iter(), case).unwrap_or_else(CharSet::all)
+}
+
+/// Returns the combined alphabet of `strings` as a set.
+pub fn find_union_char_set<S: AsRef<str>>(
+    strings: impl Iterator<Item = S>,
+    case: CasePolicy,
+) -> CharSet {
+    strings.fold(CharSet::new(), |union, string| {
+        union | CharSet::of(string.as_ref(), case)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::String;
+
+    #[test]
+    fn multilingual() {
+        let set = CharSet::of("Grüße, 世界!", CasePolicy::Insensitive);
+
+        assert_eq!(set.len(), 10);
+        assert!(set.contains('ü') && set.contains('界'));
+        assert_eq!(set.chars().collect::<String>(), " !,egrßü世界");
+        assert_eq!(
+            set.ranges().first(),
+            Some(&(' '..='!')),
+            "a run of consecutive characters"
+        );
+    }
+
+    #[test]
+    fn operations() {
+        let a = CharSet::of("abcé", CasePolicy::Sensitive);
+        let b = CharSet::of("bcdè", CasePolicy::Sensitive);
+
+        assert_eq!((&a & &b).chars().collect::<String>(), "bc");
+        assert_eq!((&a | &b).len(), 6);
+        assert_eq!((&a ^ &b).chars().collect::<String>(), "adèé");
+        assert_eq!((a - b).chars().collect::<String>(), "aé");
+    }
+
+    #[test]
+    fn all() {
+        let all = CharSet::all();
+
+        assert_eq!(all.len(), 0x11_0000 - 0x800);
+        assert_eq!(
+            all.ranges(),
+            [('\0'..='\u{D7FF}'), ('\u{E000}'..=char::MAX)]
+        );
+        assert_eq!(
+            find_common_char_set::<&str>(&[], 1024, CasePolicy::Sensitive),
+            all
+        );
+    }
+
+    #[test]
+    fn common_in_parallel() {
+        let strings = ["Ωμέγα, omega"; 1000];
+        let expected = CharSet::of(strings[0], CasePolicy::Insensitive);
+
+        assert_eq!(
+            find_common_char_set(&strings, 64, CasePolicy::Insensitive),
+            expected
+        );
+        assert_eq!(
+            find_union_char_set(strings.iter(), CasePolicy::Insensitive),
+            expected
+        );
+    }
+
+    #[test]
+    fn bytes() {
+        let set = CharSet::of("Добрый день", CasePolicy::Sensitive);
+
+        assert_eq!(CharSet::from_bytes(&set.to_bytes()).unwrap(), set);
+        assert!(CharSet::from_bytes(b"not a set").is_err());
+
+        let surrogate: RoaringBitmap = [0xD800].into_iter().collect();
+        let mut bytes = Vec::new();
+        surrogate.serialize_into(&mut bytes).unwrap();
+        assert!(CharSet::from_bytes(&bytes).is_err());
+    }
+}
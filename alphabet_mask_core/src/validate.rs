@@ -0,0 +1,214 @@
+//! Bulk validation of strings against an allowlist of characters.
+//!
+//! Unlike masking, the allowlist is not limited to the alphabet of a layout: any
+//! character can be allowed, so that identifiers such as usernames, slugs and
+//! codes can be checked in bulk:
+//!
+//! ```
+//! use alphabet_mask_core::validate::{validate_charset, Charset};
+//!
+//! let slug = Charset::new("abcdefghijklmnopqrstuvwxyz0123456789-");
+//! let violations = validate_charset(&["hello-world", "Hello_world", "ok"], &slug);
+//!
+//! assert_eq!(violations, [vec![], vec![(0, 'H'), (5, '_')], vec![]]);
+//! ```
+//!
+use alloc::collections::BTreeMap;
+use alloc::{boxed::Box, vec::Vec};
+
+#[cfg(feature = "parallel")]
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+/// A set of allowed characters.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Charset {
+    /// One bit for each allowed ASCII character.
+    ascii: u128,
+    /// Every allowed non-ASCII character, sorted.
+    other: Box<[char]>,
+}
+
+impl Charset {
+    /// The set of every character of `allowed`; duplicates are ignored.
+    pub fn new(allowed: &str) -> Self {
+        let mut ascii = 0;
+        let mut other = Vec::new();
+        for character in allowed.chars() {
+            if character.is_ascii() {
+                ascii |= 1 << (character as u32);
+            } else {
+                other.push(character);
+            }
+        }
+        other.sort_unstable();
+        other.dedup();
+
+        Self {
+            ascii,
+            other: other.into_boxed_slice(),
+        }
+    }
+
+    /// The charset of a built-in alphabet by name, if there is one of
+    /// [`PRESETS`].
+    ///
+    /// ```
+    /// use alphabet_mask_core::validate::Charset;
+    ///
+    /// let en = Charset::preset("en").unwrap();
+    /// assert!(en.contains('Q') && en.contains('"') && !en.contains('1'));
+    /// assert!(Charset::preset("klingon").is_none());
+    /// ```
+    pub fn preset(name: &str) -> Option<Self> {
+        let allowed = match name {
+            "en" => "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ .,'-\"",
+            "dna" => "acgtnACGTN",
+            "ascii" => {
+                return Some(Self {
+                    ascii: ((1 << 95) - 1) << b' ',
+                    other: Box::default(),
+                })
+            }
+            _ => return None,
+        };
+        Some(Self::new(allowed))
+    }
+
+    /// Whether `character` is allowed.
+    #[inline]
+    pub fn contains(&self, character: char) -> bool {
+        if character.is_ascii() {
+            self.ascii >> (character as u32) & 1 == 1
+        } else {
+            self.other.binary_search(&character).is_ok()
+        }
+    }
+
+    /// The position in characters and the character of every violation in
+    /// `string`, in order.
+    pub fn violations<'s>(&'s self, string: &'s str) -> impl Iterator<Item = (usize, char)> + 's {
+        string
+            .chars()
+            .enumerate()
+            .filter(|&(_, character)| !self.contains(character))
+    }
+}
+
+/// The names of the built-in alphabets of [`Charset::preset`]: `"en"`, the
+/// characters of the standard layout in either case; `"dna"`, the nucleotides
+/// of [`bio`](crate::bio); and `"ascii"`, the printable ASCII characters.
+pub const PRESETS: [&str; 3] = ["en", "dna", "ascii"];
+
+/// The violations of a charset by a collection of strings, for auditing it
+/// before ingestion.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CorpusViolations {
+    /// The number of strings checked.
+    pub strings: usize,
+    /// The index of every string with a violation, with its violations as
+    /// returned by [`validate_charset`], in order.
+    pub violations: Vec<(usize, Vec<(usize, char)>)>,
+    /// Every distinct character outside the charset, with the number of times
+    /// it occurs, in character order.
+    pub characters: Vec<(char, u64)>,
+}
+
+impl CorpusViolations {
+    /// Whether every string only uses characters of the charset.
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Check every string against `charset`, collecting all of the violations
+/// rather than stopping at the first.
+///
+/// ```
+/// use alphabet_mask_core::validate::{validate_corpus, Charset};
+///
+/// let report = validate_corpus(&["the cat", "café", "naïve café"], &Charset::preset("en").unwrap());
+///
+/// assert_eq!(report.violations[0], (1, vec![(3, 'é')]));
+/// assert_eq!(report.characters, [('é', 2), ('ï', 1)]);
+/// ```
+pub fn validate_corpus<S: AsRef<str> + Sync>(strings: &[S], charset: &Charset) -> CorpusViolations {
+    let violations: Vec<_> = validate_charset(strings, charset)
+        .into_iter()
+        .enumerate()
+        .filter(|(_, violations)| !violations.is_empty())
+        .collect();
+
+    let mut characters = BTreeMap::new();
+    for &(_, character) in violations.iter().flat_map(|(_, violations)| violations) {
+        *characters.entry(character).or_insert(0) += 1;
+    }
+
+    CorpusViolations {
+        strings: strings.len(),
+        violations,
+        characters: characters.into_iter().collect(),
+    }
+}
+
+/// Returns the violations of `charset` in each string, as positions in
+/// characters with the characters found there; a string is valid if it has none.
+///
+/// The strings are checked in parallel with the `parallel` feature.
+pub fn validate_charset<S: AsRef<str> + Sync>(
+    strings: &[S],
+    charset: &Charset,
+) -> Vec<Vec<(usize, char)>> {
+    let violations = |string: &S| charset.violations(string.as_ref()).collect();
+
+    #[cfg(feature = "parallel")]
+    return strings.par_iter().map(violations).collect();
+    #[cfg(not(feature = "parallel"))]
+    return strings.iter().map(violations).collect();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn charset() {
+        let charset = Charset::new("abc\u{7f}\0éé日");
+
+        for allowed in ['a', 'c', '\u{7f}', '\0', 'é', '日'] {
+            assert!(charset.contains(allowed), "{allowed:?}");
+        }
+        for disallowed in ['d', 'A', 'è', '本', '\u{80}'] {
+            assert!(!charset.contains(disallowed), "{disallowed:?}");
+        }
+        assert!(!Charset::default().contains('a'));
+    }
+
+    #[test]
+    fn presets() {
+        for name in PRESETS {
+            assert!(Charset::preset(name).is_some(), "{name}");
+        }
+
+        let ascii = Charset::preset("ascii").unwrap();
+        assert!(ascii.contains(' ') && ascii.contains('~'));
+        assert!(!ascii.contains('\u{7f}') && !ascii.contains('\n') && !ascii.contains('é'));
+        let report = validate_corpus(
+            &["GATTACA", "GAUUACA", "acgtn"],
+            &Charset::preset("dna").unwrap(),
+        );
+        assert_eq!(report.violations, [(1, vec![(2, 'U'), (3, 'U')])]);
+        assert_eq!(report.characters, [('U', 2)]);
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn violations() {
+        let charset = Charset::new("abcdefghijklmnopqrstuvwxyz0123456789_");
+
+        assert_eq!(
+            validate_charset(&["user_01", "", "Ülrich", "a b!"], &charset),
+            [vec![], vec![], vec![(0, 'Ü')], vec![(1, ' '), (3, '!')]]
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! Masks of any width, sharing one generic implementation.
+//!
+//! [`Mask`] is the 32-bit mask of the standard layout. Wider alphabets use a
+//! [`WideMask`] of as many 64-bit words as they need, and code written against the
+//! [`BitMask`] trait is monomorphised for each width, without branching on it at
+//! runtime.
+//!
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+use crate::Mask;
+
+/// The operations shared by masks of every width.
+pub trait BitMask:
+    Copy
+    + Eq
+    + core::fmt::Debug
+    + core::hash::Hash
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Not<Output = Self>
+{
+    /// The number of bits in a mask.
+    const BITS: usize;
+
+    /// The mask of no characters, and the identity of `|`.
+    const EMPTY: Self;
+
+    /// The mask of every character, and the identity of `&`.
+    const ALL: Self;
+
+    /// Returns the mask with only `bit` set.
+    ///
+    /// `bit` must be less than [`Self::BITS`].
+    fn from_bit(bit: usize) -> Self;
+
+    /// Returns whether `bit` is set.
+    fn contains_bit(self, bit: usize) -> bool;
+
+    /// Returns the number of bits set.
+    fn len(self) -> u32;
+
+    /// Returns whether no bit is set.
+    fn is_empty(self) -> bool {
+        self == Self::EMPTY
+    }
+
+    /// Returns whether every bit of `self` is also set in `other`.
+    fn is_subset_of(self, other: Self) -> bool {
+        self & !other == Self::EMPTY
+    }
+
+    /// Returns the indices of the bits set, in ascending order.
+    fn bits_set(self) -> impl Iterator<Item = usize> {
+        (0..Self::BITS).filter(move |&bit| self.contains_bit(bit))
+    }
+}
+
+impl BitMask for Mask {
+    const BITS: usize = Mask::WIDTH as usize;
+    const EMPTY: Self = Mask::EMPTY;
+    const ALL: Self = Mask::ALL;
+
+    fn from_bit(bit: usize) -> Self {
+        Self(1 << bit)
+    }
+
+    fn contains_bit(self, bit: usize) -> bool {
+        bit < Self::BITS && self.0 >> bit & 1 == 1
+    }
+
+    fn len(self) -> u32 {
+        Mask::len(self)
+    }
+}
+
+/// A mask of `WORDS` 64-bit words, with bit `n` in bit `n % 64` of word `n / 64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WideMask<const WORDS: usize>(pub [u64; WORDS]);
+
+/// A mask of 64 bits.
+pub type Mask64 = WideMask<1>;
+
+/// A mask of 128 bits.
+pub type Mask128 = WideMask<2>;
+
+/// A mask of 256 bits.
+pub type Mask256 = WideMask<4>;
+
+impl<const WORDS: usize> Default for WideMask<WORDS> {
+    fn default() -> Self {
+        Self([0; WORDS])
+    }
+}
+
+impl<const WORDS: usize> BitMask for WideMask<WORDS> {
+    const BITS: usize = WORDS * 64;
+    const EMPTY: Self = Self([0; WORDS]);
+    const ALL: Self = Self([u64::MAX; WORDS]);
+
+    fn from_bit(bit: usize) -> Self {
+        let mut words = [0; WORDS];
+        words[bit / 64] = 1 << (bit % 64);
+        Self(words)
+    }
+
+    fn contains_bit(self, bit: usize) -> bool {
+        bit < Self::BITS && self.0[bit / 64] >> (bit % 64) & 1 == 1
+    }
+
+    fn len(self) -> u32 {
+        self.0.iter().map(|word| word.count_ones()).sum()
+    }
+}
+
+/// Every 32-bit mask is the same mask in the low bits of a wider one.
+impl<const WORDS: usize> From<Mask> for WideMask<WORDS> {
+    fn from(mask: Mask) -> Self {
+        let mut words = [0; WORDS];
+        if let Some(word) = words.first_mut() {
+            *word = u64::from(mask.bits());
+        }
+        Self(words)
+    }
+}
+
+/// Implement the conversions between a wide mask of one or two words and the
+/// integer of the same width.
+macro_rules! impl_int_conversions {
+    ($($words:literal => $int:ty;)*) => {
+        $(
+            impl From<$int> for WideMask<$words> {
+                fn from(bits: $int) -> Self {
+                    Self(core::array::from_fn(|word| (bits >> (64 * word)) as u64))
+                }
+            }
+
+            impl From<WideMask<$words>> for $int {
+                fn from(mask: WideMask<$words>) -> Self {
+                    mask.0
+                        .iter()
+                        .rev()
+                        .fold(0, |bits, &word| bits << 32 << 32 | <$int>::from(word))
+                }
+            }
+        )*
+    };
+}
+
+impl_int_conversions!(
+    1 => u64;
+    2 => u128;
+);
+
+/// Implement a binary operator and its assigning form by applying it word by word.
+macro_rules! impl_word_op {
+    ($($trait:ident::$method:ident, $assign_trait:ident::$assign_method:ident => $op:tt;)*) => {
+        $(
+            impl<const WORDS: usize> $trait for WideMask<WORDS> {
+                type Output = Self;
+
+                fn $method(mut self, other: Self) -> Self {
+                    self.$assign_method(other);
+                    self
+                }
+            }
+
+            impl<const WORDS: usize> $assign_trait for WideMask<WORDS> {
+                fn $assign_method(&mut self, other: Self) {
+                    for (word, other) in self.0.iter_mut().zip(other.0) {
+                        *word = *word $op other;
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_word_op!(
+    BitAnd::bitand, BitAndAssign::bitand_assign => &;
+    BitOr::bitor, BitOrAssign::bitor_assign => |;
+    BitXor::bitxor, BitXorAssign::bitxor_assign => ^;
+);
+
+impl<const WORDS: usize> Not for WideMask<WORDS> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(self.0.map(|word| !word))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Exercise the generic operations for any width.
+    fn check_width<M: BitMask>() {
+        let low = M::from_bit(0) | M::from_bit(1);
+        let high = M::from_bit(M::BITS - 1);
+
+        assert!(low.contains_bit(1) && !low.contains_bit(2));
+        assert!(!low.contains_bit(M::BITS));
+        assert_eq!((low | high).len(), 3);
+        assert!(low.is_subset_of(low | high));
+        assert!((low & high).is_empty());
+        assert_eq!(!M::EMPTY, M::ALL);
+        assert_eq!(M::ALL.len() as usize, M::BITS);
+        assert!((low ^ high).bits_set().eq([0, 1, M::BITS - 1]));
+    }
+
+    #[test]
+    fn every_width() {
+        check_width::<Mask>();
+        check_width::<Mask64>();
+        check_width::<Mask128>();
+        check_width::<Mask256>();
+    }
+
+    #[test]
+    fn widen() {
+        let mask = Mask128::from(Mask(0b1110)) | Mask128::from_bit(100);
+
+        assert_eq!(mask.0, [0b1110, 1 << 36]);
+    }
+
+    #[test]
+    fn integers() {
+        let bits = 1_u128 << 100 | 0b1110;
+
+        assert_eq!(Mask128::from(bits).0, [0b1110, 1 << 36]);
+        assert_eq!(u128::from(Mask128::from(bits)), bits);
+        assert_eq!(u64::from(Mask64::from(u64::MAX)), u64::MAX);
+    }
+}
@@ -0,0 +1,87 @@
+//! Named bit positions and masks of the standard layout.
+//!
+//! Prefer these over bare bit indices, e.g. `Mask(1 << FULL_STOP_BIT)` or
+//! `mask & LETTERS_MASK`.
+//!
+use crate::Mask;
+
+/// Define a constant for each named bit, and the list of all of them.
+macro_rules! named_bits {
+    ($($name:ident = $bit:literal, $doc:literal;)*) => {
+        $(
+            #[doc = $doc]
+            pub const $name: u8 = $bit;
+        )*
+
+        /// Every named bit with its name, in bit order.
+        pub const NAMED_BITS: [(&str, u8); 32] = [$((stringify!($name), $name)),*];
+    };
+}
+
+named_bits!(
+    SPACE_BIT = 0, "The bit of a space.";
+    LETTER_A_BIT = 1, "The bit of the letter `a`, in either case.";
+    LETTER_B_BIT = 2, "The bit of the letter `b`, in either case.";
+    LETTER_C_BIT = 3, "The bit of the letter `c`, in either case.";
+    LETTER_D_BIT = 4, "The bit of the letter `d`, in either case.";
+    LETTER_E_BIT = 5, "The bit of the letter `e`, in either case.";
+    LETTER_F_BIT = 6, "The bit of the letter `f`, in either case.";
+    LETTER_G_BIT = 7, "The bit of the letter `g`, in either case.";
+    LETTER_H_BIT = 8, "The bit of the letter `h`, in either case.";
+    LETTER_I_BIT = 9, "The bit of the letter `i`, in either case.";
+    LETTER_J_BIT = 10, "The bit of the letter `j`, in either case.";
+    LETTER_K_BIT = 11, "The bit of the letter `k`, in either case.";
+    LETTER_L_BIT = 12, "The bit of the letter `l`, in either case.";
+    LETTER_M_BIT = 13, "The bit of the letter `m`, in either case.";
+    LETTER_N_BIT = 14, "The bit of the letter `n`, in either case.";
+    LETTER_O_BIT = 15, "The bit of the letter `o`, in either case.";
+    LETTER_P_BIT = 16, "The bit of the letter `p`, in either case.";
+    LETTER_Q_BIT = 17, "The bit of the letter `q`, in either case.";
+    LETTER_R_BIT = 18, "The bit of the letter `r`, in either case.";
+    LETTER_S_BIT = 19, "The bit of the letter `s`, in either case.";
+    LETTER_T_BIT = 20, "The bit of the letter `t`, in either case.";
+    LETTER_U_BIT = 21, "The bit of the letter `u`, in either case.";
+    LETTER_V_BIT = 22, "The bit of the letter `v`, in either case.";
+    LETTER_W_BIT = 23, "The bit of the letter `w`, in either case.";
+    LETTER_X_BIT = 24, "The bit of the letter `x`, in either case.";
+    LETTER_Y_BIT = 25, "The bit of the letter `y`, in either case.";
+    LETTER_Z_BIT = 26, "The bit of the letter `z`, in either case.";
+    FULL_STOP_BIT = 27, "The bit of a full stop `.`.";
+    COMMA_BIT = 28, "The bit of a comma `,`.";
+    APOSTROPHE_BIT = 29, "The bit of an apostrophe `'`.";
+    HYPHEN_BIT = 30, "The bit of a hyphen `-`.";
+    DOUBLE_QUOTE_BIT = 31, "The bit of a double quote `\"`.";
+);
+
+/// The version of the standard layout, incremented whenever a bit is reassigned,
+/// so that persisted masks can be checked against it.
+pub const LAYOUT_VERSION: u8 = 1;
+
+/// The mask of every letter from `a` to `z`.
+pub const LETTERS_MASK: Mask = Mask(((1 << 26) - 1) << LETTER_A_BIT);
+
+/// The mask of every punctuation mark: `.`, `,`, `'`, `-` and `"`.
+pub const PUNCT_MASK: Mask = Mask(u32::MAX << FULL_STOP_BIT);
+
+/// The mask of every bit assigned a character; any other bit is reserved.
+pub const DEFINED_MASK: Mask = Mask(LETTERS_MASK.0 | PUNCT_MASK.0 | 1 << SPACE_BIT);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{bit_char, char_bit};
+
+    #[test]
+    fn named_bits_match_the_layout() {
+        for (bit, (name, value)) in NAMED_BITS.into_iter().enumerate() {
+            assert_eq!(usize::from(value), bit, "{name}");
+            assert_eq!(char_bit(bit_char(value)).unwrap(), value, "{name}");
+        }
+
+        assert_eq!(char_bit('Q').unwrap(), LETTER_Q_BIT);
+        assert_eq!(LETTERS_MASK, Mask(0x07FF_FFFE));
+        assert_eq!(PUNCT_MASK, Mask(0xF800_0000));
+        assert_eq!(LETTERS_MASK | PUNCT_MASK | Mask(1 << SPACE_BIT), Mask::ALL);
+        assert_eq!(DEFINED_MASK, Mask::ALL);
+    }
+}
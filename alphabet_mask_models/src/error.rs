@@ -1,15 +1,141 @@
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
+use strum::IntoStaticStr;
 use thiserror::Error;
 
-use pyo3::{PyErr, PyTypeInfo};
+#[cfg(feature = "serde")]
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+#[cfg(feature = "python")]
+use pyo3::{exceptions, PyErr, PyTypeInfo};
+
+#[cfg(feature = "python")]
+pyo3::create_exception!(
+    alphabet_mask,
+    InputTooLargeError,
+    exceptions::PyValueError,
+    "Raised before any work begins when the inputs of a call exceed a limit set \
+     with `set_input_limits()`."
+);
 
 /// Various Error types that can arise from Graphaurus operations.
-#[derive(Error, Debug)]
+#[derive(Error, Debug, IntoStaticStr)]
 #[non_exhaustive]
 pub enum LocalError {
+    #[error("String contains invalid character {character:?}.")]
+    InvalidCharacter { character: char },
+
+    #[error("String contains control character {character:?} at position {position}.")]
+    ControlCharacter { character: char, position: usize },
+
+    #[error("String contains lone surrogate U+{code:04X} at position {position}.")]
+    LoneSurrogate { code: u16, position: usize },
+
+    #[error("input too large: {value} exceeds `{limit}` of {max}")]
+    InputTooLarge {
+        limit: &'static str,
+        value: usize,
+        max: usize,
+    },
+
+    #[error("{name} is closed")]
+    Closed { name: &'static str },
+
+    #[cfg(feature = "alloc")]
+    #[error("invalid value {value:?} for `{name}`")]
+    InvalidArgument { name: &'static str, value: String },
+
+    #[cfg(feature = "alloc")]
+    #[error("could not deserialise {kind}: {context}")]
+    Deserialisation { kind: &'static str, context: String },
+
+    #[cfg(feature = "std")]
+    #[error("could not access {path:?}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[cfg(feature = "alloc")]
+    #[error("invalid Arrow array: {context}")]
+    InvalidArrowArray { context: String },
+
+    #[cfg(feature = "std")]
+    #[error("invalid checkpoint {path:?}: {context}")]
+    InvalidCheckpoint { path: PathBuf, context: String },
+
+    #[cfg(feature = "alloc")]
+    #[error("invalid alphabet spec: {context}")]
+    InvalidSpec { context: String },
+
+    #[cfg(feature = "alloc")]
+    #[error("invalid mask: {context}")]
+    InvalidMask { context: String },
+
+    #[cfg(feature = "alloc")]
     #[error("unknown error occurred: {context}")]
     Unknown { context: String },
 }
 
+impl LocalError {
+    /// The name of the variant of this error, such as `"InvalidCharacter"`.
+    pub fn kind(&self) -> &'static str {
+        self.into()
+    }
+
+    /// The error for `character` being outside the alphabet: a
+    /// [`ControlCharacter`](Self::ControlCharacter) at position 0 if it is a control
+    /// character, or an [`InvalidCharacter`](Self::InvalidCharacter) otherwise.
+    pub fn outside_alphabet(character: char) -> Self {
+        if character.is_control() {
+            Self::ControlCharacter {
+                character,
+                position: 0,
+            }
+        } else {
+            Self::InvalidCharacter { character }
+        }
+    }
+
+    /// Returns this error with the position of its character set to `position`,
+    /// if it has one.
+    pub fn at(self, position: usize) -> Self {
+        match self {
+            Self::ControlCharacter { character, .. } => Self::ControlCharacter {
+                character,
+                position,
+            },
+            err => err,
+        }
+    }
+}
+
+/// Errors are serialised as their kind and message, as in
+/// `{"kind": "InvalidCharacter", "message": "String contains invalid character '1'."}`;
+/// they cannot be deserialised, since some carry an [`std::io::Error`].
+#[cfg(feature = "serde")]
+impl Serialize for LocalError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        /// Serialises the message of an error without allocating.
+        struct Message<'e>(&'e LocalError);
+
+        impl Serialize for Message<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self.0)
+            }
+        }
+
+        let mut state = serializer.serialize_struct("LocalError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &Message(self))?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "python")]
 impl LocalError {
     /// Consume this [`LocalError`], wrap it in a [`PyErr`], then return it.
     pub fn into_pyerr<E>(self) -> PyErr
@@ -19,3 +145,30 @@ impl LocalError {
         PyErr::new::<E, _>(self.to_string())
     }
 }
+
+#[cfg(feature = "python")]
+impl From<LocalError> for PyErr {
+    /// Map each [`LocalError`] to the closest built-in Python exception.
+    fn from(err: LocalError) -> Self {
+        match err {
+            LocalError::Io { .. } => err.into_pyerr::<exceptions::PyIOError>(),
+            LocalError::InputTooLarge { .. } => err.into_pyerr::<InputTooLargeError>(),
+            _ => err.into_pyerr::<exceptions::PyValueError>(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn serialise() {
+        let err = LocalError::InvalidCharacter { character: '1' };
+
+        assert_eq!(
+            serde_json::to_string(&err).unwrap(),
+            r#"{"kind":"InvalidCharacter","message":"String contains invalid character '1'."}"#
+        );
+    }
+}
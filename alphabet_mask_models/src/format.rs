@@ -0,0 +1,57 @@
+//! Text formats for writing masks out.
+//!
+use strum::{Display, EnumString};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How a mask is written out as text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[strum(serialize_all = "lowercase")]
+pub enum MaskFormat {
+    /// The mask as a decimal integer.
+    #[default]
+    Int,
+    /// The characters represented by the mask, as returned by `mask_to_chars`.
+    Chars,
+    /// A JSON object holding both of the above, as in `{"mask": 14, "chars": "abc"}`.
+    Json,
+}
+
+/// The container in which a batch of per-string masks is returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[strum(serialize_all = "lowercase")]
+pub enum BatchOutput {
+    /// A Python `list` of `int`, with `None` for null inputs.
+    #[default]
+    List,
+    /// A `pyarrow.UInt32Array` built in Rust, with nulls for null inputs.
+    Arrow,
+    /// A single `bytes` of little-endian `u32`, as read by
+    /// `numpy.frombuffer(..., dtype="<u4")`, with `0` for null inputs.
+    Bytes,
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn serde_round_trip() {
+        let json = serde_json::to_string(&MaskFormat::Chars).unwrap();
+
+        assert_eq!(json, r#""chars""#);
+        assert_eq!(
+            serde_json::from_str::<MaskFormat>(&json).unwrap(),
+            MaskFormat::Chars
+        );
+        assert_eq!(
+            serde_json::from_str::<BatchOutput>(r#""arrow""#).unwrap(),
+            BatchOutput::Arrow
+        );
+    }
+}
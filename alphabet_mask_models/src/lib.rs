@@ -1,3 +1,30 @@
+//! Types shared between `alphabet_mask_core` and its bindings.
+//!
+//! Without the `std` feature this crate is `no_std`; without `alloc` as well, only
+//! the errors that do not carry any text are available.
+//!
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+mod bitmask;
+pub mod bits;
 mod error;
+mod format;
+mod mask;
+mod policy;
+mod predicate;
+#[cfg(feature = "alloc")]
+mod spec;
 
+pub use bitmask::{BitMask, Mask128, Mask256, Mask64, WideMask};
+#[cfg(feature = "python")]
+pub use error::InputTooLargeError;
 pub use error::LocalError;
+pub use format::{BatchOutput, MaskFormat};
+pub use mask::{bit_char, char_bit, Mask};
+pub use policy::{CasePolicy, ControlCharPolicy, InvalidCharPolicy};
+pub use predicate::MaskPredicate;
+#[cfg(feature = "alloc")]
+pub use spec::{AlphabetSpec, MAX_WIDTH, OTHER_SYMBOL, TYPOGRAPHIC_FALLBACKS};
@@ -0,0 +1,433 @@
+//! The bit mask of an alphabet, in the standard layout.
+//!
+use core::fmt;
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+use core::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::bits::{
+    APOSTROPHE_BIT, COMMA_BIT, DOUBLE_QUOTE_BIT, FULL_STOP_BIT, HYPHEN_BIT, LETTERS_MASK,
+    PUNCT_MASK, SPACE_BIT,
+};
+use crate::LocalError;
+
+/// Returns the bit of the standard layout set by `character`.
+///
+/// Space is bit 0, the letters `a` to `z` are bits 1 to 26 regardless of case, and
+/// `.`, `,`, `'`, `-` and `"` are bits 27 to 31; see [`bits`](crate::bits) for
+/// their names.
+///
+/// A control character gives a [`LocalError::ControlCharacter`] at position 0,
+/// and any other character outside the alphabet a [`LocalError::InvalidCharacter`].
+#[inline]
+pub fn char_bit(character: char) -> Result<u8, LocalError> {
+    if !character.is_ascii() || character.is_control() {
+        return Err(LocalError::outside_alphabet(character));
+    }
+    let char_code = character as u8;
+
+    match char_code {
+        b' ' => Ok(SPACE_BIT),
+        b'.' => Ok(FULL_STOP_BIT),
+        b',' => Ok(COMMA_BIT),
+        b'\'' => Ok(APOSTROPHE_BIT),
+        b'-' => Ok(HYPHEN_BIT),
+        b'"' => Ok(DOUBLE_QUOTE_BIT),
+        v if v & 64 == 0 || v == b'@' || (b'['..=b'`').contains(&v) || v > b'z' => {
+            Err(LocalError::InvalidCharacter { character })
+        }
+        _ => Ok(char_code & 31),
+    }
+}
+
+/// Returns the character representing `bit` in the standard layout.
+///
+/// `bit` must be less than 32.
+#[inline]
+pub fn bit_char(bit: u8) -> char {
+    match bit {
+        SPACE_BIT => ' ',
+        FULL_STOP_BIT => '.',
+        COMMA_BIT => ',',
+        APOSTROPHE_BIT => '\'',
+        HYPHEN_BIT => '-',
+        DOUBLE_QUOTE_BIT => '"',
+        _ => (bit + 96) as char,
+    }
+}
+
+/// The set of characters of a string, as a bit mask in the standard layout.
+///
+/// Masks combine with `&` into their common alphabet and with `|` into their
+/// combined alphabet. They display as the characters they contain, and parse
+/// from any string made of those characters:
+///
+/// ```
+/// use alphabet_mask_models::Mask;
+///
+/// let mask: Mask = "Tea".parse().unwrap();
+/// assert_eq!(mask.to_string(), "aet");
+/// assert_eq!((mask & "ate.".parse().unwrap()).len(), 3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Mask(pub u32);
+
+impl Mask {
+    /// The mask of no characters, and the identity of `|`.
+    pub const EMPTY: Self = Self(0);
+
+    /// The mask of every character, and the identity of `&`.
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// The number of bits in a mask.
+    pub const WIDTH: u8 = 32;
+
+    /// Returns the underlying bits.
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the mask of a single character.
+    pub fn of_char(character: char) -> Result<Self, LocalError> {
+        char_bit(character).map(|bit| Self(1 << bit))
+    }
+
+    /// Returns the number of characters in this mask.
+    pub const fn len(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// Returns the number of distinct letters in this mask, also counting the
+    /// punctuation marks if `include_punctuation`; space is never counted.
+    pub const fn letter_count(self, include_punctuation: bool) -> u32 {
+        let mut counted = LETTERS_MASK.0;
+        if include_punctuation {
+            counted |= PUNCT_MASK.0;
+        }
+        (self.0 & counted).count_ones()
+    }
+
+    /// Returns whether this mask has no characters.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns whether every character of `self` is also in `other`.
+    pub const fn is_subset_of(self, other: Self) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    /// Returns whether `bit` is set.
+    pub const fn contains_bit(self, bit: u8) -> bool {
+        bit < Self::WIDTH && self.0 >> bit & 1 == 1
+    }
+
+    /// Returns the characters of this mask in bit order.
+    pub fn chars(self) -> impl Iterator<Item = char> {
+        self.bit_positions().map(bit_char)
+    }
+
+    /// Returns the position of every bit set, in order.
+    pub fn bit_positions(self) -> impl Iterator<Item = u8> {
+        (0..Self::WIDTH).filter(move |&bit| self.contains_bit(bit))
+    }
+
+    /// Returns the mask with the bits at `positions` set, or [`None`] if any is
+    /// not less than [`Mask::WIDTH`].
+    pub fn from_bit_positions(positions: impl IntoIterator<Item = u8>) -> Option<Self> {
+        positions.into_iter().try_fold(Self::EMPTY, |mask, bit| {
+            (bit < Self::WIDTH).then(|| Self(mask.0 | 1 << bit))
+        })
+    }
+
+    /// Returns whether each bit is set, from bit 0.
+    pub fn to_bools(self) -> [bool; Self::WIDTH as usize] {
+        core::array::from_fn(|bit| self.contains_bit(bit as u8))
+    }
+
+    /// Returns the mask with each bit set if its flag is, from bit 0.
+    pub fn from_bools(bools: [bool; Self::WIDTH as usize]) -> Self {
+        Self::from_bit_positions((0..Self::WIDTH).filter(|&bit| bools[usize::from(bit)]))
+            .unwrap_or_default()
+    }
+}
+
+impl From<u32> for Mask {
+    fn from(bits: u32) -> Self {
+        Self(bits)
+    }
+}
+
+impl From<Mask> for u32 {
+    fn from(mask: Mask) -> Self {
+        mask.0
+    }
+}
+
+impl fmt::Display for Mask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chars()
+            .try_for_each(|character| fmt::Write::write_char(f, character))
+    }
+}
+
+/// Implement the numeric formatting traits by formatting the bits, so that
+/// `{:#034b}` or `{:#x}` work as they do for a `u32`.
+macro_rules! impl_numeric_fmt {
+    ($($trait:ident),*) => {
+        $(
+            impl fmt::$trait for Mask {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    fmt::$trait::fmt(&self.0, f)
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric_fmt!(Binary, Octal, LowerHex, UpperHex);
+
+/// The bits of a mask labelled with their characters, returned by [`Mask::labelled`].
+struct Labelled(Mask);
+
+impl fmt::Display for Labelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (position, bit) in self.0.bit_positions().enumerate() {
+            if position > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{bit}:{:?}", bit_char(bit))?;
+        }
+        Ok(())
+    }
+}
+
+impl Mask {
+    /// Returns a display of every bit set with its character, as in
+    /// `0:' ' 1:'a' 27:'.'`.
+    pub fn labelled(self) -> impl fmt::Display {
+        Labelled(self)
+    }
+
+    /// Format this mask according to `spec`, a subset of Python's format
+    /// specification: `[#][0][width][type]`.
+    ///
+    /// The type is `c` for the characters, the default, `l` for the labelled
+    /// bits, or `d`, `b`, `o`, `x` or `X` for the bits as an integer; `#` adds
+    /// the `0b`, `0o` or `0x` prefix and the width only applies to integers,
+    /// padded with spaces, or zeros after the prefix with `0`.
+    ///
+    /// ```
+    /// use alphabet_mask_models::Mask;
+    ///
+    /// let mask = Mask(0b1110);
+    /// assert_eq!(mask.format("").unwrap(), "abc");
+    /// assert_eq!(mask.format("#010b").unwrap(), "0b00001110");
+    /// assert_eq!(mask.format("#x").unwrap(), "0xe");
+    /// assert_eq!(mask.format("l").unwrap(), "1:'a' 2:'b' 3:'c'");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn format(self, spec: &str) -> Result<alloc::string::String, LocalError> {
+        use alloc::{format, string::ToString};
+
+        let invalid = || LocalError::InvalidArgument {
+            name: "format_spec",
+            value: spec.to_string(),
+        };
+
+        let rest = spec.strip_prefix('#');
+        let alternate = rest.is_some();
+        let rest = rest.unwrap_or(spec);
+        let zero = rest.starts_with('0');
+        let (width, kind) = match rest.char_indices().last() {
+            Some((index, kind)) if kind.is_ascii_alphabetic() => (&rest[..index], kind),
+            _ => (rest, 'c'),
+        };
+        let width: usize = match width {
+            "" => 0,
+            width => width.parse().map_err(|_| invalid())?,
+        };
+
+        let (prefix, digits) = match kind {
+            'c' | 'l' if alternate || width > 0 => return Err(invalid()),
+            'c' => return Ok(self.to_string()),
+            'l' => return Ok(self.labelled().to_string()),
+            'd' if alternate => return Err(invalid()),
+            'd' => ("", format!("{}", self.0)),
+            'b' => ("0b", format!("{:b}", self.0)),
+            'o' => ("0o", format!("{:o}", self.0)),
+            'x' => ("0x", format!("{:x}", self.0)),
+            'X' => ("0X", format!("{:X}", self.0)),
+            _ => return Err(invalid()),
+        };
+        let prefix = if alternate { prefix } else { "" };
+
+        let padding = width.saturating_sub(prefix.len() + digits.len());
+        Ok(if zero {
+            format!("{prefix}{:0>padding$}{digits}", "")
+        } else {
+            format!("{:>padding$}{prefix}{digits}", "")
+        })
+    }
+}
+
+impl FromStr for Mask {
+    type Err = LocalError;
+
+    fn from_str(string: &str) -> Result<Self, LocalError> {
+        string.chars().try_fold(Self::EMPTY, |acc, character| {
+            Ok(acc | Self::of_char(character)?)
+        })
+    }
+}
+
+/// Implement a binary operator and its assigning form by applying it to the bits.
+macro_rules! impl_bit_op {
+    ($($trait:ident::$method:ident, $assign_trait:ident::$assign_method:ident => $op:tt;)*) => {
+        $(
+            impl $trait for Mask {
+                type Output = Self;
+
+                fn $method(self, other: Self) -> Self {
+                    Self(self.0 $op other.0)
+                }
+            }
+
+            impl $assign_trait for Mask {
+                fn $assign_method(&mut self, other: Self) {
+                    self.0 = self.0 $op other.0;
+                }
+            }
+        )*
+    };
+}
+
+impl_bit_op!(
+    BitAnd::bitand, BitAndAssign::bitand_assign => &;
+    BitOr::bitor, BitOrAssign::bitor_assign => |;
+    BitXor::bitxor, BitXorAssign::bitxor_assign => ^;
+);
+
+impl Not for Mask {
+    type Output = Self;
+
+    /// Returns every character not in this mask, within the valid bits.
+    fn not(self) -> Self {
+        Self(!self.0 & Self::ALL.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn char_bits() {
+        assert_eq!(char_bit('A').unwrap(), 1);
+        for character in ['\0', '\t', '\x1b', '\x7f', '\u{85}'] {
+            assert!(matches!(
+                char_bit(character),
+                Err(LocalError::ControlCharacter { character: c, position: 0 }) if c == character
+            ));
+        }
+        // Only the lowest byte of these matches a letter.
+        for character in ['1', '\u{141}', '\u{1F600}'] {
+            assert!(matches!(
+                char_bit(character),
+                Err(LocalError::InvalidCharacter { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn operators() {
+        let abc = Mask(0b1110);
+        let bcd = Mask(0b11100);
+
+        assert_eq!(abc & bcd, Mask(0b1100));
+        assert_eq!(abc | bcd, Mask(0b11110));
+        assert_eq!(abc ^ bcd, Mask(0b10010));
+        assert_eq!(!Mask::EMPTY, Mask::ALL);
+        assert_eq!((!abc & abc), Mask::EMPTY);
+        assert!((abc & bcd).is_subset_of(abc));
+        assert_eq!(abc.len(), 3);
+    }
+
+    #[test]
+    fn positional() {
+        let mask = Mask(0b1000_0000_0000_0000_0000_0000_0000_0110);
+
+        assert!(mask.bit_positions().eq([1, 2, 31]));
+        assert_eq!(Mask::from_bit_positions([31, 2, 1, 2]), Some(mask));
+        assert_eq!(Mask::from_bit_positions([32]), None);
+        assert_eq!(Mask::from_bit_positions([]), Some(Mask::EMPTY));
+
+        let bools = mask.to_bools();
+        assert_eq!(bools.iter().filter(|&&set| set).count(), 3);
+        assert!(bools[1] && bools[2] && bools[31] && !bools[0]);
+        assert_eq!(Mask::from_bools(bools), mask);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn formatting() {
+        use alloc::{format, string::ToString};
+
+        let mask: Mask = "a.".parse().unwrap();
+
+        assert_eq!(format!("{mask:#034b}"), format!("{:#034b}", mask.0));
+        assert_eq!(format!("{mask:X}"), "8000002");
+        assert_eq!(mask.labelled().to_string(), "1:'a' 27:'.'");
+        assert_eq!(Mask::EMPTY.labelled().to_string(), "");
+
+        for (spec, expected) in [
+            ("c", "a."),
+            ("d", "134217730"),
+            ("12d", "   134217730"),
+            ("b", "1000000000000000000000000010"),
+            ("#o", "0o1000000002"),
+            ("#012x", "0x0008000002"),
+            ("#12X", "   0X8000002"),
+            ("l", "1:'a' 27:'.'"),
+        ] {
+            assert_eq!(mask.format(spec).unwrap(), expected, "{spec}");
+        }
+        for spec in ["#c", "5l", "#d", "q", "x5", "-5x"] {
+            assert!(mask.format(spec).is_err(), "{spec}");
+        }
+    }
+
+    #[test]
+    fn letter_count() {
+        let mask: Mask = "Hello, world.".parse().unwrap();
+
+        assert_eq!(mask.letter_count(false), 7);
+        assert_eq!(mask.letter_count(true), 9);
+        assert_eq!(Mask::ALL.letter_count(false), 26);
+        assert_eq!(Mask::ALL.letter_count(true), 31);
+        assert_eq!(Mask::EMPTY.letter_count(true), 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn parse_and_display() {
+        let mask: Mask = "The quick brown fox jumps over the lazy dog."
+            .parse()
+            .unwrap();
+
+        assert_eq!(mask.len(), 28);
+        assert_eq!(
+            Mask(0b100_00000_00000_00000_00001_01011).to_string(),
+            " ace."
+        );
+        assert_eq!("Cab".parse::<Mask>().unwrap().to_string(), "abc");
+        assert!(matches!(
+            "a1".parse::<Mask>(),
+            Err(LocalError::InvalidCharacter { character: '1' })
+        ));
+    }
+}
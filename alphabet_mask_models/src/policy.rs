@@ -0,0 +1,51 @@
+//! Policies for how characters are classified.
+//!
+use strum::{Display, EnumString};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How the case of letters is treated when looking up their bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum CasePolicy {
+    /// Every character is looked up as its lowercase form, if that is a single character.
+    #[default]
+    Insensitive,
+    /// Characters are looked up exactly as given.
+    Sensitive,
+}
+
+/// What to do with a character that is not in the alphabet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[strum(serialize_all = "lowercase")]
+pub enum InvalidCharPolicy {
+    /// Fail with [`LocalError::InvalidCharacter`](crate::LocalError::InvalidCharacter).
+    #[default]
+    Error,
+    /// Leave the character out of the mask.
+    Skip,
+}
+
+/// What to do with a control character, such as a NUL, a tab or an escape, that
+/// is not itself a symbol of the alphabet.
+///
+/// Control characters are those of the Unicode category `Cc`: the C0 controls,
+/// DEL and the C1 controls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[strum(serialize_all = "lowercase")]
+pub enum ControlCharPolicy {
+    /// Fail with [`LocalError::ControlCharacter`](crate::LocalError::ControlCharacter),
+    /// giving its position.
+    #[default]
+    Error,
+    /// Leave the character out of the mask.
+    Skip,
+    /// Set the bit of a space instead.
+    Space,
+}
@@ -0,0 +1,72 @@
+//! Conditions on the mask of a string, relative to a reference mask.
+//!
+use strum::{Display, EnumString};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::Mask;
+
+/// How the mask of a string must relate to a reference mask to match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[strum(serialize_all = "lowercase")]
+pub enum MaskPredicate {
+    /// The string only uses characters of the reference.
+    Subset,
+    /// The string uses every character of the reference.
+    Superset,
+    /// The string uses at least one character of the reference.
+    Contains,
+    /// The string uses no character of the reference.
+    Avoids,
+}
+
+impl MaskPredicate {
+    /// Returns whether `mask` matches this predicate against `reference`.
+    ///
+    /// ```
+    /// use alphabet_mask_models::{Mask, MaskPredicate};
+    ///
+    /// let (cat, tac) = ("cat".parse::<Mask>().unwrap(), "act".parse().unwrap());
+    /// assert!(MaskPredicate::Subset.matches(cat, tac));
+    /// assert!(!MaskPredicate::Avoids.matches(cat, "at".parse().unwrap()));
+    /// ```
+    pub const fn matches(self, mask: Mask, reference: Mask) -> bool {
+        match self {
+            Self::Subset => mask.is_subset_of(reference),
+            Self::Superset => reference.is_subset_of(mask),
+            Self::Contains => mask.0 & reference.0 != 0,
+            Self::Avoids => mask.0 & reference.0 == 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn predicates() {
+        let mask: Mask = "the cat".parse().unwrap();
+        let cases = [
+            ("the cat.", [true, false, true, false]),
+            ("cat", [false, true, true, false]),
+            ("dog", [false, false, false, true]),
+            ("", [false, true, false, true]),
+        ];
+
+        for (reference, expected) in cases {
+            let reference = reference.parse().unwrap();
+            let matches = [
+                MaskPredicate::Subset,
+                MaskPredicate::Superset,
+                MaskPredicate::Contains,
+                MaskPredicate::Avoids,
+            ]
+            .map(|predicate| predicate.matches(mask, reference));
+            assert_eq!(matches, expected, "{reference:?}");
+        }
+    }
+}
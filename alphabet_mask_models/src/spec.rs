@@ -0,0 +1,550 @@
+//! The bit layout of an alphabet.
+//!
+use alloc::{format, string::String, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{CasePolicy, LocalError, Mask};
+
+/// The widest mask an [`AlphabetSpec`] can currently describe, in bits.
+pub const MAX_WIDTH: u8 = 32;
+
+/// Typographic punctuation with the ASCII symbol of the standard layout it
+/// falls back to, for [`AlphabetSpec::with_fallbacks`].
+pub const TYPOGRAPHIC_FALLBACKS: [(char, char); 10] = [
+    ('\u{2018}', '\''),
+    ('\u{2019}', '\''),
+    ('\u{201C}', '"'),
+    ('\u{201D}', '"'),
+    ('\u{2010}', '-'),
+    ('\u{2011}', '-'),
+    ('\u{2013}', '-'),
+    ('\u{2014}', '-'),
+    ('\u{2026}', '.'),
+    ('\u{00A0}', ' '),
+];
+
+/// The character representing the `other` bit of an [`AlphabetSpec`]; it is
+/// never a symbol itself, so masks back to that bit.
+pub const OTHER_SYMBOL: char = char::REPLACEMENT_CHARACTER;
+
+/// A declaration of which bit of a mask each symbol sets.
+///
+/// Several symbols may share a bit, in which case the first one listed is used
+/// when converting the bit back into a character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AlphabetSpec {
+    /// The number of bits in a mask.
+    pub width: u8,
+    /// How the case of letters is treated.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub case: CasePolicy,
+    /// Every symbol of the alphabet with the bit it sets.
+    ///
+    /// Spec files may also give the symbols as a string in bit order, as
+    /// [`from_order`](Self::from_order) does.
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_symbols"))]
+    pub symbols: Vec<(char, u8)>,
+    /// Bits that no symbol may set, kept free for future assignment.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reserved: Vec<u8>,
+    /// The bit set by every character outside the alphabet, which then no longer
+    /// makes a string invalid; no symbol may set it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub other: Option<u8>,
+    /// Characters outside the alphabet that set the bit of a symbol instead,
+    /// such as a curly apostrophe setting the bit of `'`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fallbacks: Vec<(char, char)>,
+}
+
+impl Default for AlphabetSpec {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+impl AlphabetSpec {
+    /// The layout used throughout this crate: space on bit 0, the letters `a` to
+    /// `z` on bits 1 to 26 regardless of case, then `.`, `,`, `'`, `-` and `"` on
+    /// bits 27 to 31.
+    pub fn standard() -> Self {
+        let letters = ('a'..='z').zip(1..);
+        let punctuation = ['.', ',', '\'', '-', '"'].into_iter().zip(27..);
+
+        Self {
+            width: 32,
+            case: CasePolicy::Insensitive,
+            symbols: [(' ', 0)]
+                .into_iter()
+                .chain(letters)
+                .chain(punctuation)
+                .collect(),
+            reserved: Vec::new(),
+            other: None,
+            fallbacks: Vec::new(),
+        }
+    }
+
+    /// A case insensitive layout of the characters of `order`, each on the bit of
+    /// its position, such as frequency-ordered letters:
+    ///
+    /// ```
+    /// use alphabet_mask_models::AlphabetSpec;
+    ///
+    /// let spec = AlphabetSpec::from_order(". etaoinshrdlcumwfgypbvkjxqz").unwrap();
+    /// assert_eq!((spec.width, spec.bit('E'), spec.bit('z')), (28, Some(2), Some(27)));
+    /// ```
+    pub fn from_order(order: &str) -> Result<Self, LocalError> {
+        let spec = Self {
+            width: u8::try_from(order.chars().count()).unwrap_or(u8::MAX),
+            case: CasePolicy::Insensitive,
+            symbols: order.chars().zip(0..).collect(),
+            reserved: Vec::new(),
+            other: None,
+            fallbacks: Vec::new(),
+        };
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Returns this spec with `fallbacks` added to its own, e.g.
+    /// `AlphabetSpec::standard().with_fallbacks(TYPOGRAPHIC_FALLBACKS)`.
+    pub fn with_fallbacks(mut self, fallbacks: impl IntoIterator<Item = (char, char)>) -> Self {
+        self.fallbacks.extend(fallbacks);
+        self
+    }
+
+    /// Returns the form of `character` that is looked up, according to the case policy.
+    pub fn fold(&self, character: char) -> char {
+        match self.case {
+            CasePolicy::Sensitive => character,
+            CasePolicy::Insensitive => {
+                let mut lowercase = character.to_lowercase();
+                match (lowercase.next(), lowercase.next()) {
+                    (Some(folded), None) => folded,
+                    _ => character,
+                }
+            }
+        }
+    }
+
+    /// Returns the bit set by `character`, or [`None`] if it is not in the alphabet.
+    ///
+    /// A character with a fallback sets the bit of the symbol it falls back to.
+    pub fn bit(&self, character: char) -> Option<u8> {
+        let character = self.fold(character);
+        self.symbol_bit(character).or_else(|| {
+            self.fallbacks
+                .iter()
+                .find(|&&(from, _)| self.fold(from) == character)
+                .and_then(|&(_, to)| self.symbol_bit(self.fold(to)))
+        })
+    }
+
+    /// Returns the bit of a symbol, already folded.
+    fn symbol_bit(&self, character: char) -> Option<u8> {
+        self.symbols
+            .iter()
+            .find(|(symbol, _)| *symbol == character)
+            .map(|&(_, bit)| bit)
+    }
+
+    /// Returns the character representing `bit`, or [`None`] if no symbol sets it.
+    ///
+    /// The `other` bit is represented by [`OTHER_SYMBOL`].
+    pub fn symbol(&self, bit: u8) -> Option<char> {
+        if self.other == Some(bit) {
+            return Some(OTHER_SYMBOL);
+        }
+
+        self.symbols
+            .iter()
+            .find(|&&(_, symbol_bit)| symbol_bit == bit)
+            .map(|&(symbol, _)| symbol)
+    }
+
+    /// Returns `mask` of this layout converted to the layout of `to`, moving the
+    /// bit of each symbol to the bit of the same symbol in `to`, and the `other`
+    /// bit to the `other` bit of `to`.
+    ///
+    /// Fails if a bit of `mask` has no symbol here, or its symbol has no bit in
+    /// `to`.
+    pub fn convert_mask(&self, mask: Mask, to: &Self) -> Result<Mask, LocalError> {
+        mask.bit_positions()
+            .try_fold(Mask::EMPTY, |converted, bit| {
+                let symbol = self.symbol(bit).ok_or_else(|| LocalError::InvalidMask {
+                    context: format!("bit {bit} has no symbol"),
+                })?;
+                let target = if self.other == Some(bit) {
+                    to.other
+                } else {
+                    to.bit(symbol)
+                };
+                let target = target.ok_or_else(|| LocalError::InvalidMask {
+                    context: format!("{symbol:?} has no bit in the target layout"),
+                })?;
+                Ok(converted | Mask(1 << target))
+            })
+    }
+
+    /// Returns the bit set by `character` when masking: its own bit, or the
+    /// `other` bit if it is not in the alphabet and is not a control character.
+    pub fn mask_bit(&self, character: char) -> Option<u8> {
+        self.bit(character)
+            .or_else(|| self.other.filter(|_| !character.is_control()))
+    }
+
+    /// Parse and validate a spec from a TOML document, such as:
+    ///
+    /// ```toml
+    /// width = 4
+    /// case = "sensitive"
+    /// symbols = [["a", 0], ["b", 1], ["A", 2]]
+    /// reserved = [3]
+    /// fallbacks = [["á", "a"]]
+    /// ```
+    ///
+    /// `case` defaults to `"insensitive"`, `reserved` to no bits, `other` to no
+    /// bit, so that characters outside the alphabet are invalid, and `fallbacks`
+    /// to none.
+    #[cfg(feature = "spec-files")]
+    pub fn from_toml(text: &str) -> Result<Self, LocalError> {
+        Self::parsed(toml::from_str(text).map_err(|err| format!("{err}")))
+    }
+
+    /// Parse and validate a spec from a JSON document, with the same fields as
+    /// [`from_toml`](Self::from_toml):
+    ///
+    /// ```json
+    /// {"width": 4, "case": "sensitive", "symbols": [["a", 0], ["b", 1], ["A", 2]]}
+    /// ```
+    #[cfg(feature = "spec-files")]
+    pub fn from_json(text: &str) -> Result<Self, LocalError> {
+        Self::parsed(serde_json::from_str(text).map_err(|err| format!("{err}")))
+    }
+
+    /// Load and validate a spec from a `.toml` or `.json` file, according to its
+    /// extension.
+    #[cfg(feature = "spec-files")]
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, LocalError> {
+        let path = path.as_ref();
+        let parse = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::from_toml,
+            Some("json") => Self::from_json,
+            _ => {
+                return Err(LocalError::InvalidSpec {
+                    context: format!("{path:?} is neither a .toml nor a .json file"),
+                })
+            }
+        };
+
+        let text = std::fs::read_to_string(path).map_err(|source| LocalError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        parse(&text).map_err(|err| match err {
+            LocalError::InvalidSpec { context } => LocalError::InvalidSpec {
+                context: format!("{path:?}: {context}"),
+            },
+            err => err,
+        })
+    }
+
+    /// Validate a freshly parsed spec.
+    #[cfg(feature = "spec-files")]
+    fn parsed(spec: Result<Self, String>) -> Result<Self, LocalError> {
+        let spec = spec.map_err(|context| LocalError::InvalidSpec { context })?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Check that the width is supported, that every bit fits in it and is not
+    /// reserved, that no symbol is listed twice, that the `other` bit is not
+    /// shared with any symbol, and that every fallback is from a character
+    /// outside the alphabet to a symbol.
+    pub fn validate(&self) -> Result<(), LocalError> {
+        let invalid = |context: String| Err(LocalError::InvalidSpec { context });
+
+        if self.width == 0 || self.width > MAX_WIDTH {
+            return invalid(format!(
+                "width must be 1 to {MAX_WIDTH}, not {}",
+                self.width
+            ));
+        }
+        if let Some(bit) = self.reserved.iter().find(|&&bit| bit >= self.width) {
+            return invalid(format!(
+                "reserved bit {bit} does not fit in {} bits",
+                self.width
+            ));
+        }
+
+        for (index, &(symbol, bit)) in self.symbols.iter().enumerate() {
+            if bit >= self.width {
+                return invalid(format!(
+                    "bit {bit} of {symbol:?} does not fit in {} bits",
+                    self.width
+                ));
+            }
+            if self.reserved.contains(&bit) {
+                return invalid(format!("bit {bit} of {symbol:?} is reserved"));
+            }
+            let folded = self.fold(symbol);
+            if self.symbols[..index]
+                .iter()
+                .any(|&(other, _)| self.fold(other) == folded)
+            {
+                return invalid(format!("{symbol:?} is listed more than once"));
+            }
+        }
+
+        if let Some(other) = self.other {
+            if other >= self.width {
+                return invalid(format!(
+                    "the other bit {other} does not fit in {} bits",
+                    self.width
+                ));
+            }
+            if self.reserved.contains(&other) {
+                return invalid(format!("the other bit {other} is reserved"));
+            }
+            if let Some(&(symbol, _)) = self
+                .symbols
+                .iter()
+                .find(|&&(symbol, bit)| bit == other || self.fold(symbol) == OTHER_SYMBOL)
+            {
+                return invalid(format!("{symbol:?} cannot share the other bit {other}"));
+            }
+        }
+
+        for (index, &(from, to)) in self.fallbacks.iter().enumerate() {
+            let folded = self.fold(from);
+            if self.symbol_bit(folded).is_some() {
+                return invalid(format!(
+                    "{from:?} is a symbol, so cannot fall back to {to:?}"
+                ));
+            }
+            if self.symbol_bit(self.fold(to)).is_none() {
+                return invalid(format!(
+                    "{from:?} falls back to {to:?}, which is not a symbol"
+                ));
+            }
+            if self.fallbacks[..index]
+                .iter()
+                .any(|&(other, _)| self.fold(other) == folded)
+            {
+                return invalid(format!("{from:?} falls back more than once"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Deserialise the symbols of a spec, either as pairs of a symbol and its bit,
+/// or as a string of the symbols in bit order.
+#[cfg(feature = "serde")]
+fn deserialize_symbols<'de, D>(deserializer: D) -> Result<Vec<(char, u8)>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Symbols {
+        Pairs(Vec<(char, u8)>),
+        Order(String),
+    }
+
+    Ok(match Symbols::deserialize(deserializer)? {
+        Symbols::Pairs(pairs) => pairs,
+        Symbols::Order(order) => order.chars().zip(0..).collect(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn standard() {
+        let spec = AlphabetSpec::standard();
+
+        assert!(spec.validate().is_ok());
+        assert_eq!(spec.bit(' '), Some(0));
+        assert_eq!(spec.bit('A'), Some(1));
+        assert_eq!(spec.bit('z'), Some(26));
+        assert_eq!(spec.bit('"'), Some(31));
+        assert_eq!(spec.bit('1'), None);
+        assert_eq!(spec.symbol(27), Some('.'));
+    }
+
+    #[test]
+    fn case_sensitive() {
+        let spec = AlphabetSpec {
+            case: CasePolicy::Sensitive,
+            ..AlphabetSpec::standard()
+        };
+
+        assert_eq!(spec.bit('a'), Some(1));
+        assert_eq!(spec.bit('A'), None);
+    }
+
+    #[test]
+    fn other_bit() {
+        let spec = AlphabetSpec {
+            width: 3,
+            case: CasePolicy::Insensitive,
+            symbols: alloc::vec![('a', 0), ('b', 1)],
+            reserved: Vec::new(),
+            other: Some(2),
+            fallbacks: Vec::new(),
+        };
+
+        assert_eq!(spec.mask_bit('B'), Some(1));
+        assert_eq!(spec.mask_bit('?'), Some(2));
+        assert_eq!(spec.mask_bit('\0'), None);
+        assert_eq!(spec.bit('?'), None);
+        assert_eq!(spec.symbol(2), Some(OTHER_SYMBOL));
+        assert_eq!(AlphabetSpec::standard().mask_bit('?'), None);
+    }
+
+    #[test]
+    fn custom_order() {
+        let frequency = AlphabetSpec::from_order("etaoinshrdlcumwfgypbvkjxqz .,'-\"").unwrap();
+        let standard = AlphabetSpec::standard();
+        assert_eq!(frequency.symbol(0), Some('e'));
+        assert_eq!(frequency.bit(' '), Some(26));
+
+        let mask = Mask(0b1011); // " ac" in the standard layout
+        let converted = standard.convert_mask(mask, &frequency).unwrap();
+        assert_eq!(converted, Mask(1 << 26 | 1 << 2 | 1 << 11));
+        assert_eq!(frequency.convert_mask(converted, &standard).unwrap(), mask);
+
+        let letters = AlphabetSpec::from_order("abc").unwrap();
+        assert!(standard.convert_mask(Mask(1 << 4), &letters).is_err());
+        assert!(letters.convert_mask(Mask(1 << 3), &standard).is_err());
+
+        assert!(AlphabetSpec::from_order("abca").is_err());
+        assert!(AlphabetSpec::from_order(&"x".repeat(33)).is_err());
+    }
+
+    #[test]
+    fn fallbacks() {
+        let spec = AlphabetSpec::standard().with_fallbacks(TYPOGRAPHIC_FALLBACKS);
+
+        assert!(spec.validate().is_ok());
+        assert_eq!(spec.bit('\u{2019}'), spec.bit('\''));
+        assert_eq!(spec.bit('\u{2014}'), spec.bit('-'));
+        assert_eq!(spec.bit('\u{2026}'), spec.bit('.'));
+        assert_eq!(spec.symbol(29), Some('\''));
+
+        let spec = spec.with_fallbacks([('É', 'e')]);
+        assert_eq!(spec.bit('é'), Some(5));
+        assert_eq!(AlphabetSpec::standard().bit('\u{2019}'), None);
+    }
+
+    #[cfg(feature = "spec-files")]
+    #[test]
+    fn from_documents() {
+        let expected = AlphabetSpec {
+            width: 4,
+            case: CasePolicy::Sensitive,
+            symbols: alloc::vec![('a', 0), ('b', 1), ('A', 2)],
+            reserved: alloc::vec![3],
+            other: None,
+            fallbacks: Vec::new(),
+        };
+        let toml = r#"
+            width = 4
+            case = "sensitive"
+            symbols = [["a", 0], ["b", 1], ["A", 2]]
+            reserved = [3]
+        "#;
+        let json = r#"{"width": 4, "case": "sensitive", "symbols": [["a", 0], ["b", 1], ["A", 2]], "reserved": [3]}"#;
+        let ordered = "width = 4\ncase = \"sensitive\"\nsymbols = \"abA\"\nreserved = [3]";
+        assert_eq!(AlphabetSpec::from_toml(ordered).unwrap(), expected);
+
+        assert_eq!(AlphabetSpec::from_toml(toml).unwrap(), expected);
+        assert_eq!(AlphabetSpec::from_json(json).unwrap(), expected);
+
+        let path = std::env::temp_dir().join("alphabet_mask_spec_from_documents.toml");
+        std::fs::write(&path, toml).unwrap();
+        let loaded = AlphabetSpec::from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), expected);
+
+        for invalid in [
+            AlphabetSpec::from_toml("width = 4\nsymbols = [[\"a\", 4]]"),
+            AlphabetSpec::from_toml("width = 4"),
+            AlphabetSpec::from_json("{"),
+            AlphabetSpec::from_file("spec.yaml"),
+        ] {
+            assert!(matches!(invalid, Err(LocalError::InvalidSpec { .. })));
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let spec = AlphabetSpec {
+            width: 8,
+            case: CasePolicy::Insensitive,
+            symbols: alloc::vec![('a', 0), ('b', 1)],
+            reserved: alloc::vec![7],
+            other: None,
+            fallbacks: Vec::new(),
+        };
+        assert!(spec.validate().is_ok());
+        assert!(AlphabetSpec {
+            other: Some(6),
+            ..spec.clone()
+        }
+        .validate()
+        .is_ok());
+
+        for spec in [
+            AlphabetSpec {
+                width: 40,
+                ..spec.clone()
+            },
+            AlphabetSpec {
+                symbols: alloc::vec![('a', 8)],
+                ..spec.clone()
+            },
+            AlphabetSpec {
+                symbols: alloc::vec![('a', 7)],
+                ..spec.clone()
+            },
+            AlphabetSpec {
+                symbols: alloc::vec![('a', 0), ('A', 1)],
+                ..spec.clone()
+            },
+            AlphabetSpec {
+                other: Some(8),
+                ..spec.clone()
+            },
+            AlphabetSpec {
+                other: Some(7),
+                ..spec.clone()
+            },
+            AlphabetSpec {
+                other: Some(1),
+                ..spec.clone()
+            },
+            AlphabetSpec {
+                symbols: alloc::vec![('a', 0), (OTHER_SYMBOL, 1)],
+                other: Some(6),
+                ..spec.clone()
+            },
+            spec.clone().with_fallbacks([('A', 'b')]),
+            spec.clone().with_fallbacks([('c', 'd')]),
+            spec.clone().with_fallbacks([('c', 'a'), ('C', 'b')]),
+        ] {
+            assert!(matches!(
+                spec.validate(),
+                Err(LocalError::InvalidSpec { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,16 @@
+//! Generates the C header for the `capi` feature.
+
+fn main() {
+    #[cfg(feature = "capi")]
+    {
+        println!("cargo:rerun-if-changed=src/rust/capi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+
+        cbindgen::Builder::new()
+            .with_src("src/rust/capi.rs")
+            .with_config(cbindgen::Config::from_file("cbindgen.toml").unwrap())
+            .generate()
+            .expect("Unable to generate the C header.")
+            .write_to_file("include/alphabet_mask.h");
+    }
+}
@@ -0,0 +1,84 @@
+#ifndef ALPHABET_MASK_H
+#define ALPHABET_MASK_H
+
+/* Generated by cbindgen from src/rust/capi.rs; do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The maximum number of characters in the output of `alphabet_mask_to_chars`,
+ * excluding the terminating NUL.
+ */
+#define ALPHABET_MASK_MAX_CHARS 32
+
+/**
+ * The outcome of a call into the C interface.
+ */
+typedef enum AlphabetMaskStatus {
+  /**
+   * The call succeeded.
+   */
+  ALPHABET_MASK_STATUS_OK = 0,
+  /**
+   * A string contains a character outside the alphabet.
+   */
+  ALPHABET_MASK_STATUS_INVALID_CHARACTER = 1,
+  /**
+   * A string is not valid UTF-8.
+   */
+  ALPHABET_MASK_STATUS_INVALID_UTF8 = 2,
+  /**
+   * A required pointer is NULL.
+   */
+  ALPHABET_MASK_STATUS_NULL_POINTER = 3,
+  /**
+   * Any other error.
+   */
+  ALPHABET_MASK_STATUS_OTHER = 4,
+} AlphabetMaskStatus;
+
+/**
+ * Compute the bit mask of the UTF-8 string at `ptr`, of `len` bytes, into `out`.
+ *
+ * The string does not need to be NUL-terminated.
+ *
+ * # Safety
+ *
+ * `ptr` must be valid for reads of `len` bytes, and `out` valid for a write.
+ */
+enum AlphabetMaskStatus alphabet_mask_string(const char *ptr, size_t len, uint32_t *out);
+
+/**
+ * Compute the common bit mask of `count` UTF-8 strings into `out`.
+ *
+ * The `i`-th string starts at `ptrs[i]` and is `lens[i]` bytes long. Large inputs
+ * are processed in parallel if the library is built with the `parallel` feature.
+ *
+ * # Safety
+ *
+ * `ptrs` and `lens` must both be valid for reads of `count` elements, each string
+ * must be valid for reads of its length, and `out` must be valid for a write.
+ */
+enum AlphabetMaskStatus alphabet_mask_common(const char *const *ptrs,
+                                             const size_t *lens,
+                                             size_t count,
+                                             uint32_t *out);
+
+/**
+ * Write the characters represented by `mask` into `buffer` as a NUL-terminated
+ * string, truncating it to fit `buffer_len` bytes.
+ *
+ * Returns the number of characters in the full string, excluding the NUL, in the
+ * manner of `snprintf`; a buffer of `ALPHABET_MASK_MAX_CHARS + 1` bytes always fits.
+ *
+ * # Safety
+ *
+ * `buffer` must be valid for writes of `buffer_len` bytes, unless `buffer_len` is zero.
+ */
+size_t alphabet_mask_to_chars(uint32_t mask, char *buffer, size_t buffer_len);
+
+#endif  /* ALPHABET_MASK_H */
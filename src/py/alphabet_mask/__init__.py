@@ -10,9 +10,97 @@ This project includes a Rust binary backend:
 
 - :mod:`lib_alphabet_mask` which can be loaded as
   :attr:`~alphabet_mask.bin`.
+
+Thread safety
+-------------
+
+Every function, and the methods of :class:`Masker` and :class:`WordIndex`, can
+be called concurrently from any number of Python threads; the GIL is released
+while strings are masked, so the calls also run in parallel. Shared state is
+limited to the settings of :func:`set_chunk_limit`, :func:`set_input_limits`,
+:func:`set_worker_stats` and :func:`calibrate`, which apply from the next call, and the counters of
+:func:`get_counters`; :func:`last_call_stats` is kept per thread.
+
+Calls over no more strings than the chunk limit in total run on a small pool
+of their own, so that they are not held up by another thread's large call.
+
+Determinism
+-----------
+
+Whether a call masks its strings in parallel, and however its chunks are
+scheduled, it returns the same result, bit for bit, as masking them serially,
+and raises the same exception: that of the first string outside the alphabet.
+Only the ``length_limit`` and chunk limit decide whether a call is parallel, so
+they can be tuned without affecting any result.
 """
 __all__ = [
+    "Alphabet",
+    "AlphabetMask",
+    "BatchWorker",
+    "CallStats",
+    "CountedAlphabet",
+    "FileStream",
+    "InputTooLargeError",
+    "LENGTH_LIMIT_PER_CHUNK",
+    "MaskChunks",
+    "Masker",
+    "Pipeline",
+    "SharedStrings",
+    "UnicodeMask",
+    "WordIndex",
+    "aio",
+    "bits",
+    "build_info",
+    "calibrate",
+    "chunk_limit",
+    "distributed",
+    "from_pandas",
+    "get_counters",
+    "input_limits",
+    "last_call_stats",
     "python",
+    "reset_counters",
     "rust",
+    "set_chunk_limit",
+    "set_input_limits",
+    "set_worker_stats",
 ]
-from . import python, lib_alphabet_mask as rust
+from . import aio, bits, distributed, python, lib_alphabet_mask as rust
+from .lib_alphabet_mask import (
+    Alphabet,
+    AlphabetMask,
+    BatchWorker,
+    CallStats,
+    CountedAlphabet,
+    FileStream,
+    InputTooLargeError,
+    LENGTH_LIMIT_PER_CHUNK,
+    MaskChunks,
+    Masker,
+    Pipeline,
+    SharedStrings,
+    UnicodeMask,
+    WordIndex,
+    build_info,
+    calibrate,
+    chunk_limit,
+    get_counters,
+    input_limits,
+    last_call_stats,
+    reset_counters,
+    set_chunk_limit,
+    set_input_limits,
+    set_worker_stats,
+)
+
+
+def from_pandas(series, null_policy="propagate"):
+    """
+    Return the bit mask of each value of a :class:`pandas.Series`.
+
+    See :func:`alphabet_mask.integrations.pandas.from_pandas`; pandas and pyarrow
+    are only imported on first use.
+    """
+    from .integrations.pandas import from_pandas
+
+    return from_pandas(series, null_policy)
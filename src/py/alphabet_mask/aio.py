@@ -0,0 +1,33 @@
+# -*- coding: utf-8 -*-
+"""
+Awaitable variants of the file APIs.
+
+If the Rust backend was built with the ``async-io`` feature, the file reads are
+overlapped with the masking on a tokio runtime; otherwise the synchronous
+implementation is used. Either way the work runs in an executor, so the event
+loop is never blocked.
+"""
+import asyncio
+import functools
+import os
+from typing import Iterable, Optional, Union
+
+from . import lib_alphabet_mask as rust
+
+_common_alphabets_files = getattr(
+    rust, "common_alphabets_files_async", rust.common_alphabets_files
+)
+
+
+async def common_alphabets_files(
+    paths: Iterable[Union[str, os.PathLike]],
+    length_limit: Optional[int] = None,
+) -> str:
+    """
+    Return the common alphabet of every line in the given files.
+    """
+    loop = asyncio.get_running_loop()
+    return await loop.run_in_executor(
+        None,
+        functools.partial(_common_alphabets_files, list(paths), length_limit),
+    )
@@ -0,0 +1,80 @@
+# -*- coding: utf-8 -*-
+"""
+Named bit positions and masks of the standard layout.
+
+These mirror the constants of the Rust backend, e.g. ``1 << FULL_STOP_BIT`` or
+``mask & LETTERS_MASK``.
+"""
+from . import lib_alphabet_mask as _rust
+
+__all__ = [
+    "SPACE_BIT",
+    "LETTER_A_BIT",
+    "LETTER_B_BIT",
+    "LETTER_C_BIT",
+    "LETTER_D_BIT",
+    "LETTER_E_BIT",
+    "LETTER_F_BIT",
+    "LETTER_G_BIT",
+    "LETTER_H_BIT",
+    "LETTER_I_BIT",
+    "LETTER_J_BIT",
+    "LETTER_K_BIT",
+    "LETTER_L_BIT",
+    "LETTER_M_BIT",
+    "LETTER_N_BIT",
+    "LETTER_O_BIT",
+    "LETTER_P_BIT",
+    "LETTER_Q_BIT",
+    "LETTER_R_BIT",
+    "LETTER_S_BIT",
+    "LETTER_T_BIT",
+    "LETTER_U_BIT",
+    "LETTER_V_BIT",
+    "LETTER_W_BIT",
+    "LETTER_X_BIT",
+    "LETTER_Y_BIT",
+    "LETTER_Z_BIT",
+    "FULL_STOP_BIT",
+    "COMMA_BIT",
+    "APOSTROPHE_BIT",
+    "HYPHEN_BIT",
+    "DOUBLE_QUOTE_BIT",
+    "LETTERS_MASK",
+    "PUNCT_MASK",
+]
+
+SPACE_BIT: int = _rust.SPACE_BIT
+LETTER_A_BIT: int = _rust.LETTER_A_BIT
+LETTER_B_BIT: int = _rust.LETTER_B_BIT
+LETTER_C_BIT: int = _rust.LETTER_C_BIT
+LETTER_D_BIT: int = _rust.LETTER_D_BIT
+LETTER_E_BIT: int = _rust.LETTER_E_BIT
+LETTER_F_BIT: int = _rust.LETTER_F_BIT
+LETTER_G_BIT: int = _rust.LETTER_G_BIT
+LETTER_H_BIT: int = _rust.LETTER_H_BIT
+LETTER_I_BIT: int = _rust.LETTER_I_BIT
+LETTER_J_BIT: int = _rust.LETTER_J_BIT
+LETTER_K_BIT: int = _rust.LETTER_K_BIT
+LETTER_L_BIT: int = _rust.LETTER_L_BIT
+LETTER_M_BIT: int = _rust.LETTER_M_BIT
+LETTER_N_BIT: int = _rust.LETTER_N_BIT
+LETTER_O_BIT: int = _rust.LETTER_O_BIT
+LETTER_P_BIT: int = _rust.LETTER_P_BIT
+LETTER_Q_BIT: int = _rust.LETTER_Q_BIT
+LETTER_R_BIT: int = _rust.LETTER_R_BIT
+LETTER_S_BIT: int = _rust.LETTER_S_BIT
+LETTER_T_BIT: int = _rust.LETTER_T_BIT
+LETTER_U_BIT: int = _rust.LETTER_U_BIT
+LETTER_V_BIT: int = _rust.LETTER_V_BIT
+LETTER_W_BIT: int = _rust.LETTER_W_BIT
+LETTER_X_BIT: int = _rust.LETTER_X_BIT
+LETTER_Y_BIT: int = _rust.LETTER_Y_BIT
+LETTER_Z_BIT: int = _rust.LETTER_Z_BIT
+FULL_STOP_BIT: int = _rust.FULL_STOP_BIT
+COMMA_BIT: int = _rust.COMMA_BIT
+APOSTROPHE_BIT: int = _rust.APOSTROPHE_BIT
+HYPHEN_BIT: int = _rust.HYPHEN_BIT
+DOUBLE_QUOTE_BIT: int = _rust.DOUBLE_QUOTE_BIT
+LETTERS_MASK: int = _rust.LETTERS_MASK
+PUNCT_MASK: int = _rust.PUNCT_MASK
@@ -0,0 +1,43 @@
+# -*- coding: utf-8 -*-
+"""
+Map and reduce steps for tree reductions in Dask, Ray and similar frameworks.
+
+These are plain module-level functions, so they pickle by reference::
+
+    import dask.bag as db
+    from alphabet_mask import distributed
+
+    partials = db.from_sequence(strings, npartitions=64).map_partitions(
+        lambda part: [distributed.partial_mask(list(part))]
+    )
+    mask = distributed.decode(partials.reduction(
+        distributed.combine_masks, distributed.combine_masks
+    ).compute())
+
+Partial masks are serialised in a stable, versioned 7-byte format, so they can
+be persisted or exchanged between workers running different versions.
+"""
+from typing import Iterable, Sequence
+
+from . import lib_alphabet_mask as rust
+
+
+def partial_mask(strings: Sequence[str]) -> bytes:
+    """
+    Map step: the serialised common mask of ``strings``.
+    """
+    return rust.partial_mask(strings)
+
+
+def combine_masks(partials: Iterable[bytes]) -> bytes:
+    """
+    Reduce step: combine serialised partial masks, in any order or grouping.
+    """
+    return rust.combine_masks(list(partials))
+
+
+def decode(partial: bytes) -> int:
+    """
+    The common mask held by a serialised partial mask.
+    """
+    return rust.decode_partial_mask(partial)
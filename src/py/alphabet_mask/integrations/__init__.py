@@ -0,0 +1,9 @@
+# -*- coding: utf-8 -*-
+"""
+==============
+ Integrations
+==============
+
+Optional integrations with dataframe libraries. Each submodule imports its own
+third party dependency, so only import the ones you have installed.
+"""
@@ -0,0 +1,78 @@
+# -*- coding: utf-8 -*-
+"""
+pandas convenience layer.
+
+The values of a :class:`pandas.Series` are handed to Rust as an Arrow array; for
+Series already backed by Arrow this involves no conversion at all.
+"""
+from typing import Literal
+
+import pandas as pd
+import pyarrow as pa
+
+from .. import lib_alphabet_mask as rust
+
+NullPolicy = Literal["propagate", "empty", "raise"]
+"""
+What to do with missing values (``None``, ``NaN`` or ``pd.NA``):
+
+- ``propagate``: the mask is missing too.
+- ``empty``: the mask is ``0``, as for an empty string.
+- ``raise``: raise a :class:`ValueError`.
+"""
+
+
+def _to_arrow(series: pd.Series) -> pa.Array:
+    """
+    Pull the values of a Series out as a single Arrow string array.
+    """
+    array = pa.array(series, type=pa.large_string(), from_pandas=True)
+    if isinstance(array, pa.ChunkedArray):
+        array = array.combine_chunks()
+    return array
+
+
+def from_pandas(series: pd.Series, null_policy: NullPolicy = "propagate") -> pd.Series:
+    """
+    Return the bit mask of each value of ``series``, aligned to its index.
+
+    The masks have the nullable ``UInt32`` dtype.
+    """
+    if null_policy not in ("propagate", "empty", "raise"):
+        raise ValueError(f"Unknown null policy {null_policy!r}.")
+
+    array = _to_arrow(series)
+    if null_policy == "raise" and array.null_count:
+        raise ValueError(f"Series {series.name!r} contains {array.null_count} nulls.")
+
+    masks = rust.alphabet_masks(array, output="arrow")
+    if null_policy == "empty":
+        masks = masks.fill_null(0)
+
+    return pd.Series(
+        masks.to_pandas(types_mapper={pa.uint32(): pd.UInt32Dtype()}.get).array,
+        index=series.index,
+        name=series.name,
+    )
+
+
+@pd.api.extensions.register_series_accessor("alphabet_mask")
+class AlphabetMaskAccessor:
+    """
+    ``Series.alphabet_mask`` accessor.
+    """
+
+    def __init__(self, series: pd.Series):
+        self._series = series
+
+    def masks(self, null_policy: NullPolicy = "propagate") -> pd.Series:
+        """
+        See :func:`from_pandas`.
+        """
+        return from_pandas(self._series, null_policy)
+
+    def common(self) -> str:
+        """
+        The common alphabet of all the values, ignoring missing values.
+        """
+        return rust.common_alphabets_arrow(_to_arrow(self._series))
@@ -0,0 +1,68 @@
+# -*- coding: utf-8 -*-
+"""
+Polars expression namespace.
+
+Importing this module registers an ``alphabet_mask`` namespace on polars
+expressions::
+
+    import polars as pl
+    import alphabet_mask.integrations.polars  # noqa: F401
+
+    df.select(pl.col("text").alphabet_mask.mask())
+    df.group_by("lang").agg(pl.col("text").alphabet_mask.common())
+
+Each batch of values is handed to Rust as an Arrow array, so no Python string
+is ever created per element.
+"""
+import polars as pl
+
+from .. import lib_alphabet_mask as rust
+
+
+def _to_arrow(series: pl.Series):
+    """
+    Convert a series to an Arrow array that the Rust backend understands.
+
+    Newer versions of polars export string views by default, which are not
+    supported; ask for the oldest compatible layout instead.
+    """
+    series = series.cast(pl.String)
+    try:
+        return series.to_arrow(compat_level=pl.CompatLevel.oldest())
+    except (AttributeError, TypeError):
+        return series.to_arrow()
+
+
+@pl.api.register_expr_namespace("alphabet_mask")
+class AlphabetMaskNamespace:
+    """
+    Alphabet masks of string expressions.
+    """
+
+    def __init__(self, expr: pl.Expr):
+        self._expr = expr
+
+    def mask(self) -> pl.Expr:
+        """
+        The bit mask of each string, as ``UInt32``; nulls stay null.
+        """
+        return self._expr.map_batches(
+            lambda series: pl.from_arrow(
+                rust.alphabet_masks(_to_arrow(series), output="arrow")
+            ),
+            return_dtype=pl.UInt32,
+        )
+
+    def common(self) -> pl.Expr:
+        """
+        The common alphabet of all the strings, ignoring nulls.
+
+        In a ``group_by(...).agg(...)`` context, this is computed per group.
+        """
+        return self._expr.map_batches(
+            lambda series: pl.Series(
+                [rust.common_alphabets_arrow(_to_arrow(series))], dtype=pl.String
+            ),
+            return_dtype=pl.String,
+            returns_scalar=True,
+        )
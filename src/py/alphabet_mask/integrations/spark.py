@@ -0,0 +1,74 @@
+# -*- coding: utf-8 -*-
+"""
+Entry points shaped for distributed use with PySpark.
+
+These are plain module-level functions, so they pickle by reference and can be
+shipped to executors. They operate on whole pandas Series or Arrow record
+batches, so the per-row work stays in Rust::
+
+    from pyspark.sql import functions as F
+    from alphabet_mask.integrations import spark as am
+
+    masks = F.pandas_udf(am.masks, "long")
+    partial = F.pandas_udf(am.partial_mask, "long")
+
+    df.select(masks("text"))
+    df.groupBy("lang").agg(partial("text"))
+
+Partial masks combine by a bitwise AND, which is associative and commutative
+with :data:`IDENTITY` as its identity; either use :func:`merge`, or Spark's own
+``F.bit_and`` aggregate, to reduce them further.
+"""
+import functools
+import operator
+from typing import Iterable, Iterator
+
+import pandas as pd
+import pyarrow as pa
+
+from .. import lib_alphabet_mask as rust
+from .pandas import _to_arrow, from_pandas
+
+IDENTITY = (1 << 32) - 1
+"""
+The identity of :func:`merge`; the partial mask of no strings at all.
+"""
+
+
+def masks(series: pd.Series) -> pd.Series:
+    """
+    Series to Series: the bit mask of each string, with nulls kept as nulls.
+    """
+    return from_pandas(series).astype("Int64")
+
+
+def partial_mask(series: pd.Series) -> int:
+    """
+    Series to scalar: the common mask of the non-null strings in ``series``.
+    """
+    return rust.common_mask(_to_arrow(series))
+
+
+def merge(partial_masks: Iterable[int]) -> int:
+    """
+    Combine partial masks into the common mask of all of their strings.
+    """
+    return functools.reduce(operator.and_, partial_masks, IDENTITY)
+
+
+def mask_record_batches(
+    batches: Iterable[pa.RecordBatch], column: str, output_column: str = "mask"
+) -> Iterator[pa.RecordBatch]:
+    """
+    Record batches to record batches, for ``DataFrame.mapInArrow``.
+
+    Each output batch has a single ``int64`` column holding the bit mask of each
+    string in ``column``.
+    """
+    for batch in batches:
+        array = batch.column(column)
+        if array.type != pa.large_string():
+            array = array.cast(pa.large_string())
+
+        result = rust.alphabet_masks(array, output="arrow").cast(pa.int64())
+        yield pa.RecordBatch.from_arrays([result], names=[output_column])
@@ -0,0 +1,251 @@
+//! The `Alphabet` Python class, a user-defined alphabet of any characters.
+//!
+// pyo3 0.20 expands `#[new]` into trait impls inside a function body.
+#![allow(non_local_definitions)]
+
+use std::borrow::Cow;
+
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+
+use crate::call_stats::{Counters, WorkerLoads};
+use crate::layout::Layout;
+use crate::mask::layout_mask_into_py;
+use crate::models::{CasePolicy, LocalError};
+use crate::settings::Settings;
+use crate::text;
+use alphabet_mask_core::alphabet::{Alphabet, AlphabetBuilder};
+use alphabet_mask_core::CharOrder;
+
+/// Add `symbols` to `builder`: each character of a `str` on a bit of its own, or
+/// each `str` of a sequence on a bit shared by its characters.
+fn add_symbols(builder: AlphabetBuilder, symbols: &PyAny) -> PyResult<AlphabetBuilder> {
+    if let Ok(symbols) = symbols.extract::<&str>() {
+        return Ok(builder.symbols(symbols));
+    }
+    symbols.iter()?.try_fold(builder, |builder, group| {
+        let group = group?;
+        match group.extract::<&str>() {
+            Ok(group) if !group.is_empty() => Ok(builder.group(group)),
+            _ => Err(LocalError::InvalidArgument {
+                name: "symbols",
+                value: group.to_string(),
+            }
+            .into()),
+        }
+    })
+}
+
+/// An alphabet of any characters, such as a domain-specific set of symbols, on
+/// up to 128 bits.
+///
+/// `symbols` is either a `str`, each character of which is on a bit of its own,
+/// or a sequence of `str`, the characters of each sharing a bit, such as
+/// ``["σς", "α"]``; bits are assigned in order from bit 0, and the first
+/// character of each represents its bit. Letters are masked regardless of case
+/// unless `case_sensitive`.
+///
+/// Masks of an alphabet are `int`; use them with :func:`mask_string_with` and
+/// :func:`common_alphabets_with`, or the methods of the alphabet.
+///
+/// :meth:`preset` returns a built-in alphabet: ``"greek"``, ``"cyrillic"`` or
+/// ``"latin_extended"``, whose names are also accepted as the ``alphabet`` of
+/// :func:`alphabet_mask` and :func:`common_alphabets`.
+#[pyclass(name = "Alphabet", module = "alphabet_mask", frozen)]
+pub(crate) struct PyAlphabet {
+    pub(crate) inner: Alphabet,
+}
+
+#[pymethods]
+impl PyAlphabet {
+    #[new]
+    #[pyo3(signature = (symbols, case_sensitive = false))]
+    fn new(symbols: &PyAny, case_sensitive: bool) -> PyResult<Self> {
+        let builder = match case_sensitive {
+            true => Alphabet::builder().case_sensitive(),
+            false => Alphabet::builder(),
+        };
+        Ok(Self {
+            inner: add_symbols(builder, symbols)?.build()?,
+        })
+    }
+
+    /// Returns the built-in alphabet called `name`: ``"greek"``, the Greek
+    /// letters with each vowel sharing its bit with its accented forms;
+    /// ``"cyrillic"``, the Russian letters then those of the other Cyrillic
+    /// alphabets of Europe; or ``"latin_extended"``, the standard layout then the
+    /// accented and other letters of Latin-1 and Latin Extended-A. Each also has
+    /// a space and the punctuation of the standard layout.
+    #[staticmethod]
+    fn preset(name: &str) -> PyResult<Self> {
+        Ok(Self {
+            inner: Alphabet::preset(name)?,
+        })
+    }
+
+    /// Returns a new alphabet with `symbols` added on the bits after those of
+    /// this one, given as for the constructor.
+    fn extend(&self, symbols: &PyAny) -> PyResult<Self> {
+        Ok(Self {
+            inner: add_symbols(self.inner.to_builder(), symbols)?.build()?,
+        })
+    }
+
+    /// The number of bits in the masks of this alphabet.
+    #[getter]
+    fn width(&self) -> u8 {
+        self.inner.width()
+    }
+
+    /// Whether the case of letters is significant.
+    #[getter]
+    fn case_sensitive(&self) -> bool {
+        self.inner.case() == CasePolicy::Sensitive
+    }
+
+    /// The characters of each bit, in bit order.
+    #[getter]
+    fn symbols(&self) -> Vec<String> {
+        let mut groups = vec![String::new(); self.inner.width().into()];
+        for &(symbol, bit) in self.inner.symbols() {
+            groups[usize::from(bit)].push(symbol);
+        }
+        groups
+    }
+
+    /// Returns the bit of `character`, or ``None`` if it is not in the alphabet.
+    fn bit(&self, character: char) -> Option<u8> {
+        self.inner.bit(character)
+    }
+
+    /// Returns the mask of `string`, as :func:`mask_string_with` does.
+    fn mask(
+        &self,
+        #[pyo3(from_py_with = "text::to_str")] string: &str,
+        py: Python<'_>,
+    ) -> PyResult<u128> {
+        mask_string(&self.inner, string, py)
+    }
+
+    /// Returns the characters of `mask` in bit order, or raises a `ValueError`
+    /// if it sets bits beyond the width of the alphabet.
+    fn chars(&self, mask: i128) -> PyResult<String> {
+        Ok(self.inner.chars(self.inner.validate_mask(mask)?))
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.width().into()
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let symbols = self
+            .symbols()
+            .iter()
+            .map(|group| Ok(PyString::new(py, group).repr()?.to_string()))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(format!(
+            "Alphabet([{}]{})",
+            symbols.join(", "),
+            if self.case_sensitive() {
+                ", case_sensitive=True"
+            } else {
+                ""
+            }
+        ))
+    }
+}
+
+/// Returns the mask of `string` in `alphabet`, if within the input limits.
+pub(crate) fn mask_string(alphabet: &Alphabet, string: &str, py: Python<'_>) -> PyResult<u128> {
+    Settings::get().limits.check([string])?;
+    let mask = py.allow_threads(|| alphabet.mask_string(string))?;
+    Counters::add_strings(&[string]);
+    Ok(mask.into())
+}
+
+/// Returns the alphabet given as `alphabet`, either an :class:`Alphabet` or the
+/// name of a preset.
+fn extract_alphabet(alphabet: &PyAny) -> PyResult<Cow<'_, Alphabet>> {
+    if let Ok(alphabet) = alphabet.downcast::<PyCell<PyAlphabet>>() {
+        return Ok(Cow::Borrowed(&alphabet.get().inner));
+    }
+    Ok(Cow::Owned(Alphabet::preset(alphabet.extract()?)?))
+}
+
+/// How the strings of a call are masked: in a built-in layout, or in the
+/// alphabet given as its ``alphabet`` argument.
+pub(crate) enum Masking<'a> {
+    Layout(Layout),
+    Alphabet(Cow<'a, Alphabet>),
+}
+
+impl<'a> Masking<'a> {
+    /// The masking selected by the arguments of a call, or an error if an
+    /// alphabet is given with a layout other than the standard one, or to be
+    /// returned as an :class:`AlphabetMask`.
+    pub(crate) fn of(alphabet: Option<&'a PyAny>, layout: Layout, as_mask: bool) -> PyResult<Self> {
+        let Some(alphabet) = alphabet else {
+            return Ok(Self::Layout(layout));
+        };
+        let conflict = match (layout, as_mask) {
+            (Layout::Standard, false) => None,
+            (Layout::Standard, true) => Some("as_mask"),
+            (_, _) => Some(layout.arguments().trim_start_matches(", ")),
+        };
+        if let Some(conflict) = conflict {
+            return Err(LocalError::InvalidArgument {
+                name: "alphabet",
+                value: format!("{alphabet} with {conflict}"),
+            }
+            .into());
+        }
+        Ok(Self::Alphabet(extract_alphabet(alphabet)?))
+    }
+
+    /// Mask a string.
+    pub(crate) fn mask_string(&self, string: &str) -> Result<u128, LocalError> {
+        match self {
+            Self::Layout(layout) => layout.mask_string(string),
+            Self::Alphabet(alphabet) => Ok(alphabet.mask_string(string)?.into()),
+        }
+    }
+
+    /// Returns the common mask of `strings`, as [`Layout::find_common_mask`]
+    /// does.
+    pub(crate) fn find_common_mask<S: AsRef<str> + Sync>(
+        &self,
+        strings: &[S],
+        length_limit: usize,
+        loads: Option<&WorkerLoads>,
+    ) -> Result<u128, LocalError> {
+        match self {
+            Self::Layout(layout) => layout.find_common_mask(strings, length_limit, loads),
+            Self::Alphabet(alphabet) => {
+                Ok(alphabet.find_common_mask(strings, length_limit)?.into())
+            }
+        }
+    }
+
+    /// Returns the characters of `bits` in bit order.
+    pub(crate) fn chars(&self, bits: u128) -> String {
+        match self {
+            Self::Layout(layout) => layout.chars(bits, CharOrder::Bit),
+            Self::Alphabet(alphabet) => alphabet.chars(bits.into()),
+        }
+    }
+
+    /// Returns `bits` as an :class:`AlphabetMask` if `as_mask`, otherwise as the
+    /// result of `otherwise`.
+    pub(crate) fn to_py<T: IntoPy<PyObject>>(
+        &self,
+        py: Python<'_>,
+        bits: u128,
+        as_mask: bool,
+        otherwise: impl FnOnce(u128) -> T,
+    ) -> PyObject {
+        match self {
+            Self::Layout(layout) => layout_mask_into_py(py, *layout, bits, as_mask, otherwise),
+            Self::Alphabet(_) => otherwise(bits).into_py(py),
+        }
+    }
+}
@@ -0,0 +1,798 @@
+//! Zero-copy access to Arrow string arrays through the Arrow C data interface.
+//!
+//! Arrays are imported from any Python object implementing the Arrow PyCapsule
+//! interface (`__arrow_c_array__`), falling back to pyarrow's `_export_to_c` for
+//! older versions of pyarrow. The string values are then read straight from the
+//! offsets and data buffers, without creating a Python object per element.
+//!
+//! Streams of arrays, such as a :class:`pyarrow.RecordBatchReader` over a
+//! dataset larger than memory, are imported through the Arrow C stream interface
+//! (`__arrow_c_stream__`), and read one array at a time.
+//!
+//! See <https://arrow.apache.org/docs/format/CDataInterface.html> and
+//! <https://arrow.apache.org/docs/format/CStreamInterface.html>.
+//!
+use std::ffi::{c_char, c_int, c_void, CStr};
+
+use pyo3::prelude::*;
+use pyo3::types::PyCapsule;
+
+use crate::models::LocalError;
+
+/// The `ArrowSchema` struct of the Arrow C data interface.
+#[repr(C)]
+pub(crate) struct ArrowSchema {
+    format: *const c_char,
+    name: *const c_char,
+    metadata: *const c_char,
+    flags: i64,
+    n_children: i64,
+    children: *mut *mut ArrowSchema,
+    dictionary: *mut ArrowSchema,
+    release: Option<unsafe extern "C" fn(*mut ArrowSchema)>,
+    private_data: *mut c_void,
+}
+
+/// The `ArrowArray` struct of the Arrow C data interface.
+#[repr(C)]
+pub(crate) struct ArrowArray {
+    length: i64,
+    null_count: i64,
+    offset: i64,
+    n_buffers: i64,
+    n_children: i64,
+    buffers: *mut *const c_void,
+    children: *mut *mut ArrowArray,
+    dictionary: *mut ArrowArray,
+    release: Option<unsafe extern "C" fn(*mut ArrowArray)>,
+    private_data: *mut c_void,
+}
+
+/// The `ArrowArrayStream` struct of the Arrow C stream interface.
+#[repr(C)]
+pub(crate) struct ArrowArrayStream {
+    get_schema: Option<unsafe extern "C" fn(*mut ArrowArrayStream, *mut ArrowSchema) -> c_int>,
+    get_next: Option<unsafe extern "C" fn(*mut ArrowArrayStream, *mut ArrowArray) -> c_int>,
+    get_last_error: Option<unsafe extern "C" fn(*mut ArrowArrayStream) -> *const c_char>,
+    release: Option<unsafe extern "C" fn(*mut ArrowArrayStream)>,
+    private_data: *mut c_void,
+}
+
+macro_rules! impl_empty {
+    ($($struct:ident { $($field:ident: $value:expr),* $(,)? }),* $(,)?) => {
+        $(
+            impl $struct {
+                /// An empty, released struct for a producer to export into.
+                fn empty() -> Self {
+                    Self {
+                        $($field: $value,)*
+                        release: None,
+                        private_data: std::ptr::null_mut(),
+                    }
+                }
+            }
+
+            impl Drop for $struct {
+                fn drop(&mut self) {
+                    if let Some(release) = self.release {
+                        // SAFETY: the producer guarantees `release` is valid to call once.
+                        unsafe { release(self) }
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_empty!(
+    ArrowSchema {
+        format: std::ptr::null(),
+        name: std::ptr::null(),
+        metadata: std::ptr::null(),
+        flags: 0,
+        n_children: 0,
+        children: std::ptr::null_mut(),
+        dictionary: std::ptr::null_mut(),
+    },
+    ArrowArray {
+        length: 0,
+        null_count: 0,
+        offset: 0,
+        n_buffers: 0,
+        n_children: 0,
+        buffers: std::ptr::null_mut(),
+        children: std::ptr::null_mut(),
+        dictionary: std::ptr::null_mut(),
+    },
+    ArrowArrayStream {
+        get_schema: None,
+        get_next: None,
+        get_last_error: None,
+    },
+);
+
+/// The width of the offsets of an Arrow string array.
+///
+/// Parsed from the format string of a schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OffsetWidth {
+    /// `utf8`, format string `"u"`.
+    I32,
+    /// `large_utf8`, format string `"U"`.
+    I64,
+}
+
+impl OffsetWidth {
+    /// The width of the offsets of a string array of `schema`.
+    fn of_schema(schema: &ArrowSchema) -> Result<Self, LocalError> {
+        // SAFETY: a non-released schema always has a valid format string.
+        match unsafe { CStr::from_ptr(schema.format) }.to_bytes() {
+            b"u" => Ok(Self::I32),
+            b"U" => Ok(Self::I64),
+            other => Err(LocalError::InvalidArrowArray {
+                context: format!(
+                    "expected a utf8 or large_utf8 array, found format {:?}",
+                    String::from_utf8_lossy(other)
+                ),
+            }),
+        }
+    }
+}
+
+/// An Arrow string array imported from Python, released when dropped.
+pub(crate) struct StringArray {
+    // Keep the schema alive alongside the array, as some producers share
+    // their private data between the two.
+    _schema: Box<ArrowSchema>,
+    array: Box<ArrowArray>,
+    width: OffsetWidth,
+}
+
+// SAFETY: the C data interface requires the buffers of an exported array to be
+// immutable, and to remain valid until released, which we only do on drop.
+unsafe impl Send for StringArray {}
+unsafe impl Sync for StringArray {}
+
+/// Move an exported struct out of a PyCapsule, marking the original as released,
+/// or return an error if the capsule is not named `name`.
+///
+/// # Safety
+///
+/// A capsule named `name` must contain a pointer to a valid `T` whose first
+/// `release` field is the one we clear.
+unsafe fn take_from_capsule<T>(
+    capsule: &PyCapsule,
+    name: &CStr,
+    release: impl FnOnce(&mut T),
+) -> PyResult<Box<T>> {
+    let actual = capsule.name()?;
+    if actual != Some(name) {
+        return Err(LocalError::InvalidArrowArray {
+            context: format!("expected a capsule named {name:?}, got {actual:?}"),
+        }
+        .into());
+    }
+    let pointer = capsule.pointer() as *mut T;
+    let taken = Box::new(std::ptr::read(pointer));
+    release(&mut *pointer);
+    Ok(taken)
+}
+
+impl StringArray {
+    /// Import a string array from a Python object.
+    pub(crate) fn from_pyobject(object: &PyAny) -> PyResult<Self> {
+        span!(DEBUG, "import_arrow_array");
+        let (schema, array) = if object.hasattr("__arrow_c_array__")? {
+            let (schema, array): (&PyCapsule, &PyCapsule) =
+                object.call_method0("__arrow_c_array__")?.extract()?;
+
+            // SAFETY: the PyCapsule interface specifies the contents of capsules of
+            // both names, and lets the consumer move the structs out by clearing
+            // `release`.
+            unsafe {
+                (
+                    take_from_capsule::<ArrowSchema>(schema, c"arrow_schema", |s| {
+                        s.release = None
+                    })?,
+                    take_from_capsule::<ArrowArray>(array, c"arrow_array", |a| a.release = None)?,
+                )
+            }
+        } else {
+            let mut schema = Box::new(ArrowSchema::empty());
+            let mut array = Box::new(ArrowArray::empty());
+            object.call_method1(
+                "_export_to_c",
+                (
+                    &mut *array as *mut ArrowArray as usize,
+                    &mut *schema as *mut ArrowSchema as usize,
+                ),
+            )?;
+            (schema, array)
+        };
+
+        Ok(Self::from_ffi(schema, array)?)
+    }
+
+    /// Wrap exported structs, checking that they describe a string array.
+    fn from_ffi(schema: Box<ArrowSchema>, array: Box<ArrowArray>) -> Result<Self, LocalError> {
+        let invalid = |context: &str| LocalError::InvalidArrowArray {
+            context: context.to_owned(),
+        };
+
+        if schema.release.is_none() || array.release.is_none() {
+            return Err(invalid("the array has already been released"));
+        }
+
+        let width = OffsetWidth::of_schema(&schema)?;
+        Self::with_width(schema, array, width)
+    }
+
+    /// Wrap an exported array of a schema already checked to be of `width`.
+    fn with_width(
+        schema: Box<ArrowSchema>,
+        array: Box<ArrowArray>,
+        width: OffsetWidth,
+    ) -> Result<Self, LocalError> {
+        if array.n_buffers != 3 {
+            return Err(LocalError::InvalidArrowArray {
+                context: "a string array must have 3 buffers".to_owned(),
+            });
+        }
+
+        Ok(Self {
+            _schema: schema,
+            array,
+            width,
+        })
+    }
+
+    /// The number of values in this array, including nulls.
+    pub(crate) fn len(&self) -> usize {
+        self.array.length as usize
+    }
+
+    /// Returns the buffer at `index`, which may be null.
+    fn buffer(&self, index: usize) -> *const u8 {
+        // SAFETY: we checked there are 3 buffers on construction.
+        unsafe { *self.array.buffers.add(index) as *const u8 }
+    }
+
+    /// Returns whether the value at `index` is valid, i.e. not null.
+    fn is_valid(&self, index: usize) -> bool {
+        let validity = self.buffer(0);
+        if self.array.null_count == 0 || validity.is_null() {
+            return true;
+        }
+
+        let bit = index + self.array.offset as usize;
+        // SAFETY: the validity bitmap covers `offset + length` bits.
+        unsafe { *validity.add(bit / 8) & (1 << (bit % 8)) != 0 }
+    }
+
+    /// Returns the byte range of the value at `index` in the data buffer.
+    fn value_range(&self, index: usize) -> (usize, usize) {
+        let position = index + self.array.offset as usize;
+        let offsets = self.buffer(1);
+
+        // SAFETY: the offsets buffer holds `offset + length + 1` aligned values.
+        unsafe {
+            match self.width {
+                OffsetWidth::I32 => {
+                    let offsets = offsets as *const i32;
+                    (
+                        *offsets.add(position) as usize,
+                        *offsets.add(position + 1) as usize,
+                    )
+                }
+                OffsetWidth::I64 => {
+                    let offsets = offsets as *const i64;
+                    (
+                        *offsets.add(position) as usize,
+                        *offsets.add(position + 1) as usize,
+                    )
+                }
+            }
+        }
+    }
+
+    /// Returns every value of this array, with [`None`] in place of nulls.
+    ///
+    /// The returned strings borrow directly from the Arrow data buffer.
+    pub(crate) fn values(&self) -> Result<Vec<Option<&str>>, LocalError> {
+        span!(DEBUG, "extract_arrow_values", length = self.len());
+        let data = self.buffer(2);
+
+        (0..self.len())
+            .map(|index| {
+                if !self.is_valid(index) {
+                    return Ok(None);
+                }
+
+                let (start, end) = self.value_range(index);
+                if start == end {
+                    return Ok(Some(""));
+                }
+
+                // SAFETY: the offsets of a valid array always lie within the data buffer.
+                let bytes = unsafe { std::slice::from_raw_parts(data.add(start), end - start) };
+                std::str::from_utf8(bytes)
+                    .map(Some)
+                    .map_err(|_| LocalError::InvalidArrowArray {
+                        context: format!("value at index {index} is not valid UTF-8"),
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Returns whether `object` is an Arrow stream rather than an array, exporting
+/// `__arrow_c_stream__` but not `__arrow_c_array__`.
+pub(crate) fn is_stream(object: &PyAny) -> PyResult<bool> {
+    Ok(object.hasattr("__arrow_c_stream__")? && !object.hasattr("__arrow_c_array__")?)
+}
+
+/// A stream of Arrow string arrays imported from Python, released when dropped.
+///
+/// The stream either yields string arrays, as for a chunked array, or struct
+/// arrays, as for the record batches of a table, of which one column is read.
+pub(crate) struct StringArrayStream {
+    stream: Box<ArrowArrayStream>,
+    /// The position of the column read from each struct array, if any.
+    column: Option<usize>,
+    width: OffsetWidth,
+}
+
+// SAFETY: the stream is only ever used by one thread at a time, through `&mut`.
+unsafe impl Send for StringArrayStream {}
+
+impl StringArrayStream {
+    /// Import a stream from a Python object, reading `column` of its record
+    /// batches, which may only be omitted if they have one column.
+    pub(crate) fn from_pyobject(object: &PyAny, column: Option<&str>) -> PyResult<Self> {
+        span!(DEBUG, "import_arrow_stream");
+        let stream = if object.hasattr("__arrow_c_stream__")? {
+            let capsule: &PyCapsule = object.call_method0("__arrow_c_stream__")?.downcast()?;
+
+            // SAFETY: the PyCapsule interface specifies the contents of a capsule of
+            // this name, and lets the consumer move the struct out by clearing
+            // `release`.
+            unsafe {
+                take_from_capsule::<ArrowArrayStream>(capsule, c"arrow_array_stream", |s| {
+                    s.release = None
+                })?
+            }
+        } else {
+            let mut stream = Box::new(ArrowArrayStream::empty());
+            object.call_method1(
+                "_export_to_c",
+                (&mut *stream as *mut ArrowArrayStream as usize,),
+            )?;
+            stream
+        };
+
+        Ok(Self::from_ffi(stream, column)?)
+    }
+
+    /// Wrap an exported stream, checking that its schema has a string column.
+    fn from_ffi(
+        mut stream: Box<ArrowArrayStream>,
+        column: Option<&str>,
+    ) -> Result<Self, LocalError> {
+        let (Some(_), Some(get_schema)) = (stream.release, stream.get_schema) else {
+            return Err(LocalError::InvalidArrowArray {
+                context: "the stream has already been released".to_owned(),
+            });
+        };
+
+        let mut schema = Box::new(ArrowSchema::empty());
+        // SAFETY: a non-released stream has valid callbacks.
+        let status = unsafe { get_schema(&mut *stream, &mut *schema) };
+        if status != 0 {
+            return Err(stream_error(&mut stream, status));
+        }
+
+        // SAFETY: a non-released schema always has a valid format string.
+        if unsafe { CStr::from_ptr(schema.format) }.to_bytes() != b"+s" {
+            return match column {
+                None => Ok(Self {
+                    width: OffsetWidth::of_schema(&schema)?,
+                    stream,
+                    column: None,
+                }),
+                Some(column) => Err(LocalError::InvalidArgument {
+                    name: "column",
+                    value: format!("{column} of a stream of arrays rather than record batches"),
+                }),
+            };
+        }
+
+        // SAFETY: a struct schema has `n_children` valid children.
+        let children: Vec<&ArrowSchema> = (0..schema.n_children as usize)
+            .map(|index| unsafe { &**schema.children.add(index) })
+            .collect();
+        // SAFETY: the name of a field, if any, is a valid string.
+        let name = |child: &ArrowSchema| {
+            (!child.name.is_null()).then(|| unsafe { CStr::from_ptr(child.name) }.to_string_lossy())
+        };
+        let index = match column {
+            Some(column) => children
+                .iter()
+                .position(|child| name(child).is_some_and(|name| name == column)),
+            None => (children.len() == 1).then_some(0),
+        }
+        .ok_or_else(|| LocalError::InvalidArgument {
+            name: "column",
+            value: format!(
+                "{} of the columns {:?}",
+                column.unwrap_or("None"),
+                children
+                    .iter()
+                    .filter_map(|child| name(child))
+                    .collect::<Vec<_>>()
+            ),
+        })?;
+
+        Ok(Self {
+            width: OffsetWidth::of_schema(children[index])?,
+            stream,
+            column: Some(index),
+        })
+    }
+
+    /// Returns the next string array of the stream, or [`None`] at its end.
+    pub(crate) fn next_array(&mut self) -> Result<Option<StringArray>, LocalError> {
+        let get_next = self
+            .stream
+            .get_next
+            .ok_or_else(|| LocalError::InvalidArrowArray {
+                context: "the stream has already been released".to_owned(),
+            })?;
+
+        let mut array = Box::new(ArrowArray::empty());
+        // SAFETY: a non-released stream has valid callbacks.
+        let status = unsafe { get_next(&mut *self.stream, &mut *array) };
+        if status != 0 {
+            return Err(stream_error(&mut self.stream, status));
+        }
+        if array.release.is_none() {
+            return Ok(None);
+        }
+
+        let array = match self.column {
+            None => array,
+            Some(index) => {
+                if index >= array.n_children as usize {
+                    return Err(LocalError::InvalidArrowArray {
+                        context: "a record batch does not match the schema".to_owned(),
+                    });
+                }
+                // SAFETY: a struct array has `n_children` valid children, which may
+                // be moved out by clearing `release` before the parent is released.
+                let mut child = unsafe {
+                    let pointer = *array.children.add(index);
+                    let child = Box::new(std::ptr::read(pointer));
+                    (*pointer).release = None;
+                    child
+                };
+                child.offset += array.offset;
+                child.length = array.length;
+                child
+            }
+        };
+
+        StringArray::with_width(Box::new(ArrowSchema::empty()), array, self.width).map(Some)
+    }
+}
+
+/// The error of a failed call to a stream, with its message if any.
+fn stream_error(stream: &mut ArrowArrayStream, status: c_int) -> LocalError {
+    // SAFETY: the last error, if any, is valid until the next call to the stream.
+    let message = stream
+        .get_last_error
+        .map(|get_last_error| unsafe { get_last_error(stream) })
+        .filter(|message| !message.is_null())
+        .map(|message| {
+            unsafe { CStr::from_ptr(message) }
+                .to_string_lossy()
+                .into_owned()
+        });
+
+    LocalError::InvalidArrowArray {
+        context: message.unwrap_or_else(|| format!("the stream failed with error code {status}")),
+    }
+}
+
+/// The buffers of a `uint32` array exported from Rust, owned by its `private_data`.
+struct ExportedBuffers {
+    _values: Vec<u32>,
+    _validity: Option<Vec<u8>>,
+    pointers: [*const c_void; 2],
+}
+
+unsafe extern "C" fn release_exported_schema(schema: *mut ArrowSchema) {
+    // The format string is static, so there is nothing to free.
+    (*schema).release = None;
+}
+
+unsafe extern "C" fn release_exported_array(array: *mut ArrowArray) {
+    drop(Box::from_raw((*array).private_data as *mut ExportedBuffers));
+    (*array).release = None;
+}
+
+/// Export `values` as an Arrow `uint32` array, with nulls in place of [`None`].
+pub(crate) fn export_u32_array(values: &[Option<u32>]) -> (Box<ArrowSchema>, Box<ArrowArray>) {
+    let null_count = values.iter().filter(|value| value.is_none()).count();
+    let validity = (null_count > 0).then(|| {
+        let mut validity = vec![0_u8; values.len().div_ceil(8)];
+        for (index, value) in values.iter().enumerate() {
+            if value.is_some() {
+                validity[index / 8] |= 1 << (index % 8);
+            }
+        }
+        validity
+    });
+    let values: Vec<u32> = values.iter().map(|value| value.unwrap_or(0)).collect();
+
+    let mut buffers = Box::new(ExportedBuffers {
+        pointers: [
+            validity.as_ref().map_or(std::ptr::null(), |validity| {
+                validity.as_ptr() as *const c_void
+            }),
+            values.as_ptr() as *const c_void,
+        ],
+        _values: values,
+        _validity: validity,
+    });
+
+    let schema = Box::new(ArrowSchema {
+        format: c"I".as_ptr(),
+        release: Some(release_exported_schema),
+        ..ArrowSchema::empty()
+    });
+    let array = Box::new(ArrowArray {
+        length: buffers._values.len() as i64,
+        null_count: null_count as i64,
+        n_buffers: 2,
+        buffers: buffers.pointers.as_mut_ptr(),
+        release: Some(release_exported_array),
+        private_data: Box::into_raw(buffers) as *mut c_void,
+        ..ArrowArray::empty()
+    });
+
+    (schema, array)
+}
+
+/// Export `values` as a :class:`pyarrow.UInt32Array`, with nulls in place of [`None`].
+pub(crate) fn u32_array_to_pyarrow(py: Python<'_>, values: &[Option<u32>]) -> PyResult<PyObject> {
+    let (mut schema, mut array) = export_u32_array(values);
+
+    // pyarrow moves the structs out, leaving them released for us to drop.
+    py.import("pyarrow")?
+        .getattr("Array")?
+        .call_method1(
+            "_import_from_c",
+            (
+                &mut *array as *mut ArrowArray as usize,
+                &mut *schema as *mut ArrowSchema as usize,
+            ),
+        )
+        .map(Into::into)
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    unsafe extern "C" fn release_schema(schema: *mut ArrowSchema) {
+        (*schema).release = None;
+    }
+    unsafe extern "C" fn release_array(array: *mut ArrowArray) {
+        (*array).release = None;
+    }
+
+    /// Export a string array the way a producer would, for testing without
+    /// pyarrow.
+    ///
+    /// Everything is leaked, which is fine for the lifetime of a test.
+    fn export_string_array(
+        values: &[Option<&str>],
+        offset: usize,
+    ) -> (Box<ArrowSchema>, Box<ArrowArray>) {
+        let mut offsets = vec![0_i32];
+        let mut data = Vec::new();
+        let mut validity = vec![0_u8; values.len().div_ceil(8)];
+        for (index, value) in values.iter().enumerate() {
+            if let Some(value) = value {
+                data.extend_from_slice(value.as_bytes());
+                validity[index / 8] |= 1 << (index % 8);
+            }
+            offsets.push(data.len() as i32);
+        }
+
+        let buffers: &mut [*const c_void] = Box::leak(Box::new([
+            Box::leak(validity.into_boxed_slice()).as_ptr() as *const c_void,
+            Box::leak(offsets.into_boxed_slice()).as_ptr() as *const c_void,
+            Box::leak(data.into_boxed_slice()).as_ptr() as *const c_void,
+        ]));
+
+        let mut schema = Box::new(ArrowSchema::empty());
+        schema.format = c"u".as_ptr();
+        schema.release = Some(release_schema);
+
+        let mut array = Box::new(ArrowArray::empty());
+        array.length = (values.len() - offset) as i64;
+        array.null_count = values.iter().filter(|value| value.is_none()).count() as i64;
+        array.offset = offset as i64;
+        array.n_buffers = 3;
+        array.buffers = buffers.as_mut_ptr();
+        array.release = Some(release_array);
+
+        (schema, array)
+    }
+
+    /// Build a string array the way a producer would, for testing without pyarrow.
+    pub(crate) fn string_array(values: &[Option<&str>], offset: usize) -> StringArray {
+        let (schema, array) = export_string_array(values, offset);
+        StringArray::from_ffi(schema, array).unwrap()
+    }
+
+    /// Export a stream of record batches with an `id` column of integers and a
+    /// `text` column of each of `batches`, the way a producer would.
+    fn export_stream(batches: Vec<Vec<Option<&'static str>>>) -> Box<ArrowArrayStream> {
+        unsafe extern "C" fn get_schema(
+            _stream: *mut ArrowArrayStream,
+            out: *mut ArrowSchema,
+        ) -> c_int {
+            let children: Vec<*mut ArrowSchema> = [(c"id", c"i"), (c"text", c"u")]
+                .into_iter()
+                .map(|(name, format)| {
+                    let mut child = Box::new(ArrowSchema::empty());
+                    child.name = name.as_ptr();
+                    child.format = format.as_ptr();
+                    child.release = Some(release_schema);
+                    Box::into_raw(child)
+                })
+                .collect();
+
+            let mut schema = ArrowSchema::empty();
+            schema.format = c"+s".as_ptr();
+            schema.n_children = 2;
+            schema.children = Box::leak(children.into_boxed_slice()).as_mut_ptr();
+            schema.release = Some(release_schema);
+            std::ptr::write(out, schema);
+            0
+        }
+
+        /// Release a batch and whichever of its columns were not moved out.
+        unsafe extern "C" fn release_batch(array: *mut ArrowArray) {
+            for index in 0..(*array).n_children as usize {
+                drop(Box::from_raw(*(*array).children.add(index)));
+            }
+            (*array).release = None;
+        }
+
+        unsafe extern "C" fn get_next(
+            stream: *mut ArrowArrayStream,
+            out: *mut ArrowArray,
+        ) -> c_int {
+            let batches = &mut *((*stream).private_data as *mut Vec<Vec<Option<&str>>>);
+            let mut batch = ArrowArray::empty();
+            if !batches.is_empty() {
+                let values = batches.remove(0);
+                let (_, text) = export_string_array(&values, 0);
+                let id = export_string_array(&[], 0).1;
+                let children = vec![Box::into_raw(id), Box::into_raw(text)];
+
+                batch.length = values.len() as i64;
+                batch.n_buffers = 1;
+                batch.buffers = Box::leak(Box::new([std::ptr::null::<c_void>()])).as_mut_ptr();
+                batch.n_children = 2;
+                batch.children = Box::leak(children.into_boxed_slice()).as_mut_ptr();
+                batch.release = Some(release_batch);
+            }
+            std::ptr::write(out, batch);
+            0
+        }
+
+        unsafe extern "C" fn release_stream(stream: *mut ArrowArrayStream) {
+            drop(Box::from_raw(
+                (*stream).private_data as *mut Vec<Vec<Option<&str>>>,
+            ));
+            (*stream).release = None;
+        }
+
+        let mut stream = Box::new(ArrowArrayStream::empty());
+        stream.get_schema = Some(get_schema);
+        stream.get_next = Some(get_next);
+        stream.release = Some(release_stream);
+        stream.private_data = Box::into_raw(Box::new(batches)) as *mut c_void;
+        stream
+    }
+
+    #[test]
+    fn stream() {
+        let batches = vec![vec![Some("hello"), None], vec![Some("world")], vec![]];
+        let mut stream =
+            StringArrayStream::from_ffi(export_stream(batches.clone()), Some("text")).unwrap();
+
+        for batch in batches {
+            assert_eq!(
+                stream.next_array().unwrap().unwrap().values().unwrap(),
+                batch
+            );
+        }
+        assert!(stream.next_array().unwrap().is_none());
+    }
+
+    #[test]
+    fn stream_columns() {
+        for column in [None, Some("title")] {
+            assert!(matches!(
+                StringArrayStream::from_ffi(export_stream(vec![]), column),
+                Err(LocalError::InvalidArgument { name: "column", .. })
+            ));
+        }
+        assert!(matches!(
+            StringArrayStream::from_ffi(export_stream(vec![]), Some("id")),
+            Err(LocalError::InvalidArrowArray { .. })
+        ));
+    }
+
+    #[test]
+    fn values() {
+        let array = string_array(&[Some("hello"), None, Some(""), Some("world")], 0);
+
+        assert_eq!(
+            array.values().unwrap(),
+            vec![Some("hello"), None, Some(""), Some("world")]
+        );
+    }
+
+    #[test]
+    fn values_with_offset() {
+        let array = string_array(&[Some("hello"), None, Some("world")], 1);
+
+        assert_eq!(array.len(), 2);
+        assert_eq!(array.values().unwrap(), vec![None, Some("world")]);
+    }
+
+    #[test]
+    fn export() {
+        let (schema, array) = export_u32_array(&[Some(1), None, Some(3)]);
+
+        assert_eq!(unsafe { CStr::from_ptr(schema.format) }.to_bytes(), b"I");
+        assert_eq!(array.length, 3);
+        assert_eq!(array.null_count, 1);
+
+        let (validity, values) = unsafe {
+            (
+                *(*array.buffers as *const u8),
+                std::slice::from_raw_parts(*array.buffers.add(1) as *const u32, 3),
+            )
+        };
+        assert_eq!(validity, 0b101);
+        assert_eq!(values, [1, 0, 3]);
+    }
+
+    #[test]
+    fn export_without_nulls() {
+        let (_schema, array) = export_u32_array(&[Some(1), Some(2)]);
+
+        assert_eq!(array.null_count, 0);
+        assert!(unsafe { *array.buffers }.is_null());
+    }
+
+    #[test]
+    fn wrong_format() {
+        let mut schema = Box::new(ArrowSchema::empty());
+        schema.format = c"i".as_ptr();
+        schema.release = Some(release_schema);
+        let mut array = Box::new(ArrowArray::empty());
+        array.release = Some(release_array);
+
+        assert!(matches!(
+            StringArray::from_ffi(schema, array),
+            Err(LocalError::InvalidArrowArray { .. })
+        ));
+    }
+}
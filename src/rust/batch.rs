@@ -0,0 +1,218 @@
+//! Batch inputs and outputs of per-string masks.
+//!
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyString};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::arrow::{self, StringArray};
+use crate::models::bits::LETTERS_MASK;
+use crate::models::{BatchOutput, LocalError, Mask};
+use crate::shared::SharedStrings;
+use crate::text;
+use alphabet_mask_core::{mask_string, mask_to_chars};
+
+/// A batch of strings passed in from Python.
+pub(crate) enum Batch<'py> {
+    /// A sequence of Python `str`.
+    List(Vec<&'py str>),
+    /// An Arrow string array, read without conversion.
+    Arrow(StringArray),
+    /// Strings in a buffer shared between processes, read without conversion.
+    Shared(&'py SharedStrings),
+}
+
+impl<'py> Batch<'py> {
+    /// Extract a batch from an Arrow string array, `SharedStrings` or a sequence
+    /// of `str`.
+    pub(crate) fn from_pyobject(object: &'py PyAny) -> PyResult<Self> {
+        span!(DEBUG, "extract_batch");
+        if let Ok(shared) = object.downcast::<PyCell<SharedStrings>>() {
+            Ok(Self::Shared(shared.get()))
+        } else if object.hasattr("__arrow_c_array__")? || object.hasattr("_export_to_c")? {
+            StringArray::from_pyobject(object).map(Self::Arrow)
+        } else {
+            text::to_strs(object).map(Self::List)
+        }
+    }
+
+    /// Returns every string of this batch, with [`None`] in place of nulls.
+    pub(crate) fn values(&self) -> Result<Vec<Option<&str>>, LocalError> {
+        match self {
+            Self::List(strings) => Ok(strings.iter().copied().map(Some).collect()),
+            Self::Arrow(array) => array.values(),
+            Self::Shared(shared) => Ok(shared.values()?.into_iter().map(Some).collect()),
+        }
+    }
+}
+
+/// One value of a `dict` of groups.
+pub(crate) enum Group<'py> {
+    /// A single `str`.
+    One(&'py str),
+    /// A sequence of `str`.
+    Many(Vec<&'py str>),
+}
+
+/// Groups of strings passed in from Python as a `dict`, keyed by any hashable.
+pub(crate) struct Groups<'py> {
+    keys: Vec<&'py PyAny>,
+    pub(crate) groups: Vec<Group<'py>>,
+}
+
+impl<'py> Groups<'py> {
+    /// Extract groups from `object` if it is a `dict` of `str` or sequences of `str`.
+    pub(crate) fn from_pyobject(object: &'py PyAny) -> PyResult<Option<Self>> {
+        let Ok(dict) = object.downcast::<PyDict>() else {
+            return Ok(None);
+        };
+
+        let (mut keys, mut groups) = (Vec::with_capacity(dict.len()), Vec::new());
+        for (key, value) in dict {
+            keys.push(key);
+            groups.push(if value.is_instance_of::<PyString>() {
+                Group::One(text::to_str(value)?)
+            } else {
+                Group::Many(text::to_strs(value)?)
+            });
+        }
+        Ok(Some(Self { keys, groups }))
+    }
+
+    /// Returns a `dict` of each key with the result of its group, in order.
+    pub(crate) fn into_dict<T: ToPyObject>(
+        self,
+        py: Python<'py>,
+        results: Vec<T>,
+    ) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        for (key, result) in self.keys.into_iter().zip(results) {
+            dict.set_item(key, result)?;
+        }
+        Ok(dict)
+    }
+}
+
+/// Returns every string of every group.
+pub(crate) fn group_strings<'a>(groups: &'a [Group<'a>]) -> impl Iterator<Item = &'a str> {
+    groups
+        .iter()
+        .flat_map(|group| match group {
+            Group::One(string) => std::slice::from_ref(string),
+            Group::Many(strings) => strings.as_slice(),
+        })
+        .copied()
+}
+
+/// Apply `f` to each group in parallel.
+pub(crate) fn map_groups<T: Send>(
+    groups: &[Group<'_>],
+    f: impl Fn(&Group<'_>) -> Result<T, LocalError> + Send + Sync,
+) -> Result<Vec<T>, LocalError> {
+    span!(DEBUG, "map_groups", groups = groups.len());
+    groups.par_iter().map(f).collect()
+}
+
+/// Returns the mask of each of the given strings, computed in parallel.
+pub(crate) fn find_masks(strings: &[Option<&str>]) -> Result<Vec<Option<Mask>>, LocalError> {
+    span!(DEBUG, "find_masks", strings = strings.len());
+    strings
+        .par_iter()
+        .map(|string| string.map(mask_string).transpose())
+        .collect()
+}
+
+/// Find the letters missing from each string, in parallel, keeping nulls.
+pub(crate) fn find_missing_letters(
+    strings: &[Option<&str>],
+) -> Result<Vec<Option<String>>, LocalError> {
+    span!(DEBUG, "find_missing_letters", strings = strings.len());
+    strings
+        .par_iter()
+        .map(|string| {
+            string
+                .map(|string| {
+                    let mask = mask_string(string)?;
+                    Ok(mask_to_chars(LETTERS_MASK & !mask))
+                })
+                .transpose()
+        })
+        .collect()
+}
+
+/// Convert per-string masks into the requested Python container.
+pub(crate) fn masks_into_py(
+    py: Python<'_>,
+    masks: Vec<Option<Mask>>,
+    output: BatchOutput,
+) -> PyResult<PyObject> {
+    let masks: Vec<Option<u32>> = masks.into_iter().map(|mask| mask.map(Mask::bits)).collect();
+
+    match output {
+        BatchOutput::List => Ok(masks.into_py(py)),
+        BatchOutput::Arrow => arrow::u32_array_to_pyarrow(py, &masks),
+        BatchOutput::Bytes => masks_into_bytes(py, &masks).map(Into::into),
+    }
+}
+
+/// Write per-string masks straight into a Python `bytes` of little-endian `u32`,
+/// with `0` in place of nulls.
+fn masks_into_bytes<'py>(py: Python<'py>, masks: &[Option<u32>]) -> PyResult<&'py PyBytes> {
+    PyBytes::new_with(py, masks.len() * 4, |buffer| {
+        for (bytes, mask) in buffer.chunks_exact_mut(4).zip(masks) {
+            bytes.copy_from_slice(&mask.unwrap_or(0).to_le_bytes());
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::conftest;
+
+    #[test]
+    fn masks() {
+        let strings: Vec<_> = conftest::COLLECTION_OF_50_CHARS_STRINGS
+            .iter()
+            .copied()
+            .map(Some)
+            .chain([None])
+            .collect();
+        let masks = find_masks(&strings).unwrap();
+
+        assert_eq!(masks.len(), strings.len());
+        assert_eq!(masks.last(), Some(&None));
+        for (mask, string) in masks.iter().zip(strings.iter()).take(strings.len() - 1) {
+            assert_eq!(*mask, Some(mask_string(string.unwrap()).unwrap()));
+        }
+    }
+
+    #[test]
+    fn groups() {
+        let groups = [
+            Group::One("abc"),
+            Group::Many(vec!["ab", "bc"]),
+            Group::Many(vec![]),
+        ];
+        let lengths = map_groups(&groups, |group| match group {
+            Group::One(string) => Ok(string.len()),
+            Group::Many(strings) => Ok(strings.len()),
+        });
+
+        assert_eq!(lengths.unwrap(), [3, 2, 0]);
+        assert!(
+            map_groups(&groups, |_| Err::<(), _>(LocalError::InvalidCharacter {
+                character: '1'
+            }))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn masks_of_arrow_array() {
+        let array = arrow::test::string_array(&[Some("abc"), None, Some("b")], 0);
+        let masks = find_masks(&Batch::Arrow(array).values().unwrap()).unwrap();
+
+        assert_eq!(masks, vec![Some(Mask(0b1110)), None, Some(Mask(0b100))]);
+    }
+}
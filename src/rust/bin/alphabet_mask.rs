@@ -0,0 +1,5 @@
+//! The `alphabet-mask` command line tool; see [`lib_alphabet_mask::cli`].
+
+fn main() -> std::process::ExitCode {
+    lib_alphabet_mask::cli::main()
+}
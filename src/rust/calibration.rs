@@ -0,0 +1,229 @@
+//! Measuring the input size from which masking in parallel pays off on the
+//! current machine, which sets the chunk limit of the dispatch heuristic.
+//!
+//! Calls parallelise once their strings exceed the chunk limit in total, in
+//! chunks of that size. For each candidate limit, strings of twice its length
+//! are masked both serially and in parallel; the calibrated limit is the smallest
+//! candidate from which the parallel path is faster at every larger candidate.
+//!
+//! A calibration can be saved to a file and loaded on import through
+//! `ALPHABET_MASK_CALIBRATION`.
+//!
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::models::{LocalError, Mask};
+use alphabet_mask_core::{find_common_mask, find_common_mask_parallel};
+
+/// The first line of every calibration file, identifying the format version.
+const HEADER: &str = "alphabet_mask calibration v1";
+
+/// The smallest chunk limit measured.
+pub(crate) const MIN_CHUNK_LIMIT: usize = 1 << 12; // 4 KiB
+
+/// The largest chunk limit measured, if not specified.
+pub(crate) const DEFAULT_MAX_CHUNK_LIMIT: usize = 1 << 22; // 4 MiB
+
+/// The number of times each path is timed per candidate, if not specified; the
+/// fastest time is kept.
+pub(crate) const DEFAULT_REPEATS: usize = 5;
+
+/// The synthetic string repeated to make up the inputs.
+const SAMPLE: &str = "the quick brown fox jumps over the lazy dog";
+
+/// The timings of one candidate chunk limit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Sample {
+    pub(crate) chunk_limit: usize,
+    pub(crate) serial: Duration,
+    pub(crate) parallel: Duration,
+}
+
+/// The outcome of a calibration.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Calibration {
+    /// The chunk limit from which the parallel path is faster.
+    pub(crate) chunk_limit: usize,
+    /// The number of threads of the pool it was measured with.
+    pub(crate) threads: usize,
+    /// The timings of each candidate, which are not saved.
+    pub(crate) samples: Vec<Sample>,
+}
+
+/// Returns the fastest of `repeats` runs of `f`.
+fn fastest(repeats: usize, mut f: impl FnMut() -> Result<Mask, LocalError>) -> Duration {
+    (0..repeats.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            // The sample is within the alphabet, so this never fails.
+            let _ = std::hint::black_box(f());
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+/// Returns the smallest chunk limit of `samples`, in ascending order, from which
+/// the parallel path is faster at every later one; or twice the largest if it
+/// never is, as serial masking won at every size measured.
+fn crossover(samples: &[Sample]) -> usize {
+    let losing = samples
+        .iter()
+        .rposition(|sample| sample.parallel >= sample.serial);
+
+    match losing {
+        None => samples
+            .first()
+            .map_or(MIN_CHUNK_LIMIT, |sample| sample.chunk_limit),
+        Some(index) => samples.get(index + 1).map_or_else(
+            || samples[index].chunk_limit.saturating_mul(2),
+            |sample| sample.chunk_limit,
+        ),
+    }
+}
+
+impl Calibration {
+    /// Time the serial and parallel paths for every power of two chunk limit from
+    /// [`MIN_CHUNK_LIMIT`] up to `max_chunk_limit`.
+    pub(crate) fn measure(max_chunk_limit: usize, repeats: usize) -> Self {
+        span!(DEBUG, "calibrate", max_chunk_limit, repeats);
+        let longest = max_chunk_limit.max(MIN_CHUNK_LIMIT) * 2 / SAMPLE.len() + 1;
+        let strings = vec![SAMPLE; longest];
+
+        let samples: Vec<Sample> =
+            std::iter::successors(Some(MIN_CHUNK_LIMIT), |limit| limit.checked_mul(2))
+                .take_while(|&limit| limit <= max_chunk_limit.max(MIN_CHUNK_LIMIT))
+                .map(|chunk_limit| {
+                    let strings = &strings[..chunk_limit * 2 / SAMPLE.len() + 1];
+                    Sample {
+                        chunk_limit,
+                        serial: fastest(repeats, || find_common_mask(strings.iter())),
+                        parallel: fastest(repeats, || {
+                            find_common_mask_parallel(strings.iter(), Some(chunk_limit))
+                        }),
+                    }
+                })
+                .collect();
+
+        Self {
+            chunk_limit: crossover(&samples),
+            threads: rayon::current_num_threads(),
+            samples,
+        }
+    }
+
+    /// Load a calibration from `path`, without its samples.
+    pub(crate) fn load(path: &Path) -> Result<Self, LocalError> {
+        let text = fs::read_to_string(path).map_err(|source| LocalError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let invalid = |context: &str| LocalError::InvalidArgument {
+            name: "ALPHABET_MASK_CALIBRATION",
+            value: format!("{}: {context}", path.display()),
+        };
+
+        let mut lines = text.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(invalid("unrecognised header"));
+        }
+
+        let (mut chunk_limit, mut threads) = (None, None);
+        for line in lines {
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid("expected `key=value` lines"))?;
+
+            match key {
+                "chunk_limit" => {
+                    chunk_limit = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|&limit| limit > 0)
+                            .ok_or_else(|| invalid("bad chunk limit"))?,
+                    )
+                }
+                "threads" => {
+                    threads = Some(value.parse().map_err(|_| invalid("bad thread count"))?)
+                }
+                _ => return Err(invalid(&format!("unknown key {key:?}"))),
+            }
+        }
+
+        Ok(Self {
+            chunk_limit: chunk_limit.ok_or_else(|| invalid("missing chunk limit"))?,
+            threads: threads.unwrap_or_default(),
+            samples: Vec::new(),
+        })
+    }
+
+    /// Save the calibration to `path`, replacing any file there.
+    pub(crate) fn save(&self, path: &Path) -> Result<(), LocalError> {
+        let text = format!(
+            "{HEADER}\nchunk_limit={}\nthreads={}\n",
+            self.chunk_limit, self.threads
+        );
+        fs::write(path, text).map_err(|source| LocalError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn samples(wins: &[bool]) -> Vec<Sample> {
+        wins.iter()
+            .enumerate()
+            .map(|(index, &win)| Sample {
+                chunk_limit: MIN_CHUNK_LIMIT << index,
+                serial: Duration::from_millis(10),
+                parallel: Duration::from_millis(if win { 5 } else { 20 }),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn crossover_of_samples() {
+        assert_eq!(crossover(&samples(&[true, true, true])), MIN_CHUNK_LIMIT);
+        assert_eq!(
+            crossover(&samples(&[false, true, true])),
+            MIN_CHUNK_LIMIT << 1
+        );
+        // A noisy win below a loss does not count.
+        assert_eq!(
+            crossover(&samples(&[true, false, true])),
+            MIN_CHUNK_LIMIT << 2
+        );
+        assert_eq!(crossover(&samples(&[false, false])), MIN_CHUNK_LIMIT << 2);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = std::env::temp_dir().join("alphabet_mask_calibration");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("calibration.txt");
+
+        let calibration = Calibration::measure(MIN_CHUNK_LIMIT * 2, 1);
+        assert_eq!(calibration.samples.len(), 2);
+        calibration.save(&path).unwrap();
+        assert_eq!(
+            Calibration::load(&path).unwrap(),
+            Calibration {
+                samples: Vec::new(),
+                ..calibration
+            }
+        );
+
+        fs::write(&path, format!("{HEADER}\nchunk_limit=0\n")).unwrap();
+        assert!(matches!(
+            Calibration::load(&path),
+            Err(LocalError::InvalidArgument { .. })
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
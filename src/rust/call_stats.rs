@@ -0,0 +1,367 @@
+//! Statistics about the last call made from each Python thread.
+//!
+//! Every entry point over a collection of strings records a [`CallStats`] when it
+//! returns successfully, which `last_call_stats()` returns to the same thread.
+//! The strings and bytes it processed are also added to [`Counters`] for the
+//! whole process, which `get_counters()` returns and `reset_counters()` clears.
+//!
+//! With worker statistics enabled, the calls finding a common mask also report
+//! the chunks and bytes masked by each thread, through [`WorkerLoads`].
+//!
+// pyo3 0.20 expands `#[pyclass]` into trait impls inside a function body.
+#![allow(non_local_definitions)]
+
+use std::cell::RefCell;
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use pyo3::prelude::*;
+
+use crate::models::{LocalError, Mask};
+use crate::settings::Settings;
+use alphabet_mask_core::{find_common_mask_dispatch, find_common_mask_dispatch_with};
+
+thread_local! {
+    /// The statistics of the last call made on this thread.
+    static LAST_CALL: RefCell<Option<CallStats>> = const { RefCell::new(None) };
+}
+
+/// The totals of every call recorded since the process started, or since they
+/// were last reset.
+static COUNTERS: AtomicCounters = AtomicCounters {
+    calls: AtomicU64::new(0),
+    strings: AtomicU64::new(0),
+    bytes: AtomicU64::new(0),
+};
+
+struct AtomicCounters {
+    calls: AtomicU64,
+    strings: AtomicU64,
+    bytes: AtomicU64,
+}
+
+/// The total length of `strings` in bytes.
+fn total_bytes<S: AsRef<str>>(strings: &[S]) -> usize {
+    strings.iter().map(|string| string.as_ref().len()).sum()
+}
+
+/// The totals of the calls recorded across every thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct Counters {
+    pub(crate) calls: u64,
+    pub(crate) strings: u64,
+    pub(crate) bytes: u64,
+}
+
+impl Counters {
+    /// The totals so far.
+    pub(crate) fn get() -> Self {
+        Self {
+            calls: COUNTERS.calls.load(Ordering::Relaxed),
+            strings: COUNTERS.strings.load(Ordering::Relaxed),
+            bytes: COUNTERS.bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Set the totals back to zero, returning what they were; each counter is
+    /// swapped on its own, so no call is lost or counted twice, though one
+    /// recorded meanwhile may be split across the two.
+    pub(crate) fn reset() -> Self {
+        Self {
+            calls: COUNTERS.calls.swap(0, Ordering::Relaxed),
+            strings: COUNTERS.strings.swap(0, Ordering::Relaxed),
+            bytes: COUNTERS.bytes.swap(0, Ordering::Relaxed),
+        }
+    }
+
+    /// Count a call which processed `strings`, for calls which record no
+    /// [`CallStats`].
+    pub(crate) fn add_strings<S: AsRef<str>>(strings: &[S]) {
+        Self::add(strings.len(), total_bytes(strings));
+    }
+
+    fn add(strings: usize, bytes: usize) {
+        COUNTERS.calls.fetch_add(1, Ordering::Relaxed);
+        COUNTERS
+            .strings
+            .fetch_add(strings as u64, Ordering::Relaxed);
+        COUNTERS.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+}
+
+/// An estimate of the auxiliary memory used by a call, besides its inputs and
+/// outputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct MemoryEstimate {
+    /// The references to the strings extracted from the Python objects.
+    pub(crate) references: usize,
+    /// The chunks of references or blocks of text alive at once.
+    pub(crate) chunks: usize,
+    /// The state held by each thread.
+    pub(crate) threads: usize,
+}
+
+impl MemoryEstimate {
+    /// The memory used to mask `strings`, which are split into chunks of
+    /// `length_limit` bytes masked in parallel if they are longer in total.
+    pub(crate) fn of_strings<S: AsRef<str>>(strings: &[S], length_limit: usize) -> Self {
+        let bytes: usize = strings.iter().map(|string| string.as_ref().len()).sum();
+        let parallel = bytes > length_limit;
+        let threads = if parallel {
+            rayon::current_num_threads()
+        } else {
+            1
+        };
+        // Each thread masks one chunk at a time, of the average number of strings.
+        let chunks = if parallel {
+            let per_chunk = strings.len().saturating_mul(length_limit) / bytes.max(1);
+            per_chunk.saturating_mul(threads).min(strings.len()) * size_of::<&&str>()
+        } else {
+            0
+        };
+
+        Self {
+            references: strings.len() * size_of::<&str>(),
+            chunks,
+            threads: threads * size_of::<Mask>(),
+        }
+    }
+
+    /// The memory used to mask `strings` strings each on their own, in parallel.
+    pub(crate) fn of_each(strings: usize) -> Self {
+        Self {
+            references: strings * size_of::<&str>(),
+            chunks: 0,
+            threads: rayon::current_num_threads() * size_of::<Mask>(),
+        }
+    }
+
+    /// The memory used to read files in blocks of `length_limit` bytes, one per
+    /// thread; a block ends with a whole line, so may be longer.
+    pub(crate) fn of_blocks(length_limit: usize) -> Self {
+        let threads = rayon::current_num_threads();
+
+        Self {
+            references: 0,
+            chunks: threads.saturating_mul(length_limit),
+            threads: threads * size_of::<Mask>(),
+        }
+    }
+
+    /// The total of every estimate.
+    pub(crate) fn peak(&self) -> usize {
+        self.references + self.chunks + self.threads
+    }
+}
+
+/// The work done by one thread during a call: its rayon worker index, or
+/// [`None`] for the thread which made the call, and the chunks and bytes it
+/// masked.
+pub(crate) type WorkerLoad = (Option<usize>, usize, usize);
+
+/// The work done by each thread during a call, collected as its chunks are
+/// masked.
+#[derive(Debug, Default)]
+pub(crate) struct WorkerLoads(Mutex<Vec<WorkerLoad>>);
+
+impl WorkerLoads {
+    /// A new collector if worker statistics are enabled, or [`None`].
+    pub(crate) fn if_enabled() -> Option<Self> {
+        Settings::get().worker_stats.then(Self::default)
+    }
+
+    /// Count a chunk of `bytes` bytes masked on the current thread.
+    pub(crate) fn add(&self, bytes: usize) {
+        let thread = rayon::current_thread_index();
+        let mut loads = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        match loads.iter_mut().find(|load| load.0 == thread) {
+            Some(load) => {
+                load.1 += 1;
+                load.2 += bytes;
+            }
+            None => loads.push((thread, 1, bytes)),
+        }
+    }
+
+    /// The work of each thread, the calling thread first then by worker index.
+    pub(crate) fn into_vec(self) -> Vec<WorkerLoad> {
+        let mut loads = self.0.into_inner().unwrap_or_else(|err| err.into_inner());
+        loads.sort_unstable();
+        loads
+    }
+}
+
+/// Returns the common mask of `strings` as [`find_common_mask_dispatch`] does,
+/// adding the chunks masked by each thread to `loads` if given.
+pub(crate) fn find_common_mask_counted<S: AsRef<str> + Sync>(
+    strings: &[S],
+    length_limit: usize,
+    loads: Option<&WorkerLoads>,
+) -> Result<Mask, LocalError> {
+    match loads {
+        Some(loads) => {
+            find_common_mask_dispatch_with(strings, length_limit, |_, bytes| loads.add(bytes))
+        }
+        None => find_common_mask_dispatch(strings, length_limit),
+    }
+}
+
+/// Statistics about one call.
+///
+/// ``peak_memory`` is an estimate in bytes of the auxiliary memory the call
+/// needed at its peak, besides its inputs and outputs; it is the sum of
+/// ``reference_memory`` for the strings extracted from Python, ``chunk_memory``
+/// for the chunks or file blocks being masked at once, and ``thread_memory`` for
+/// the state of each thread.
+///
+/// ``strings`` and ``bytes`` count the input processed by the call: the strings,
+/// or the lines of files, and their total length in UTF-8.
+///
+/// ``workers`` is ``None`` unless worker statistics are enabled by
+/// :func:`set_worker_stats` or ``ALPHABET_MASK_WORKER_STATS``; then, for calls
+/// finding a common alphabet, it is a `list` of ``(thread, chunks, bytes)`` for
+/// each thread which masked part of the input, where ``thread`` is the index of
+/// the rayon worker, or ``None`` outside the thread pool, such as the calling
+/// thread when the input was too small to mask in parallel. A thread with far more bytes than the others, such
+/// as from one giant string, shows where the work was serialised.
+#[pyclass(name = "CallStats", module = "alphabet_mask", frozen, get_all)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CallStats {
+    /// The name of the function called.
+    pub(crate) function: &'static str,
+    pub(crate) peak_memory: usize,
+    pub(crate) reference_memory: usize,
+    pub(crate) chunk_memory: usize,
+    pub(crate) thread_memory: usize,
+    pub(crate) strings: usize,
+    pub(crate) bytes: usize,
+    pub(crate) workers: Option<Vec<WorkerLoad>>,
+}
+
+impl CallStats {
+    /// The statistics of a call to `function`.
+    pub(crate) fn new(function: &'static str, memory: MemoryEstimate) -> Self {
+        Self {
+            function,
+            peak_memory: memory.peak(),
+            reference_memory: memory.references,
+            chunk_memory: memory.chunks,
+            thread_memory: memory.threads,
+            strings: 0,
+            bytes: 0,
+            workers: None,
+        }
+    }
+
+    /// These statistics, for a call which processed `strings` strings of `bytes`
+    /// bytes in total.
+    pub(crate) fn processed(self, strings: usize, bytes: usize) -> Self {
+        Self {
+            strings,
+            bytes,
+            ..self
+        }
+    }
+
+    /// These statistics, for a call which processed `strings`.
+    pub(crate) fn processed_strings<S: AsRef<str>>(self, strings: &[S]) -> Self {
+        self.processed(strings.len(), total_bytes(strings))
+    }
+
+    /// These statistics, with the work of each thread collected by `loads`.
+    pub(crate) fn with_workers(self, loads: Option<WorkerLoads>) -> Self {
+        Self {
+            workers: loads.map(WorkerLoads::into_vec),
+            ..self
+        }
+    }
+
+    /// Make these the statistics of the last call on this thread, and add them
+    /// to the [`Counters`].
+    pub(crate) fn record(self) {
+        Counters::add(self.strings, self.bytes);
+        LAST_CALL.with(|last| *last.borrow_mut() = Some(self));
+    }
+
+    /// The statistics of the last call on this thread, if any.
+    pub(crate) fn last() -> Option<Self> {
+        LAST_CALL.with(|last| last.borrow().clone())
+    }
+}
+
+#[pymethods]
+impl CallStats {
+    fn __repr__(&self) -> String {
+        format!(
+            "CallStats(function={:?}, peak_memory={}, strings={}, bytes={})",
+            self.function, self.peak_memory, self.strings, self.bytes
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn estimates() {
+        let strings = ["0123456789"; 100];
+        let serial = MemoryEstimate::of_strings(&strings, 1 << 20);
+        assert_eq!(serial.references, 100 * size_of::<&str>());
+        assert_eq!(serial.chunks, 0);
+        assert_eq!(serial.peak(), serial.references + size_of::<Mask>());
+
+        // Ten strings fit in a chunk of 100 bytes.
+        let parallel = MemoryEstimate::of_strings(&strings, 100);
+        let threads = rayon::current_num_threads();
+        assert_eq!(
+            parallel.chunks,
+            (10 * threads).min(100) * size_of::<&&str>()
+        );
+        assert_eq!(parallel.threads, threads * size_of::<Mask>());
+
+        assert_eq!(MemoryEstimate::of_blocks(10).chunks, 10 * threads);
+    }
+
+    #[test]
+    fn last_call_per_thread() {
+        CallStats::new("common_alphabets", MemoryEstimate::default()).record();
+
+        assert_eq!(CallStats::last().unwrap().function, "common_alphabets");
+        assert_eq!(std::thread::spawn(CallStats::last).join().unwrap(), None);
+    }
+
+    #[test]
+    fn counters() {
+        // Other tests may record calls meanwhile, so only lower bounds hold.
+        let before = Counters::get();
+        CallStats::new("common_alphabets", MemoryEstimate::default())
+            .processed_strings(&["abc", "de"])
+            .record();
+
+        let last = CallStats::last().unwrap();
+        assert_eq!((last.strings, last.bytes), (2, 5));
+
+        let after = Counters::reset();
+        assert!(after.calls > before.calls);
+        assert!(after.strings >= before.strings + 2);
+        assert!(after.bytes >= before.bytes + 5);
+    }
+
+    #[test]
+    fn worker_loads() {
+        let loads = WorkerLoads::default();
+        let strings = ["abcdefghij"; 100];
+        find_common_mask_counted(&strings, 100, Some(&loads)).unwrap();
+
+        let loads = loads.into_vec();
+        assert!(loads.iter().all(|load| load.0.is_some()));
+        assert_eq!(loads.iter().map(|load| load.1).sum::<usize>(), 10);
+        assert_eq!(loads.iter().map(|load| load.2).sum::<usize>(), 1000);
+
+        let loads = WorkerLoads::default();
+        find_common_mask_counted(&strings, 1000, Some(&loads)).unwrap();
+        assert_eq!(loads.into_vec(), [(None, 1, 1000)]);
+    }
+}
@@ -0,0 +1,205 @@
+//! A C interface to the masking functions, enabled by the `capi` feature.
+//!
+//! The header `include/alphabet_mask.h` is generated from this module by cbindgen
+//! whenever the crate is built with the feature. For a library without any
+//! Python symbols, build with `--no-default-features --features capi,parallel`.
+//!
+use std::ffi::c_char;
+
+use crate::models::{LocalError, Mask};
+use alphabet_mask_core::{chunks, find_common_mask_dispatch, mask_string, mask_to_chars};
+
+/// The maximum number of characters in the output of `alphabet_mask_to_chars`,
+/// excluding the terminating NUL.
+pub const ALPHABET_MASK_MAX_CHARS: usize = 32;
+
+/// The outcome of a call into the C interface.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlphabetMaskStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A string contains a character outside the alphabet.
+    InvalidCharacter = 1,
+    /// A string is not valid UTF-8.
+    InvalidUtf8 = 2,
+    /// A required pointer is NULL.
+    NullPointer = 3,
+    /// Any other error.
+    Other = 4,
+}
+
+impl From<LocalError> for AlphabetMaskStatus {
+    fn from(err: LocalError) -> Self {
+        match err {
+            LocalError::InvalidCharacter { .. } | LocalError::ControlCharacter { .. } => {
+                Self::InvalidCharacter
+            }
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Borrow a string from a pointer and a length in bytes.
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads of `len` bytes, unless `len` is zero.
+unsafe fn borrow_str<'a>(ptr: *const c_char, len: usize) -> Result<&'a str, AlphabetMaskStatus> {
+    if len == 0 {
+        return Ok("");
+    }
+    if ptr.is_null() {
+        return Err(AlphabetMaskStatus::NullPointer);
+    }
+
+    std::str::from_utf8(std::slice::from_raw_parts(ptr as *const u8, len))
+        .map_err(|_| AlphabetMaskStatus::InvalidUtf8)
+}
+
+/// Compute the bit mask of the UTF-8 string at `ptr`, of `len` bytes, into `out`.
+///
+/// The string does not need to be NUL-terminated.
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads of `len` bytes, and `out` valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn alphabet_mask_string(
+    ptr: *const c_char,
+    len: usize,
+    out: *mut u32,
+) -> AlphabetMaskStatus {
+    if out.is_null() {
+        return AlphabetMaskStatus::NullPointer;
+    }
+
+    match borrow_str(ptr, len).and_then(|string| mask_string(string).map_err(Into::into)) {
+        Ok(mask) => {
+            *out = mask.bits();
+            AlphabetMaskStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Compute the common bit mask of `count` UTF-8 strings into `out`.
+///
+/// The `i`-th string starts at `ptrs[i]` and is `lens[i]` bytes long. Large inputs
+/// are processed in parallel if the library is built with the `parallel` feature.
+///
+/// # Safety
+///
+/// `ptrs` and `lens` must both be valid for reads of `count` elements, each string
+/// must be valid for reads of its length, and `out` must be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn alphabet_mask_common(
+    ptrs: *const *const c_char,
+    lens: *const usize,
+    count: usize,
+    out: *mut u32,
+) -> AlphabetMaskStatus {
+    if out.is_null() || (count > 0 && (ptrs.is_null() || lens.is_null())) {
+        return AlphabetMaskStatus::NullPointer;
+    }
+
+    let strings = if count == 0 {
+        Ok(Vec::new())
+    } else {
+        let ptrs = std::slice::from_raw_parts(ptrs, count);
+        let lens = std::slice::from_raw_parts(lens, count);
+        ptrs.iter()
+            .zip(lens)
+            .map(|(&ptr, &len)| borrow_str(ptr, len))
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    match strings.and_then(|strings| {
+        find_common_mask_dispatch(&strings, chunks::LENGTH_LIMIT_PER_CHUNK).map_err(Into::into)
+    }) {
+        Ok(mask) => {
+            *out = mask.bits();
+            AlphabetMaskStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Write the characters represented by `mask` into `buffer` as a NUL-terminated
+/// string, truncating it to fit `buffer_len` bytes.
+///
+/// Returns the number of characters in the full string, excluding the NUL, in the
+/// manner of `snprintf`; a buffer of `ALPHABET_MASK_MAX_CHARS + 1` bytes always fits.
+///
+/// # Safety
+///
+/// `buffer` must be valid for writes of `buffer_len` bytes, unless `buffer_len` is zero.
+#[no_mangle]
+pub unsafe extern "C" fn alphabet_mask_to_chars(
+    mask: u32,
+    buffer: *mut c_char,
+    buffer_len: usize,
+) -> usize {
+    let chars = mask_to_chars(Mask(mask));
+
+    if buffer_len > 0 && !buffer.is_null() {
+        let written = chars.len().min(buffer_len - 1);
+        std::ptr::copy_nonoverlapping(chars.as_ptr() as *const c_char, buffer, written);
+        *buffer.add(written) = 0;
+    }
+
+    chars.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mask_one_string() {
+        let string = "a c e.";
+        let mut mask = 0;
+        let status =
+            unsafe { alphabet_mask_string(string.as_ptr().cast(), string.len(), &mut mask) };
+
+        assert_eq!(status, AlphabetMaskStatus::Ok);
+        assert_eq!(mask, 0b100_00000_00000_00000_00001_01011);
+    }
+
+    #[test]
+    fn mask_invalid_string() {
+        let string = "a1";
+        let mut mask = 0;
+        let status =
+            unsafe { alphabet_mask_string(string.as_ptr().cast(), string.len(), &mut mask) };
+
+        assert_eq!(status, AlphabetMaskStatus::InvalidCharacter);
+    }
+
+    #[test]
+    fn common() {
+        let strings = crate::conftest::COLLECTION_OF_50_CHARS_STRINGS;
+        let ptrs: Vec<_> = strings.iter().map(|s| s.as_ptr().cast()).collect();
+        let lens: Vec<_> = strings.iter().map(|s| s.len()).collect();
+        let mut mask = 0;
+        let status =
+            unsafe { alphabet_mask_common(ptrs.as_ptr(), lens.as_ptr(), strings.len(), &mut mask) };
+
+        assert_eq!(status, AlphabetMaskStatus::Ok);
+        assert_eq!(mask_to_chars(Mask(mask)), " ert.");
+    }
+
+    #[test]
+    fn to_chars_truncated() {
+        let mut buffer = [0 as c_char; 4];
+        let length = unsafe { alphabet_mask_to_chars(0b1110, buffer.as_mut_ptr(), buffer.len()) };
+
+        assert_eq!(length, 3);
+        assert_eq!(buffer.map(|c| c as u8), *b"abc\0");
+
+        let length = unsafe { alphabet_mask_to_chars(0b11110, buffer.as_mut_ptr(), buffer.len()) };
+
+        assert_eq!(length, 4);
+        assert_eq!(buffer.map(|c| c as u8), *b"abc\0");
+    }
+}
@@ -0,0 +1,204 @@
+//! Periodic checkpoints for long-running scans over many files.
+//!
+//! A checkpoint records the running mask, together with the file and byte offset
+//! up to which the mask has been computed, so that a preempted job can resume
+//! where it left off instead of starting from scratch. It also records a
+//! fingerprint of the list of files, so that it is never resumed over another.
+//!
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::models::{LocalError, Mask};
+
+/// The number of bytes to process between two checkpoints, if not specified.
+pub const DEFAULT_CHECKPOINT_INTERVAL: u64 = 1 << 28; // 256 MiB
+
+/// The first line of every checkpoint file, identifying the format version.
+const HEADER: &str = "alphabet_mask checkpoint v2";
+
+/// The state of a scan over a list of files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Checkpoint {
+    /// The common mask of everything before `file_index` and `byte_offset`.
+    pub(crate) mask: Mask,
+    /// The index of the file currently being scanned.
+    pub(crate) file_index: usize,
+    /// The number of bytes of the current file already included in `mask`.
+    pub(crate) byte_offset: u64,
+    /// The [fingerprint](Self::fingerprint) of the files being scanned.
+    pub(crate) paths: u64,
+}
+
+impl Default for Checkpoint {
+    fn default() -> Self {
+        Self {
+            mask: Mask::ALL,
+            file_index: 0,
+            byte_offset: 0,
+            paths: 0,
+        }
+    }
+}
+
+impl Checkpoint {
+    /// The initial checkpoint of a scan over `paths`.
+    pub(crate) fn new(paths: &[PathBuf]) -> Self {
+        Self {
+            paths: Self::fingerprint(paths),
+            ..Self::default()
+        }
+    }
+
+    /// Returns a fingerprint of `paths` in order, the 64-bit FNV-1a hash of each
+    /// path followed by a nul byte, which cannot occur in a path.
+    ///
+    /// Unlike [`std::hash::DefaultHasher`], the hash is stable across Rust
+    /// versions, so a checkpoint survives an upgrade.
+    pub(crate) fn fingerprint(paths: &[PathBuf]) -> u64 {
+        paths
+            .iter()
+            .flat_map(|path| path.as_os_str().as_encoded_bytes().iter().chain([&0]))
+            .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            })
+    }
+
+    /// Load a checkpoint from `path`, or return [`None`] if it does not exist.
+    pub(crate) fn load(path: &Path) -> Result<Option<Self>, LocalError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => {
+                return Err(LocalError::Io {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+
+        let invalid = |context: &str| LocalError::InvalidCheckpoint {
+            path: path.to_path_buf(),
+            context: context.to_owned(),
+        };
+
+        let mut lines = text.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(invalid("unrecognised header"));
+        }
+
+        let mut checkpoint = Self::default();
+        for line in lines {
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid("expected `key=value` lines"))?;
+
+            match key {
+                "mask" => checkpoint.mask = Mask(value.parse().map_err(|_| invalid("bad mask"))?),
+                "file_index" => {
+                    checkpoint.file_index = value.parse().map_err(|_| invalid("bad file index"))?
+                }
+                "byte_offset" => {
+                    checkpoint.byte_offset =
+                        value.parse().map_err(|_| invalid("bad byte offset"))?
+                }
+                "paths" => {
+                    checkpoint.paths =
+                        u64::from_str_radix(value, 16).map_err(|_| invalid("bad paths"))?
+                }
+                _ => return Err(invalid(&format!("unknown key {key:?}"))),
+            }
+        }
+
+        Ok(Some(checkpoint))
+    }
+
+    /// Save this checkpoint to `path`.
+    ///
+    /// The checkpoint is written to a temporary file first then renamed over `path`,
+    /// so a job killed mid-write never leaves a truncated checkpoint behind.
+    pub(crate) fn save(&self, path: &Path) -> Result<(), LocalError> {
+        let mut temp_path = PathBuf::from(path);
+        temp_path.as_mut_os_string().push(".tmp");
+
+        let text = format!(
+            "{HEADER}\nmask={}\nfile_index={}\nbyte_offset={}\npaths={:016x}\n",
+            self.mask.bits(),
+            self.file_index,
+            self.byte_offset,
+            self.paths
+        );
+
+        fs::write(&temp_path, text)
+            .and_then(|_| fs::rename(&temp_path, path))
+            .map_err(|source| LocalError::Io {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A path in the temp directory unique to this process.
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "alphabet_mask_checkpoint_{name}_{}",
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn round_trip() {
+        let path = temp_path("round_trip");
+        let checkpoint = Checkpoint {
+            mask: Mask(0b1011),
+            file_index: 3,
+            byte_offset: 12345,
+            paths: Checkpoint::fingerprint(&["a.txt".into(), "b.txt".into()]),
+        };
+
+        checkpoint.save(&path).unwrap();
+        let loaded = Checkpoint::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, Some(checkpoint));
+    }
+
+    #[test]
+    fn missing() {
+        let path = temp_path("missing");
+
+        assert_eq!(Checkpoint::load(&path).unwrap(), None);
+    }
+
+    #[test]
+    fn bad_header() {
+        let path = temp_path("bad_header");
+        fs::write(&path, "not a checkpoint\n").unwrap();
+        let loaded = Checkpoint::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(loaded, Err(LocalError::InvalidCheckpoint { .. })));
+    }
+
+    #[test]
+    fn fingerprint_order() {
+        let (a, b) = (PathBuf::from("a.txt"), PathBuf::from("b.txt"));
+
+        assert_eq!(
+            Checkpoint::fingerprint(&[a.clone(), b.clone()]),
+            Checkpoint::fingerprint(&[a.clone(), b.clone()])
+        );
+        assert_ne!(
+            Checkpoint::fingerprint(&[a.clone(), b.clone()]),
+            Checkpoint::fingerprint(&[b, a.clone()])
+        );
+        assert_ne!(
+            Checkpoint::fingerprint(&["ab".into()]),
+            Checkpoint::fingerprint(&["a".into(), "b".into()])
+        );
+        assert_ne!(Checkpoint::fingerprint(&[a]), Checkpoint::fingerprint(&[]));
+    }
+}
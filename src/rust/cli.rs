@@ -0,0 +1,529 @@
+//! The `alphabet-mask` command line tool, enabled by the `cli` feature.
+//!
+//! Every subcommand reads newline-delimited records from the given files, or from
+//! stdin if there are none (or one of them is `-`). Build without Python with
+//! `cargo build --release --no-default-features --features cli`.
+//!
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::checkpoint::DEFAULT_CHECKPOINT_INTERVAL;
+use crate::files::{self, format_mask};
+use crate::models::bits::LETTERS_MASK;
+use crate::models::{bit_char, LocalError, Mask, MaskFormat};
+use alphabet_mask_core::expression::Expression;
+use alphabet_mask_core::stats::CorpusStats;
+use alphabet_mask_core::{chunks, mask_string, mask_to_chars};
+
+/// Create alphabet masks from lines of text.
+#[derive(Debug, Parser)]
+#[command(name = "alphabet-mask", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// How to print the results.
+    #[arg(long, global = true, value_enum, default_value_t = Output::Text)]
+    output: Output,
+
+    /// The approximate number of bytes to mask per parallel chunk.
+    #[arg(long, global = true, default_value_t = chunks::LENGTH_LIMIT_PER_CHUNK)]
+    length_limit: usize,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print the mask of every line.
+    Mask {
+        /// Print each mask as an integer, or as the characters it represents;
+        /// ignored for JSON output.
+        #[arg(long, value_enum, default_value_t = TextFormat::Int)]
+        format: TextFormat,
+
+        /// The files to read; stdin if omitted.
+        files: Vec<PathBuf>,
+    },
+    /// Print the alphabet common to every line.
+    Common {
+        /// Save the progress to this file periodically, and resume from it if it
+        /// exists; only for files, not stdin.
+        #[arg(long, value_name = "PATH")]
+        checkpoint: Option<PathBuf>,
+
+        /// The number of bytes to read between two checkpoints.
+        #[arg(
+            long,
+            value_name = "BYTES",
+            requires = "checkpoint",
+            default_value_t = DEFAULT_CHECKPOINT_INTERVAL,
+            value_parser = clap::value_parser!(u64).range(1..),
+        )]
+        checkpoint_interval: u64,
+
+        /// The files to read; stdin if omitted.
+        files: Vec<PathBuf>,
+    },
+    /// Print the alphabet of all the lines combined.
+    Union {
+        /// The files to read; stdin if omitted.
+        files: Vec<PathBuf>,
+    },
+    /// Print statistics about the lines, including how many contain each character.
+    Stats {
+        /// The files to read; stdin if omitted.
+        files: Vec<PathBuf>,
+    },
+    /// Print only the lines whose alphabet satisfies every given condition.
+    ///
+    /// Lines containing characters outside the alphabet never match.
+    Filter {
+        #[command(flatten)]
+        predicate: Predicate,
+
+        /// The files to read; stdin if omitted.
+        files: Vec<PathBuf>,
+    },
+}
+
+/// The conditions a line must satisfy to pass through `filter`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, clap::Args)]
+struct Predicate {
+    /// Only use these characters.
+    #[arg(long, value_name = "CHARS", value_parser = mask_string)]
+    subset_of: Option<Mask>,
+
+    /// Use every one of these characters.
+    #[arg(long, value_name = "CHARS", value_parser = mask_string)]
+    contains: Option<Mask>,
+
+    /// Use every letter from `a` to `z`.
+    #[arg(long)]
+    pangram: bool,
+
+    /// Match a predicate expression, such as
+    /// "contains(a,e) and not contains(z) and subset(' aeglrst')".
+    #[arg(long = "where", value_name = "EXPRESSION", value_parser = Expression::from_str)]
+    expression: Option<Expression>,
+}
+
+impl Predicate {
+    /// Returns whether a line with the given mask satisfies every condition.
+    fn matches(&self, mask: Mask) -> bool {
+        let required = self.contains.unwrap_or(Mask::EMPTY)
+            | if self.pangram {
+                LETTERS_MASK
+            } else {
+                Mask::EMPTY
+            };
+
+        self.subset_of
+            .is_none_or(|allowed| mask.is_subset_of(allowed))
+            && required.is_subset_of(mask)
+            && self
+                .expression
+                .as_ref()
+                .is_none_or(|expression| expression.matches(mask))
+    }
+
+    /// Append every line of `block` that satisfies the conditions to `output`.
+    fn filter_block(&self, block: &str, output: &mut String) -> Result<(), LocalError> {
+        for line in block.lines() {
+            if mask_string(line).is_ok_and(|mask| self.matches(mask)) {
+                output.push_str(line);
+                output.push('\n');
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// How to print the results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Output {
+    /// Plain text.
+    Text,
+    /// JSON; one object per line for the `mask` subcommand.
+    Json,
+}
+
+/// How to print masks as text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum TextFormat {
+    /// As integers.
+    Int,
+    /// As the characters they represent.
+    Chars,
+}
+
+/// The path shown in error messages for stdin.
+const STDIN: &str = "-";
+
+/// Returns the inputs to read, substituting stdin if there are none.
+fn inputs(files: &[PathBuf]) -> Vec<&Path> {
+    if files.is_empty() {
+        vec![Path::new(STDIN)]
+    } else {
+        files.iter().map(PathBuf::as_path).collect()
+    }
+}
+
+/// Open an input for reading, where `-` stands for stdin.
+fn open(path: &Path) -> Result<Box<dyn BufRead + Send>, LocalError> {
+    if path == Path::new(STDIN) {
+        Ok(Box::new(BufReader::new(io::stdin())))
+    } else {
+        let file = File::open(path).map_err(files::io_error(path))?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// Returns the alphabet common to every line of every input.
+fn find_common_mask(inputs: &[&Path], length_limit: usize) -> Result<Mask, LocalError> {
+    inputs.iter().try_fold(Mask::ALL, |acc, path| {
+        files::find_common_mask_in_reader(open(path)?, length_limit, path)
+            .map(|(mask, _)| acc & mask)
+    })
+}
+
+/// Returns the alphabet of every line of every input combined.
+fn find_union_mask(inputs: &[&Path], length_limit: usize) -> Result<Mask, LocalError> {
+    inputs.iter().try_fold(Mask::EMPTY, |acc, path| {
+        files::reduce_blocks_in_reader(
+            open(path)?,
+            length_limit,
+            path,
+            Mask::EMPTY,
+            |block| alphabet_mask_core::find_union_mask(block.lines()),
+            |a, b| a | b,
+        )
+        .map(|mask| acc | mask)
+    })
+}
+
+/// Gather the statistics of every line of every input.
+fn gather_stats(inputs: &[&Path], length_limit: usize) -> Result<CorpusStats, LocalError> {
+    inputs.iter().try_fold(CorpusStats::default(), |acc, path| {
+        files::reduce_blocks_in_reader(
+            open(path)?,
+            length_limit,
+            path,
+            CorpusStats::default(),
+            |block| CorpusStats::default().extend(block.lines()),
+            CorpusStats::merge,
+        )
+        .map(|stats| acc.merge(stats))
+    })
+}
+
+/// Format statistics as text or JSON.
+fn format_stats(stats: &CorpusStats, output: Output) -> String {
+    let coverage: Vec<(char, u64)> = (0..Mask::WIDTH)
+        .filter(|&bit| stats.union.contains_bit(bit))
+        .map(|bit| (bit_char(bit), stats.coverage[usize::from(bit)]))
+        .collect();
+
+    match output {
+        Output::Text => {
+            let mut text = format!(
+                "lines:  {}\nbytes:  {}\ncommon: {:?}\nunion:  {:?}\ncoverage:\n",
+                stats.strings,
+                stats.bytes,
+                mask_to_chars(stats.common),
+                mask_to_chars(stats.union),
+            );
+            for (char, count) in coverage {
+                text.push_str(&format!("  {char:?}: {count}\n"));
+            }
+            text
+        }
+        Output::Json => {
+            let coverage: Vec<String> = coverage
+                .into_iter()
+                .map(|(char, count)| format!("{:?}: {count}", char.to_string()))
+                .collect();
+            format!(
+                "{{\"lines\": {}, \"bytes\": {}, \"common\": {}, \"union\": {}, \"coverage\": {{{}}}}}\n",
+                stats.strings,
+                stats.bytes,
+                format_mask(stats.common, MaskFormat::Json),
+                format_mask(stats.union, MaskFormat::Json),
+                coverage.join(", "),
+            )
+        }
+    }
+}
+
+/// Format a single mask as text or JSON.
+fn format_result(mask: Mask, output: Output) -> String {
+    match output {
+        Output::Text => format!("{}\n", mask_to_chars(mask)),
+        Output::Json => format!("{}\n", format_mask(mask, MaskFormat::Json)),
+    }
+}
+
+/// Run a parsed command line, writing the results to stdout.
+fn execute(cli: Cli) -> Result<(), LocalError> {
+    let stdout = Path::new("<stdout>");
+    let mut writer = BufWriter::new(io::stdout().lock());
+
+    let text = match cli.command {
+        Command::Mask { format, files } => {
+            let format = match (cli.output, format) {
+                (Output::Json, _) => MaskFormat::Json,
+                (Output::Text, TextFormat::Int) => MaskFormat::Int,
+                (Output::Text, TextFormat::Chars) => MaskFormat::Chars,
+            };
+            for path in inputs(&files) {
+                files::write_masks(
+                    open(path)?,
+                    &mut writer,
+                    format,
+                    cli.length_limit,
+                    path,
+                    stdout,
+                )?;
+            }
+            return Ok(());
+        }
+        Command::Common {
+            checkpoint: Some(checkpoint),
+            checkpoint_interval,
+            files,
+        } => {
+            if inputs(&files).contains(&Path::new(STDIN)) {
+                return Err(LocalError::InvalidArgument {
+                    name: "checkpoint",
+                    value: format!("{} with stdin", checkpoint.display()),
+                });
+            }
+            let (mask, _) = files::find_common_mask_in_files_resumable(
+                &files,
+                cli.length_limit,
+                &checkpoint,
+                checkpoint_interval,
+            )?;
+            format_result(mask, cli.output)
+        }
+        Command::Common { files, .. } => format_result(
+            find_common_mask(&inputs(&files), cli.length_limit)?,
+            cli.output,
+        ),
+        Command::Union { files } => format_result(
+            find_union_mask(&inputs(&files), cli.length_limit)?,
+            cli.output,
+        ),
+        Command::Filter { predicate, files } => {
+            for path in inputs(&files) {
+                files::write_mapped_blocks(
+                    open(path)?,
+                    &mut writer,
+                    cli.length_limit,
+                    path,
+                    stdout,
+                    |block, output| predicate.filter_block(block, output),
+                )?;
+            }
+            return Ok(());
+        }
+        Command::Stats { files } => format_stats(
+            &gather_stats(&inputs(&files), cli.length_limit)?,
+            cli.output,
+        ),
+    };
+
+    writer
+        .write_all(text.as_bytes())
+        .and_then(|_| writer.flush())
+        .map_err(files::io_error(stdout))
+}
+
+/// The entry point of the `alphabet-mask` binary.
+pub fn main() -> ExitCode {
+    match execute(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("alphabet-mask: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_arguments() {
+        let cli = Cli::parse_from(["alphabet-mask", "mask", "--format", "chars", "a.txt"]);
+
+        assert_eq!(cli.output, Output::Text);
+        assert!(matches!(
+            cli.command,
+            Command::Mask { format: TextFormat::Chars, files } if files == [PathBuf::from("a.txt")]
+        ));
+    }
+
+    #[test]
+    fn parse_checkpoint() {
+        let cli = Cli::parse_from(["alphabet-mask", "common", "--checkpoint", "scan", "a.txt"]);
+
+        assert!(matches!(
+            cli.command,
+            Command::Common {
+                checkpoint: Some(_),
+                checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL,
+                ..
+            }
+        ));
+        assert!(Cli::try_parse_from([
+            "alphabet-mask",
+            "common",
+            "--checkpoint",
+            "scan",
+            "--checkpoint-interval",
+            "0",
+        ])
+        .is_err());
+        assert!(
+            Cli::try_parse_from(["alphabet-mask", "common", "--checkpoint-interval", "8"]).is_err()
+        );
+    }
+
+    #[test]
+    fn parallel_matches_serial() {
+        let dir = std::env::temp_dir().join(format!(
+            "alphabet_mask_cli_parallel_matches_serial_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lines.txt");
+
+        for seed in 0..20 {
+            let lines = crate::conftest::strings_failing_twice(seed, 200);
+            std::fs::write(&path, lines.join("\n")).unwrap();
+            let inputs = [path.as_path()];
+
+            for length_limit in [1, 40, 400] {
+                let context = format!("seed {seed}, length limit {length_limit}");
+                // Errors are compared by their message, as the error types are
+                // not comparable.
+                assert_eq!(
+                    format!("{:?}", find_common_mask(&inputs, length_limit)),
+                    format!("{:?}", alphabet_mask_core::find_common_mask(lines.iter())),
+                    "{context}"
+                );
+                assert_eq!(
+                    format!("{:?}", find_union_mask(&inputs, length_limit)),
+                    format!("{:?}", alphabet_mask_core::find_union_mask(lines.iter())),
+                    "{context}"
+                );
+                assert_eq!(
+                    format!("{:?}", gather_stats(&inputs, length_limit)),
+                    format!(
+                        "{:?}",
+                        CorpusStats::default().extend(lines.iter().map(String::as_str))
+                    ),
+                    "{context}"
+                );
+            }
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn filter_lines() {
+        let cli = Cli::parse_from([
+            "alphabet-mask",
+            "filter",
+            "--subset-of",
+            "aeht. ",
+            "--contains",
+            "h",
+        ]);
+        let Command::Filter { predicate, .. } = cli.command else {
+            panic!("expected the filter subcommand");
+        };
+
+        let mut output = String::new();
+        predicate
+            .filter_block("he ate.\ntea\nahead\nhat 1\nhat\n", &mut output)
+            .unwrap();
+
+        assert_eq!(output, "he ate.\nhat\n");
+    }
+
+    #[test]
+    fn filter_expressions() {
+        let cli = Cli::parse_from([
+            "alphabet-mask",
+            "filter",
+            "--where",
+            "contains(a,e) and not contains(z) and subset(' aeglrst')",
+        ]);
+        let Command::Filter { predicate, .. } = cli.command else {
+            panic!("expected the filter subcommand");
+        };
+
+        let mut output = String::new();
+        predicate
+            .filter_block(
+                "great
+tree
+zebra
+teal
+gate 1
+",
+                &mut output,
+            )
+            .unwrap();
+
+        assert_eq!(
+            output,
+            "great
+teal
+"
+        );
+        assert!(Cli::try_parse_from(["alphabet-mask", "filter", "--where", "has(a)"]).is_err());
+    }
+
+    #[test]
+    fn filter_pangrams() {
+        let predicate = Predicate {
+            pangram: true,
+            ..Default::default()
+        };
+
+        let mut output = String::new();
+        predicate
+            .filter_block(
+                "the quick brown fox jumps over the lazy dog.\nthe lazy dog.\n",
+                &mut output,
+            )
+            .unwrap();
+
+        assert_eq!(output, "the quick brown fox jumps over the lazy dog.\n");
+    }
+
+    #[test]
+    fn stats_as_json() {
+        let stats = CorpusStats::default()
+            .extend(["ab", "b\""].into_iter())
+            .unwrap();
+
+        assert_eq!(
+            format_stats(&stats, Output::Json),
+            concat!(
+                r#"{"lines": 2, "bytes": 4, "common": {"mask": 4, "chars": "b"}, "#,
+                r#""union": {"mask": 2147483654, "chars": "ab\""}, "#,
+                r#""coverage": {"a": 1, "b": 2, "\"": 1}}"#,
+                "\n"
+            )
+        );
+    }
+}
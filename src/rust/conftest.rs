@@ -1,25 +0,0 @@
-//! Static test data for unit tests.
-//!
-
-pub const COLLECTION_OF_50_CHARS_STRINGS: [&str; 20] = [
-    "A red pen drew her new dress with elegance.",
-    "He led them west, where trees swayed gently.",
-    "Complex tasks vex experts, yet they excel.",
-    "Velvet drapes framed a hexagonal window.",
-    "Twelve birds flew swiftly over the meadow.",
-    "Beneath the green ferns, a secret dwelled.",
-    "She felt blessed, renewed with energy.",
-    "Hexagonal patterns emerged in the twilight.",
-    "A melody weaved through the evening air.",
-    "Emerald eyes met hers, sparking interest.",
-    "We trekked through the dense, eerie forest.",
-    "In the evening, he brewed herbal tea.",
-    "The chef perfected the recipe with zest.",
-    "A gentle breeze swept through the valley.",
-    "Beneath the tree, a deer rested peacefully.",
-    "Evening descended, revealing celestial beauty.",
-    "Nebulous clouds obscured the moon's glow.",
-    "Serene landscapes emerged in the dusk.",
-    "Elegant swans gracefully swam in the pond.",
-    "Hexagons echoed in her geometric designs.",
-];
@@ -0,0 +1,147 @@
+//! The `CountedAlphabet` Python class, which keeps the alphabets of a collection
+//! of strings as they are added and removed.
+//!
+// pyo3 0.20 expands `#[new]` into trait impls inside a function body.
+#![allow(non_local_definitions)]
+
+use std::sync::{Mutex, MutexGuard};
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::call_stats::Counters;
+use crate::lanes;
+use crate::models::{self, LocalError, Mask};
+use crate::settings::Settings;
+use crate::text::Text;
+use alphabet_mask_core::stats::CountedAlphabet;
+use alphabet_mask_core::{mask_string, mask_to_chars};
+
+/// Returns the masks of `texts`, computed in parallel, if within the input
+/// limits.
+fn masks(texts: &[Text<'_>], py: Python<'_>) -> PyResult<Vec<Mask>> {
+    let strings = texts
+        .iter()
+        .map(Text::checked)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(py.allow_threads(|| {
+        Settings::get().limits.check(strings.iter().copied())?;
+        let masks = lanes::install(strings.iter().copied(), || {
+            strings
+                .par_iter()
+                .map(|string| mask_string(string))
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+        Counters::add_strings(&strings);
+        Ok::<_, LocalError>(masks)
+    })?)
+}
+
+/// The alphabets of a collection of strings which can be removed as well as
+/// added, such as the records of the last 24 hours of a log.
+///
+/// Rather than only the common and combined alphabets, the number of strings
+/// containing each character is kept, so that both can be recomputed once
+/// strings are removed. Each call to :meth:`add` or :meth:`remove` either
+/// applies to every one of its strings or, on an error, to none of them.
+#[pyclass(name = "CountedAlphabet", module = "alphabet_mask", frozen)]
+pub(crate) struct PyCountedAlphabet {
+    inner: Mutex<CountedAlphabet>,
+}
+
+impl PyCountedAlphabet {
+    /// The common and combined masks of the strings so far.
+    pub(crate) fn masks(&self) -> (Mask, Mask) {
+        let inner = self.inner();
+        (inner.common(), inner.union())
+    }
+
+    fn inner(&self) -> MutexGuard<'_, CountedAlphabet> {
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[pymethods]
+impl PyCountedAlphabet {
+    #[new]
+    #[pyo3(signature = (strings = Vec::new()))]
+    fn new(strings: Vec<Text<'_>>, py: Python<'_>) -> PyResult<Self> {
+        let mut inner = CountedAlphabet::default();
+        masks(&strings, py)?
+            .into_iter()
+            .for_each(|mask| inner.add_mask(mask));
+        Ok(Self {
+            inner: Mutex::new(inner),
+        })
+    }
+
+    /// Adds every one of `strings`.
+    ///
+    /// Raises a `ValueError` if any contains a character outside the alphabet.
+    fn add(&self, strings: Vec<Text<'_>>, py: Python<'_>) -> PyResult<()> {
+        let masks = masks(&strings, py)?;
+        let mut inner = self.inner();
+        masks.into_iter().for_each(|mask| inner.add_mask(mask));
+        Ok(())
+    }
+
+    /// Removes every one of `strings`, each of which must have been added.
+    ///
+    /// Raises a `ValueError` if any contains a character outside the alphabet,
+    /// or if they cannot all have been added, such as a string with a character
+    /// in no string added.
+    fn remove(&self, strings: Vec<Text<'_>>, py: Python<'_>) -> PyResult<()> {
+        let masks = masks(&strings, py)?;
+        let mut inner = self.inner();
+        let mut removed = *inner;
+        if let Some(index) = masks.iter().position(|&mask| !removed.remove_mask(mask)) {
+            return Err(LocalError::InvalidArgument {
+                name: "strings",
+                value: format!("string {index}, which was not added"),
+            }
+            .into());
+        }
+        *inner = removed;
+        Ok(())
+    }
+
+    /// The characters contained in every string; without strings, every
+    /// character of the alphabet, as for :func:`common_alphabets`.
+    #[getter]
+    fn common(&self) -> String {
+        mask_to_chars(self.inner().common())
+    }
+
+    /// The characters contained in any string.
+    #[getter]
+    fn union(&self) -> String {
+        mask_to_chars(self.inner().union())
+    }
+
+    /// Returns the number of strings containing each character of :attr:`union`,
+    /// as a `dict`.
+    fn counts(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let counts = self.inner().counts;
+        let dict = PyDict::new(py);
+        for bit in (0..Mask::WIDTH).filter(|&bit| counts[usize::from(bit)] > 0) {
+            dict.set_item(models::bit_char(bit), counts[usize::from(bit)])?;
+        }
+        Ok(dict.into())
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner().strings as usize
+    }
+
+    fn __repr__(&self) -> String {
+        let inner = self.inner();
+        format!(
+            "CountedAlphabet({} strings, common={:?}, union={:?})",
+            inner.strings,
+            mask_to_chars(inner.common()),
+            mask_to_chars(inner.union())
+        )
+    }
+}
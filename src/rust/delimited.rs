@@ -0,0 +1,170 @@
+//! Masking columns of CSV files, every column in a single pass.
+//!
+//! The first record of a file is its header, naming the columns; each field of
+//! a column below it is treated as one string. Quoted fields may hold the
+//! delimiter and line endings.
+//!
+use std::path::Path;
+
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::files::Scanned;
+use crate::models::{LocalError, Mask};
+use alphabet_mask_core::mask_string;
+
+/// The number of records masked in parallel at a time.
+pub(crate) const BATCH_LENGTH: usize = 4096;
+
+/// The common and combined masks of the fields of one column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ColumnMasks {
+    pub(crate) common: Mask,
+    pub(crate) union: Mask,
+}
+
+impl ColumnMasks {
+    /// The masks of no fields at all.
+    const NONE: Self = Self {
+        common: Mask::ALL,
+        union: Mask::EMPTY,
+    };
+
+    fn add(self, mask: Mask) -> Self {
+        Self {
+            common: self.common & mask,
+            union: self.union | mask,
+        }
+    }
+}
+
+/// Returns a function converting a [`csv::Error`] reading `path`.
+fn csv_error(path: &Path) -> impl Fn(csv::Error) -> LocalError + '_ {
+    move |err| {
+        let context = format!("{path:?}: {err}");
+        match err.into_kind() {
+            csv::ErrorKind::Io(source) => LocalError::Io {
+                path: path.to_path_buf(),
+                source,
+            },
+            _ => LocalError::Deserialisation {
+                kind: "CSV record",
+                context,
+            },
+        }
+    }
+}
+
+/// The masks of each of `columns` of the CSV file at `path`, read once.
+///
+/// Records are masked in parallel, [`BATCH_LENGTH`] at a time; the scanned
+/// bytes are those of the fields of `columns` only.
+pub(crate) fn find_column_masks(
+    path: &Path,
+    columns: &[String],
+    delimiter: u8,
+) -> Result<(Vec<ColumnMasks>, Scanned), LocalError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_path(path)
+        .map_err(csv_error(path))?;
+    let headers = reader.headers().map_err(csv_error(path))?;
+    let indices = columns
+        .iter()
+        .map(|column| {
+            headers
+                .iter()
+                .position(|header| header == column)
+                .ok_or_else(|| LocalError::InvalidArgument {
+                    name: "columns",
+                    value: column.clone(),
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut masks = vec![ColumnMasks::NONE; columns.len()];
+    let mut scanned = Scanned::default();
+    let mut records = reader.into_records();
+    loop {
+        let batch = records
+            .by_ref()
+            .take(BATCH_LENGTH)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(csv_error(path))?;
+        if batch.is_empty() {
+            break;
+        }
+
+        let batch_masks: Vec<Result<Vec<Mask>, LocalError>> = batch
+            .par_iter()
+            .map(|record| {
+                indices
+                    .iter()
+                    .map(|&index| mask_string(&record[index]))
+                    .collect()
+            })
+            .collect();
+        // Fail on the first invalid field in file order, not the first found.
+        for record_masks in batch_masks {
+            for (column, mask) in masks.iter_mut().zip(record_masks?) {
+                *column = column.add(mask);
+            }
+        }
+        scanned = scanned
+            + Scanned {
+                lines: batch.len(),
+                bytes: batch
+                    .iter()
+                    .map(|record| {
+                        indices
+                            .iter()
+                            .map(|&index| record[index].len())
+                            .sum::<usize>()
+                    })
+                    .sum(),
+            };
+    }
+    Ok((masks, scanned))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alphabet_mask_core::mask_to_chars;
+
+    #[test]
+    fn masks_of_each_column() {
+        let dir = std::env::temp_dir().join("alphabet_mask_delimited");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("table.csv");
+        std::fs::write(
+            &path,
+            "id,name,notes\n1,the cat,\"hello, world\"\n2,the hat,hi\n",
+        )
+        .unwrap();
+
+        let columns = ["notes".to_owned(), "name".to_owned()];
+        let (masks, scanned) = find_column_masks(&path, &columns, b',').unwrap();
+        assert_eq!(mask_to_chars(masks[0].common), "h");
+        assert_eq!(mask_to_chars(masks[0].union), " dehilorw,");
+        assert_eq!(mask_to_chars(masks[1].common), " aeht");
+        assert_eq!(
+            scanned,
+            Scanned {
+                lines: 2,
+                bytes: 28
+            }
+        );
+
+        let missing = find_column_masks(&path, &["age".to_owned()], b',');
+        assert!(matches!(
+            missing,
+            Err(LocalError::InvalidArgument {
+                name: "columns",
+                ..
+            })
+        ));
+        // The digits of the `id` column are outside the alphabet.
+        assert!(find_column_masks(&path, &["id".to_owned()], b',').is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
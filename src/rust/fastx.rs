@@ -0,0 +1,237 @@
+//! Streaming FASTA and FASTQ records from files.
+//!
+//! The format is detected per record from its first character: `>` starts a
+//! FASTA record, whose sequence may span several lines, and `@` a FASTQ record
+//! of exactly four lines. The identifier of a record is the first word of its
+//! header.
+//!
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines};
+use std::path::{Path, PathBuf};
+
+use rayon::iter::{IntoParallelIterator, ParallelExtend, ParallelIterator};
+
+use crate::files::io_error;
+use crate::models::LocalError;
+use alphabet_mask_core::bio::InvalidBases;
+
+/// The number of records validated in parallel at a time.
+pub(crate) const BATCH_LENGTH: usize = 4096;
+
+/// One record of a FASTA or FASTQ file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Record {
+    pub(crate) id: String,
+    pub(crate) sequence: String,
+}
+
+/// An iterator over the records read from a [`BufRead`].
+pub(crate) struct Records<R: BufRead> {
+    lines: Lines<R>,
+    path: PathBuf,
+    /// The header of the next FASTA record, read at the end of the previous one.
+    header: Option<String>,
+}
+
+impl<R: BufRead> Records<R> {
+    /// Create a new record reader; `path` is only used in error messages.
+    pub(crate) fn new(reader: R, path: &Path) -> Self {
+        Self {
+            lines: reader.lines(),
+            path: path.to_path_buf(),
+            header: None,
+        }
+    }
+
+    /// The next line, without its line ending.
+    fn line(&mut self) -> Option<Result<String, LocalError>> {
+        self.lines.next().map(|line| {
+            line.map(|mut line| {
+                line.truncate(line.trim_end_matches('\r').len());
+                line
+            })
+            .map_err(io_error(&self.path))
+        })
+    }
+
+    /// An error for a record that does not follow either format.
+    fn error(&self, context: String) -> LocalError {
+        LocalError::Deserialisation {
+            kind: "FASTA/FASTQ record",
+            context: format!("{}: {context}", self.path.display()),
+        }
+    }
+
+    /// Read the rest of a FASTA record, up to the next header.
+    fn fasta(&mut self, id: String) -> Result<Record, LocalError> {
+        let mut sequence = String::new();
+        while let Some(line) = self.line() {
+            let line = line?;
+            if line.starts_with('>') {
+                self.header = Some(line);
+                break;
+            }
+            sequence.push_str(line.trim());
+        }
+
+        Ok(Record { id, sequence })
+    }
+
+    /// Read the rest of a FASTQ record.
+    fn fastq(&mut self, id: String) -> Result<Record, LocalError> {
+        let mut rest = [String::new(), String::new(), String::new()];
+        for line in rest.iter_mut() {
+            *line = self
+                .line()
+                .ok_or_else(|| self.error(format!("record {id:?} is truncated")))??;
+        }
+        let [sequence, separator, quality] = rest;
+
+        if !separator.starts_with('+') {
+            return Err(self.error(format!("record {id:?} has no `+` separator")));
+        }
+        if quality.len() != sequence.len() {
+            return Err(self.error(format!(
+                "record {id:?} has {} bases but {} quality scores",
+                sequence.len(),
+                quality.len()
+            )));
+        }
+        Ok(Record { id, sequence })
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = Result<Record, LocalError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let header = match self.header.take() {
+            Some(header) => header,
+            None => loop {
+                match self.line()? {
+                    Ok(line) if line.trim().is_empty() => continue,
+                    Ok(line) => break line,
+                    Err(error) => return Some(Err(error)),
+                }
+            },
+        };
+
+        let id = header
+            .get(1..)
+            .and_then(|rest| rest.split_whitespace().next())
+            .unwrap_or_default()
+            .to_owned();
+        Some(match header.chars().next() {
+            Some('>') => self.fasta(id),
+            Some('@') => self.fastq(id),
+            _ => Err(self.error(format!("unexpected line {header:?}"))),
+        })
+    }
+}
+
+/// The records of a file with invalid bases, with the number of records and
+/// bytes of sequence read.
+#[derive(Debug, Default)]
+pub(crate) struct InvalidRecords {
+    /// The identifier and invalid bases of each record with any.
+    pub(crate) invalid: Vec<(String, InvalidBases)>,
+    pub(crate) records: usize,
+    pub(crate) bytes: usize,
+}
+
+/// Returns the identifier and invalid bases of every record of the file at
+/// `path` with any, validating batches of records in parallel.
+pub(crate) fn invalid_bases_in_file(path: &Path) -> Result<InvalidRecords, LocalError> {
+    let file = File::open(path).map_err(io_error(path))?;
+    let mut records = Records::new(BufReader::new(file), path);
+
+    let mut scan = InvalidRecords::default();
+    loop {
+        let batch = records
+            .by_ref()
+            .take(BATCH_LENGTH)
+            .collect::<Result<Vec<_>, _>>()?;
+        if batch.is_empty() {
+            return Ok(scan);
+        }
+        scan.records += batch.len();
+        scan.bytes += batch
+            .iter()
+            .map(|record| record.sequence.len())
+            .sum::<usize>();
+
+        scan.invalid.par_extend(
+            batch
+                .into_par_iter()
+                .map(|record| (record.id, InvalidBases::of(&record.sequence)))
+                .filter(|(_, bases)| !bases.is_empty()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn records(text: &str) -> Result<Vec<Record>, LocalError> {
+        Records::new(text.as_bytes(), Path::new("<test>")).collect()
+    }
+
+    fn record(id: &str, sequence: &str) -> Record {
+        Record {
+            id: id.to_owned(),
+            sequence: sequence.to_owned(),
+        }
+    }
+
+    #[test]
+    fn fasta() {
+        assert_eq!(
+            records(">one first\nGATT\r\nACA\n\n>two\n>three\nNNN\n").unwrap(),
+            [
+                record("one", "GATTACA"),
+                record("two", ""),
+                record("three", "NNN")
+            ]
+        );
+        assert_eq!(records("").unwrap(), []);
+    }
+
+    #[test]
+    fn fastq() {
+        assert_eq!(
+            records("@one\nGATTACA\n+\nIIIIIII\n@two x\nACGU\n+two\nIIII\n").unwrap(),
+            [record("one", "GATTACA"), record("two", "ACGU")]
+        );
+        for malformed in [
+            "@one\nGATTACA\n+\n",
+            "@one\nACGT\n-\nIIII\n",
+            "@one\nACGT\n+\nII\n",
+        ] {
+            assert!(matches!(
+                records(malformed),
+                Err(LocalError::Deserialisation { .. })
+            ));
+        }
+        assert!(records("GATTACA\n").is_err());
+    }
+
+    #[test]
+    fn invalid_bases() {
+        let path = std::env::temp_dir().join("alphabet_mask_invalid_bases.fa");
+        std::fs::write(&path, ">ok\nACGT\n>bad\nACGU\nXA\n").unwrap();
+        let invalid = invalid_bases_in_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let InvalidRecords {
+            invalid,
+            records,
+            bytes,
+        } = invalid.unwrap();
+        assert_eq!((records, bytes), (2, 10));
+        assert_eq!(invalid.len(), 1);
+        assert_eq!(invalid[0].0, "bad");
+        assert_eq!(invalid[0].1.characters, ['U', 'X']);
+        assert_eq!(invalid[0].1.positions, [3, 4]);
+    }
+}
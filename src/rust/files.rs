@@ -0,0 +1,903 @@
+//! Reading newline-delimited records from files, and masking them in blocks.
+//!
+//! Every line of a file is treated as one string, exactly as if the lines were
+//! passed to `common_alphabets` as a list.
+//!
+use std::fs::File;
+#[cfg(feature = "python")]
+use std::io::BufWriter;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "python")]
+use std::sync::Mutex;
+
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelRefIterator, ParallelBridge, ParallelIterator,
+};
+
+use crate::checkpoint::Checkpoint;
+use crate::models::{LocalError, Mask, MaskFormat};
+#[cfg(feature = "python")]
+use alphabet_mask_core::expression::Expression;
+use alphabet_mask_core::{find_common_mask, mask_string, mask_to_chars, reduce_in_order};
+
+/// An iterator over blocks of whole lines read from a [`BufRead`].
+///
+/// Each block is at least `length_limit` bytes long, unless the reader is exhausted
+/// before that; a block therefore only exceeds the limit by its last line.
+pub(crate) struct LineBlocks<R>
+where
+    R: BufRead,
+{
+    reader: R,
+    length_limit: usize,
+    finished: bool,
+}
+
+impl<R> LineBlocks<R>
+where
+    R: BufRead,
+{
+    /// Create a new block reader with the given length limit.
+    pub(crate) fn new(reader: R, length_limit: usize) -> Self {
+        Self {
+            reader,
+            length_limit,
+            finished: false,
+        }
+    }
+}
+
+impl<R> Iterator for LineBlocks<R>
+where
+    R: BufRead,
+{
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<io::Result<String>> {
+        if self.finished {
+            return None;
+        }
+
+        let mut block = String::new();
+        while block.len() < self.length_limit {
+            match self.reader.read_line(&mut block) {
+                Ok(0) => {
+                    self.finished = true;
+                    break;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        if block.is_empty() {
+            None
+        } else {
+            Some(Ok(block))
+        }
+    }
+}
+
+/// The number of lines and bytes read by a scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct Scanned {
+    pub(crate) lines: usize,
+    pub(crate) bytes: usize,
+}
+
+impl Scanned {
+    /// The lines and bytes of a block of whole lines.
+    pub(crate) fn of_block(block: &str) -> Self {
+        Self {
+            lines: block.lines().count(),
+            bytes: block.len(),
+        }
+    }
+}
+
+impl std::ops::Add for Scanned {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            lines: self.lines + other.lines,
+            bytes: self.bytes + other.bytes,
+        }
+    }
+}
+
+/// Wrap an [`io::Error`] encountered on `path` in a [`LocalError`].
+pub(crate) fn io_error(path: &Path) -> impl Fn(io::Error) -> LocalError + '_ {
+    move |source| LocalError::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+/// Map every block of lines in `reader` in parallel, then reduce the results.
+///
+/// As with [`reduce_in_order`], the error of the first block to fail is returned,
+/// as if the blocks were mapped in order. `path` is only used in error messages.
+pub(crate) fn reduce_blocks_in_reader<R, T>(
+    reader: R,
+    length_limit: usize,
+    path: &Path,
+    identity: T,
+    map: impl Fn(&str) -> Result<T, LocalError> + Sync + Send,
+    reduce: impl Fn(T, T) -> T + Sync + Send,
+) -> Result<T, LocalError>
+where
+    R: BufRead + Send,
+    T: Clone + Send + Sync,
+{
+    reduce_in_order(
+        LineBlocks::new(reader, length_limit)
+            .enumerate()
+            .par_bridge(),
+        identity,
+        |block| {
+            let block = block.map_err(io_error(path))?;
+            span!(TRACE, "map_block", bytes = block.len());
+            map(&block)
+        },
+        reduce,
+    )
+}
+
+/// Returns the common mask of every line in `reader`, masking blocks of lines in
+/// parallel, with the lines and bytes read.
+pub(crate) fn find_common_mask_in_reader<R>(
+    reader: R,
+    length_limit: usize,
+    path: &Path,
+) -> Result<(Mask, Scanned), LocalError>
+where
+    R: BufRead + Send,
+{
+    reduce_blocks_in_reader(
+        reader,
+        length_limit,
+        path,
+        (Mask::ALL, Scanned::default()),
+        |block| Ok((find_common_mask(block.lines())?, Scanned::of_block(block))),
+        |(a, a_scanned), (b, b_scanned)| (a & b, a_scanned + b_scanned),
+    )
+}
+
+/// Returns the common mask of every line in every one of the given files, with
+/// the lines and bytes read.
+#[cfg(feature = "python")]
+pub(crate) fn find_common_mask_in_files(
+    paths: &[PathBuf],
+    length_limit: usize,
+) -> Result<(Mask, Scanned), LocalError> {
+    paths
+        .iter()
+        .try_fold((Mask::ALL, Scanned::default()), |(acc, scanned), path| {
+            let file = File::open(path).map_err(io_error(path))?;
+
+            find_common_mask_in_reader(BufReader::new(file), length_limit, path)
+                .map(|(mask, file_scanned)| (acc & mask, scanned + file_scanned))
+        })
+}
+
+/// The common and combined masks of the lines of one file.
+#[cfg(feature = "python")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FileMasks {
+    pub(crate) common: Mask,
+    pub(crate) union: Mask,
+    pub(crate) scanned: Scanned,
+}
+
+#[cfg(feature = "python")]
+impl FileMasks {
+    /// The masks of no lines at all.
+    const NONE: Self = Self {
+        common: Mask::ALL,
+        union: Mask::EMPTY,
+        scanned: Scanned { lines: 0, bytes: 0 },
+    };
+
+    /// The masks of the lines of `block`.
+    fn of_block(block: &str) -> Result<Self, LocalError> {
+        block.lines().try_fold(
+            Self {
+                scanned: Scanned::of_block(block),
+                ..Self::NONE
+            },
+            |masks, line| {
+                let mask = mask_string(line)?;
+                Ok(Self {
+                    common: masks.common & mask,
+                    union: masks.union | mask,
+                    ..masks
+                })
+            },
+        )
+    }
+
+    /// The masks of the lines of both `self` and `other`.
+    fn merge(self, other: Self) -> Self {
+        Self {
+            common: self.common & other.common,
+            union: self.union | other.union,
+            scanned: self.scanned + other.scanned,
+        }
+    }
+}
+
+/// Returns the common and combined masks of every line in each of the given
+/// files; the files are read in parallel, as well as the blocks of each.
+#[cfg(feature = "python")]
+pub(crate) fn find_masks_of_files(
+    paths: &[PathBuf],
+    length_limit: usize,
+) -> Result<Vec<FileMasks>, LocalError> {
+    paths
+        .par_iter()
+        .map(|path| {
+            let file = File::open(path).map_err(io_error(path))?;
+
+            reduce_blocks_in_reader(
+                BufReader::new(file),
+                length_limit,
+                path,
+                FileMasks::NONE,
+                FileMasks::of_block,
+                FileMasks::merge,
+            )
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Returns the common mask of every line in every one of the given files, saving
+/// a [`Checkpoint`] to `checkpoint_path` every `checkpoint_interval` bytes.
+///
+/// If a checkpoint already exists at `checkpoint_path`, the scan resumes from it,
+/// unless it was saved for a different list of files; the checkpoint is removed
+/// once the scan completes. Only the lines and bytes read by this call are
+/// returned with the mask.
+pub(crate) fn find_common_mask_in_files_resumable(
+    paths: &[PathBuf],
+    length_limit: usize,
+    checkpoint_path: &Path,
+    checkpoint_interval: u64,
+) -> Result<(Mask, Scanned), LocalError> {
+    let mut checkpoint = match Checkpoint::load(checkpoint_path)? {
+        Some(checkpoint) if checkpoint.paths != Checkpoint::fingerprint(paths) => {
+            return Err(LocalError::InvalidCheckpoint {
+                path: checkpoint_path.to_path_buf(),
+                context: "saved for a different list of files".to_owned(),
+            })
+        }
+        Some(checkpoint) => checkpoint,
+        None => Checkpoint::new(paths),
+    };
+    let mut scanned = Scanned::default();
+
+    if checkpoint.file_index > paths.len() {
+        return Err(LocalError::InvalidCheckpoint {
+            path: checkpoint_path.to_path_buf(),
+            context: format!(
+                "file index {} is out of range for {} files",
+                checkpoint.file_index,
+                paths.len()
+            ),
+        });
+    }
+
+    while let Some(path) = paths.get(checkpoint.file_index) {
+        let mut file = File::open(path).map_err(io_error(path))?;
+        file.seek(SeekFrom::Start(checkpoint.byte_offset))
+            .map_err(io_error(path))?;
+        let mut blocks = LineBlocks::new(BufReader::new(file), length_limit).peekable();
+
+        while blocks.peek().is_some() {
+            // Read one checkpoint interval worth of blocks, and at least one, then
+            // mask them in parallel.
+            let mut segment = Vec::new();
+            let mut segment_length = 0;
+            for block in blocks.by_ref() {
+                let block = block.map_err(io_error(path))?;
+                segment_length += block.len() as u64;
+                segment.push(block);
+                if segment_length >= checkpoint_interval {
+                    break;
+                }
+            }
+
+            checkpoint.mask &= reduce_in_order(
+                segment.par_iter().enumerate(),
+                Mask::ALL,
+                |block| find_common_mask(block.lines()),
+                |a, b| a & b,
+            )?;
+            scanned = segment
+                .iter()
+                .fold(scanned, |scanned, block| scanned + Scanned::of_block(block));
+            checkpoint.byte_offset += segment_length;
+            checkpoint.save(checkpoint_path)?;
+        }
+
+        checkpoint.file_index += 1;
+        checkpoint.byte_offset = 0;
+        checkpoint.save(checkpoint_path)?;
+    }
+
+    std::fs::remove_file(checkpoint_path).map_err(io_error(checkpoint_path))?;
+
+    Ok((checkpoint.mask, scanned))
+}
+
+/// Format a mask as one line of text in the given format, without the newline.
+pub(crate) fn format_mask(mask: Mask, format: MaskFormat) -> String {
+    match format {
+        MaskFormat::Int => mask.bits().to_string(),
+        MaskFormat::Chars => mask_to_chars(mask),
+        MaskFormat::Json => format!(
+            r#"{{"mask": {}, "chars": "{}"}}"#,
+            mask.bits(),
+            mask_to_chars(mask).replace('"', r#"\""#)
+        ),
+    }
+}
+
+/// Mask every line of `block`, and append the masks to `output` one per line.
+fn write_block_masks(
+    block: &str,
+    format: MaskFormat,
+    output: &mut String,
+) -> Result<(), LocalError> {
+    for line in block.lines() {
+        let mask = mask_string(line)?;
+
+        output.push_str(&format_mask(mask, format));
+        output.push('\n');
+    }
+
+    Ok(())
+}
+
+/// Mask every line read from `reader`, and write the masks to `writer` one per line
+/// in the given format, returning the number of lines written.
+///
+/// `path_in` and `path_out` are only used in error messages.
+pub(crate) fn write_masks<R, W>(
+    reader: R,
+    writer: W,
+    format: MaskFormat,
+    length_limit: usize,
+    path_in: &Path,
+    path_out: &Path,
+) -> Result<usize, LocalError>
+where
+    R: BufRead,
+    W: Write,
+{
+    write_mapped_blocks(
+        reader,
+        writer,
+        length_limit,
+        path_in,
+        path_out,
+        |block, output| write_block_masks(block, format, output),
+    )
+}
+
+/// Map every block of lines read from `reader` to some text, and write the text
+/// to `writer`, returning the number of lines written.
+///
+/// Blocks of lines are mapped in parallel, in batches of one block per rayon thread
+/// so that the output stays in order while memory use stays bounded. `path_in` and
+/// `path_out` are only used in error messages.
+pub(crate) fn write_mapped_blocks<R, W>(
+    reader: R,
+    mut writer: W,
+    length_limit: usize,
+    path_in: &Path,
+    path_out: &Path,
+    map: impl Fn(&str, &mut String) -> Result<(), LocalError> + Sync + Send,
+) -> Result<usize, LocalError>
+where
+    R: BufRead,
+    W: Write,
+{
+    let batch_size = rayon::current_num_threads();
+    let mut blocks = LineBlocks::new(reader, length_limit).peekable();
+    let mut count = 0;
+
+    while blocks.peek().is_some() {
+        let batch = blocks
+            .by_ref()
+            .take(batch_size)
+            .collect::<io::Result<Vec<_>>>()
+            .map_err(io_error(path_in))?;
+
+        // Collect every result before the first error, which rayon would not
+        // pick in order.
+        let outputs = batch
+            .par_iter()
+            .map(|block| {
+                let mut output = String::with_capacity(block.len());
+                map(block, &mut output).map(|_| output)
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for output in outputs {
+            count += output.lines().count();
+            writer
+                .write_all(output.as_bytes())
+                .map_err(io_error(path_out))?;
+        }
+    }
+
+    writer.flush().map_err(io_error(path_out))?;
+
+    Ok(count)
+}
+
+/// Mask every line of the file at `path_in`, and write the masks to `path_out`
+/// one per line in the given format, returning the number of lines written.
+#[cfg(feature = "python")]
+pub(crate) fn write_masks_of_file(
+    path_in: &Path,
+    path_out: &Path,
+    format: MaskFormat,
+    length_limit: usize,
+) -> Result<usize, LocalError> {
+    let reader = BufReader::new(File::open(path_in).map_err(io_error(path_in))?);
+    let writer = BufWriter::new(File::create(path_out).map_err(io_error(path_out))?);
+
+    write_masks(reader, writer, format, length_limit, path_in, path_out)
+}
+
+/// Write every line of the file at `path_in` whose mask matches `expression` to
+/// `path_out`, in order, returning the number of lines written with the lines
+/// and bytes read.
+///
+/// Lines with a character outside the alphabet never match.
+#[cfg(feature = "python")]
+pub(crate) fn filter_file(
+    path_in: &Path,
+    path_out: &Path,
+    expression: &Expression,
+    length_limit: usize,
+) -> Result<(usize, Scanned), LocalError> {
+    let reader = BufReader::new(File::open(path_in).map_err(io_error(path_in))?);
+    let writer = BufWriter::new(File::create(path_out).map_err(io_error(path_out))?);
+    let scanned = Mutex::new(Scanned::default());
+
+    let kept = write_mapped_blocks(
+        reader,
+        writer,
+        length_limit,
+        path_in,
+        path_out,
+        |block, output| {
+            for line in block.lines() {
+                if mask_string(line).is_ok_and(|mask| expression.matches(mask)) {
+                    output.push_str(line);
+                    output.push('\n');
+                }
+            }
+            let mut scanned = scanned.lock().unwrap_or_else(|err| err.into_inner());
+            *scanned = *scanned + Scanned::of_block(block);
+            Ok(())
+        },
+    )?;
+
+    Ok((
+        kept,
+        scanned.into_inner().unwrap_or_else(|err| err.into_inner()),
+    ))
+}
+
+/// Async variants of the file APIs, which overlap disk reads with masking.
+///
+/// Reading happens on a tokio runtime, while each block that has been read is
+/// handed to the rayon pool for masking; this pays off when the files live on
+/// high-latency storage such as NFS or a mounted object store.
+#[cfg(feature = "async-io")]
+pub(crate) mod async_io {
+    use std::collections::VecDeque;
+    use std::future::Future;
+    use std::path::PathBuf;
+    use std::sync::OnceLock;
+
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::runtime::Runtime;
+    use tokio::sync::oneshot;
+
+    use super::{io_error, Scanned};
+    use crate::models::LocalError;
+    use alphabet_mask_core::find_common_mask;
+    use alphabet_mask_core::models::Mask;
+
+    /// The number of blocks per file allowed to wait for rayon at any one time,
+    /// per rayon thread; this bounds memory use when reading outpaces masking.
+    const BLOCKS_IN_FLIGHT_PER_THREAD: usize = 2;
+
+    /// Run a future to completion on the shared runtime.
+    pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+        static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+        RUNTIME
+            .get_or_init(|| {
+                tokio::runtime::Builder::new_multi_thread()
+                    .enable_all()
+                    .build()
+                    .expect("Failed to build the tokio runtime.")
+            })
+            .block_on(future)
+    }
+
+    /// Returns the common mask of every line in the given file, with the lines and
+    /// bytes read.
+    pub(crate) async fn find_common_mask_in_file(
+        path: PathBuf,
+        length_limit: usize,
+    ) -> Result<(Mask, Scanned), LocalError> {
+        let file = tokio::fs::File::open(&path)
+            .await
+            .map_err(io_error(&path))?;
+        let mut reader = BufReader::new(file);
+
+        let max_in_flight = rayon::current_num_threads() * BLOCKS_IN_FLIGHT_PER_THREAD;
+        let mut pending = VecDeque::new();
+        let mut mask = Mask::ALL;
+        let mut scanned = Scanned::default();
+
+        loop {
+            let mut block = String::new();
+            while block.len() < length_limit {
+                let read = reader
+                    .read_line(&mut block)
+                    .await
+                    .map_err(io_error(&path))?;
+                if read == 0 {
+                    break;
+                }
+            }
+
+            if block.is_empty() {
+                break;
+            }
+            scanned = scanned + Scanned::of_block(&block);
+
+            if pending.len() >= max_in_flight {
+                // This is safe because we just checked the length.
+                let receiver: oneshot::Receiver<_> = pending.pop_front().unwrap();
+                mask &= receiver.await.expect("rayon dropped a masking task.")?;
+            }
+
+            let (sender, receiver) = oneshot::channel();
+            rayon::spawn(move || {
+                // The receiver is only dropped if an earlier block failed.
+                let _ = sender.send(find_common_mask(block.lines()));
+            });
+            pending.push_back(receiver);
+        }
+
+        for receiver in pending {
+            mask &= receiver.await.expect("rayon dropped a masking task.")?;
+        }
+
+        Ok((mask, scanned))
+    }
+
+    /// Returns the common mask of every line in every one of the given files,
+    /// reading all the files concurrently, with the lines and bytes read.
+    ///
+    /// The results are awaited in the order of the files, so that the error of
+    /// the first file to fail is returned, as if they were read in order.
+    pub(crate) async fn find_common_mask_in_files(
+        paths: Vec<PathBuf>,
+        length_limit: usize,
+    ) -> Result<(Mask, Scanned), LocalError> {
+        let tasks: Vec<_> = paths
+            .into_iter()
+            .map(|path| tokio::spawn(find_common_mask_in_file(path, length_limit)))
+            .collect();
+
+        let (mut mask, mut scanned) = (Mask::ALL, Scanned::default());
+        let mut tasks = tasks.into_iter();
+        while let Some(task) = tasks.next() {
+            match task.await.expect("A file reading task panicked.") {
+                Ok((file_mask, file_scanned)) => {
+                    mask &= file_mask;
+                    scanned = scanned + file_scanned;
+                }
+                Err(err) => {
+                    // Stop reading the remaining files.
+                    tasks.for_each(|task| task.abort());
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok((mask, scanned))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::conftest;
+    use std::io::Cursor;
+
+    /// Create a directory in the temp directory unique to this process.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("alphabet_mask_{name}_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn line_blocks() {
+        let text = conftest::COLLECTION_OF_50_CHARS_STRINGS.join("\n");
+        let blocks: Vec<_> = LineBlocks::new(Cursor::new(text.as_bytes()), 100)
+            .collect::<io::Result<_>>()
+            .unwrap();
+
+        // Every block except the last one is at least 100 bytes long.
+        for block in &blocks[..blocks.len() - 1] {
+            assert!(block.len() >= 100);
+        }
+
+        // Blocks only ever split on line boundaries.
+        let lines: Vec<_> = blocks.iter().flat_map(|block| block.lines()).collect();
+        assert_eq!(lines, conftest::COLLECTION_OF_50_CHARS_STRINGS.to_vec());
+    }
+
+    #[test]
+    fn line_blocks_empty() {
+        let blocks: Vec<_> = LineBlocks::new(Cursor::new(b""), 100).collect();
+
+        assert!(blocks.is_empty());
+    }
+
+    #[test]
+    fn common_mask_in_reader() {
+        let text = conftest::COLLECTION_OF_50_CHARS_STRINGS.join("\n");
+        let (mask, scanned) =
+            find_common_mask_in_reader(Cursor::new(text.as_bytes()), 100, Path::new("")).unwrap();
+
+        assert_eq!(&alphabet_mask_core::mask_to_chars(mask), " ert.");
+        assert_eq!(
+            scanned,
+            Scanned {
+                lines: conftest::COLLECTION_OF_50_CHARS_STRINGS.len(),
+                bytes: text.len(),
+            }
+        );
+    }
+
+    #[cfg(feature = "python")]
+    #[test]
+    fn filter_lines_of_file() {
+        let dir = temp_dir("filter_file");
+        let (path_in, path_out) = (dir.join("in.txt"), dir.join("out.txt"));
+        std::fs::write(&path_in, "the cat\nerror 404\n\nthe hat\ntea\n").unwrap();
+
+        let expression = "subset('the ca')".parse().unwrap();
+        let (kept, scanned) = filter_file(&path_in, &path_out, &expression, 8).unwrap();
+        let output = std::fs::read_to_string(&path_out).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(output, "the cat\n\nthe hat\ntea\n");
+        assert_eq!(kept, 4);
+        assert_eq!(
+            scanned,
+            Scanned {
+                lines: 5,
+                bytes: 31
+            }
+        );
+    }
+
+    #[cfg(feature = "python")]
+    #[test]
+    fn masks_of_files() {
+        let dir = temp_dir("masks_of_files");
+        let paths: Vec<_> = [
+            ("a.txt", "the cat\nthe hat\n"),
+            ("b.txt", "that"),
+            ("c.txt", ""),
+        ]
+        .into_iter()
+        .map(|(name, text)| {
+            let path = dir.join(name);
+            std::fs::write(&path, text).unwrap();
+            path
+        })
+        .collect();
+
+        let masks = find_masks_of_files(&paths, 4).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let chars = |mask| alphabet_mask_core::mask_to_chars(mask);
+        assert_eq!(chars(masks[0].common), " aeht");
+        assert_eq!(chars(masks[0].union), " aceht");
+        assert_eq!(chars(masks[1].union), "aht");
+        assert_eq!(masks[1].scanned, Scanned { lines: 1, bytes: 4 });
+        assert_eq!(masks[2], FileMasks::NONE);
+    }
+
+    #[test]
+    fn common_mask_in_files_resumable() {
+        let dir = temp_dir("files_resumable");
+
+        let paths: Vec<_> = conftest::COLLECTION_OF_50_CHARS_STRINGS
+            .chunks(5)
+            .enumerate()
+            .map(|(i, lines)| {
+                let path = dir.join(format!("{i}.txt"));
+                std::fs::write(&path, lines.join("\n")).unwrap();
+                path
+            })
+            .collect();
+        let checkpoint_path = dir.join("checkpoint");
+
+        // Pretend that a previous run stopped after the first 100 bytes of the
+        // second file, with a mask that would not otherwise be possible.
+        Checkpoint {
+            mask: alphabet_mask_core::mask_string("bert.").unwrap(),
+            file_index: 1,
+            byte_offset: 100,
+            paths: Checkpoint::fingerprint(&paths),
+        }
+        .save(&checkpoint_path)
+        .unwrap();
+
+        let (mask, resumed) =
+            find_common_mask_in_files_resumable(&paths, 100, &checkpoint_path, 50).unwrap();
+        let (resumed_from_scratch, scanned) =
+            find_common_mask_in_files_resumable(&paths, 100, &checkpoint_path, 50).unwrap();
+        let skipped = std::fs::metadata(&paths[0]).unwrap().len() as usize + 100;
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(&alphabet_mask_core::mask_to_chars(mask), "ert.");
+        assert_eq!(
+            &alphabet_mask_core::mask_to_chars(resumed_from_scratch),
+            " ert."
+        );
+        // Only what is read after the checkpoint counts.
+        assert_eq!(resumed.bytes + skipped, scanned.bytes);
+        assert_eq!(
+            scanned.lines,
+            conftest::COLLECTION_OF_50_CHARS_STRINGS.len()
+        );
+    }
+
+    #[test]
+    fn common_mask_in_files_resumable_checks_paths() {
+        let dir = temp_dir("files_resumable_paths");
+        let paths: Vec<_> = ["a.txt", "b.txt"]
+            .into_iter()
+            .map(|name| {
+                let path = dir.join(name);
+                std::fs::write(&path, "the cat\nthe hat\n").unwrap();
+                path
+            })
+            .collect();
+        let checkpoint_path = dir.join("checkpoint");
+        Checkpoint::new(&paths).save(&checkpoint_path).unwrap();
+
+        let reordered = [paths[1].clone(), paths[0].clone()];
+        let mismatched = find_common_mask_in_files_resumable(&reordered, 4, &checkpoint_path, 8);
+        // Even without a minimum interval, every segment reads a block.
+        let (mask, scanned) =
+            find_common_mask_in_files_resumable(&paths, 4, &checkpoint_path, 0).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(
+            mismatched,
+            Err(LocalError::InvalidCheckpoint { .. })
+        ));
+        assert_eq!(&alphabet_mask_core::mask_to_chars(mask), " aeht");
+        assert_eq!(
+            scanned,
+            Scanned {
+                lines: 4,
+                bytes: 32
+            }
+        );
+    }
+
+    #[cfg(feature = "python")]
+    #[test]
+    fn masks_of_file() {
+        let dir = temp_dir("masks_of_file");
+        let (path_in, path_out) = (dir.join("in.txt"), dir.join("out.txt"));
+        std::fs::write(
+            &path_in,
+            conftest::COLLECTION_OF_50_CHARS_STRINGS.join("\n"),
+        )
+        .unwrap();
+
+        let count = write_masks_of_file(&path_in, &path_out, MaskFormat::Chars, 100).unwrap();
+        let output = std::fs::read_to_string(&path_out).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(count, conftest::COLLECTION_OF_50_CHARS_STRINGS.len());
+        for (line, string) in output
+            .lines()
+            .zip(conftest::COLLECTION_OF_50_CHARS_STRINGS.iter())
+        {
+            assert_eq!(line, mask_to_chars(mask_string(string).unwrap()));
+        }
+    }
+
+    #[test]
+    fn parallel_matches_serial() {
+        let dir = temp_dir("files_parallel_matches_serial");
+        let (path, checkpoint_path) = (dir.join("lines.txt"), dir.join("checkpoint"));
+
+        for seed in 0..20 {
+            let lines = conftest::strings_failing_twice(seed, 200);
+            std::fs::write(&path, lines.join("\n")).unwrap();
+            // Errors are compared by their message, as the error types are not
+            // comparable.
+            let serial = format!("{:?}", find_common_mask(lines.iter()));
+            assert!(serial.contains("'1'"), "{serial}");
+
+            for length_limit in [1, 40, 400] {
+                let reader =
+                    find_common_mask_in_reader(Cursor::new(lines.join("\n")), length_limit, &path)
+                        .map(|(mask, _)| mask);
+                let resumable = find_common_mask_in_files_resumable(
+                    std::slice::from_ref(&path),
+                    length_limit,
+                    &checkpoint_path,
+                    400,
+                )
+                .map(|(mask, _)| mask);
+                let _ = std::fs::remove_file(&checkpoint_path);
+
+                let context = format!("seed {seed}, length limit {length_limit}");
+                assert_eq!(format!("{reader:?}"), serial, "{context}");
+                assert_eq!(format!("{resumable:?}"), serial, "{context}");
+                #[cfg(feature = "python")]
+                assert_eq!(
+                    format!(
+                        "{:?}",
+                        find_masks_of_files(std::slice::from_ref(&path), length_limit)
+                            .map(|masks| masks[0].common)
+                    ),
+                    serial,
+                    "{context}"
+                );
+            }
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn format_mask_json() {
+        let mask = mask_string("\"Hi.\"").unwrap();
+
+        assert_eq!(
+            format_mask(mask, MaskFormat::Json),
+            format!(r#"{{"mask": {}, "chars": "hi.\""}}"#, mask.bits())
+        );
+    }
+
+    #[test]
+    fn common_mask_in_reader_invalid() {
+        let mask = find_common_mask_in_reader(Cursor::new(b"hello\nw0rld\n"), 1, Path::new(""));
+
+        assert!(matches!(
+            mask,
+            Err(LocalError::InvalidCharacter { character: '0' })
+        ));
+    }
+}
@@ -0,0 +1,431 @@
+//! The `FileStream` Python class, which masks the lines of a file on a
+//! background thread, optionally following it as records are appended.
+//!
+// pyo3 0.20 expands `#[new]` into trait impls inside a function body.
+#![allow(non_local_definitions)]
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::files::io_error;
+use crate::models::{self, LocalError, Mask};
+use alphabet_mask_core::sample::{self, Reservoir, DEFAULT_SAMPLE_SIZE};
+use alphabet_mask_core::stats::{self, CorpusStats};
+use alphabet_mask_core::{mask_string, mask_to_chars};
+
+/// The default time in seconds to wait for more records at the end of a file.
+pub(crate) const DEFAULT_POLL_INTERVAL: f64 = 0.1;
+
+/// The number of lines masked between updates of the shared statistics.
+const LINES_PER_UPDATE: usize = 1024;
+
+/// The progress of a stream, shared with its thread.
+#[derive(Debug, Default)]
+struct Progress {
+    stats: CorpusStats,
+    /// The masks of a sample of the lines, with ``approx=True``.
+    sample: Option<Reservoir<Mask>>,
+    /// The error which stopped the stream, until it is raised.
+    error: Option<LocalError>,
+    done: bool,
+}
+
+/// Returns `line` without its line ending.
+fn trim_line_ending(line: &str) -> &str {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+/// Read every line of `path` into `progress`, then, if `tail`, wait
+/// `poll_interval` at a time for more until `stop` is set.
+///
+/// While following, a line is only masked once its line ending is written; if
+/// the file is truncated, such as by log rotation, it is read again from the
+/// start.
+///
+/// With a `sample`, only the lines it takes are masked, and only counted
+/// otherwise.
+fn follow(
+    path: &Path,
+    tail: bool,
+    poll_interval: Duration,
+    stop: &AtomicBool,
+    progress: &Mutex<Progress>,
+    mut sample: Option<Reservoir<Mask>>,
+) -> Result<(), LocalError> {
+    let mut reader = BufReader::new(File::open(path).map_err(io_error(path))?);
+    let (mut position, mut line) = (0_u64, String::new());
+    let mut pending = CorpusStats::default();
+    let mut pending_lines = 0;
+
+    let update = |pending: &mut CorpusStats, sample: &Option<Reservoir<Mask>>| {
+        let mut progress = progress.lock().unwrap_or_else(|err| err.into_inner());
+        progress.stats = progress.stats.merge(std::mem::take(pending));
+        progress.sample.clone_from(sample);
+    };
+
+    while !stop.load(Ordering::Relaxed) {
+        let read = reader.read_line(&mut line).map_err(io_error(path))?;
+        position += read as u64;
+
+        if line.ends_with('\n') || (read == 0 && !tail && !line.is_empty()) {
+            let record = trim_line_ending(&line);
+            let added = match &mut sample {
+                Some(sample) => {
+                    pending.strings += 1;
+                    pending.bytes += record.len() as u64;
+                    sample.offer().map_or(Ok(()), |slot| {
+                        mask_string(record).map(|mask| sample.store(slot, mask))
+                    })
+                }
+                None => pending.add(record),
+            };
+            if let Err(err) = added {
+                update(&mut pending, &sample);
+                return Err(err);
+            }
+            line.clear();
+            pending_lines += 1;
+            if pending_lines < LINES_PER_UPDATE {
+                continue;
+            }
+        } else if read > 0 {
+            continue;
+        }
+
+        update(&mut pending, &sample);
+        pending_lines = 0;
+        if read > 0 {
+            continue;
+        }
+        if !tail {
+            break;
+        }
+
+        std::thread::sleep(poll_interval);
+        let length = std::fs::metadata(path).map_err(io_error(path))?.len();
+        if length < position {
+            reader.seek(SeekFrom::Start(0)).map_err(io_error(path))?;
+            position = 0;
+            line.clear();
+        }
+    }
+    update(&mut pending, &sample);
+    Ok(())
+}
+
+/// Masks every line of a file on a background thread, keeping the statistics
+/// of the lines so far.
+///
+/// With ``tail=True``, the stream keeps following the file once it reaches the
+/// end, checking for appended records every `poll_interval` seconds until it is
+/// closed, as ``tail -f`` does; a line is only masked once its line ending is
+/// written. A truncated file, such as after log rotation, is read again from the
+/// start.
+///
+/// With ``approx=True``, only a uniform random sample of `sample_size` of the
+/// lines so far is kept and masked, drawn from `seed`, so that an endless stream
+/// is profiled in constant memory and time per line; the snapshots then estimate
+/// the proportion of the lines using each character, within bounds.
+///
+/// :meth:`snapshot` returns the statistics of the lines masked so far; the
+/// stream can also be used as a context manager, which closes it on exit.
+#[pyclass(name = "FileStream", module = "alphabet_mask", frozen)]
+pub(crate) struct FileStream {
+    path: PathBuf,
+    tail: bool,
+    approx: bool,
+    stop: Arc<AtomicBool>,
+    progress: Arc<Mutex<Progress>>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl FileStream {
+    /// Wait for the thread to stop, with the GIL released.
+    fn join(&self, py: Python<'_>) {
+        let thread = self
+            .thread
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+        if let Some(thread) = thread {
+            py.allow_threads(|| thread.join())
+                .expect("The file stream thread panicked.");
+        }
+    }
+}
+
+#[pymethods]
+impl FileStream {
+    #[new]
+    #[pyo3(signature = (
+        path,
+        tail = false,
+        poll_interval = DEFAULT_POLL_INTERVAL,
+        approx = false,
+        sample_size = DEFAULT_SAMPLE_SIZE,
+        seed = 0,
+    ))]
+    fn new(
+        path: PathBuf,
+        tail: bool,
+        poll_interval: f64,
+        approx: bool,
+        sample_size: usize,
+        seed: u64,
+    ) -> PyResult<Self> {
+        let poll_interval = Duration::try_from_secs_f64(poll_interval).map_err(|_| {
+            LocalError::InvalidArgument {
+                name: "poll_interval",
+                value: poll_interval.to_string(),
+            }
+        })?;
+        if sample_size == 0 {
+            return Err(LocalError::InvalidArgument {
+                name: "sample_size",
+                value: sample_size.to_string(),
+            }
+            .into());
+        }
+        let sample = approx.then(|| Reservoir::new(sample_size, seed));
+        // Fail now rather than on the first snapshot if the file cannot be read.
+        File::open(&path).map_err(io_error(&path))?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let progress = Arc::new(Mutex::new(Progress::default()));
+        let thread = {
+            let (path, stop, progress) = (path.clone(), stop.clone(), progress.clone());
+            std::thread::Builder::new()
+                .name("alphabet-mask-file-stream".to_owned())
+                .spawn(move || {
+                    let result = follow(&path, tail, poll_interval, &stop, &progress, sample);
+                    let mut progress = progress.lock().unwrap_or_else(|err| err.into_inner());
+                    progress.error = result.err();
+                    progress.done = true;
+                })
+                .map_err(|err| LocalError::Unknown {
+                    context: format!("could not start a file stream: {err}"),
+                })?
+        };
+
+        Ok(Self {
+            path,
+            tail,
+            approx,
+            stop,
+            progress,
+            thread: Mutex::new(Some(thread)),
+        })
+    }
+
+    /// Whether the stream has stopped, at the end of the file without
+    /// ``tail=True``, on an error, or once closed.
+    #[getter]
+    fn done(&self) -> bool {
+        self.progress
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .done
+    }
+
+    /// Returns the statistics of the lines masked so far, as a `dict` of
+    /// ``strings``, the number of lines; ``bytes``, their total length without
+    /// line endings; and ``common`` and ``union``, the common and combined
+    /// alphabets of the lines.
+    ///
+    /// With ``approx=True``, ``common`` and ``union`` are those of the sample,
+    /// of ``sample_size`` lines; and ``coverage`` maps each character to the
+    /// proportion of the sample containing it, with the bounds of its 95%
+    /// confidence interval for all the lines, as ``(ratio, low, high)``.
+    ///
+    /// Raises the error which stopped the stream, such as a line with a character
+    /// outside the alphabet, once; later snapshots return the statistics up to
+    /// that line. With ``approx=True``, only the sampled lines are checked.
+    fn snapshot(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let (stats, sample) = {
+            let mut progress = self.progress.lock().unwrap_or_else(|err| err.into_inner());
+            if let Some(err) = progress.error.take() {
+                return Err(err.into());
+            }
+            (progress.stats, progress.sample.clone())
+        };
+
+        let dict = PyDict::new(py);
+        dict.set_item("strings", stats.strings)?;
+        dict.set_item("bytes", stats.bytes)?;
+        let Some(sample) = sample.filter(|_| self.approx) else {
+            dict.set_item("common", mask_to_chars(stats.common))?;
+            dict.set_item("union", mask_to_chars(stats.union))?;
+            return Ok(dict.into());
+        };
+
+        let masks = sample.items();
+        let common = masks.iter().fold(Mask::ALL, |acc, &mask| acc & mask);
+        let union = masks.iter().fold(Mask::EMPTY, |acc, &mask| acc | mask);
+        dict.set_item("common", mask_to_chars(common))?;
+        dict.set_item("union", mask_to_chars(union))?;
+        dict.set_item("sample_size", masks.len())?;
+
+        let coverage = PyDict::new(py);
+        let estimates = sample::coverage_estimates(masks, sample::Z_95);
+        for bit in 0..Mask::WIDTH {
+            let estimate = estimates[usize::from(bit)];
+            coverage.set_item(
+                models::bit_char(bit),
+                (estimate.ratio, estimate.low, estimate.high),
+            )?;
+        }
+        dict.set_item("coverage", coverage)?;
+        Ok(dict.into())
+    }
+
+    /// Returns the `k` letters in the most lines so far, with the number of lines
+    /// containing each, as a `list` of ``(letter, count)`` from the most to the
+    /// least; ties are alphabetical, and letters in no line are left out.
+    ///
+    /// The ranking is updated as the lines are masked, so it can be polled while
+    /// the stream runs; unlike :meth:`snapshot`, it does not raise the error
+    /// which stopped the stream. With ``approx=True``, the counts are estimated
+    /// from the sample.
+    fn top_letters(&self, k: usize) -> Vec<(char, u64)> {
+        let progress = self.progress.lock().unwrap_or_else(|err| err.into_inner());
+        let Some(sample) = progress.sample.as_ref().filter(|_| self.approx) else {
+            return progress.stats.top_letters(k);
+        };
+
+        let masks = sample.items();
+        let mut coverage = [0_u64; 32];
+        for mask in masks {
+            for (bit, count) in coverage.iter_mut().enumerate() {
+                *count += u64::from(mask.bits() >> bit & 1);
+            }
+        }
+        let scale = progress.stats.strings as f64 / masks.len().max(1) as f64;
+        stats::top_letters(
+            &coverage.map(|count| (count as f64 * scale).round() as u64),
+            k,
+        )
+    }
+
+    /// Blocks until the end of the file is reached, then returns
+    /// :meth:`snapshot`; with ``tail=True`` this only returns once the stream is
+    /// closed from another thread.
+    fn wait(&self, py: Python<'_>) -> PyResult<PyObject> {
+        self.join(py);
+        self.snapshot(py)
+    }
+
+    /// Stops reading the file; closing again does nothing.
+    fn close(&self, py: Python<'_>) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.join(py);
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __exit__(&self, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny, py: Python<'_>) {
+        self.close(py);
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "FileStream(path={:?}, tail={}, approx={})",
+            self.path,
+            if self.tail { "True" } else { "False" },
+            if self.approx { "True" } else { "False" }
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn follow_appended_lines() {
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join("alphabet_mask_follow");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("log.txt");
+        std::fs::write(&path, "the cat\r\nthe ha").unwrap();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let progress = Arc::new(Mutex::new(Progress::default()));
+        let thread = {
+            let (path, stop, progress) = (path.clone(), stop.clone(), progress.clone());
+            std::thread::spawn(move || {
+                follow(
+                    &path,
+                    true,
+                    Duration::from_millis(1),
+                    &stop,
+                    &progress,
+                    None,
+                )
+            })
+        };
+
+        let stats = |strings| loop {
+            let stats = progress.lock().unwrap().stats;
+            if stats.strings >= strings {
+                return stats;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        };
+        // The partial line is not masked until it is complete.
+        assert_eq!(mask_to_chars(stats(1).union), " aceht");
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(stats(1).strings, 1);
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(b"t\nlog\n").unwrap();
+        let stats = stats(3);
+        assert_eq!(mask_to_chars(stats.common), "");
+        assert_eq!(mask_to_chars(stats.union), " aceghlot");
+
+        stop.store(true, Ordering::Relaxed);
+        thread.join().unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn follow_with_a_sample() {
+        let dir = std::env::temp_dir().join("alphabet_mask_follow_sample");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("log.txt");
+        let lines: Vec<&str> = ["the cat", "a dog", "the hat"].repeat(100);
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let progress = Mutex::new(Progress::default());
+        let sample = Some(Reservoir::new(10, 0));
+        follow(
+            &path,
+            false,
+            Duration::ZERO,
+            &AtomicBool::new(false),
+            &progress,
+            sample,
+        )
+        .unwrap();
+
+        let progress = progress.into_inner().unwrap();
+        assert_eq!(progress.stats.strings, 300);
+        let sample = progress.sample.unwrap();
+        assert_eq!((sample.seen(), sample.items().len()), (300, 10));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
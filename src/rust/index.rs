@@ -0,0 +1,94 @@
+//! The `WordIndex` Python class, for repeated mask queries against one word list.
+//!
+// pyo3 0.20 expands `#[new]` into trait impls inside a function body.
+#![allow(non_local_definitions)]
+
+use std::path::PathBuf;
+
+use pyo3::prelude::*;
+
+use crate::models::Mask;
+use alphabet_mask_core::codec::Encode;
+use alphabet_mask_core::index::WordIndex;
+use alphabet_mask_core::mask_string;
+
+/// A query of a `WordIndex`, either as a mask or as the characters of a string.
+#[derive(FromPyObject)]
+enum Query<'a> {
+    Mask(u32),
+    Chars(&'a str),
+}
+
+impl Query<'_> {
+    /// The mask of this query.
+    fn mask(&self) -> PyResult<Mask> {
+        match self {
+            Self::Mask(mask) => Ok(Mask(*mask)),
+            Self::Chars(chars) => Ok(mask_string(chars)?),
+        }
+    }
+}
+
+/// An index of the masks of a list of words, for answering many queries quickly.
+///
+/// Every word is masked once when the index is built, which fails if any word
+/// contains a character outside the alphabet. Queries are either a mask or a
+/// string of characters, and results keep the order of the original list among
+/// words with the same number of distinct characters.
+#[pyclass(name = "WordIndex", module = "alphabet_mask", frozen)]
+pub(crate) struct PyWordIndex {
+    inner: WordIndex,
+}
+
+#[pymethods]
+impl PyWordIndex {
+    #[new]
+    fn new(words: Vec<String>, py: Python<'_>) -> PyResult<Self> {
+        Ok(Self {
+            inner: py.allow_threads(|| WordIndex::new(words))?,
+        })
+    }
+
+    /// Loads an index saved with `save`, without masking the words again.
+    #[staticmethod]
+    fn load(path: PathBuf, py: Python<'_>) -> PyResult<Self> {
+        Ok(Self {
+            inner: py.allow_threads(|| WordIndex::load_mapped(&path))?,
+        })
+    }
+
+    /// Saves the index to a file, replacing it atomically.
+    fn save(&self, path: PathBuf, py: Python<'_>) -> PyResult<()> {
+        Ok(py.allow_threads(|| self.inner.save(&path))?)
+    }
+
+    /// Returns the words using only the characters of `query`.
+    fn subsets(&self, query: Query<'_>) -> PyResult<Vec<&str>> {
+        Ok(self.inner.subsets_of(query.mask()?).collect())
+    }
+
+    /// Returns the words using every character of `query`.
+    fn supersets(&self, query: Query<'_>) -> PyResult<Vec<&str>> {
+        Ok(self.inner.supersets_of(query.mask()?).collect())
+    }
+
+    /// Returns the words using exactly the characters of `query`.
+    fn equal(&self, query: Query<'_>) -> PyResult<Vec<&str>> {
+        Ok(self.inner.equal_to(query.mask()?).collect())
+    }
+
+    /// Returns the `k` words most similar to `query` by the Jaccard index of their
+    /// masks, as ``(word, similarity)`` pairs, most similar first.
+    #[pyo3(signature = (query, k = 10))]
+    fn similar(&self, query: Query<'_>, k: usize) -> PyResult<Vec<(&str, f64)>> {
+        Ok(self.inner.most_similar(query.mask()?, k))
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("WordIndex({} words)", self.inner.len())
+    }
+}
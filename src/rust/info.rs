@@ -0,0 +1,89 @@
+//! Introspection of how this library was built, and what the CPU supports.
+//!
+use crate::models::bits::LAYOUT_VERSION;
+
+/// The optional features this library was built with.
+const FEATURES: &[(&str, bool)] = &[
+    ("parallel", cfg!(feature = "parallel")),
+    ("simd", cfg!(feature = "simd")),
+    ("async-io", cfg!(feature = "async-io")),
+    ("serde", cfg!(feature = "serde")),
+    ("trace", cfg!(feature = "trace")),
+];
+
+/// The CPU features relevant to masking performance, if detected at runtime.
+fn cpu_features() -> Vec<&'static str> {
+    let mut detected = Vec::new();
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        macro_rules! detect {
+            ($($feature:tt),*) => {
+                $(
+                    if std::arch::is_x86_feature_detected!($feature) {
+                        detected.push($feature);
+                    }
+                )*
+            };
+        }
+        detect!("sse2", "sse4.2", "popcnt", "avx2", "avx512f", "avx512bw");
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    if std::arch::is_aarch64_feature_detected!("neon") {
+        detected.push("neon");
+    }
+
+    detected
+}
+
+/// A description of this build and the machine it runs on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BuildInfo {
+    /// The version of this crate.
+    pub(crate) version: &'static str,
+    /// The optional features enabled at compile time.
+    pub(crate) features: Vec<&'static str>,
+    /// The version of the standard bit layout.
+    pub(crate) layout_version: u8,
+    /// The architecture compiled for.
+    pub(crate) target_arch: &'static str,
+    /// The relevant CPU features detected at runtime.
+    pub(crate) cpu_features: Vec<&'static str>,
+}
+
+impl BuildInfo {
+    /// Describe the running build.
+    pub(crate) fn current() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            features: FEATURES
+                .iter()
+                .filter(|(_, enabled)| *enabled)
+                .map(|(name, _)| *name)
+                .collect(),
+            layout_version: LAYOUT_VERSION,
+            target_arch: std::env::consts::ARCH,
+            cpu_features: cpu_features(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn current() {
+        let info = BuildInfo::current();
+
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(info.layout_version, 1);
+        assert_eq!(
+            info.features.contains(&"parallel"),
+            cfg!(feature = "parallel")
+        );
+        #[cfg(target_arch = "x86_64")]
+        assert!(info.cpu_features.contains(&"sse2"));
+    }
+}
@@ -0,0 +1,75 @@
+//! A reserved lane for small calls, so that they are not starved by large ones.
+//!
+//! Every call from Python releases the GIL, so calls from several Python threads
+//! run concurrently and share the global rayon pool. Rayon workers finish the work
+//! they have queued themselves before taking new jobs from outside the pool, so a
+//! small call made while another thread's large call is running would wait for
+//! most of it to complete. Calls over no more than the chunk limit in total are
+//! instead run on a small pool of their own.
+//!
+//! Calls which mask their strings without parallelism, such as
+//! `common_alphabets()` below the chunk limit, never touch either pool.
+//!
+use std::sync::OnceLock;
+
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use crate::settings::Settings;
+
+/// The number of threads of the small lane.
+pub(crate) const SMALL_LANE_THREADS: usize = 2;
+
+/// The pool of the small lane, or [`None`] if it could not be started, in which
+/// case small calls share the global pool.
+fn small_lane() -> Option<&'static ThreadPool> {
+    static SMALL_LANE: OnceLock<Option<ThreadPool>> = OnceLock::new();
+
+    SMALL_LANE
+        .get_or_init(|| {
+            ThreadPoolBuilder::new()
+                .num_threads(SMALL_LANE_THREADS)
+                .thread_name(|index| format!("alphabet-mask-small-{index}"))
+                .build()
+                .ok()
+        })
+        .as_ref()
+}
+
+/// Run `f`, which processes `strings` in parallel, on the small lane if they are
+/// no longer than the chunk limit in total, or on the global pool otherwise.
+pub(crate) fn install<'s, R: Send>(
+    strings: impl IntoIterator<Item = &'s str>,
+    f: impl FnOnce() -> R + Send,
+) -> R {
+    let limit = Settings::get().chunk_limit;
+    let small = strings
+        .into_iter()
+        .try_fold(0_usize, |acc, string| {
+            acc.checked_add(string.len())
+                .filter(|&bytes| bytes <= limit)
+        })
+        .is_some();
+
+    match small_lane().filter(|_| small) {
+        Some(pool) => pool.install(f),
+        None => f(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn small_calls_use_the_small_lane() {
+        let in_small_lane = || {
+            rayon::current_thread_index().is_some()
+                && rayon::current_num_threads() == SMALL_LANE_THREADS
+        };
+
+        assert!(install(["abc"], in_small_lane));
+
+        let large = vec!["abcdefghij"; Settings::get().chunk_limit / 10 + 1];
+        assert!(!install(large, in_small_lane));
+    }
+}
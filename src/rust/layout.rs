@@ -0,0 +1,164 @@
+//! The layout of the masks of a Python call, chosen by its keyword arguments,
+//! with every mask as a `u128` whatever its width.
+//!
+use crate::call_stats::{find_common_mask_counted, WorkerLoads};
+use alphabet_mask_core::layout::{ALPHANUMERIC, CASE_SENSITIVE, EXTENDED};
+use alphabet_mask_core::models::{LocalError, Mask};
+use alphabet_mask_core::{mask_string, mask_to_chars_ordered, CharOrder};
+
+/// A layout of masks available from Python.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Layout {
+    /// The standard 32-bit layout.
+    #[default]
+    Standard,
+    /// The standard layout with the digits, in 64 bits.
+    Alphanumeric,
+    /// The alphanumeric layout with every other ASCII punctuation mark, in
+    /// 128 bits.
+    Extended,
+    /// The standard layout with the uppercase letters on bits of their own, in
+    /// 64 bits.
+    CaseSensitive,
+}
+
+impl Layout {
+    /// The layout selected by the keyword arguments of a call, or an error if
+    /// they select more than one.
+    pub(crate) fn of(
+        digits: bool,
+        extended: bool,
+        case_sensitive: bool,
+    ) -> Result<Self, LocalError> {
+        match (digits || extended, case_sensitive) {
+            (true, true) => Err(LocalError::InvalidArgument {
+                name: "case_sensitive",
+                value: "True with digits or extended".to_owned(),
+            }),
+            _ if extended => Ok(Self::Extended),
+            _ if digits => Ok(Self::Alphanumeric),
+            (_, true) => Ok(Self::CaseSensitive),
+            _ => Ok(Self::Standard),
+        }
+    }
+
+    /// The keyword arguments selecting this layout, as written in Python.
+    pub(crate) fn arguments(self) -> &'static str {
+        match self {
+            Self::Standard => "",
+            Self::Alphanumeric => ", digits=True",
+            Self::Extended => ", extended=True",
+            Self::CaseSensitive => ", case_sensitive=True",
+        }
+    }
+
+    /// Whether this layout includes the digits.
+    pub(crate) fn digits(self) -> bool {
+        matches!(self, Self::Alphanumeric | Self::Extended)
+    }
+
+    /// Whether this layout includes every ASCII punctuation mark.
+    pub(crate) fn extended(self) -> bool {
+        self == Self::Extended
+    }
+
+    /// Whether this layout tells letters apart by case.
+    pub(crate) fn case_sensitive(self) -> bool {
+        self == Self::CaseSensitive
+    }
+
+    /// The mask of every bit set by a symbol.
+    pub(crate) fn defined(self) -> u128 {
+        match self {
+            Self::Standard => Mask::ALL.bits().into(),
+            Self::Alphanumeric => u64::from(ALPHANUMERIC.defined()).into(),
+            Self::Extended => EXTENDED.defined().into(),
+            Self::CaseSensitive => u64::from(CASE_SENSITIVE.defined()).into(),
+        }
+    }
+
+    /// Mask a string.
+    pub(crate) fn mask_string(self, string: &str) -> Result<u128, LocalError> {
+        match self {
+            Self::Standard => Ok(mask_string(string)?.bits().into()),
+            Self::Alphanumeric => Ok(u64::from(ALPHANUMERIC.mask_string(string)?).into()),
+            Self::Extended => Ok(EXTENDED.mask_string(string)?.into()),
+            Self::CaseSensitive => Ok(u64::from(CASE_SENSITIVE.mask_string(string)?).into()),
+        }
+    }
+
+    /// Returns the common mask of `strings`, in parallel chunks of
+    /// `length_limit` bytes if they are longer in total; in the standard layout,
+    /// the chunks masked by each thread are added to `loads` if given.
+    pub(crate) fn find_common_mask<S: AsRef<str> + Sync>(
+        self,
+        strings: &[S],
+        length_limit: usize,
+        loads: Option<&WorkerLoads>,
+    ) -> Result<u128, LocalError> {
+        match self {
+            Self::Standard => Ok(find_common_mask_counted(strings, length_limit, loads)?
+                .bits()
+                .into()),
+            Self::Alphanumeric => {
+                Ok(u64::from(ALPHANUMERIC.find_common_mask(strings, length_limit)?).into())
+            }
+            Self::Extended => Ok(EXTENDED.find_common_mask(strings, length_limit)?.into()),
+            Self::CaseSensitive => {
+                Ok(u64::from(CASE_SENSITIVE.find_common_mask(strings, length_limit)?).into())
+            }
+        }
+    }
+
+    /// Returns `bits` as a mask of this layout if it only sets bits defined by
+    /// it, or a descriptive error otherwise.
+    pub(crate) fn validate(self, bits: i128) -> Result<u128, LocalError> {
+        match self {
+            Self::Standard => Ok(alphabet_mask_core::validate_mask(bits)?.bits().into()),
+            Self::Alphanumeric | Self::Extended | Self::CaseSensitive => {
+                let undefined =
+                    u128::try_from(bits).map_or(u128::MAX, |bits| bits & !self.defined());
+                if undefined != 0 {
+                    return Err(LocalError::InvalidMask {
+                        context: format!("{bits:#x} sets bits undefined by the layout"),
+                    });
+                }
+                Ok(bits as u128)
+            }
+        }
+    }
+
+    /// Returns the characters of `bits` in the given order.
+    pub(crate) fn chars(self, bits: u128, order: CharOrder<'_>) -> String {
+        let chars: Vec<char> = match self {
+            Self::Standard => return mask_to_chars_ordered(Mask(bits as u32), order),
+            Self::Alphanumeric => ALPHANUMERIC.chars((bits as u64).into()).collect(),
+            Self::Extended => EXTENDED.chars(bits.into()).collect(),
+            Self::CaseSensitive => CASE_SENSITIVE.chars((bits as u64).into()).collect(),
+        };
+
+        match order {
+            CharOrder::Bit => chars.into_iter().collect(),
+            CharOrder::Alphabetical => {
+                let mut chars = chars;
+                chars.sort_unstable();
+                chars.into_iter().collect()
+            }
+            CharOrder::Custom(custom) => {
+                // The characters of `custom` in the mask, by bit, then the others.
+                let mut seen = 0_u128;
+                let mut ordered = String::new();
+                for character in custom.chars() {
+                    if let Ok(bit) = self.mask_string(&character.to_string()) {
+                        if bits & bit != 0 && seen & bit == 0 {
+                            seen |= bit;
+                            ordered.push_str(&self.chars(bit, CharOrder::Bit));
+                        }
+                    }
+                }
+                ordered.push_str(&self.chars(bits & !seen, CharOrder::Bit));
+                ordered
+            }
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! The `MaskChunks` Python class, an iterator over the masks of a corpus one
+//! chunk at a time, so that memory stays bounded however large the corpus.
+//!
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, TryLockError};
+
+use pyo3::prelude::*;
+use pyo3::types::PyIterator;
+
+use crate::arrow::{StringArray, StringArrayStream};
+use crate::batch;
+use crate::call_stats::Counters;
+use crate::files::{self, LineBlocks};
+use crate::lanes;
+use crate::models::{BatchOutput, LocalError};
+use crate::settings::Settings;
+use crate::text::Text;
+
+/// Where the strings of the chunks come from.
+enum Source {
+    /// Any Python iterable of `str`, taken `chunk_size` at a time.
+    Strings {
+        iterator: Py<PyIterator>,
+        chunk_size: usize,
+    },
+    /// The lines of a file, read in blocks of roughly a length limit.
+    File {
+        blocks: LineBlocks<BufReader<File>>,
+        path: PathBuf,
+    },
+    /// The arrays of an Arrow stream, one per chunk.
+    Arrow { stream: StringArrayStream },
+}
+
+/// The strings of one chunk.
+enum Chunk {
+    Owned(Vec<String>),
+    /// An Arrow array, read straight from its buffers.
+    Arrow(StringArray),
+}
+
+impl Chunk {
+    /// Returns every string of this chunk, with [`None`] in place of nulls.
+    fn values(&self) -> Result<Vec<Option<&str>>, LocalError> {
+        match self {
+            Self::Owned(strings) => {
+                Ok(strings.iter().map(|string| Some(string.as_str())).collect())
+            }
+            Self::Arrow(array) => array.values(),
+        }
+    }
+}
+
+impl Source {
+    /// Returns the next chunk of strings, or [`None`] once there are no more.
+    fn next_chunk(&mut self, py: Python<'_>) -> PyResult<Option<Chunk>> {
+        match self {
+            Self::Strings {
+                iterator,
+                chunk_size,
+            } => {
+                let mut chunk = Vec::new();
+                for string in iterator.as_ref(py).take(*chunk_size) {
+                    chunk.push(string?.extract::<Text<'_>>()?.checked()?.to_owned());
+                }
+                Ok((!chunk.is_empty()).then_some(Chunk::Owned(chunk)))
+            }
+            Self::File { blocks, path } => Ok(py
+                .allow_threads(|| blocks.next())
+                .transpose()
+                .map_err(files::io_error(path))?
+                .map(|block| Chunk::Owned(block.lines().map(str::to_owned).collect()))),
+            Self::Arrow { stream } => {
+                Ok(py.allow_threads(|| stream.next_array())?.map(Chunk::Arrow))
+            }
+        }
+    }
+}
+
+/// An iterator over the masks of a corpus, yielding those of each chunk of its
+/// strings as soon as they are masked, in parallel, rather than one `list` of
+/// them all at the end.
+///
+/// Returned by :func:`alphabet_masks` with a `chunk_size`, and by
+/// :func:`iter_file_masks` and :func:`iter_arrow_masks`. Each chunk of masks is in the container of
+/// `output`, as for :func:`alphabet_masks`; only one chunk of strings is held at
+/// a time. A string outside the alphabet raises a `ValueError` from the chunk
+/// containing it, after which the iterator is exhausted.
+#[pyclass(name = "MaskChunks", module = "alphabet_mask", frozen)]
+pub(crate) struct MaskChunks {
+    source: Mutex<Option<Source>>,
+    output: BatchOutput,
+    /// The number of strings masked so far.
+    strings: Mutex<usize>,
+}
+
+impl MaskChunks {
+    /// An iterator over the masks of every `str` of `iterable`.
+    pub(crate) fn of_strings(
+        iterable: &PyAny,
+        chunk_size: usize,
+        output: BatchOutput,
+    ) -> PyResult<Self> {
+        if chunk_size == 0 {
+            return Err(LocalError::InvalidArgument {
+                name: "chunk_size",
+                value: chunk_size.to_string(),
+            }
+            .into());
+        }
+        Ok(Self::new(
+            Source::Strings {
+                iterator: iterable.iter()?.into(),
+                chunk_size,
+            },
+            output,
+        ))
+    }
+
+    /// An iterator over the masks of every line of the file at `path`.
+    pub(crate) fn of_file(
+        path: PathBuf,
+        length_limit: usize,
+        output: BatchOutput,
+    ) -> Result<Self, LocalError> {
+        Settings::get()
+            .limits
+            .check_files(std::slice::from_ref(&path))?;
+        let file = File::open(&path).map_err(files::io_error(&path))?;
+        Ok(Self::new(
+            Source::File {
+                blocks: LineBlocks::new(BufReader::new(file), length_limit),
+                path,
+            },
+            output,
+        ))
+    }
+
+    /// An iterator over the masks of every array of an Arrow stream.
+    pub(crate) fn of_arrow(stream: StringArrayStream, output: BatchOutput) -> Self {
+        Self::new(Source::Arrow { stream }, output)
+    }
+
+    fn new(source: Source, output: BatchOutput) -> Self {
+        Self {
+            source: Mutex::new(Some(source)),
+            output,
+            strings: Mutex::new(0),
+        }
+    }
+
+    fn strings(&self) -> MutexGuard<'_, usize> {
+        self.strings.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[pymethods]
+impl MaskChunks {
+    /// The number of strings masked so far.
+    #[getter]
+    fn position(&self) -> usize {
+        *self.strings()
+    }
+
+    fn __iter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        // Wait for another thread's chunk without the GIL, which it needs to finish.
+        let mut source = loop {
+            match self.source.try_lock() {
+                Ok(source) => break source,
+                Err(TryLockError::Poisoned(err)) => break err.into_inner(),
+                Err(TryLockError::WouldBlock) => py.allow_threads(std::thread::yield_now),
+            }
+        };
+        let chunk = match source.as_mut().map(|source| source.next_chunk(py)) {
+            Some(Ok(Some(chunk))) => chunk,
+            Some(Ok(None)) | None => {
+                source.take();
+                return Ok(None);
+            }
+            Some(Err(err)) => {
+                source.take();
+                return Err(err);
+            }
+        };
+
+        let masks = py.allow_threads(|| {
+            let strings = chunk.values()?;
+            let present: Vec<&str> = strings.iter().flatten().copied().collect();
+            Settings::get().limits.check(present.iter().copied())?;
+            let masks = lanes::install(present.iter().copied(), || batch::find_masks(&strings))?;
+            Counters::add_strings(&present);
+            Ok::<_, LocalError>(masks)
+        });
+        let masks = match masks {
+            Ok(masks) => masks,
+            Err(err) => {
+                source.take();
+                return Err(err.into());
+            }
+        };
+
+        *self.strings() += masks.len();
+        batch::masks_into_py(py, masks, self.output).map(Some)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("MaskChunks(position={})", self.position())
+    }
+}
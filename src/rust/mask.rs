@@ -0,0 +1,246 @@
+//! The `AlphabetMask` Python class, a mask which composes with the bitwise
+//! operators and displays as its characters.
+//!
+// pyo3 0.20 expands `#[new]` into trait impls inside a function body.
+#![allow(non_local_definitions)]
+
+use pyo3::basic::CompareOp;
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+
+use crate::layout::Layout;
+use crate::models::{LocalError, Mask};
+use alphabet_mask_core::CharOrder;
+
+/// The mask of an alphabet, as returned by the functions given ``as_mask=True``.
+///
+/// Masks combine with ``&`` into their common alphabet, ``|`` into their
+/// combined alphabet and ``^`` into the characters of only one of them, with
+/// another :class:`AlphabetMask`, an `int` mask or a string of characters; ``~``
+/// gives every other character of the alphabet. ``"a" in mask`` tests a single
+/// character, which is never in the mask if outside the alphabet.
+///
+/// With ``digits=True``, the mask is of the standard layout with the digits
+/// ``0`` to ``9`` on bits 32 to 41, and only combines with masks of that layout;
+/// with ``extended=True``, it also has every other ASCII punctuation mark, such as
+/// ``;``, ``?`` and ``(``, on bits 42 to 68. With ``case_sensitive=True``, ``A``
+/// to ``Z`` are on bits 32 to 57, apart from ``a`` to ``z``.
+///
+/// An :class:`AlphabetMask` can be used wherever an `int` mask is accepted, and
+/// is equal to, and hashes as, its `int` value.
+#[pyclass(name = "AlphabetMask", module = "alphabet_mask", frozen)]
+#[derive(Clone)]
+pub(crate) struct PyAlphabetMask {
+    bits: u128,
+    layout: Layout,
+}
+
+impl From<Mask> for PyAlphabetMask {
+    fn from(mask: Mask) -> Self {
+        Self {
+            bits: mask.bits().into(),
+            layout: Layout::Standard,
+        }
+    }
+}
+
+impl PyAlphabetMask {
+    /// The mask of `other` in the layout of this mask: another mask of the same
+    /// layout, a string to mask or an `int` mask.
+    fn operand(&self, other: &PyAny) -> PyResult<u128> {
+        if let Ok(other) = other.downcast::<PyCell<Self>>() {
+            let other = other.get();
+            if other.layout != self.layout {
+                return Err(LocalError::InvalidArgument {
+                    name: "other",
+                    value: format!("a mask of another layout{}", other.layout.arguments()),
+                }
+                .into());
+            }
+            return Ok(other.bits);
+        }
+        match other.extract::<&str>() {
+            Ok(string) => Ok(self.layout.mask_string(string)?),
+            Err(_) => Ok(self.layout.validate(other.extract()?)?),
+        }
+    }
+
+    fn with_bits(&self, bits: u128) -> Self {
+        Self {
+            bits,
+            layout: self.layout,
+        }
+    }
+}
+
+/// Returns `mask` as an :class:`AlphabetMask` if `as_mask`, otherwise as the
+/// result of `otherwise`.
+pub(crate) fn mask_into_py<T: IntoPy<PyObject>>(
+    py: Python<'_>,
+    mask: Mask,
+    as_mask: bool,
+    otherwise: impl FnOnce(Mask) -> T,
+) -> PyObject {
+    if as_mask {
+        PyAlphabetMask::from(mask).into_py(py)
+    } else {
+        otherwise(mask).into_py(py)
+    }
+}
+
+/// Returns the `bits` of a mask of `layout` as an :class:`AlphabetMask` if
+/// `as_mask`, otherwise as the result of `otherwise`.
+pub(crate) fn layout_mask_into_py<T: IntoPy<PyObject>>(
+    py: Python<'_>,
+    layout: Layout,
+    bits: u128,
+    as_mask: bool,
+    otherwise: impl FnOnce(u128) -> T,
+) -> PyObject {
+    if as_mask {
+        PyAlphabetMask { bits, layout }.into_py(py)
+    } else {
+        otherwise(bits).into_py(py)
+    }
+}
+
+#[pymethods]
+impl PyAlphabetMask {
+    /// The mask of `value`, either a mask or a string of characters; without
+    /// one, the empty mask.
+    #[new]
+    #[pyo3(signature = (value = None, digits = false, extended = false, case_sensitive = false))]
+    fn new(
+        value: Option<&PyAny>,
+        digits: bool,
+        extended: bool,
+        case_sensitive: bool,
+    ) -> PyResult<Self> {
+        let empty = Self {
+            bits: 0,
+            layout: Layout::of(digits, extended, case_sensitive)?,
+        };
+        match value {
+            Some(value) => Ok(empty.with_bits(empty.operand(value)?)),
+            None => Ok(empty),
+        }
+    }
+
+    /// Whether this mask includes the digits.
+    #[getter]
+    fn digits(&self) -> bool {
+        self.layout.digits()
+    }
+
+    /// Whether this mask includes every ASCII punctuation mark.
+    #[getter]
+    fn extended(&self) -> bool {
+        self.layout.extended()
+    }
+
+    /// Whether this mask tells letters apart by case.
+    #[getter]
+    fn case_sensitive(&self) -> bool {
+        self.layout.case_sensitive()
+    }
+
+    /// Returns the characters of this mask, in bit order.
+    fn chars(&self) -> String {
+        self.layout.chars(self.bits, CharOrder::Bit)
+    }
+
+    /// Returns this mask as an `int`.
+    fn to_int(&self) -> u128 {
+        self.bits
+    }
+
+    fn __and__(&self, other: &PyAny) -> PyResult<Self> {
+        Ok(self.with_bits(self.bits & self.operand(other)?))
+    }
+
+    fn __rand__(&self, other: &PyAny) -> PyResult<Self> {
+        self.__and__(other)
+    }
+
+    fn __or__(&self, other: &PyAny) -> PyResult<Self> {
+        Ok(self.with_bits(self.bits | self.operand(other)?))
+    }
+
+    fn __ror__(&self, other: &PyAny) -> PyResult<Self> {
+        self.__or__(other)
+    }
+
+    fn __xor__(&self, other: &PyAny) -> PyResult<Self> {
+        Ok(self.with_bits(self.bits ^ self.operand(other)?))
+    }
+
+    fn __rxor__(&self, other: &PyAny) -> PyResult<Self> {
+        self.__xor__(other)
+    }
+
+    fn __invert__(&self) -> Self {
+        self.with_bits(!self.bits & self.layout.defined())
+    }
+
+    /// Returns whether `character`, a single character, is in this mask.
+    fn __contains__(&self, character: &str) -> PyResult<bool> {
+        let mut chars = character.chars();
+        match (chars.next(), chars.next()) {
+            (Some(character), None) => {
+                let bit = self.layout.mask_string(character.encode_utf8(&mut [0; 4]));
+                Ok(bit.is_ok_and(|bit| bit & !self.bits == 0))
+            }
+            _ => Err(LocalError::InvalidArgument {
+                name: "character",
+                value: character.to_owned(),
+            }
+            .into()),
+        }
+    }
+
+    fn __len__(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    fn __bool__(&self) -> bool {
+        self.bits != 0
+    }
+
+    fn __int__(&self) -> u128 {
+        self.bits
+    }
+
+    fn __index__(&self) -> u128 {
+        self.bits
+    }
+
+    fn __hash__(&self, py: Python<'_>) -> PyResult<isize> {
+        self.bits.into_py(py).as_ref(py).hash()
+    }
+
+    fn __richcmp__(&self, other: &PyAny, op: CompareOp, py: Python<'_>) -> PyObject {
+        // An `AlphabetMask` extracts through `__index__`, like an `int`.
+        match (op, other.extract::<u128>().ok()) {
+            (CompareOp::Eq, Some(other)) => (self.bits == other).into_py(py),
+            (CompareOp::Ne, Some(other)) => (self.bits != other).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn __getnewargs__(&self) -> (u128, bool, bool, bool) {
+        (
+            self.bits,
+            self.layout.digits(),
+            self.layout.extended(),
+            self.layout.case_sensitive(),
+        )
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "AlphabetMask({}{})",
+            PyString::new(py, &self.chars()).repr()?,
+            self.layout.arguments()
+        ))
+    }
+}
@@ -0,0 +1,341 @@
+//! The `Masker` Python class, a reusable masker for any alphabet spec.
+//!
+// pyo3 0.20 expands `#[new]` into trait impls inside a function body.
+#![allow(non_local_definitions)]
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use pyo3::prelude::*;
+
+use crate::call_stats::Counters;
+use crate::models::{
+    AlphabetSpec, ControlCharPolicy, InvalidCharPolicy, LocalError, Mask, TYPOGRAPHIC_FALLBACKS,
+};
+use crate::settings::Settings;
+use crate::text::Text;
+use alphabet_mask_core::masker::{Masker, MaskerBuilder};
+use alphabet_mask_core::transform::Transform;
+
+/// Apply the options shared by every constructor of `Masker` to a builder.
+fn configure(
+    builder: MaskerBuilder,
+    case_sensitive: Option<bool>,
+    on_invalid: Option<&str>,
+    on_control: Option<&str>,
+    threads: Option<usize>,
+    fallbacks: Option<&PyAny>,
+    transforms: Option<&PyAny>,
+) -> PyResult<Masker> {
+    let fallbacks: Vec<(char, char)> = match fallbacks {
+        None => Vec::new(),
+        Some(fallbacks) => match fallbacks.extract::<&str>() {
+            Ok("typographic") => TYPOGRAPHIC_FALLBACKS.to_vec(),
+            Ok(name) => {
+                return Err(LocalError::InvalidArgument {
+                    name: "fallbacks",
+                    value: name.to_owned(),
+                }
+                .into())
+            }
+            Err(_) => fallbacks
+                .extract::<HashMap<char, char>>()?
+                .into_iter()
+                .collect(),
+        },
+    };
+    let on_invalid = match on_invalid {
+        Some(on_invalid) => {
+            InvalidCharPolicy::from_str(on_invalid).map_err(|_| LocalError::InvalidArgument {
+                name: "on_invalid",
+                value: on_invalid.to_owned(),
+            })?
+        }
+        None => Settings::get().on_invalid,
+    };
+    let on_control = match on_control {
+        Some(on_control) => {
+            ControlCharPolicy::from_str(on_control).map_err(|_| LocalError::InvalidArgument {
+                name: "on_control",
+                value: on_control.to_owned(),
+            })?
+        }
+        None => Settings::get().on_control,
+    };
+
+    let builder = match case_sensitive {
+        Some(true) => builder.case_sensitive(),
+        Some(false) => builder.case_insensitive(),
+        None => builder,
+    };
+    let builder = match threads {
+        Some(threads) => builder.parallel(threads),
+        None => builder,
+    };
+
+    Ok(builder
+        .fallbacks(fallbacks)
+        .on_invalid(on_invalid)
+        .on_control(on_control)
+        .transforms(extract_transforms(transforms)?)
+        .build()?)
+}
+
+/// Extract a sequence of transforms, each either the name of one or a `dict` of
+/// characters to replace, with ``""`` or ``None`` to remove them.
+fn extract_transforms(transforms: Option<&PyAny>) -> PyResult<Vec<Transform>> {
+    let Some(transforms) = transforms else {
+        return Ok(Vec::new());
+    };
+    let invalid = |value: String| LocalError::InvalidArgument {
+        name: "transforms",
+        value,
+    };
+
+    transforms
+        .iter()?
+        .map(|transform| {
+            let transform = transform?;
+            if let Ok(name) = transform.extract::<&str>() {
+                return Ok(Transform::from_str(name)?);
+            }
+            let pairs = transform
+                .extract::<HashMap<char, Option<String>>>()
+                .map_err(|_| invalid(transform.to_string()))?;
+            let pairs = pairs
+                .into_iter()
+                .map(|(from, to)| {
+                    let mut chars = to.as_deref().unwrap_or_default().chars();
+                    match (chars.next(), chars.next()) {
+                        (to, None) => Ok((from, to)),
+                        _ => Err(invalid(format!("{from:?}: {to:?}"))),
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Transform::map(pairs))
+        })
+        .collect()
+}
+
+/// A reusable masker, compiled once from an alphabet spec.
+///
+/// Lone surrogates, as decoded with ``errors="surrogateescape"``, are invalid
+/// characters: they set the other bit of the spec if it has one, are skipped with
+/// ``on_invalid="skip"``, and raise a `ValueError` with their position otherwise.
+///
+/// Without a spec file, the standard layout is used. `case_sensitive` overrides
+/// the case policy of the spec, `on_invalid` is ``"error"`` or ``"skip"`` for
+/// characters outside the alphabet, defaulting to ``ALPHABET_MASK_ON_INVALID``
+/// or ``"error"``, and `threads` masks collections on a
+/// dedicated pool of that many threads, or one per CPU if ``0``.
+///
+/// `on_control` is ``"error"``, ``"skip"`` or ``"space"`` for control characters
+/// such as NUL, defaulting to ``ALPHABET_MASK_ON_CONTROL`` or ``"error"``; the
+/// error gives the position of the character in its string.
+///
+/// `fallbacks` maps characters outside the alphabet to the symbol whose bit they
+/// set, in addition to those of the spec; ``"typographic"`` maps curly quotes,
+/// dashes, the ellipsis and the no-break space to their ASCII counterparts.
+///
+/// `transforms` is a sequence applied in order to each character before it is
+/// classified, in place of pre-processing the strings in Python: ``"lowercase"``;
+/// ``"strip_punctuation"``, removing punctuation; ``"fold_whitespace"``,
+/// replacing any whitespace with a space; ``"fold_diacritics"``, replacing such
+/// as ``é`` with ``e``; or a `dict` replacing each of its characters with another,
+/// or removing it if ``""`` or ``None``. On ASCII characters, they are composed
+/// into the lookup table of the masker, so they cost nothing.
+#[pyclass(name = "Masker", module = "alphabet_mask", frozen)]
+pub(crate) struct PyMasker {
+    inner: Masker,
+}
+
+impl PyMasker {
+    /// Returns `text` as masked by this masker, with any lone surrogate as an
+    /// invalid character.
+    fn text<'t>(&self, text: &'t Text<'_>) -> Result<&'t str, LocalError> {
+        if self.inner.on_invalid() == InvalidCharPolicy::Skip || self.inner.spec().other.is_some() {
+            Ok(text.lossy())
+        } else {
+            text.checked()
+        }
+    }
+
+    /// Returns every one of `texts` as masked by this masker, if within the input
+    /// limits.
+    fn texts<'t>(&self, texts: &'t [Text<'_>]) -> Result<Vec<&'t str>, LocalError> {
+        let strings = texts
+            .iter()
+            .map(|text| self.text(text))
+            .collect::<Result<Vec<_>, _>>()?;
+        Settings::get().limits.check(strings.iter().copied())?;
+        Ok(strings)
+    }
+}
+
+#[pymethods]
+impl PyMasker {
+    #[new]
+    #[pyo3(signature = (case_sensitive = None, on_invalid = None, threads = None, fallbacks = None, on_control = None, transforms = None))]
+    fn new(
+        case_sensitive: Option<bool>,
+        on_invalid: Option<&str>,
+        threads: Option<usize>,
+        fallbacks: Option<&PyAny>,
+        on_control: Option<&str>,
+        transforms: Option<&PyAny>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            inner: configure(
+                Masker::builder(),
+                case_sensitive,
+                on_invalid,
+                on_control,
+                threads,
+                fallbacks,
+                transforms,
+            )?,
+        })
+    }
+
+    /// Creates a masker from a ``.toml`` or ``.json`` alphabet spec, validated
+    /// before use.
+    #[staticmethod]
+    #[pyo3(signature = (path, case_sensitive = None, on_invalid = None, threads = None, fallbacks = None, on_control = None, transforms = None))]
+    fn from_spec_file(
+        path: PathBuf,
+        case_sensitive: Option<bool>,
+        on_invalid: Option<&str>,
+        threads: Option<usize>,
+        fallbacks: Option<&PyAny>,
+        on_control: Option<&str>,
+        transforms: Option<&PyAny>,
+    ) -> PyResult<Self> {
+        let spec = AlphabetSpec::from_file(path)?;
+
+        Ok(Self {
+            inner: configure(
+                Masker::builder().alphabet(spec),
+                case_sensitive,
+                on_invalid,
+                on_control,
+                threads,
+                fallbacks,
+                transforms,
+            )?,
+        })
+    }
+
+    /// Creates a masker for a layout of the characters of `order`, each on the
+    /// bit of its position, such as ``" etaoinshrdlcumwfgypbvkjxqz"`` for
+    /// frequency-ordered letters after a space.
+    #[staticmethod]
+    #[pyo3(signature = (order, case_sensitive = None, on_invalid = None, threads = None, fallbacks = None, on_control = None, transforms = None))]
+    fn from_order(
+        order: &str,
+        case_sensitive: Option<bool>,
+        on_invalid: Option<&str>,
+        threads: Option<usize>,
+        fallbacks: Option<&PyAny>,
+        on_control: Option<&str>,
+        transforms: Option<&PyAny>,
+    ) -> PyResult<Self> {
+        let spec = AlphabetSpec::from_order(order)?;
+
+        Ok(Self {
+            inner: configure(
+                Masker::builder().alphabet(spec),
+                case_sensitive,
+                on_invalid,
+                on_control,
+                threads,
+                fallbacks,
+                transforms,
+            )?,
+        })
+    }
+
+    /// The number of bits in the masks of this masker.
+    #[getter]
+    fn width(&self) -> u8 {
+        self.inner.spec().width
+    }
+
+    /// The bit set by every character outside the alphabet, if the spec has one,
+    /// in which case `on_invalid` has no effect.
+    #[getter]
+    fn other_bit(&self) -> Option<u8> {
+        self.inner.spec().other
+    }
+
+    /// Returns the bit mask of the given string.
+    fn mask(&self, string: Text<'_>, py: Python<'_>) -> PyResult<u32> {
+        let string = self.text(&string)?;
+        Settings::get().limits.check([string])?;
+        let mask = py.allow_threads(|| self.inner.mask(string))?;
+        Counters::add_strings(&[string]);
+        Ok(mask.bits())
+    }
+
+    /// Returns the characters represented by the given mask.
+    fn mask_to_chars(&self, mask: i128) -> PyResult<String> {
+        Ok(self.inner.mask_to_chars(self.inner.validate_mask(mask)?))
+    }
+
+    /// Returns the mask of the given characters, as :meth:`mask` does; the
+    /// inverse of :meth:`mask_to_chars`.
+    fn chars_to_mask(&self, chars: Text<'_>, py: Python<'_>) -> PyResult<u32> {
+        self.mask(chars, py)
+    }
+
+    /// Returns `mask` of this masker converted to the layout of the masker
+    /// `target`, moving the bit of each symbol to the bit of the same symbol.
+    ///
+    /// Raises a `ValueError` if a bit of `mask` has no symbol, or its symbol has
+    /// no bit in `target`.
+    fn convert_mask(&self, mask: i128, target: &Self) -> PyResult<u32> {
+        let mask = self.inner.validate_mask(mask)?;
+        Ok(self.inner.convert_mask(mask, &target.inner)?.bits())
+    }
+
+    /// Returns the given integer if it only sets bits with a symbol in the spec,
+    /// or raises a `ValueError` otherwise.
+    fn validate_mask(&self, value: i128) -> PyResult<u32> {
+        Ok(self.inner.validate_mask(value)?.bits())
+    }
+
+    /// Returns the given mask with every bit without a symbol cleared.
+    fn normalize_mask(&self, mask: u32) -> u32 {
+        self.inner.normalize_mask(Mask(mask)).bits()
+    }
+
+    /// Returns the bit mask of the common alphabet of the given strings.
+    fn common_mask(&self, strings: Vec<Text<'_>>, py: Python<'_>) -> PyResult<u32> {
+        let strings = self.texts(&strings)?;
+        let mask = py.allow_threads(|| self.inner.common_mask(&strings))?;
+        Counters::add_strings(&strings);
+        Ok(mask.bits())
+    }
+
+    /// Returns the bit mask of the combined alphabet of the given strings.
+    fn union_mask(&self, strings: Vec<Text<'_>>, py: Python<'_>) -> PyResult<u32> {
+        let strings = self.texts(&strings)?;
+        let mask = py.allow_threads(|| self.inner.union_mask(&strings))?;
+        Counters::add_strings(&strings);
+        Ok(mask.bits())
+    }
+
+    /// Returns the common alphabet of the given strings.
+    fn common_alphabets(&self, strings: Vec<Text<'_>>, py: Python<'_>) -> PyResult<String> {
+        let mask = self.common_mask(strings, py)?;
+        Ok(self.inner.mask_to_chars(Mask(mask)))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Masker(width={}, symbols={})",
+            self.inner.spec().width,
+            self.inner.spec().symbols.len()
+        )
+    }
+}
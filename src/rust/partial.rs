@@ -0,0 +1,91 @@
+//! A stable serialisation of partial masks, for tree reductions across workers.
+//!
+//! A partial mask is the common mask of some subset of a corpus. Since partial
+//! masks combine by a bitwise AND, they can be computed on separate workers and
+//! reduced in any order. The serialised form is:
+//!
+//! | bytes | content                           |
+//! |-------|-----------------------------------|
+//! | 0..2  | the magic bytes `AM`              |
+//! | 2     | the format version, currently `1` |
+//! | 3..7  | the mask, as a little-endian u32  |
+//!
+use alphabet_mask_core::codec::Format;
+
+use crate::models::{LocalError, Mask};
+
+/// The header of every serialised partial mask.
+const FORMAT: Format = Format {
+    kind: "partial mask",
+    magic: *b"AM",
+    version: 1,
+};
+
+/// The length of a serialised partial mask in bytes.
+pub(crate) const ENCODED_LENGTH: usize = Format::HEADER_LENGTH + 4;
+
+/// Serialise a partial mask.
+pub(crate) fn encode(mask: Mask) -> [u8; ENCODED_LENGTH] {
+    let mut bytes = [0; ENCODED_LENGTH];
+    bytes[..2].copy_from_slice(&FORMAT.magic);
+    bytes[2] = FORMAT.version;
+    bytes[3..].copy_from_slice(&mask.bits().to_le_bytes());
+    bytes
+}
+
+/// Deserialise a partial mask, checking its magic bytes and version.
+pub(crate) fn decode(bytes: &[u8]) -> Result<Mask, LocalError> {
+    let body: [u8; 4] = FORMAT
+        .read_header(bytes)?
+        .try_into()
+        .map_err(|_| FORMAT.error(format!("unrecognised bytes {bytes:?}")))?;
+
+    Ok(Mask(u32::from_le_bytes(body)))
+}
+
+/// Combine serialised partial masks into one.
+pub(crate) fn combine<'b>(partials: impl Iterator<Item = &'b [u8]>) -> Result<Mask, LocalError> {
+    alphabet_mask_core::intersect_masks(partials.map(decode))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for mask in [Mask::EMPTY, Mask(1), Mask(0b1011), Mask::ALL] {
+            assert_eq!(decode(&encode(mask)).unwrap(), mask);
+        }
+    }
+
+    #[test]
+    fn combine_partials() {
+        let partials = [
+            encode(Mask(0b1110)),
+            encode(Mask(0b0111)),
+            encode(Mask::ALL),
+        ];
+
+        assert_eq!(
+            combine(partials.iter().map(|partial| &partial[..])).unwrap(),
+            Mask(0b0110)
+        );
+    }
+
+    #[test]
+    fn bad_version() {
+        let mut bytes = encode(Mask(1));
+        bytes[2] = FORMAT.version + 1;
+
+        assert!(matches!(
+            decode(&bytes),
+            Err(LocalError::Deserialisation { .. })
+        ));
+    }
+
+    #[test]
+    fn bad_length() {
+        assert!(decode(b"AM\x01").is_err());
+    }
+}
@@ -0,0 +1,217 @@
+//! The `Pipeline` Python class, which chains masking, filtering and aggregation
+//! into a single parallel pass over a corpus.
+//!
+// pyo3 0.20 expands `#[new]` into trait impls inside a function body.
+#![allow(non_local_definitions)]
+
+use std::str::FromStr;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::call_stats::{CallStats, MemoryEstimate};
+use crate::lanes;
+use crate::models::{LocalError, MaskPredicate};
+use crate::python::extract_mask;
+use crate::settings::Settings;
+use crate::text;
+use alphabet_mask_core::expression::Expression;
+use alphabet_mask_core::mask_to_chars;
+use alphabet_mask_core::pipeline::{Aggregate, Aggregated, Pipeline, PipelineResult};
+
+/// A pipeline of masking, filtering and aggregation, built one stage at a time
+/// and run over a corpus in a single fused parallel pass, keeping only the final
+/// aggregate rather than the masks of every string.
+///
+/// Each stage returns a new pipeline, so that a partial one can be reused, such
+/// as ``Pipeline().mask().filter(subset_of=" aeglrst").group_by_mask().count()``,
+/// which is then run with :meth:`run`. The stages must be in this order:
+/// :meth:`mask`; any number of :meth:`filter`, which must all match; optionally
+/// :meth:`group_by_mask`; then one aggregate of :meth:`count`, :meth:`common`,
+/// :meth:`union` or :meth:`positions`.
+#[pyclass(name = "Pipeline", module = "alphabet_mask", frozen)]
+#[derive(Clone)]
+pub(crate) struct PyPipeline {
+    masked: bool,
+    filters: Vec<Expression>,
+    group_by_mask: bool,
+    aggregate: Option<Aggregate>,
+    /// The stages so far, as they would be written in Python.
+    stages: Vec<String>,
+}
+
+impl PyPipeline {
+    /// Returns this pipeline followed by `stage`, if `allowed` at this point.
+    fn then(&self, stage: String, allowed: bool, f: impl FnOnce(&mut Self)) -> PyResult<Self> {
+        let mut pipeline = self.clone();
+        pipeline.stages.push(stage);
+        if !allowed {
+            return Err(LocalError::InvalidArgument {
+                name: "pipeline",
+                value: pipeline.__repr__(),
+            }
+            .into());
+        }
+        f(&mut pipeline);
+        Ok(pipeline)
+    }
+
+    /// Returns this pipeline followed by an aggregate.
+    fn aggregate(&self, aggregate: Aggregate, stage: &str) -> PyResult<Self> {
+        self.then(
+            format!("{stage}()"),
+            self.masked && self.aggregate.is_none(),
+            |pipeline| pipeline.aggregate = Some(aggregate),
+        )
+    }
+}
+
+/// Returns an aggregate as a Python object.
+fn aggregated_into_py(py: Python<'_>, aggregated: Aggregated) -> PyObject {
+    match aggregated {
+        Aggregated::Count(count) => count.into_py(py),
+        Aggregated::Common(mask) | Aggregated::Union(mask) => mask_to_chars(mask).into_py(py),
+        Aggregated::Positions(positions) => positions.into_py(py),
+    }
+}
+
+#[pymethods]
+impl PyPipeline {
+    #[new]
+    fn new() -> Self {
+        Self {
+            masked: false,
+            filters: Vec::new(),
+            group_by_mask: false,
+            aggregate: None,
+            stages: Vec::new(),
+        }
+    }
+
+    /// Masks each string; the first stage of every pipeline.
+    fn mask(&self) -> PyResult<Self> {
+        self.then("mask()".to_owned(), !self.masked, |pipeline| {
+            pipeline.masked = true;
+        })
+    }
+
+    /// Keeps only the strings whose masks match every given condition:
+    /// `expression`, a predicate expression such as
+    /// ``"contains(a,e) and not contains(z)"``; or using only characters of
+    /// `subset_of`, every character of `superset_of`, any character of
+    /// `contains`, or no character of `avoids`, each either a mask or a string
+    /// of characters.
+    #[pyo3(signature = (expression = None, subset_of = None, superset_of = None, contains = None, avoids = None))]
+    fn filter(
+        &self,
+        expression: Option<&str>,
+        subset_of: Option<&PyAny>,
+        superset_of: Option<&PyAny>,
+        contains: Option<&PyAny>,
+        avoids: Option<&PyAny>,
+    ) -> PyResult<Self> {
+        let mut filters = Vec::new();
+        let mut arguments = Vec::new();
+        if let Some(expression) = expression {
+            filters.push(Expression::from_str(expression)?);
+            arguments.push(format!("{expression:?}"));
+        }
+        for (predicate, name, reference) in [
+            (MaskPredicate::Subset, "subset_of", subset_of),
+            (MaskPredicate::Superset, "superset_of", superset_of),
+            (MaskPredicate::Contains, "contains", contains),
+            (MaskPredicate::Avoids, "avoids", avoids),
+        ] {
+            if let Some(reference) = reference {
+                let reference = extract_mask(reference)?;
+                filters.push(Expression::Match(predicate, reference));
+                arguments.push(format!("{name}={:?}", mask_to_chars(reference)));
+            }
+        }
+
+        self.then(
+            format!("filter({})", arguments.join(", ")),
+            self.masked && !self.group_by_mask && self.aggregate.is_none(),
+            |pipeline| pipeline.filters.extend(filters),
+        )
+    }
+
+    /// Aggregates the strings of each mask separately, so that the result is a
+    /// `dict` of the characters of each mask with its aggregate.
+    fn group_by_mask(&self) -> PyResult<Self> {
+        self.then(
+            "group_by_mask()".to_owned(),
+            self.masked && !self.group_by_mask && self.aggregate.is_none(),
+            |pipeline| pipeline.group_by_mask = true,
+        )
+    }
+
+    /// Aggregates the number of strings.
+    fn count(&self) -> PyResult<Self> {
+        self.aggregate(Aggregate::Count, "count")
+    }
+
+    /// Aggregates the common alphabet of the strings.
+    fn common(&self) -> PyResult<Self> {
+        self.aggregate(Aggregate::Common, "common")
+    }
+
+    /// Aggregates the combined alphabet of the strings.
+    fn union(&self) -> PyResult<Self> {
+        self.aggregate(Aggregate::Union, "union")
+    }
+
+    /// Aggregates the position of each string in the corpus, in order.
+    fn positions(&self) -> PyResult<Self> {
+        self.aggregate(Aggregate::Positions, "positions")
+    }
+
+    /// Runs the pipeline over `strings`, a sequence of `str`, and returns its
+    /// aggregate; a string outside the alphabet raises a `ValueError`.
+    fn run(
+        &self,
+        #[pyo3(from_py_with = "text::to_strs")] strings: Vec<&str>,
+        py: Python<'_>,
+    ) -> PyResult<PyObject> {
+        let Some(aggregate) = self.aggregate else {
+            return Err(LocalError::InvalidArgument {
+                name: "pipeline",
+                value: self.__repr__(),
+            }
+            .into());
+        };
+        let pipeline = Pipeline {
+            filters: self.filters.clone(),
+            group_by_mask: self.group_by_mask,
+            aggregate,
+        };
+
+        let result = py.allow_threads(|| {
+            Settings::get().limits.check(strings.iter().copied())?;
+            let result = lanes::install(strings.iter().copied(), || pipeline.run(&strings))?;
+            CallStats::new("Pipeline.run", MemoryEstimate::of_each(strings.len()))
+                .processed_strings(&strings)
+                .record();
+            Ok::<_, LocalError>(result)
+        })?;
+
+        match result {
+            PipelineResult::Total(aggregated) => Ok(aggregated_into_py(py, aggregated)),
+            PipelineResult::Groups(groups) => {
+                let dict = PyDict::new(py);
+                for (mask, aggregated) in groups {
+                    dict.set_item(mask_to_chars(mask), aggregated_into_py(py, aggregated))?;
+                }
+                Ok(dict.into())
+            }
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        ["Pipeline()".to_owned()]
+            .into_iter()
+            .chain(self.stages.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+}
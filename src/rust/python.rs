@@ -0,0 +1,2176 @@
+//! The Python bindings of this crate, exposed as the `lib_alphabet_mask` module.
+//!
+//! Every entry point can be called concurrently from many Python threads: the
+//! Python classes are `frozen` and immutable, the [`Settings`] are behind a lock
+//! and copied at the start of each call, and the statistics of each call are
+//! either thread local or atomic. Parallel work on few strings goes to the small
+//! lane of [`lanes`].
+//!
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use fxhash::FxHashSet;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+use crate::alphabet::{Masking, PyAlphabet};
+use crate::call_stats::{
+    find_common_mask_counted, CallStats, Counters, MemoryEstimate, WorkerLoads,
+};
+use crate::layout::Layout;
+use crate::mask::{mask_into_py, PyAlphabetMask};
+use crate::models::{self, LocalError, Mask};
+use crate::settings::{Limits, Settings};
+use crate::unicode::PyUnicodeMask;
+use crate::{
+    alphabet, arrow, batch, calibration, checkpoint, counted, delimited, fastx, files, follow,
+    index, info, lanes, lazy, masker, partial, pipeline, shared, text, unicode, worker,
+};
+use alphabet_mask_core::bio;
+use alphabet_mask_core::classify::CharClass;
+use alphabet_mask_core::expression::Expression;
+use alphabet_mask_core::games::{self, Signature, WordleConstraints};
+use alphabet_mask_core::scripts;
+use alphabet_mask_core::stats;
+use alphabet_mask_core::transform::strip_diacritics;
+use alphabet_mask_core::unicode::{find_common_char_set, CharSet};
+use alphabet_mask_core::validate::{self, Charset};
+use alphabet_mask_core::{find_union_mask, mask_string, CharOrder};
+
+/// Returns `string` as it is masked: with its diacritics stripped if
+/// `fold_diacritics`.
+fn preprocess(string: &str, fold_diacritics: bool) -> Cow<'_, str> {
+    if fold_diacritics {
+        strip_diacritics(string)
+    } else {
+        Cow::Borrowed(string)
+    }
+}
+
+/// Returns a bit mask representing the alphabet of the given string.
+///
+/// Masked characters are:
+/// - space (#0)
+/// - A-Z (case insensitive) (#1-26)
+/// - full stop (#27)
+/// - comma (#28)
+///
+/// With ``digits=True``, the digits ``0`` to ``9`` are also masked, on bits 32
+/// to 41 of a 64-bit mask. With ``extended=True``, every other ASCII punctuation
+/// mark, such as ``;``, ``?`` and ``(``, is masked too, on bits 42 to 68 of a
+/// 128-bit mask that also includes the digits. With ``case_sensitive=True``,
+/// ``A`` to ``Z`` are masked apart from ``a`` to ``z``, on bits 32 to 57 of a
+/// 64-bit mask; it cannot be combined with the digits.
+///
+/// With ``fold_diacritics=True``, the string is decomposed (NFKD) and stripped of
+/// combining marks before it is masked, so that ``"Café"`` masks as ``"Cafe"``.
+///
+/// With ``alphabet``, an :class:`Alphabet` or the name of a preset such as
+/// ``"greek"``, the string is masked in that alphabet instead, as an `int` of up
+/// to 128 bits.
+///
+/// With ``as_mask=True``, the mask is returned as an :class:`AlphabetMask`.
+#[pyfunction]
+#[pyo3(signature = (
+    string,
+    as_mask = false,
+    digits = false,
+    extended = false,
+    case_sensitive = false,
+    fold_diacritics = false,
+    alphabet = None,
+))]
+// Every keyword argument of the Python function is an argument here.
+#[allow(clippy::too_many_arguments)]
+fn alphabet_mask(
+    #[pyo3(from_py_with = "text::to_str")] string: &str,
+    as_mask: bool,
+    digits: bool,
+    extended: bool,
+    case_sensitive: bool,
+    fold_diacritics: bool,
+    alphabet: Option<&PyAny>,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    let layout = Layout::of(digits, extended, case_sensitive)?;
+    let masking = Masking::of(alphabet, layout, as_mask)?;
+    Settings::get().limits.check([string])?;
+    let bits =
+        py.allow_threads(
+            || match masking.mask_string(&preprocess(string, fold_diacritics)) {
+                Ok(bits) => {
+                    Counters::add_strings(&[string]);
+                    Ok(bits)
+                }
+                Err(e) => Err::<_, PyErr>(e.into()),
+            },
+        )?;
+    Ok(masking.to_py(py, bits, as_mask, |bits| bits))
+}
+
+/// Returns the characters represented by the given mask.
+///
+/// With ``order="bit"``, the default, the characters are always in bit order:
+/// space, the letters from ``a`` to ``z``, then ``.``, ``,``, ``'``, ``-`` and
+/// ``"``. With ``order="alphabetical"`` they are in code point order, with space
+/// and punctuation before the letters. With ``order="custom"`` they are in the
+/// order of the characters of `custom`, followed by any others in bit order.
+///
+/// With ``digits=True``, `mask` is a 64-bit mask including the digits, as
+/// returned by :func:`alphabet_mask` with ``digits=True``; likewise, with
+/// ``extended=True``, `mask` is a 128-bit mask of the extended layout, and with
+/// ``case_sensitive=True``, a 64-bit mask with ``A`` to ``Z`` on bits 32 to 57.
+#[pyfunction]
+#[pyo3(signature = (
+    mask, order = "bit", custom = None, digits = false, extended = false, case_sensitive = false
+))]
+fn mask_to_chars(
+    mask: i128,
+    order: &str,
+    custom: Option<&str>,
+    digits: bool,
+    extended: bool,
+    case_sensitive: bool,
+) -> PyResult<String> {
+    let layout = Layout::of(digits, extended, case_sensitive)?;
+    let mask = layout.validate(mask)?;
+    let order = match (order, custom) {
+        ("bit", None) => CharOrder::Bit,
+        ("alphabetical", None) => CharOrder::Alphabetical,
+        ("custom", Some(custom)) => CharOrder::Custom(custom),
+        ("bit" | "alphabetical", Some(custom)) => {
+            return Err(LocalError::InvalidArgument {
+                name: "custom",
+                value: custom.to_owned(),
+            }
+            .into())
+        }
+        _ => {
+            return Err(LocalError::InvalidArgument {
+                name: "order",
+                value: order.to_owned(),
+            }
+            .into())
+        }
+    };
+
+    Ok(layout.chars(mask, order))
+}
+
+/// Returns the bit mask of the given characters; the inverse of
+/// :func:`mask_to_chars`, and otherwise the same as :func:`alphabet_mask`.
+///
+/// ``chars_to_mask(mask_to_chars(mask)) == normalize_mask(mask)`` for any mask.
+#[pyfunction]
+fn chars_to_mask(chars: &str) -> PyResult<u32> {
+    Ok(mask_string(chars)?.bits())
+}
+
+/// Returns the given mask with every reserved bit of the standard layout cleared.
+///
+/// Use this to clean up masks persisted by any version. Integers of up to 64
+/// bits are accepted: negative ones are read as two's complement, as stored in
+/// signed 32-bit columns, and every bit from 32 up is cleared.
+#[pyfunction]
+fn normalize_mask(mask: i64) -> u32 {
+    alphabet_mask_core::normalize_mask(Mask(mask as u32)).bits()
+}
+
+/// Returns the mask of `value`, either a mask or a string to mask.
+pub(crate) fn extract_mask(value: &PyAny) -> PyResult<Mask> {
+    match value.extract::<&str>() {
+        Ok(string) => Ok(mask_string(string)?),
+        Err(_) => Ok(alphabet_mask_core::validate_mask(value.extract()?)?),
+    }
+}
+
+/// Returns the position of every bit set in the given mask, in order.
+#[pyfunction]
+fn mask_to_bits(mask: i128) -> PyResult<Vec<u8>> {
+    Ok(alphabet_mask_core::validate_mask(mask)?
+        .bit_positions()
+        .collect())
+}
+
+/// Returns the mask with the bits at the given positions set; the inverse of
+/// :func:`mask_to_bits`.
+#[pyfunction]
+fn bits_to_mask(bits: Vec<u8>) -> PyResult<u32> {
+    Mask::from_bit_positions(bits.iter().copied())
+        .map(Mask::bits)
+        .ok_or_else(|| {
+            LocalError::InvalidMask {
+                context: format!("bits {bits:?} are not all less than {}", Mask::WIDTH),
+            }
+            .into()
+        })
+}
+
+/// Returns whether each of the 32 bits of the given mask is set, from bit 0.
+#[pyfunction]
+fn mask_to_bools(mask: i128) -> PyResult<Vec<bool>> {
+    Ok(alphabet_mask_core::validate_mask(mask)?.to_bools().to_vec())
+}
+
+/// Returns the mask with each bit set if its flag is, from bit 0; the inverse of
+/// :func:`mask_to_bools`, taking exactly 32 flags.
+#[pyfunction]
+fn bools_to_mask(bools: Vec<bool>) -> PyResult<u32> {
+    let length = bools.len();
+    let bools = bools.try_into().map_err(|_| LocalError::InvalidMask {
+        context: format!("expected {} flags, got {length}", Mask::WIDTH),
+    })?;
+
+    Ok(Mask::from_bools(bools).bits())
+}
+
+/// Formats the given mask according to `format_spec`, as ``[#][0][width][type]``.
+///
+/// The type is ``c`` for its characters, the default, ``l`` for its bits labelled
+/// with their characters as in ``1:'a' 27:'.'``, or ``d``, ``b``, ``o``, ``x`` or
+/// ``X`` for the mask as an integer, where ``#`` adds the ``0b``, ``0o`` or ``0x``
+/// prefix and the width pads with spaces, or with zeros after a ``0``; e.g.
+/// ``format_mask(mask, "#034b")`` for all 32 bits in binary.
+#[pyfunction]
+#[pyo3(signature = (mask, format_spec = ""))]
+fn format_mask(mask: i128, format_spec: &str) -> PyResult<String> {
+    Ok(alphabet_mask_core::validate_mask(mask)?.format(format_spec)?)
+}
+
+/// Returns the given integer if it is a mask of the standard layout, or raises a
+/// `ValueError` naming the bits it sets that the layout does not define.
+///
+/// Every function taking a mask validates it this way, except for
+/// :func:`normalize_mask` which clears those bits instead.
+#[pyfunction]
+fn validate_mask(value: i128) -> PyResult<u32> {
+    Ok(alphabet_mask_core::validate_mask(value)?.bits())
+}
+
+/// Returns the number of distinct letters of the given mask or string, also
+/// counting the punctuation marks if `include_punctuation`.
+#[pyfunction]
+#[pyo3(signature = (mask_or_string, include_punctuation = false))]
+fn letter_count(mask_or_string: &PyAny, include_punctuation: bool) -> PyResult<u32> {
+    Ok(extract_mask(mask_or_string)?.letter_count(include_punctuation))
+}
+
+/// Returns the common alphabet of the given strings.
+///
+/// `strings` can also be a `dict` whose values are each a `str` or a sequence of
+/// `str`, such as groups from :func:`itertools.groupby`; the groups are then
+/// processed in parallel, and a `dict` of the common alphabet of each is
+/// returned with the same keys.
+///
+/// With ``digits=True``, the digits ``0`` to ``9`` are also in the alphabet;
+/// with ``extended=True``, so are the digits and every ASCII punctuation mark.
+/// With ``case_sensitive=True``, ``A`` and ``a`` are different characters of the
+/// alphabet. With ``fold_diacritics=True``, the strings are stripped of their
+/// diacritics before they are masked, as by :func:`alphabet_mask`. With
+/// ``alphabet``, an :class:`Alphabet` or the name of a preset such as
+/// ``"cyrillic"``, the common alphabet is of the characters of that alphabet.
+/// With ``as_mask=True``, each alphabet is returned as an :class:`AlphabetMask`
+/// rather than a `str`.
+#[pyfunction]
+#[pyo3(signature = (
+    strings,
+    length_limit = None,
+    as_mask = false,
+    digits = false,
+    extended = false,
+    case_sensitive = false,
+    fold_diacritics = false,
+    alphabet = None,
+))]
+// Every keyword argument of the Python function is an argument here.
+#[allow(clippy::too_many_arguments)]
+fn common_alphabets(
+    strings: &PyAny,
+    length_limit: Option<usize>,
+    as_mask: bool,
+    digits: bool,
+    extended: bool,
+    case_sensitive: bool,
+    fold_diacritics: bool,
+    alphabet: Option<&PyAny>,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    let layout = Layout::of(digits, extended, case_sensitive)?;
+    let masking = Masking::of(alphabet, layout, as_mask)?;
+    let length_limit = length_limit.unwrap_or(Settings::get().chunk_limit);
+
+    if let Some(groups) = batch::Groups::from_pyobject(strings)? {
+        let alphabets = py.allow_threads(|| {
+            let strings: Vec<&str> = batch::group_strings(&groups.groups).collect();
+            Settings::get().limits.check(strings.iter().copied())?;
+            let loads = WorkerLoads::if_enabled();
+            let alphabets = lanes::install(strings.iter().copied(), || {
+                batch::map_groups(&groups.groups, |group| match group {
+                    batch::Group::One(string) => {
+                        if let Some(loads) = &loads {
+                            loads.add(string.len());
+                        }
+                        masking.mask_string(&preprocess(string, fold_diacritics))
+                    }
+                    batch::Group::Many(strings) => {
+                        let strings: Vec<_> = strings
+                            .iter()
+                            .map(|string| preprocess(string, fold_diacritics))
+                            .collect();
+                        masking.find_common_mask(&strings, length_limit, loads.as_ref())
+                    }
+                })
+            })?;
+            CallStats::new(
+                "common_alphabets",
+                MemoryEstimate::of_strings(&strings, length_limit),
+            )
+            .processed_strings(&strings)
+            .with_workers(loads)
+            .record();
+            Ok::<_, LocalError>(alphabets)
+        })?;
+        let alphabets: Vec<PyObject> = alphabets
+            .into_iter()
+            .map(|bits| masking.to_py(py, bits, as_mask, |bits| masking.chars(bits)))
+            .collect();
+        return Ok(groups.into_dict(py, alphabets)?.into());
+    }
+
+    let strings = text::to_strs(strings)?;
+    let bits = py.allow_threads(|| {
+        Settings::get().limits.check(strings.iter().copied())?;
+        let loads = WorkerLoads::if_enabled();
+        let preprocessed: Vec<_> = strings
+            .iter()
+            .map(|string| preprocess(string, fold_diacritics))
+            .collect();
+        let bits = masking.find_common_mask(&preprocessed, length_limit, loads.as_ref())?;
+        CallStats::new(
+            "common_alphabets",
+            MemoryEstimate::of_strings(&strings, length_limit),
+        )
+        .processed_strings(&strings)
+        .with_workers(loads)
+        .record();
+        Ok::<_, LocalError>(bits)
+    })?;
+    Ok(masking.to_py(py, bits, as_mask, |bits| masking.chars(bits)))
+}
+
+/// Returns the mask of `string` in `alphabet`, an :class:`Alphabet`, as an `int`
+/// of up to 128 bits.
+///
+/// Raises a `ValueError` if `string` has a character outside the alphabet.
+#[pyfunction]
+fn mask_string_with(
+    alphabet: &PyAlphabet,
+    #[pyo3(from_py_with = "text::to_str")] string: &str,
+    py: Python<'_>,
+) -> PyResult<u128> {
+    alphabet::mask_string(&alphabet.inner, string, py)
+}
+
+/// Returns the common alphabet of the given strings in `alphabet`, an
+/// :class:`Alphabet`, as :func:`common_alphabets` does in the standard layout.
+#[pyfunction]
+#[pyo3(signature = (alphabet, strings, length_limit = None))]
+fn common_alphabets_with(
+    alphabet: &PyAlphabet,
+    strings: &PyAny,
+    length_limit: Option<usize>,
+    py: Python<'_>,
+) -> PyResult<String> {
+    let alphabet = &alphabet.inner;
+    let length_limit = length_limit.unwrap_or(Settings::get().chunk_limit);
+    let strings = text::to_strs(strings)?;
+    let mask = py.allow_threads(move || {
+        Settings::get().limits.check(strings.iter().copied())?;
+        let mask = lanes::install(strings.iter().copied(), || {
+            alphabet.find_common_mask(&strings, length_limit)
+        })?;
+        CallStats::new(
+            "common_alphabets_with",
+            MemoryEstimate::of_strings(&strings, length_limit),
+        )
+        .processed_strings(&strings)
+        .record();
+        Ok::<_, LocalError>(mask)
+    })?;
+    Ok(alphabet.chars(mask))
+}
+
+/// Returns the characters of `string` as a :class:`UnicodeMask`, in which no
+/// character is an error; letters are folded to lowercase unless
+/// `case_sensitive`.
+#[pyfunction]
+#[pyo3(signature = (string, case_sensitive = false))]
+fn unicode_mask(
+    #[pyo3(from_py_with = "text::to_str")] string: &str,
+    case_sensitive: bool,
+    py: Python<'_>,
+) -> PyResult<PyUnicodeMask> {
+    let case = unicode::case_policy(case_sensitive);
+    Settings::get().limits.check([string])?;
+    let set = py.allow_threads(|| CharSet::of(string, case));
+    Counters::add_strings(&[string]);
+    Ok(PyUnicodeMask::new_with(set, case))
+}
+
+/// Returns the common alphabet of the given strings as a :class:`UnicodeMask`,
+/// as :func:`common_alphabets` does in the standard layout but for any
+/// characters; without strings, every Unicode character.
+#[pyfunction]
+#[pyo3(signature = (strings, length_limit = None, case_sensitive = false))]
+fn common_unicode_alphabets(
+    strings: &PyAny,
+    length_limit: Option<usize>,
+    case_sensitive: bool,
+    py: Python<'_>,
+) -> PyResult<PyUnicodeMask> {
+    let case = unicode::case_policy(case_sensitive);
+    let length_limit = length_limit.unwrap_or(Settings::get().chunk_limit);
+    let strings = text::to_strs(strings)?;
+    let set = py.allow_threads(move || {
+        Settings::get().limits.check(strings.iter().copied())?;
+        let set = lanes::install(strings.iter().copied(), || {
+            find_common_char_set(&strings, length_limit, case)
+        });
+        CallStats::new(
+            "common_unicode_alphabets",
+            MemoryEstimate::of_strings(&strings, length_limit),
+        )
+        .processed_strings(&strings)
+        .record();
+        Ok::<_, LocalError>(set)
+    })?;
+    Ok(PyUnicodeMask::new_with(set, case))
+}
+
+/// Returns the intersection of the common alphabet of the given strings with
+/// each of `reference_masks`, as a `list` of masks in the same order.
+///
+/// The strings are masked only once, however many references there are.
+#[pyfunction]
+fn intersect_with(
+    #[pyo3(from_py_with = "text::to_strs")] strings: Vec<&str>,
+    reference_masks: Vec<i128>,
+    length_limit: Option<usize>,
+    py: Python<'_>,
+) -> PyResult<Vec<u32>> {
+    let length_limit = length_limit.unwrap_or(Settings::get().chunk_limit);
+    let references = reference_masks
+        .into_iter()
+        .map(alphabet_mask_core::validate_mask)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    py.allow_threads(move || {
+        Settings::get().limits.check(strings.iter().copied())?;
+        let masks = alphabet_mask_core::intersect_with(&strings, &references, length_limit)?;
+        CallStats::new(
+            "intersect_with",
+            MemoryEstimate::of_strings(&strings, length_limit),
+        )
+        .processed_strings(&strings)
+        .record();
+        Ok(masks.into_iter().map(Mask::bits).collect())
+    })
+}
+
+/// Returns how :func:`common_alphabets` would split the work on the given
+/// strings, without masking them.
+///
+/// The result is a `dict` of ``parallel``, whether the chunks would be masked in
+/// parallel, ``length_limit``, and ``chunks``, a `list` of ``(start, end, bytes)``
+/// for the positions of the strings of each chunk and their total length. Without
+/// parallelism, there is a single chunk of every string.
+#[pyfunction]
+fn plan(
+    #[pyo3(from_py_with = "text::to_strs")] strings: Vec<&str>,
+    length_limit: Option<usize>,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    let length_limit = length_limit.unwrap_or(Settings::get().chunk_limit);
+    let plan = py.allow_threads(|| alphabet_mask_core::chunks::plan(&strings, length_limit));
+
+    let dict = PyDict::new(py);
+    dict.set_item("parallel", plan.parallel)?;
+    dict.set_item("length_limit", length_limit)?;
+    dict.set_item(
+        "chunks",
+        plan.chunks
+            .iter()
+            .map(|chunk| (chunk.start, chunk.end, chunk.bytes))
+            .collect::<Vec<_>>(),
+    )?;
+    Ok(dict.into())
+}
+
+/// Returns the combined alphabet of the given strings, as an
+/// :class:`AlphabetMask` with ``as_mask=True``.
+#[pyfunction]
+#[pyo3(signature = (strings, as_mask = false))]
+fn union_alphabets(
+    #[pyo3(from_py_with = "text::to_strs")] strings: Vec<&str>,
+    as_mask: bool,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    let mask = py.allow_threads(move || {
+        Settings::get().limits.check(strings.iter().copied())?;
+        let mask = find_union_mask(strings.iter())?;
+        CallStats::new(
+            "union_alphabets",
+            MemoryEstimate::of_strings(&strings, usize::MAX),
+        )
+        .processed_strings(&strings)
+        .record();
+        Ok::<_, LocalError>(mask)
+    })?;
+    Ok(mask_into_py(
+        py,
+        mask,
+        as_mask,
+        alphabet_mask_core::mask_to_chars,
+    ))
+}
+
+/// Parse the `output` argument of the functions returning one mask per string.
+fn parse_output(output: &str) -> Result<models::BatchOutput, LocalError> {
+    models::BatchOutput::from_str(output).map_err(|_| LocalError::InvalidArgument {
+        name: "output",
+        value: output.to_owned(),
+    })
+}
+
+/// Returns the bit mask of each of the given strings, computed in parallel.
+///
+/// `strings` is either a sequence of `str`, an Arrow string array or
+/// :class:`SharedStrings`. With
+/// ``output="arrow"`` the masks are returned as a :class:`pyarrow.UInt32Array`
+/// built in Rust, otherwise as a `list`; null inputs give null masks. With
+/// ``output="bytes"`` they are returned as one `bytes` of little-endian ``u32``
+/// for ``numpy.frombuffer(result, dtype="<u4")``, with ``0`` for null inputs.
+///
+/// `strings` can also be a `dict` whose values are each a `str` or a sequence of
+/// `str`; the groups are then processed in parallel, and a `dict` with the same
+/// keys is returned, of the mask of each `str` and the masks of each sequence in
+/// the container of `output`.
+///
+/// With a `chunk_size`, `strings` is instead any iterable of `str`, such as a
+/// generator, and a :class:`MaskChunks` iterator is returned, which yields the
+/// masks of each `chunk_size` strings in the container of `output` as soon as
+/// they are masked.
+#[pyfunction]
+#[pyo3(signature = (strings, output = "list", chunk_size = None))]
+fn alphabet_masks(
+    strings: &PyAny,
+    output: &str,
+    chunk_size: Option<usize>,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    let output = parse_output(output)?;
+    if let Some(chunk_size) = chunk_size {
+        return Ok(lazy::MaskChunks::of_strings(strings, chunk_size, output)?.into_py(py));
+    }
+
+    if let Some(groups) = batch::Groups::from_pyobject(strings)? {
+        let masks = py.allow_threads(|| {
+            let strings: Vec<&str> = batch::group_strings(&groups.groups).collect();
+            Settings::get().limits.check(strings.iter().copied())?;
+            let masks = lanes::install(strings.iter().copied(), || {
+                batch::map_groups(&groups.groups, |group| match group {
+                    batch::Group::One(string) => Ok(Err(mask_string(string)?)),
+                    batch::Group::Many(strings) => {
+                        let strings: Vec<_> = strings.iter().copied().map(Some).collect();
+                        Ok(Ok(batch::find_masks(&strings)?))
+                    }
+                })
+            })?;
+            CallStats::new("alphabet_masks", MemoryEstimate::of_each(strings.len()))
+                .processed_strings(&strings)
+                .record();
+            Ok::<_, LocalError>(masks)
+        })?;
+        let masks = masks
+            .into_iter()
+            .map(|masks| match masks {
+                Ok(masks) => batch::masks_into_py(py, masks, output),
+                Err(mask) => Ok(mask.bits().into_py(py)),
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        return Ok(groups.into_dict(py, masks)?.into());
+    }
+
+    let batch = batch::Batch::from_pyobject(strings)?;
+
+    let masks = py.allow_threads(|| {
+        let strings = batch.values()?;
+        Settings::get()
+            .limits
+            .check(strings.iter().flatten().copied())?;
+        let values: Vec<&str> = strings.iter().flatten().copied().collect();
+        let masks = lanes::install(values.iter().copied(), || batch::find_masks(&strings))?;
+        CallStats::new("alphabet_masks", MemoryEstimate::of_each(strings.len()))
+            .processed_strings(&values)
+            .record();
+        Ok::<_, LocalError>(masks)
+    })?;
+    batch::masks_into_py(py, masks, output)
+}
+
+/// Returns the letters ``a`` to ``z`` which each of the given strings lacks, as
+/// a `str` of them in alphabetical order, computed in parallel; a pangram gives
+/// ``""``.
+///
+/// `strings` is either a sequence of `str`, an Arrow string array or
+/// :class:`SharedStrings`; null inputs give ``None``. Case, spaces and
+/// punctuation are ignored, but any character outside the alphabet raises a
+/// `ValueError`.
+#[pyfunction]
+fn missing_letters_batch(strings: &PyAny, py: Python<'_>) -> PyResult<Vec<Option<String>>> {
+    let batch = batch::Batch::from_pyobject(strings)?;
+
+    py.allow_threads(|| {
+        let strings = batch.values()?;
+        Settings::get()
+            .limits
+            .check(strings.iter().flatten().copied())?;
+        let values: Vec<&str> = strings.iter().flatten().copied().collect();
+        let missing = lanes::install(values.iter().copied(), || {
+            batch::find_missing_letters(&strings)
+        })?;
+        CallStats::new(
+            "missing_letters_batch",
+            MemoryEstimate::of_each(strings.len()),
+        )
+        .processed_strings(&values)
+        .record();
+        Ok(missing)
+    })
+}
+
+/// Returns the number of times each character is directly followed by each
+/// other in the given strings, counted in parallel, as a cheap stylometric
+/// fingerprint.
+///
+/// The result is a 32 by 32 `list` of `list` of `int`, whose rows and columns are
+/// the bits of the mask named in :mod:`bits`, so that ``result[a][b]`` counts
+/// the character of bit ``a`` followed by that of bit ``b``. Letters are counted
+/// regardless of case, and pairs only within a string.
+///
+/// `strings` is either a sequence of `str`, an Arrow string array or
+/// :class:`SharedStrings`, whose null values are ignored.
+#[pyfunction]
+fn char_transitions(strings: &PyAny, py: Python<'_>) -> PyResult<Vec<Vec<u64>>> {
+    let batch = batch::Batch::from_pyobject(strings)?;
+
+    py.allow_threads(|| {
+        let strings: Vec<&str> = batch.values()?.into_iter().flatten().collect();
+        Settings::get().limits.check(strings.iter().copied())?;
+        let transitions = lanes::install(strings.iter().copied(), || {
+            stats::Transitions::gather(&strings)
+        })?;
+        CallStats::new("char_transitions", MemoryEstimate::of_each(strings.len()))
+            .processed_strings(&strings)
+            .record();
+        Ok(transitions.counts.iter().map(|row| row.to_vec()).collect())
+    })
+}
+
+/// Returns the masks of the tokens of a document, split on any whitespace in
+/// Rust without creating a Python `str` for each, with their common and
+/// combined alphabets.
+///
+/// The result is a `dict` of ``masks``, the mask of each token in order, in the
+/// container of `output` as for :func:`alphabet_masks`; ``common``, the
+/// characters of every token; and ``union``, those of any token. A token with a
+/// character outside the alphabet raises a `ValueError`.
+#[pyfunction]
+#[pyo3(signature = (text, output = "list"))]
+fn token_masks(
+    #[pyo3(from_py_with = "text::to_str")] text: &str,
+    output: &str,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    let output = parse_output(output)?;
+    let tokens = py.allow_threads(|| {
+        Settings::get().limits.check([text])?;
+        let tokens = lanes::install([text], || stats::PartMasks::tokens(text))?;
+        CallStats::new("token_masks", MemoryEstimate::of_each(tokens.masks.len()))
+            .processed_strings(&[text])
+            .record();
+        Ok::<_, LocalError>(tokens)
+    })?;
+
+    part_masks_into_py(py, tokens, output)
+}
+
+/// Returns the masks of the lines of a multi-line string, split in Rust without
+/// :meth:`str.splitlines` or a `list` of the lines, with their common and
+/// combined alphabets.
+///
+/// Lines end with ``"\\n"`` or ``"\\r\\n"``, and a final line ending does not add
+/// an empty line; an empty line in between empties the common alphabet. The
+/// result is a `dict` of ``masks``, the mask of each line in order, in the
+/// container of `output` as for :func:`alphabet_masks`; ``common``, the
+/// characters of every line; and ``union``, those of any line.
+#[pyfunction]
+#[pyo3(signature = (text, output = "list"))]
+fn line_masks(
+    #[pyo3(from_py_with = "text::to_str")] text: &str,
+    output: &str,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    let output = parse_output(output)?;
+    let lines = py.allow_threads(|| {
+        Settings::get().limits.check([text])?;
+        let lines = lanes::install([text], || stats::PartMasks::lines(text))?;
+        CallStats::new("line_masks", MemoryEstimate::of_each(lines.masks.len()))
+            .processed_strings(&[text])
+            .record();
+        Ok::<_, LocalError>(lines)
+    })?;
+    part_masks_into_py(py, lines, output)
+}
+
+/// Returns a `dict` of the masks of the parts of a text in the container of
+/// `output`, and their common and combined alphabets.
+fn part_masks_into_py(
+    py: Python<'_>,
+    parts: stats::PartMasks,
+    output: models::BatchOutput,
+) -> PyResult<PyObject> {
+    let (common, union) = (parts.common, parts.union);
+    let masks = batch::masks_into_py(py, parts.masks.into_iter().map(Some).collect(), output)?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("masks", masks)?;
+    dict.set_item("common", alphabet_mask_core::mask_to_chars(common))?;
+    dict.set_item("union", alphabet_mask_core::mask_to_chars(union))?;
+    Ok(dict.into())
+}
+
+/// Splits the given strings into maximal contiguous runs whose common alphabet
+/// keeps at least `min_letters` letters, walking them in order.
+///
+/// Returns a `list` of ``(start, end, alphabet)`` for the positions of the
+/// strings of each run and their common alphabet. A run is extended for as long
+/// as the next string keeps enough letters in common, then a new run starts from
+/// it; a string with fewer than `min_letters` letters of its own is a run by
+/// itself. The strings are masked in parallel.
+#[pyfunction]
+fn segment_by_common_alphabet(
+    #[pyo3(from_py_with = "text::to_strs")] strings: Vec<&str>,
+    min_letters: u32,
+    py: Python<'_>,
+) -> PyResult<Vec<(usize, usize, String)>> {
+    py.allow_threads(|| {
+        Settings::get().limits.check(strings.iter().copied())?;
+        let masks = lanes::install(strings.iter().copied(), || {
+            batch::find_masks(&strings.iter().copied().map(Some).collect::<Vec<_>>())
+        })?;
+        let masks: Vec<Mask> = masks.into_iter().flatten().collect();
+        let segments = alphabet_mask_core::segment::segment_by_common_mask(&masks, min_letters);
+        CallStats::new(
+            "segment_by_common_alphabet",
+            MemoryEstimate::of_each(strings.len()),
+        )
+        .processed_strings(&strings)
+        .record();
+
+        Ok(segments
+            .into_iter()
+            .map(|segment| {
+                (
+                    segment.start,
+                    segment.end,
+                    alphabet_mask_core::mask_to_chars(segment.common),
+                )
+            })
+            .collect())
+    })
+}
+
+/// Returns the bit mask of the common alphabet of the given strings.
+///
+/// `strings` is either a sequence of `str`, :class:`SharedStrings`, or an Arrow
+/// string array whose null values are ignored. The result can be combined with other partial results by
+/// a bitwise AND, with ``0xFFFFFFFF`` as the identity. With ``as_mask=True``,
+/// the mask is returned as an :class:`AlphabetMask`.
+#[pyfunction]
+#[pyo3(signature = (strings, length_limit = None, as_mask = false))]
+fn common_mask(
+    strings: &PyAny,
+    length_limit: Option<usize>,
+    as_mask: bool,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    let mask = find_common_mask(strings, length_limit, py)?;
+    Ok(mask_into_py(py, mask, as_mask, Mask::bits))
+}
+
+/// Returns the common mask of `strings`, as for :func:`common_mask`.
+fn find_common_mask(
+    strings: &PyAny,
+    length_limit: Option<usize>,
+    py: Python<'_>,
+) -> PyResult<Mask> {
+    let length_limit = length_limit.unwrap_or(Settings::get().chunk_limit);
+    let batch = batch::Batch::from_pyobject(strings)?;
+
+    py.allow_threads(|| {
+        let strings: Vec<&str> = batch.values()?.into_iter().flatten().collect();
+        Settings::get().limits.check(strings.iter().copied())?;
+        let loads = WorkerLoads::if_enabled();
+        let mask = find_common_mask_counted(&strings, length_limit, loads.as_ref())?;
+        CallStats::new(
+            "common_mask",
+            MemoryEstimate::of_strings(&strings, length_limit),
+        )
+        .processed_strings(&strings)
+        .with_workers(loads)
+        .record();
+        Ok(mask)
+    })
+}
+
+/// Returns the common mask of the given strings, serialised as a partial mask.
+///
+/// Partial masks are meant for map-reduce style computations, where each worker
+/// masks a subset of the corpus; combine them with :func:`combine_masks`.
+#[pyfunction]
+fn partial_mask<'py>(
+    strings: &PyAny,
+    length_limit: Option<usize>,
+    py: Python<'py>,
+) -> PyResult<&'py PyBytes> {
+    let mask = find_common_mask(strings, length_limit, py)?;
+    Ok(PyBytes::new(py, &partial::encode(mask)))
+}
+
+/// Combines serialised partial masks into one, in any order.
+#[pyfunction]
+fn combine_masks<'py>(partials: Vec<&[u8]>, py: Python<'py>) -> PyResult<&'py PyBytes> {
+    let mask = partial::combine(partials.into_iter())?;
+    Ok(PyBytes::new(py, &partial::encode(mask)))
+}
+
+/// Returns the common mask held by a serialised partial mask.
+#[pyfunction]
+fn decode_partial_mask(partial: &[u8]) -> PyResult<u32> {
+    Ok(partial::decode(partial)?.bits())
+}
+
+/// Returns the common alphabet of the strings in an Arrow string array, or in
+/// each array of an Arrow stream.
+///
+/// `array` can be any ``utf8`` or ``large_utf8`` array implementing the Arrow
+/// PyCapsule interface, such as a :class:`pyarrow.StringArray`; the values are
+/// read straight from the Arrow buffers without conversion to Python strings.
+/// Null values are ignored.
+///
+/// `array` can also be a stream implementing ``__arrow_c_stream__``, such as a
+/// :class:`pyarrow.ChunkedArray` or a :class:`pyarrow.RecordBatchReader` over a
+/// dataset larger than memory, which is read one batch at a time; `column` is
+/// then the name of the string column of its record batches, which may be
+/// omitted if they only have one.
+#[pyfunction]
+#[pyo3(signature = (array, length_limit = None, column = None))]
+fn common_alphabets_arrow(
+    array: &PyAny,
+    length_limit: Option<usize>,
+    column: Option<&str>,
+    py: Python<'_>,
+) -> PyResult<String> {
+    let length_limit = length_limit.unwrap_or(Settings::get().chunk_limit);
+    if arrow::is_stream(array)? {
+        let stream = arrow::StringArrayStream::from_pyobject(array, column)?;
+        return py.allow_threads(move || common_alphabets_arrow_stream(stream, length_limit));
+    }
+    if let Some(column) = column {
+        return Err(LocalError::InvalidArgument {
+            name: "column",
+            value: format!("{column} of an array rather than a stream"),
+        }
+        .into());
+    }
+    let array = arrow::StringArray::from_pyobject(array)?;
+
+    py.allow_threads(move || {
+        let strings: Vec<&str> = array.values()?.into_iter().flatten().collect();
+        Settings::get().limits.check(strings.iter().copied())?;
+        let loads = WorkerLoads::if_enabled();
+        let mask = find_common_mask_counted(&strings, length_limit, loads.as_ref())?;
+        CallStats::new(
+            "common_alphabets_arrow",
+            MemoryEstimate::of_strings(&strings, length_limit),
+        )
+        .processed_strings(&strings)
+        .with_workers(loads)
+        .record();
+        Ok(alphabet_mask_core::mask_to_chars(mask))
+    })
+}
+
+/// Returns the common alphabet of every array of `stream`, holding one at a time.
+fn common_alphabets_arrow_stream(
+    mut stream: arrow::StringArrayStream,
+    length_limit: usize,
+) -> PyResult<String> {
+    let loads = WorkerLoads::if_enabled();
+    let mut mask = Mask::ALL;
+    let (mut memory, mut strings_count, mut bytes) = (MemoryEstimate::default(), 0, 0);
+
+    while let Some(array) = stream.next_array()? {
+        let strings: Vec<&str> = array.values()?.into_iter().flatten().collect();
+        Settings::get().limits.check(strings.iter().copied())?;
+        mask &= find_common_mask_counted(&strings, length_limit, loads.as_ref())?;
+
+        let estimate = MemoryEstimate::of_strings(&strings, length_limit);
+        if estimate.peak() > memory.peak() {
+            memory = estimate;
+        }
+        strings_count += strings.len();
+        bytes += strings.iter().map(|string| string.len()).sum::<usize>();
+    }
+
+    CallStats::new("common_alphabets_arrow", memory)
+        .processed(strings_count, bytes)
+        .with_workers(loads)
+        .record();
+    Ok(alphabet_mask_core::mask_to_chars(mask))
+}
+
+/// Returns the common alphabet of every line in the given files.
+///
+/// Each line of each file is treated as one string; the files are read in blocks
+/// of roughly `length_limit` bytes which are masked in parallel.
+///
+/// If `checkpoint` is given, the progress is saved to that path every
+/// `checkpoint_interval` bytes, and an interrupted scan is resumed from it; a
+/// checkpoint saved for a different list of files raises a `ValueError`.
+#[pyfunction]
+fn common_alphabets_files(
+    paths: Vec<PathBuf>,
+    length_limit: Option<usize>,
+    checkpoint: Option<PathBuf>,
+    checkpoint_interval: Option<u64>,
+    py: Python<'_>,
+) -> PyResult<String> {
+    let length_limit = length_limit.unwrap_or(Settings::get().chunk_limit);
+    if checkpoint_interval == Some(0) {
+        return Err(LocalError::InvalidArgument {
+            name: "checkpoint_interval",
+            value: "0".to_owned(),
+        }
+        .into());
+    }
+
+    py.allow_threads(move || {
+        Settings::get().limits.check_files(&paths)?;
+        let (mask, scanned) = if let Some(checkpoint) = checkpoint {
+            files::find_common_mask_in_files_resumable(
+                &paths,
+                length_limit,
+                &checkpoint,
+                checkpoint_interval.unwrap_or(checkpoint::DEFAULT_CHECKPOINT_INTERVAL),
+            )?
+        } else {
+            files::find_common_mask_in_files(&paths, length_limit)?
+        };
+        CallStats::new(
+            "common_alphabets_files",
+            MemoryEstimate::of_blocks(length_limit),
+        )
+        .processed(scanned.lines, scanned.bytes)
+        .record();
+        Ok(alphabet_mask_core::mask_to_chars(mask))
+    })
+}
+
+/// Returns the combined alphabet mask of every line of each of the given files,
+/// with the common mask of every line of them all.
+///
+/// The result is a `dict` of ``masks``, a `dict` of the mask of each file keyed
+/// by its path as given, and ``common``, the mask of the characters used by
+/// every line of every file. The files are read in parallel, each in blocks of
+/// roughly `length_limit` bytes.
+#[pyfunction]
+fn alphabet_masks_for_files(
+    paths: Vec<&PyAny>,
+    length_limit: Option<usize>,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    let length_limit = length_limit.unwrap_or(Settings::get().chunk_limit);
+    let keys = paths;
+    let paths = keys
+        .iter()
+        .map(|path| path.extract())
+        .collect::<PyResult<Vec<PathBuf>>>()?;
+
+    let masks = py.allow_threads(|| {
+        Settings::get().limits.check_files(&paths)?;
+        let masks = files::find_masks_of_files(&paths, length_limit)?;
+        let scanned = masks
+            .iter()
+            .fold(files::Scanned::default(), |scanned, masks| {
+                scanned + masks.scanned
+            });
+        CallStats::new(
+            "alphabet_masks_for_files",
+            MemoryEstimate::of_blocks(length_limit),
+        )
+        .processed(scanned.lines, scanned.bytes)
+        .record();
+        Ok::<_, LocalError>(masks)
+    })?;
+
+    let by_path = PyDict::new(py);
+    for (key, masks) in keys.into_iter().zip(&masks) {
+        by_path.set_item(key, masks.union.bits())?;
+    }
+    let common = masks
+        .iter()
+        .fold(Mask::ALL, |common, masks| common & masks.common);
+
+    let dict = PyDict::new(py);
+    dict.set_item("masks", by_path)?;
+    dict.set_item("common", common.bits())?;
+    Ok(dict.into())
+}
+
+/// One or several column names.
+#[derive(FromPyObject)]
+enum Columns {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Returns the common and combined alphabets of each of the given columns of a
+/// CSV file, read only once however many columns there are.
+///
+/// The first record of the file names the columns; `columns` is one name or a
+/// sequence of them, and `delimiter` a single ASCII character. The result is a
+/// `dict` keyed by column name of a `dict` of ``common`` and ``union``.
+///
+/// Raises a `ValueError` if a column is not in the header, if a record is
+/// malformed, or if any field of the columns contains a character outside the
+/// alphabet.
+#[pyfunction]
+#[pyo3(signature = (path, columns, delimiter = ","))]
+fn column_alphabets_csv(
+    path: PathBuf,
+    columns: Columns,
+    delimiter: &str,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    let columns = match columns {
+        Columns::One(column) => vec![column],
+        Columns::Many(columns) => columns,
+    };
+    let delimiter = match delimiter.as_bytes() {
+        &[delimiter] if delimiter.is_ascii() => delimiter,
+        _ => {
+            return Err(LocalError::InvalidArgument {
+                name: "delimiter",
+                value: delimiter.to_owned(),
+            }
+            .into())
+        }
+    };
+
+    let masks = py.allow_threads(|| {
+        Settings::get()
+            .limits
+            .check_files(std::slice::from_ref(&path))?;
+        let (masks, scanned) = delimited::find_column_masks(&path, &columns, delimiter)?;
+        CallStats::new(
+            "column_alphabets_csv",
+            MemoryEstimate::of_each(delimited::BATCH_LENGTH * columns.len()),
+        )
+        .processed(scanned.lines, scanned.bytes)
+        .record();
+        Ok::<_, LocalError>(masks)
+    })?;
+
+    let dict = PyDict::new(py);
+    for (column, masks) in columns.iter().zip(masks) {
+        let alphabets = PyDict::new(py);
+        alphabets.set_item("common", alphabet_mask_core::mask_to_chars(masks.common))?;
+        alphabets.set_item("union", alphabet_mask_core::mask_to_chars(masks.union))?;
+        dict.set_item(column, alphabets)?;
+    }
+    Ok(dict.into())
+}
+
+/// Masks every line of `path_in`, and writes the masks to `path_out` one per line.
+///
+/// `format` is either ``"int"`` for the mask as an integer, or ``"chars"`` for the
+/// characters it represents. Returns the number of lines written.
+#[pyfunction]
+#[pyo3(signature = (path_in, path_out, format = "int", length_limit = None))]
+fn mask_file(
+    path_in: PathBuf,
+    path_out: PathBuf,
+    format: &str,
+    length_limit: Option<usize>,
+    py: Python<'_>,
+) -> PyResult<usize> {
+    let format = models::MaskFormat::from_str(format).map_err(|_| LocalError::InvalidArgument {
+        name: "format",
+        value: format.to_owned(),
+    })?;
+    let length_limit = length_limit.unwrap_or(Settings::get().chunk_limit);
+
+    py.allow_threads(move || {
+        Settings::get()
+            .limits
+            .check_files(std::slice::from_ref(&path_in))?;
+        let lines = files::write_masks_of_file(&path_in, &path_out, format, length_limit)?;
+        let bytes = std::fs::metadata(&path_in)
+            .map_err(|source| LocalError::Io {
+                path: path_in.clone(),
+                source,
+            })?
+            .len();
+        CallStats::new("mask_file", MemoryEstimate::of_blocks(length_limit))
+            .processed(lines, bytes as usize)
+            .record();
+        Ok(lines)
+    })
+}
+
+/// Returns a :class:`MaskChunks` iterator over the masks of every line of the
+/// file at `path`, which yields the masks of each block of roughly
+/// `length_limit` bytes of lines in the container of `output`, as for
+/// :func:`alphabet_masks`, as soon as they are masked.
+///
+/// Only one block is held at a time, however large the file.
+#[pyfunction]
+#[pyo3(signature = (path, output = "list", length_limit = None))]
+fn iter_file_masks(
+    path: PathBuf,
+    output: &str,
+    length_limit: Option<usize>,
+) -> PyResult<lazy::MaskChunks> {
+    let output = parse_output(output)?;
+    let length_limit = length_limit.unwrap_or(Settings::get().chunk_limit);
+    Ok(lazy::MaskChunks::of_file(path, length_limit, output)?)
+}
+
+/// Returns a :class:`MaskChunks` iterator over the masks of the strings of an
+/// Arrow stream, which yields the masks of each of its arrays in the container
+/// of `output`, as for :func:`alphabet_masks`, as soon as they are masked.
+///
+/// `stream` is any object implementing ``__arrow_c_stream__``, such as a
+/// :class:`pyarrow.RecordBatchReader` over a dataset larger than memory, of
+/// ``utf8`` or ``large_utf8`` arrays or of record batches with such a column,
+/// named by `column` unless they only have one. Only one batch is held at a
+/// time, read straight from its Arrow buffers; nulls have no mask.
+#[pyfunction]
+#[pyo3(signature = (stream, column = None, output = "list"))]
+fn iter_arrow_masks(
+    stream: &PyAny,
+    column: Option<&str>,
+    output: &str,
+) -> PyResult<lazy::MaskChunks> {
+    let output = parse_output(output)?;
+    let stream = arrow::StringArrayStream::from_pyobject(stream, column)?;
+    Ok(lazy::MaskChunks::of_arrow(stream, output))
+}
+
+/// Writes every line of `path_in` whose mask matches `predicate` against `mask`
+/// to `path_out`, in order, and returns the number of lines written.
+///
+/// `predicate` is one of ``"subset"``, for lines only using characters of
+/// `mask`; ``"superset"``, for lines using every character of `mask`;
+/// ``"contains"``, for lines using any character of `mask`; or ``"avoids"``, for
+/// lines using none of them. `mask` is either a mask or a string of characters.
+/// Without `mask`, `predicate` is instead a predicate expression, such as
+/// ``"contains(a,e) and not contains(z) and subset(' aeglrst')"``; see
+/// :meth:`Pipeline.filter`. Lines with a character outside the alphabet never
+/// match. The file is read in blocks of roughly `length_limit` bytes which are
+/// masked in parallel.
+#[pyfunction]
+#[pyo3(signature = (path_in, path_out, predicate, mask = None, length_limit = None))]
+fn filter_file(
+    path_in: PathBuf,
+    path_out: PathBuf,
+    predicate: &str,
+    mask: Option<&PyAny>,
+    length_limit: Option<usize>,
+    py: Python<'_>,
+) -> PyResult<usize> {
+    let expression = match mask {
+        Some(mask) => Expression::Match(
+            models::MaskPredicate::from_str(predicate).map_err(|_| {
+                LocalError::InvalidArgument {
+                    name: "predicate",
+                    value: predicate.to_owned(),
+                }
+            })?,
+            extract_mask(mask)?,
+        ),
+        None => Expression::from_str(predicate)?,
+    };
+    let length_limit = length_limit.unwrap_or(Settings::get().chunk_limit);
+
+    py.allow_threads(move || {
+        Settings::get()
+            .limits
+            .check_files(std::slice::from_ref(&path_in))?;
+        let (kept, scanned) = files::filter_file(&path_in, &path_out, &expression, length_limit)?;
+        CallStats::new("filter_file", MemoryEstimate::of_blocks(length_limit))
+            .processed(scanned.lines, scanned.bytes)
+            .record();
+        Ok(kept)
+    })
+}
+
+/// Returns the common alphabet of every line in the given files, overlapping
+/// the file reads with the masking on an internal tokio runtime.
+///
+/// This call still blocks the calling thread (with the GIL released); see
+/// :mod:`alphabet_mask.aio` for an awaitable wrapper.
+#[cfg(feature = "async-io")]
+#[pyfunction]
+fn common_alphabets_files_async(
+    paths: Vec<PathBuf>,
+    length_limit: Option<usize>,
+    py: Python<'_>,
+) -> PyResult<String> {
+    let length_limit = length_limit.unwrap_or(Settings::get().chunk_limit);
+
+    py.allow_threads(move || {
+        Settings::get().limits.check_files(&paths)?;
+        let (mask, scanned) = files::async_io::block_on(
+            files::async_io::find_common_mask_in_files(paths, length_limit),
+        )?;
+        CallStats::new(
+            "common_alphabets_files_async",
+            MemoryEstimate::of_blocks(length_limit),
+        )
+        .processed(scanned.lines, scanned.bytes)
+        .record();
+        Ok(alphabet_mask_core::mask_to_chars(mask))
+    })
+}
+
+/// Returns the words matching the feedback of a game of Wordle so far, in their
+/// original order.
+///
+/// `greens` maps positions to the letter known at each, `yellows` maps positions
+/// to the letters in the word but not at that position, and `grays` holds the
+/// letters not in the word. Positions count from ``0``.
+#[pyfunction]
+#[pyo3(signature = (words, greens = HashMap::new(), yellows = HashMap::new(), grays = ""))]
+fn filter_wordle(
+    #[pyo3(from_py_with = "text::to_strs")] words: Vec<&str>,
+    greens: HashMap<usize, char>,
+    yellows: HashMap<usize, String>,
+    grays: &str,
+    py: Python<'_>,
+) -> PyResult<Vec<String>> {
+    let yellows = yellows
+        .iter()
+        .flat_map(|(&position, letters)| letters.chars().map(move |letter| (position, letter)));
+    let constraints = WordleConstraints::new(greens, yellows, grays)?;
+    Settings::get().limits.check(words.iter().copied())?;
+
+    py.allow_threads(|| {
+        let matches = lanes::install(words.iter().copied(), || {
+            games::filter_wordle(&words, &constraints)
+        })?;
+        CallStats::new("filter_wordle", MemoryEstimate::of_each(words.len()))
+            .processed_strings(&words)
+            .record();
+        Ok(matches.into_iter().map(String::from).collect())
+    })
+}
+
+/// Returns the words that can be spelt from the tiles of `rack`, plus `blanks`
+/// tiles standing in for any letter, in their original order.
+///
+/// Unlike a comparison of masks, this counts repeated letters: a rack with one
+/// ``e`` cannot spell ``"tee"``.
+#[pyfunction]
+#[pyo3(signature = (words, rack, blanks = 0))]
+fn playable_from_rack(
+    #[pyo3(from_py_with = "text::to_strs")] words: Vec<&str>,
+    rack: &str,
+    blanks: u32,
+    py: Python<'_>,
+) -> PyResult<Vec<String>> {
+    let rack = Signature::new(rack)?;
+    Settings::get().limits.check(words.iter().copied())?;
+
+    py.allow_threads(|| {
+        let matches = lanes::install(words.iter().copied(), || {
+            games::playable_from_rack(&words, &rack, blanks)
+        })?;
+        CallStats::new("playable_from_rack", MemoryEstimate::of_each(words.len()))
+            .processed_strings(&words)
+            .record();
+        Ok(matches.into_iter().map(String::from).collect())
+    })
+}
+
+/// Returns the given words grouped into anagrams of each other, counting repeated
+/// letters.
+///
+/// Groups are in the order of their first word, and words in each group in
+/// their original order; words without any anagram are groups of their own.
+#[pyfunction]
+fn group_anagrams(
+    #[pyo3(from_py_with = "text::to_strs")] words: Vec<&str>,
+    py: Python<'_>,
+) -> PyResult<Vec<Vec<String>>> {
+    Settings::get().limits.check(words.iter().copied())?;
+    py.allow_threads(|| {
+        let groups = lanes::install(words.iter().copied(), || games::group_anagrams(&words))?;
+        CallStats::new("group_anagrams", MemoryEstimate::of_each(words.len()))
+            .processed_strings(&words)
+            .record();
+        Ok(groups
+            .into_iter()
+            .map(|group| group.into_iter().map(String::from).collect())
+            .collect())
+    })
+}
+
+/// Returns which character classes each of the given strings uses, with
+/// statistics of the whole collection, for auditing password composition.
+///
+/// The result is a `dict` of ``masks``, the class mask of each string with bits
+/// :data:`CLASS_LOWER`, :data:`CLASS_UPPER`, :data:`CLASS_DIGIT`,
+/// :data:`CLASS_SYMBOL` and :data:`CLASS_SPACE`; ``strings``, the number of
+/// strings; ``common`` and ``union``, the classes used by every and by any
+/// string; ``coverage``, the number of strings using each class by name; and
+/// ``class_counts``, the number of strings using exactly ``n`` classes at index
+/// ``n``.
+#[pyfunction]
+fn analyse_classes(
+    #[pyo3(from_py_with = "text::to_strs")] strings: Vec<&str>,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    let (masks, stats) = py.allow_threads(|| {
+        Settings::get().limits.check(strings.iter().copied())?;
+        let analysis = lanes::install(strings.iter().copied(), || stats::analyse_classes(&strings));
+        CallStats::new("analyse_classes", MemoryEstimate::of_each(strings.len()))
+            .processed_strings(&strings)
+            .record();
+        Ok::<_, LocalError>(analysis)
+    })?;
+    let coverage = PyDict::new(py);
+    for (class, count) in CharClass::ALL.into_iter().zip(stats.coverage) {
+        coverage.set_item(class.name(), count)?;
+    }
+
+    let dict = PyDict::new(py);
+    dict.set_item(
+        "masks",
+        masks
+            .into_iter()
+            .map(|mask| mask.bits())
+            .collect::<Vec<_>>(),
+    )?;
+    dict.set_item("strings", stats.strings)?;
+    dict.set_item("common", stats.common.bits())?;
+    dict.set_item("union", stats.union.bits())?;
+    dict.set_item("coverage", coverage)?;
+    dict.set_item("class_counts", stats.class_counts.to_vec())?;
+    Ok(dict.into())
+}
+
+/// The violations of a string, as ``(index, character)``.
+type Violations = Vec<(usize, char)>;
+
+/// Returns, for each of the given strings, whether it only uses characters of
+/// `allowed`, with the violations as a list of ``(index, character)``, where
+/// ``index`` counts characters as Python does.
+///
+/// Any character can be allowed, not only those of the masks; the strings are
+/// checked in parallel.
+#[pyfunction]
+fn validate_charset(
+    #[pyo3(from_py_with = "text::to_strs")] strings: Vec<&str>,
+    allowed: &str,
+    py: Python<'_>,
+) -> PyResult<Vec<(bool, Violations)>> {
+    let charset = Charset::new(allowed);
+    Settings::get().limits.check(strings.iter().copied())?;
+
+    Ok(py.allow_threads(|| {
+        let violations = lanes::install(strings.iter().copied(), || {
+            validate::validate_charset(&strings, &charset)
+        });
+        CallStats::new("validate_charset", MemoryEstimate::of_each(strings.len()))
+            .processed_strings(&strings)
+            .record();
+        violations
+            .into_iter()
+            .map(|violations| (violations.is_empty(), violations))
+            .collect()
+    }))
+}
+
+/// Reports the Unicode scripts of the given strings, counted in parallel,
+/// rather than failing on the first character outside the alphabet.
+///
+/// The result is a `dict` of ``strings``, the number of strings; ``union``, the
+/// combined alphabet of their characters within the alphabet; and ``scripts``, a
+/// `dict` keyed by the name of each script found, such as ``"Latin"``,
+/// ``"Greek"`` or ``"Han"``, of a `dict` of ``strings``, the number of strings
+/// using the script, and ``characters``, its number of characters. Digits,
+/// spaces and most punctuation are ``"Common"``, and combining marks
+/// ``"Inherited"``.
+#[pyfunction]
+fn script_coverage(
+    #[pyo3(from_py_with = "text::to_strs")] strings: Vec<&str>,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    let coverage = py.allow_threads(|| {
+        Settings::get().limits.check(strings.iter().copied())?;
+        let coverage = lanes::install(strings.iter().copied(), || {
+            scripts::ScriptCoverage::gather(&strings)
+        });
+        CallStats::new("script_coverage", MemoryEstimate::of_each(strings.len()))
+            .processed_strings(&strings)
+            .record();
+        Ok::<_, LocalError>(coverage)
+    })?;
+
+    let by_script = PyDict::new(py);
+    for (script, count) in coverage.scripts {
+        let dict = PyDict::new(py);
+        dict.set_item("strings", count.strings)?;
+        dict.set_item("characters", count.characters)?;
+        by_script.set_item(script, dict)?;
+    }
+    let dict = PyDict::new(py);
+    dict.set_item("strings", coverage.strings)?;
+    dict.set_item("union", alphabet_mask_core::mask_to_chars(coverage.union))?;
+    dict.set_item("scripts", by_script)?;
+    Ok(dict.into())
+}
+
+/// Checks that every one of the given strings only uses characters of the
+/// built-in `alphabet`, collecting every violation in parallel rather than
+/// stopping at the first, as an audit before ingestion.
+///
+/// `alphabet` is one of ``"en"``, the characters of the masks in either case;
+/// ``"dna"``, the nucleotides ``ACGTN`` in either case; or ``"ascii"``, the
+/// printable ASCII characters.
+///
+/// The result is a `dict` of ``valid``, whether there are no violations;
+/// ``strings``, the number of strings checked; ``violations``, a `list` of
+/// ``(index, violations)`` for each string with any, where ``violations`` is a
+/// `list` of ``(position, character)`` as for :func:`validate_charset`; and
+/// ``characters``, a `dict` of the number of occurrences of each character
+/// outside the alphabet.
+#[pyfunction]
+#[pyo3(signature = (strings, alphabet = "en"))]
+fn validate_corpus(
+    #[pyo3(from_py_with = "text::to_strs")] strings: Vec<&str>,
+    alphabet: &str,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    let charset = Charset::preset(alphabet).ok_or_else(|| LocalError::InvalidArgument {
+        name: "alphabet",
+        value: alphabet.to_owned(),
+    })?;
+
+    let report = py.allow_threads(|| {
+        Settings::get().limits.check(strings.iter().copied())?;
+        let report = lanes::install(strings.iter().copied(), || {
+            validate::validate_corpus(&strings, &charset)
+        });
+        CallStats::new("validate_corpus", MemoryEstimate::of_each(strings.len()))
+            .processed_strings(&strings)
+            .record();
+        Ok::<_, LocalError>(report)
+    })?;
+
+    let characters = PyDict::new(py);
+    for (character, count) in &report.characters {
+        characters.set_item(character, count)?;
+    }
+    let dict = PyDict::new(py);
+    dict.set_item("valid", report.is_valid())?;
+    dict.set_item("strings", report.strings)?;
+    dict.set_item("violations", report.violations)?;
+    dict.set_item("characters", characters)?;
+    Ok(dict.into())
+}
+
+/// Returns the characters contained in at least `threshold` of the given
+/// strings, a proportion from ``0`` to ``1``; with ``1``, the same as
+/// :func:`common_alphabets`.
+///
+/// Unlike the common alphabet, a character is kept even if a few noisy strings
+/// lack it. The number of strings containing each character is counted in
+/// parallel; without strings, every character is returned.
+///
+/// With `weights`, a sequence of non-negative `float` of the same length as
+/// `strings`, such as their lengths, each string counts for its weight instead,
+/// and `threshold` is a proportion of the total weight.
+#[pyfunction]
+#[pyo3(signature = (strings, threshold = 0.95, weights = None))]
+fn common_alphabets_threshold(
+    #[pyo3(from_py_with = "text::to_strs")] strings: Vec<&str>,
+    threshold: f64,
+    weights: Option<Vec<f64>>,
+    py: Python<'_>,
+) -> PyResult<String> {
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(LocalError::InvalidArgument {
+            name: "threshold",
+            value: threshold.to_string(),
+        }
+        .into());
+    }
+    if let Some(weights) = &weights {
+        check_weights(weights, strings.len())?;
+    }
+
+    py.allow_threads(|| {
+        Settings::get().limits.check(strings.iter().copied())?;
+        let mask = lanes::install(strings.iter().copied(), || match &weights {
+            Some(weights) => stats::WeightedCoverage::gather(&strings, weights)
+                .map(|coverage| coverage.threshold_mask(threshold)),
+            None => {
+                stats::CorpusStats::gather(&strings).map(|stats| stats.threshold_mask(threshold))
+            }
+        })?;
+        CallStats::new(
+            "common_alphabets_threshold",
+            MemoryEstimate::of_each(strings.len()),
+        )
+        .processed_strings(&strings)
+        .record();
+        Ok(alphabet_mask_core::mask_to_chars(mask))
+    })
+}
+
+/// Returns the combined alphabet of the given strings and the characters
+/// contained in at least `threshold` of them, as :func:`union_alphabets` and
+/// :func:`common_alphabets_threshold` do, in a single parallel pass.
+///
+/// With ``approx=True``, only a random sample of the strings is masked, of
+/// `sample_size` strings or a `fraction` of them, 10,000 strings by default; the
+/// same `seed` always selects the same strings. Both alphabets are then
+/// estimates: a character used by few strings may be missing from ``union``.
+///
+/// The result is a `dict` of ``union`` and ``common``, the two alphabets;
+/// ``strings``, the number of strings given; and ``sample_size``, the number of
+/// strings masked.
+#[pyfunction]
+#[pyo3(signature = (
+    strings,
+    threshold = 0.95,
+    approx = false,
+    sample_size = None,
+    fraction = None,
+    seed = 0,
+))]
+fn summarise_alphabets(
+    #[pyo3(from_py_with = "text::to_strs")] strings: Vec<&str>,
+    threshold: f64,
+    approx: bool,
+    sample_size: Option<usize>,
+    fraction: Option<f64>,
+    seed: u64,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(LocalError::InvalidArgument {
+            name: "threshold",
+            value: threshold.to_string(),
+        }
+        .into());
+    }
+    let size = match (approx, sample_size, fraction) {
+        (false, None, None) => strings.len(),
+        (true, None, None) => alphabet_mask_core::sample::DEFAULT_SAMPLE_SIZE,
+        (true, Some(0), None) => {
+            return Err(LocalError::InvalidArgument {
+                name: "sample_size",
+                value: "0".to_owned(),
+            }
+            .into())
+        }
+        (true, Some(size), None) => size,
+        (true, None, Some(fraction)) if fraction > 0.0 && fraction <= 1.0 => {
+            (fraction * strings.len() as f64).ceil() as usize
+        }
+        (true, None, Some(fraction)) => {
+            return Err(LocalError::InvalidArgument {
+                name: "fraction",
+                value: fraction.to_string(),
+            }
+            .into())
+        }
+        (false, None, Some(_)) => {
+            return Err(LocalError::InvalidArgument {
+                name: "fraction",
+                value: "a fraction without approx=True".to_owned(),
+            }
+            .into())
+        }
+        _ => {
+            return Err(LocalError::InvalidArgument {
+                name: "sample_size",
+                value: "a sample size without approx=True, or with a fraction".to_owned(),
+            }
+            .into())
+        }
+    };
+
+    let stats = py.allow_threads(|| {
+        // The whole input is limited, not only the sample, before sampling.
+        Settings::get().limits.check(strings.iter().copied())?;
+        let sample: Vec<&str> = if size < strings.len() {
+            alphabet_mask_core::sample::sample_indices(strings.len(), size, seed)
+                .into_iter()
+                .map(|index| strings[index])
+                .collect()
+        } else {
+            strings.clone()
+        };
+        let stats = lanes::install(sample.iter().copied(), || {
+            stats::CorpusStats::gather(&sample)
+        })?;
+        CallStats::new("summarise_alphabets", MemoryEstimate::of_each(sample.len()))
+            .processed_strings(&sample)
+            .record();
+        Ok::<_, LocalError>(stats)
+    })?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("union", alphabet_mask_core::mask_to_chars(stats.union))?;
+    dict.set_item(
+        "common",
+        alphabet_mask_core::mask_to_chars(stats.threshold_mask(threshold)),
+    )?;
+    dict.set_item("strings", strings.len())?;
+    dict.set_item("sample_size", stats.strings)?;
+    Ok(dict.into())
+}
+
+/// Fail unless there are `length` weights, each finite and non-negative.
+fn check_weights(weights: &[f64], length: usize) -> Result<(), LocalError> {
+    if weights.len() != length {
+        return Err(LocalError::InvalidArgument {
+            name: "weights",
+            value: format!("{} weights for {length} strings", weights.len()),
+        });
+    }
+    match weights
+        .iter()
+        .find(|weight| !weight.is_finite() || weight.is_sign_negative())
+    {
+        Some(weight) => Err(LocalError::InvalidArgument {
+            name: "weights",
+            value: weight.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Compares two collections of strings, such as a corpus before and after
+/// cleaning.
+///
+/// The result is a `dict` of ``strings_a`` and ``strings_b``, the number of
+/// strings in each; ``common_a``, ``common_b``, ``union_a`` and ``union_b``, the
+/// common and combined alphabets of each; ``only_a`` and ``only_b``, the
+/// characters used by one collection but not the other; and ``coverage_delta``,
+/// the change in the proportion of strings containing each character used by
+/// either collection, from `a` to `b`.
+#[pyfunction]
+fn compare_corpora(
+    #[pyo3(from_py_with = "text::to_strs")] a: Vec<&str>,
+    #[pyo3(from_py_with = "text::to_strs")] b: Vec<&str>,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    let comparison = py.allow_threads(|| {
+        Settings::get().limits.check(a.iter().chain(&b).copied())?;
+        let comparison = lanes::install(a.iter().chain(&b).copied(), || {
+            stats::compare_corpora(&a, &b)
+        })?;
+        let strings = a.len() + b.len();
+        let bytes = a.iter().chain(&b).map(|string| string.len()).sum();
+        CallStats::new("compare_corpora", MemoryEstimate::of_each(strings))
+            .processed(strings, bytes)
+            .record();
+        Ok::<_, LocalError>(comparison)
+    })?;
+    let deltas = comparison.coverage_deltas();
+    let coverage_delta = PyDict::new(py);
+    for bit in
+        (0..Mask::WIDTH).filter(|&bit| (comparison.a.union | comparison.b.union).contains_bit(bit))
+    {
+        coverage_delta.set_item(models::bit_char(bit), deltas[usize::from(bit)])?;
+    }
+
+    let chars = alphabet_mask_core::mask_to_chars;
+    let dict = PyDict::new(py);
+    dict.set_item("strings_a", comparison.a.strings)?;
+    dict.set_item("strings_b", comparison.b.strings)?;
+    dict.set_item("common_a", chars(comparison.a.common))?;
+    dict.set_item("common_b", chars(comparison.b.common))?;
+    dict.set_item("union_a", chars(comparison.a.union))?;
+    dict.set_item("union_b", chars(comparison.b.union))?;
+    dict.set_item("only_a", chars(comparison.only_a()))?;
+    dict.set_item("only_b", chars(comparison.only_b()))?;
+    dict.set_item("coverage_delta", coverage_delta)?;
+    Ok(dict.into())
+}
+
+/// Returns the common and combined masks of `alphabets`, either a sequence of
+/// `str` or a :class:`CountedAlphabet`, with the number of strings and bytes
+/// masked, none for a :class:`CountedAlphabet`.
+fn alphabets_of(alphabets: &PyAny, py: Python<'_>) -> PyResult<((Mask, Mask), usize, usize)> {
+    if let Ok(counted) = alphabets.extract::<PyRef<'_, counted::PyCountedAlphabet>>() {
+        return Ok((counted.masks(), 0, 0));
+    }
+
+    let strings = text::to_strs(alphabets)?;
+    let stats = py.allow_threads(|| {
+        Settings::get().limits.check(strings.iter().copied())?;
+        lanes::install(strings.iter().copied(), || {
+            stats::CorpusStats::gather(&strings)
+        })
+    })?;
+    let bytes = strings.iter().map(|string| string.len()).sum();
+    Ok(((stats.common, stats.union), strings.len(), bytes))
+}
+
+/// Returns the characters gained and lost by the common and combined alphabets
+/// from `old` to `new`, such as two releases of a dataset, each either a
+/// sequence of `str` or a :class:`CountedAlphabet`.
+///
+/// The result is a `dict` of ``union_gained`` and ``union_lost``, the characters
+/// of only the new or only the old combined alphabet; and ``common_gained`` and
+/// ``common_lost``, likewise for the common alphabets.
+#[pyfunction]
+fn diff_alphabets(old: &PyAny, new: &PyAny, py: Python<'_>) -> PyResult<PyObject> {
+    let (old_masks, old_strings, old_bytes) = alphabets_of(old, py)?;
+    let (new_masks, new_strings, new_bytes) = alphabets_of(new, py)?;
+    let diff = stats::AlphabetDiff::between(old_masks, new_masks);
+    let strings = old_strings + new_strings;
+    CallStats::new("diff_alphabets", MemoryEstimate::of_each(strings))
+        .processed(strings, old_bytes + new_bytes)
+        .record();
+
+    let chars = alphabet_mask_core::mask_to_chars;
+    let dict = PyDict::new(py);
+    dict.set_item("union_gained", chars(diff.union_gained))?;
+    dict.set_item("union_lost", chars(diff.union_lost))?;
+    dict.set_item("common_gained", chars(diff.common_gained))?;
+    dict.set_item("common_lost", chars(diff.common_lost))?;
+    Ok(dict.into())
+}
+
+/// Profiles each of the given strings in a single parallel pass, returning a
+/// `dict` of columns ready for a DataFrame.
+///
+/// The columns are ``mask``, the bit mask of each string with characters outside
+/// the alphabet skipped; ``coverage``, the proportion of the 26 letters used;
+/// ``pangram``, whether every letter is used; ``has_digits``, whether any ASCII
+/// digit was skipped; and ``punctuation_only``, whether the string has ASCII
+/// punctuation and nothing else besides whitespace.
+#[pyfunction]
+fn profile(
+    #[pyo3(from_py_with = "text::to_strs")] strings: Vec<&str>,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    let profiles = py.allow_threads(|| {
+        Settings::get().limits.check(strings.iter().copied())?;
+        let profiles = lanes::install(strings.iter().copied(), || {
+            alphabet_mask_core::profile::profile(&strings)
+        });
+        CallStats::new("profile", MemoryEstimate::of_each(strings.len()))
+            .processed_strings(&strings)
+            .record();
+        Ok::<_, LocalError>(profiles)
+    })?;
+    let masks: Vec<u32> = profiles.masks.iter().map(|mask| mask.bits()).collect();
+
+    let dict = PyDict::new(py);
+    dict.set_item("mask", masks)?;
+    dict.set_item("coverage", profiles.coverage)?;
+    dict.set_item("pangram", profiles.pangram)?;
+    dict.set_item("has_digits", profiles.has_digits)?;
+    dict.set_item("punctuation_only", profiles.punctuation_only)?;
+    Ok(dict.into())
+}
+
+/// Returns, for each of the given DNA sequences, the characters outside the
+/// nucleotide alphabet ``ACGTN`` as a sorted `str`, and their positions.
+///
+/// Sequences are validated in parallel, regardless of case.
+#[pyfunction]
+fn invalid_bases(
+    #[pyo3(from_py_with = "text::to_strs")] sequences: Vec<&str>,
+    py: Python<'_>,
+) -> PyResult<Vec<(String, Vec<usize>)>> {
+    Settings::get().limits.check(sequences.iter().copied())?;
+    Ok(py.allow_threads(|| {
+        let invalid = lanes::install(sequences.iter().copied(), || bio::invalid_bases(&sequences));
+        CallStats::new("invalid_bases", MemoryEstimate::of_each(sequences.len()))
+            .processed_strings(&sequences)
+            .record();
+        invalid
+            .into_iter()
+            .map(|invalid| (invalid.characters.into_iter().collect(), invalid.positions))
+            .collect()
+    }))
+}
+
+/// Returns ``(id, characters, positions)`` for every record of a FASTA or FASTQ
+/// file with any invalid bases, as :func:`invalid_bases` does.
+///
+/// The file is streamed, and its records validated in parallel batches.
+#[pyfunction]
+fn invalid_bases_file(
+    path: PathBuf,
+    py: Python<'_>,
+) -> PyResult<Vec<(String, String, Vec<usize>)>> {
+    Settings::get()
+        .limits
+        .check_files(std::slice::from_ref(&path))?;
+    py.allow_threads(|| {
+        let scan = fastx::invalid_bases_in_file(&path)?;
+        CallStats::new(
+            "invalid_bases_file",
+            MemoryEstimate::of_each(fastx::BATCH_LENGTH),
+        )
+        .processed(scan.records, scan.bytes)
+        .record();
+        Ok(scan
+            .invalid
+            .into_iter()
+            .map(|(id, invalid)| {
+                (
+                    id,
+                    invalid.characters.into_iter().collect(),
+                    invalid.positions,
+                )
+            })
+            .collect())
+    })
+}
+
+/// Forwards the `tracing` spans of this library at `level` or above to the
+/// ``alphabet_mask`` logger of :mod:`logging`, with the duration of each span.
+///
+/// `level` is one of ``"trace"``, ``"debug"``, ``"info"``, ``"warn"`` or
+/// ``"error"``. Returns ``False`` if spans were already being forwarded.
+#[cfg(feature = "trace")]
+#[pyfunction]
+#[pyo3(signature = (level = "debug"))]
+fn enable_tracing(level: &str) -> PyResult<bool> {
+    let level = tracing::Level::from_str(level).map_err(|_| LocalError::InvalidArgument {
+        name: "level",
+        value: level.to_owned(),
+    })?;
+
+    Ok(crate::trace::logging::enable(level))
+}
+
+/// Sets the default `length_limit` in bytes of every later call which omits it,
+/// replacing ``ALPHABET_MASK_CHUNK_LIMIT`` or the result of :func:`calibrate`.
+///
+/// Calls mask their strings in parallel, in chunks of this size, once they exceed
+/// it in total; :data:`LENGTH_LIMIT_PER_CHUNK` is the built-in default. Raises a
+/// `ValueError` unless `length_limit` is positive.
+#[pyfunction]
+fn set_chunk_limit(length_limit: usize) -> PyResult<()> {
+    if length_limit == 0 {
+        return Err(LocalError::InvalidArgument {
+            name: "length_limit",
+            value: length_limit.to_string(),
+        }
+        .into());
+    }
+    Settings::update(|settings| settings.chunk_limit = length_limit);
+    Ok(())
+}
+
+/// Returns the default `length_limit` of every call, as set by
+/// :func:`set_chunk_limit`.
+#[pyfunction]
+fn chunk_limit() -> usize {
+    Settings::get().chunk_limit
+}
+
+/// Measures from which input size masking in parallel is faster than serially
+/// on this machine, and uses it as the default `length_limit` of every later
+/// call, which parallelises once its strings exceed it in total.
+///
+/// Each power of two limit from 4 KiB up to `max_chunk_limit` bytes is timed
+/// `repeats` times on synthetic strings; calibrating takes longer the larger
+/// `max_chunk_limit` is. If `path` is given, the result is also saved there, to be
+/// loaded on import by setting ``ALPHABET_MASK_CALIBRATION`` to the path.
+///
+/// Returns a `dict` of ``chunk_limit``; ``threads``, the size of the pool it was
+/// measured with; and ``samples``, a `list` of ``(chunk_limit, serial,
+/// parallel)`` with the fastest time in seconds of each path.
+#[pyfunction]
+#[pyo3(signature = (
+    path = None,
+    max_chunk_limit = calibration::DEFAULT_MAX_CHUNK_LIMIT,
+    repeats = calibration::DEFAULT_REPEATS,
+))]
+fn calibrate(
+    path: Option<PathBuf>,
+    max_chunk_limit: usize,
+    repeats: usize,
+    py: Python<'_>,
+) -> PyResult<PyObject> {
+    if repeats == 0 {
+        return Err(LocalError::InvalidArgument {
+            name: "repeats",
+            value: repeats.to_string(),
+        }
+        .into());
+    }
+
+    let calibration =
+        py.allow_threads(|| calibration::Calibration::measure(max_chunk_limit, repeats));
+    if let Some(path) = path {
+        calibration.save(&path)?;
+    }
+    Settings::update(|settings| settings.chunk_limit = calibration.chunk_limit);
+
+    let dict = PyDict::new(py);
+    dict.set_item("chunk_limit", calibration.chunk_limit)?;
+    dict.set_item("threads", calibration.threads)?;
+    dict.set_item(
+        "samples",
+        calibration
+            .samples
+            .iter()
+            .map(|sample| {
+                (
+                    sample.chunk_limit,
+                    sample.serial.as_secs_f64(),
+                    sample.parallel.as_secs_f64(),
+                )
+            })
+            .collect::<Vec<_>>(),
+    )?;
+    Ok(dict.into())
+}
+
+/// Limits the inputs of every later call, which raises
+/// :class:`InputTooLargeError` before any work begins if its strings exceed
+/// `max_bytes` in total length, `max_strings` in number, or `max_string_length`
+/// for any one of them; files are only checked against `max_bytes`.
+///
+/// Each limit defaults to ``None``, for no limit, replacing any set before or
+/// read from ``ALPHABET_MASK_MAX_BYTES``, ``ALPHABET_MASK_MAX_STRINGS`` and
+/// ``ALPHABET_MASK_MAX_STRING_LENGTH``.
+#[pyfunction]
+#[pyo3(signature = (max_bytes = None, max_strings = None, max_string_length = None))]
+fn set_input_limits(
+    max_bytes: Option<usize>,
+    max_strings: Option<usize>,
+    max_string_length: Option<usize>,
+) {
+    Settings::update(|settings| {
+        settings.limits = Limits {
+            max_bytes,
+            max_strings,
+            max_string_length,
+        }
+    });
+}
+
+/// Enables or disables the ``workers`` of :class:`CallStats`, which report the
+/// chunks and bytes masked by each thread during every later call finding a
+/// common alphabet, replacing ``ALPHABET_MASK_WORKER_STATS``.
+///
+/// Collecting them adds a little work to each chunk, so they are disabled by
+/// default.
+#[pyfunction]
+#[pyo3(signature = (enabled = true))]
+fn set_worker_stats(enabled: bool) {
+    Settings::update(|settings| settings.worker_stats = enabled);
+}
+
+/// Returns the limits set by :func:`set_input_limits`, as a `dict` of
+/// ``max_bytes``, ``max_strings`` and ``max_string_length``.
+#[pyfunction]
+fn input_limits(py: Python<'_>) -> PyResult<PyObject> {
+    let limits = Settings::get().limits;
+    let dict = PyDict::new(py);
+    dict.set_item("max_bytes", limits.max_bytes)?;
+    dict.set_item("max_strings", limits.max_strings)?;
+    dict.set_item("max_string_length", limits.max_string_length)?;
+    Ok(dict.into())
+}
+
+/// Simply returns a set of the alphabet letters in the given string.
+///
+/// For speed comparisons only.
+#[pyfunction]
+fn alphabet_set(string: &str, py: Python<'_>) -> PyResult<PyObject> {
+    let set = FxHashSet::from_iter(string.chars().map(|c| c.to_ascii_lowercase() as u8));
+    Ok(set.into_py(py))
+}
+
+/// Returns how this library was built and what the CPU supports, as a `dict` of
+/// ``version``, ``features``, ``layout_version``, ``target_arch`` and
+/// ``cpu_features``.
+///
+/// Compare these across deployment targets when their performance differs.
+#[pyfunction]
+fn build_info(py: Python<'_>) -> PyResult<PyObject> {
+    let info = info::BuildInfo::current();
+    let dict = PyDict::new(py);
+
+    dict.set_item("version", info.version)?;
+    dict.set_item("features", info.features)?;
+    dict.set_item("layout_version", info.layout_version)?;
+    dict.set_item("target_arch", info.target_arch)?;
+    dict.set_item("cpu_features", info.cpu_features)?;
+    Ok(dict.into())
+}
+
+/// Returns the :class:`CallStats` of the last call on this thread of a function
+/// over many strings, or ``None`` if there was none.
+#[pyfunction]
+fn last_call_stats() -> Option<CallStats> {
+    CallStats::last()
+}
+
+/// Returns a `dict` of the ``calls``, ``strings`` and ``bytes`` processed so far
+/// by every thread, since the module was imported or :func:`reset_counters` was
+/// last called.
+///
+/// Every function masking strings or the lines of files counts, as well as the
+/// methods of :class:`Masker`; a call counts once it succeeds.
+#[pyfunction]
+fn get_counters(py: Python<'_>) -> PyResult<PyObject> {
+    counters_into_py(py, Counters::get())
+}
+
+/// Sets the counters of :func:`get_counters` back to zero, returning what they
+/// were in the same `dict`.
+#[pyfunction]
+fn reset_counters(py: Python<'_>) -> PyResult<PyObject> {
+    counters_into_py(py, Counters::reset())
+}
+
+fn counters_into_py(py: Python<'_>, counters: Counters) -> PyResult<PyObject> {
+    let dict = PyDict::new(py);
+    dict.set_item("calls", counters.calls)?;
+    dict.set_item("strings", counters.strings)?;
+    dict.set_item("bytes", counters.bytes)?;
+    Ok(dict.into())
+}
+
+/// A Python module implemented in Rust.
+#[pymodule]
+fn lib_alphabet_mask(py: Python, m: &PyModule) -> PyResult<()> {
+    Settings::init()?;
+    m.add(
+        "InputTooLargeError",
+        py.get_type::<models::InputTooLargeError>(),
+    )?;
+    m.add(
+        "LENGTH_LIMIT_PER_CHUNK",
+        alphabet_mask_core::chunks::LENGTH_LIMIT_PER_CHUNK,
+    )?;
+    m.add_class::<masker::PyMasker>()?;
+    m.add_class::<index::PyWordIndex>()?;
+    m.add_class::<follow::FileStream>()?;
+    m.add_class::<counted::PyCountedAlphabet>()?;
+    m.add_class::<pipeline::PyPipeline>()?;
+    m.add_class::<PyAlphabetMask>()?;
+    m.add_class::<PyAlphabet>()?;
+    m.add_class::<PyUnicodeMask>()?;
+    m.add_class::<lazy::MaskChunks>()?;
+    m.add_class::<shared::SharedStrings>()?;
+    m.add_class::<worker::PyBatchWorker>()?;
+    m.add_class::<CallStats>()?;
+    m.add_function(wrap_pyfunction!(alphabet_mask, m)?)?;
+    m.add_function(wrap_pyfunction!(analyse_classes, m)?)?;
+    m.add_function(wrap_pyfunction!(alphabet_masks, m)?)?;
+    m.add_function(wrap_pyfunction!(alphabet_masks_for_files, m)?)?;
+    m.add_function(wrap_pyfunction!(alphabet_set, m)?)?;
+    m.add_function(wrap_pyfunction!(bits_to_mask, m)?)?;
+    m.add_function(wrap_pyfunction!(bools_to_mask, m)?)?;
+    m.add_function(wrap_pyfunction!(build_info, m)?)?;
+    m.add_function(wrap_pyfunction!(calibrate, m)?)?;
+    m.add_function(wrap_pyfunction!(chars_to_mask, m)?)?;
+    m.add_function(wrap_pyfunction!(chunk_limit, m)?)?;
+    m.add_function(wrap_pyfunction!(common_alphabets, m)?)?;
+    m.add_function(wrap_pyfunction!(common_alphabets_with, m)?)?;
+    m.add_function(wrap_pyfunction!(mask_string_with, m)?)?;
+    m.add_function(wrap_pyfunction!(common_unicode_alphabets, m)?)?;
+    m.add_function(wrap_pyfunction!(unicode_mask, m)?)?;
+    m.add_function(wrap_pyfunction!(common_mask, m)?)?;
+    m.add_function(wrap_pyfunction!(compare_corpora, m)?)?;
+    m.add_function(wrap_pyfunction!(diff_alphabets, m)?)?;
+    m.add_function(wrap_pyfunction!(filter_file, m)?)?;
+    m.add_function(wrap_pyfunction!(iter_file_masks, m)?)?;
+    m.add_function(wrap_pyfunction!(iter_arrow_masks, m)?)?;
+    m.add_function(wrap_pyfunction!(filter_wordle, m)?)?;
+    m.add_function(wrap_pyfunction!(format_mask, m)?)?;
+    m.add_function(wrap_pyfunction!(get_counters, m)?)?;
+    m.add_function(wrap_pyfunction!(group_anagrams, m)?)?;
+    m.add_function(wrap_pyfunction!(union_alphabets, m)?)?;
+    m.add_function(wrap_pyfunction!(partial_mask, m)?)?;
+    m.add_function(wrap_pyfunction!(plan, m)?)?;
+    m.add_function(wrap_pyfunction!(reset_counters, m)?)?;
+    m.add_function(wrap_pyfunction!(segment_by_common_alphabet, m)?)?;
+    m.add_function(wrap_pyfunction!(set_chunk_limit, m)?)?;
+    m.add_function(wrap_pyfunction!(summarise_alphabets, m)?)?;
+    m.add_function(wrap_pyfunction!(set_input_limits, m)?)?;
+    m.add_function(wrap_pyfunction!(set_worker_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_charset, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_corpus, m)?)?;
+    m.add_function(wrap_pyfunction!(script_coverage, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_mask, m)?)?;
+    m.add_function(wrap_pyfunction!(playable_from_rack, m)?)?;
+    m.add_function(wrap_pyfunction!(profile, m)?)?;
+    m.add_function(wrap_pyfunction!(combine_masks, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_partial_mask, m)?)?;
+    m.add_function(wrap_pyfunction!(common_alphabets_arrow, m)?)?;
+    m.add_function(wrap_pyfunction!(common_alphabets_files, m)?)?;
+    m.add_function(wrap_pyfunction!(column_alphabets_csv, m)?)?;
+    m.add_function(wrap_pyfunction!(common_alphabets_threshold, m)?)?;
+    m.add_function(wrap_pyfunction!(mask_file, m)?)?;
+    m.add_function(wrap_pyfunction!(invalid_bases, m)?)?;
+    m.add_function(wrap_pyfunction!(input_limits, m)?)?;
+    m.add_function(wrap_pyfunction!(intersect_with, m)?)?;
+    m.add_function(wrap_pyfunction!(invalid_bases_file, m)?)?;
+    m.add_function(wrap_pyfunction!(last_call_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(letter_count, m)?)?;
+    m.add_function(wrap_pyfunction!(mask_to_bits, m)?)?;
+    m.add_function(wrap_pyfunction!(mask_to_bools, m)?)?;
+    m.add_function(wrap_pyfunction!(mask_to_chars, m)?)?;
+    m.add_function(wrap_pyfunction!(missing_letters_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(char_transitions, m)?)?;
+    m.add_function(wrap_pyfunction!(token_masks, m)?)?;
+    m.add_function(wrap_pyfunction!(line_masks, m)?)?;
+    m.add_function(wrap_pyfunction!(normalize_mask, m)?)?;
+    #[cfg(feature = "async-io")]
+    m.add_function(wrap_pyfunction!(common_alphabets_files_async, m)?)?;
+    #[cfg(feature = "trace")]
+    m.add_function(wrap_pyfunction!(enable_tracing, m)?)?;
+    for (name, bit) in models::bits::NAMED_BITS {
+        m.add(name, bit)?;
+    }
+    m.add("LETTERS_MASK", models::bits::LETTERS_MASK.bits())?;
+    m.add("PUNCT_MASK", models::bits::PUNCT_MASK.bits())?;
+    for class in CharClass::ALL {
+        let name = format!("CLASS_{}", class.name().to_uppercase());
+        m.add(name.as_str(), class.mask().bits())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,320 @@
+//! Process-wide defaults, read from environment variables when the Python module
+//! is imported:
+//!
+//! - `ALPHABET_MASK_CHUNK_LIMIT`: the default `length_limit` in bytes of the
+//!   chunks masked in parallel, which `set_chunk_limit()` replaces.
+//! - `ALPHABET_MASK_CALIBRATION`: a file saved by `calibrate()`, whose chunk
+//!   limit is used unless `ALPHABET_MASK_CHUNK_LIMIT` is also set.
+//! - `ALPHABET_MASK_NUM_THREADS`: the number of threads of the global pool, or
+//!   one per CPU if `0`.
+//! - `ALPHABET_MASK_ON_INVALID`: the default `on_invalid` policy of `Masker`,
+//!   `error` or `skip`.
+//! - `ALPHABET_MASK_ON_CONTROL`: the default `on_control` policy of `Masker`,
+//!   `error`, `skip` or `space`.
+//! - `ALPHABET_MASK_WORKER_STATS`: whether `CallStats` report the work of each
+//!   thread, `true` or `false`, which `set_worker_stats()` replaces.
+//! - `ALPHABET_MASK_MAX_BYTES`, `ALPHABET_MASK_MAX_STRINGS` and
+//!   `ALPHABET_MASK_MAX_STRING_LENGTH`: the initial [`Limits`] on the inputs of
+//!   every call, which `set_input_limits()` replaces.
+//!
+//! Arguments given to a call always take precedence; an invalid value fails the
+//! import rather than the first call using it.
+//!
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use crate::calibration::Calibration;
+use crate::models::{ControlCharPolicy, InvalidCharPolicy, LocalError};
+use alphabet_mask_core::chunks;
+
+static SETTINGS: RwLock<Option<Settings>> = RwLock::new(None);
+
+/// Limits on the size of the inputs of a call, checked before any work begins;
+/// [`None`] is unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct Limits {
+    /// The total length of every string in bytes, or of every file.
+    pub(crate) max_bytes: Option<usize>,
+    /// The number of strings.
+    pub(crate) max_strings: Option<usize>,
+    /// The length of any one string in bytes.
+    pub(crate) max_string_length: Option<usize>,
+}
+
+impl Limits {
+    /// Fail with [`LocalError::InputTooLarge`] if `strings` exceed any limit.
+    pub(crate) fn check<'s>(
+        &self,
+        strings: impl IntoIterator<Item = &'s str>,
+    ) -> Result<(), LocalError> {
+        if *self == Self::default() {
+            return Ok(());
+        }
+
+        let (mut count, mut bytes) = (0_usize, 0_usize);
+        for string in strings {
+            count += 1;
+            bytes = bytes.saturating_add(string.len());
+            exceeds("max_strings", count, self.max_strings)?;
+            exceeds("max_bytes", bytes, self.max_bytes)?;
+            exceeds("max_string_length", string.len(), self.max_string_length)?;
+        }
+        Ok(())
+    }
+
+    /// Fail with [`LocalError::InputTooLarge`] if the files at `paths` are longer
+    /// than `max_bytes` in total; the other limits are not known before reading.
+    pub(crate) fn check_files(&self, paths: &[PathBuf]) -> Result<(), LocalError> {
+        if self.max_bytes.is_none() {
+            return Ok(());
+        }
+
+        let mut bytes = 0_usize;
+        for path in paths {
+            let metadata = std::fs::metadata(path).map_err(|source| LocalError::Io {
+                path: path.clone(),
+                source,
+            })?;
+            bytes = bytes.saturating_add(usize::try_from(metadata.len()).unwrap_or(usize::MAX));
+            exceeds("max_bytes", bytes, self.max_bytes)?;
+        }
+        Ok(())
+    }
+}
+
+/// Fail if `value` exceeds the limit `name` of `max`.
+fn exceeds(name: &'static str, value: usize, max: Option<usize>) -> Result<(), LocalError> {
+    match max {
+        Some(max) if value > max => Err(LocalError::InputTooLarge {
+            limit: name,
+            value,
+            max,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// The defaults of every call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Settings {
+    pub(crate) chunk_limit: usize,
+    pub(crate) num_threads: Option<usize>,
+    pub(crate) on_invalid: InvalidCharPolicy,
+    pub(crate) on_control: ControlCharPolicy,
+    pub(crate) worker_stats: bool,
+    pub(crate) limits: Limits,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            chunk_limit: chunks::LENGTH_LIMIT_PER_CHUNK,
+            num_threads: None,
+            on_invalid: InvalidCharPolicy::default(),
+            on_control: ControlCharPolicy::default(),
+            worker_stats: false,
+            limits: Limits::default(),
+        }
+    }
+}
+
+/// Parse the variable `name` if it is set.
+fn parse<T: FromStr>(
+    var: &impl Fn(&str) -> Option<String>,
+    name: &'static str,
+) -> Result<Option<T>, LocalError> {
+    var(name)
+        .map(|value| {
+            value
+                .trim()
+                .parse()
+                .map_err(|_| LocalError::InvalidArgument { name, value })
+        })
+        .transpose()
+}
+
+impl Settings {
+    /// Read the settings with `var`, falling back to the defaults for any unset.
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self, LocalError> {
+        let defaults = Self::default();
+        let chunk_limit = parse(&var, "ALPHABET_MASK_CHUNK_LIMIT")?;
+        if chunk_limit == Some(0) {
+            return Err(LocalError::InvalidArgument {
+                name: "ALPHABET_MASK_CHUNK_LIMIT",
+                value: "0".to_owned(),
+            });
+        }
+
+        let chunk_limit = match (chunk_limit, var("ALPHABET_MASK_CALIBRATION")) {
+            (None, Some(path)) => Some(Calibration::load(path.trim().as_ref())?.chunk_limit),
+            (chunk_limit, _) => chunk_limit,
+        };
+
+        Ok(Self {
+            chunk_limit: chunk_limit.unwrap_or(defaults.chunk_limit),
+            num_threads: parse(&var, "ALPHABET_MASK_NUM_THREADS")?,
+            on_invalid: parse(&var, "ALPHABET_MASK_ON_INVALID")?.unwrap_or(defaults.on_invalid),
+            on_control: parse(&var, "ALPHABET_MASK_ON_CONTROL")?.unwrap_or(defaults.on_control),
+            worker_stats: parse(&var, "ALPHABET_MASK_WORKER_STATS")?
+                .unwrap_or(defaults.worker_stats),
+            limits: Limits {
+                max_bytes: parse(&var, "ALPHABET_MASK_MAX_BYTES")?,
+                max_strings: parse(&var, "ALPHABET_MASK_MAX_STRINGS")?,
+                max_string_length: parse(&var, "ALPHABET_MASK_MAX_STRING_LENGTH")?,
+            },
+        })
+    }
+
+    /// Read the settings from the environment, and size the global thread pool.
+    ///
+    /// Only the first call reads the environment.
+    pub(crate) fn init() -> Result<Self, LocalError> {
+        // A poisoned lock still holds valid settings, since they are `Copy`.
+        let mut current = SETTINGS.write().unwrap_or_else(|err| err.into_inner());
+        if let Some(settings) = *current {
+            return Ok(settings);
+        }
+
+        let settings = Self::from_vars(|name| std::env::var(name).ok())?;
+        if let Some(threads) = settings.num_threads {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build_global()
+                .map_err(|err| LocalError::Unknown {
+                    context: format!("could not start a thread pool: {err}"),
+                })?;
+        }
+        *current = Some(settings);
+        Ok(settings)
+    }
+
+    /// The settings read by [`Settings::init`], or the defaults before then.
+    pub(crate) fn get() -> Self {
+        SETTINGS
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .unwrap_or_default()
+    }
+
+    /// Change the settings of every later call.
+    pub(crate) fn update(change: impl FnOnce(&mut Self)) {
+        let mut current = SETTINGS.write().unwrap_or_else(|err| err.into_inner());
+        change(current.get_or_insert_with(Self::default));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn settings(vars: &[(&str, &str)]) -> Result<Settings, LocalError> {
+        Settings::from_vars(|name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn from_vars() {
+        assert_eq!(settings(&[]).unwrap(), Settings::default());
+        assert_eq!(
+            settings(&[
+                ("ALPHABET_MASK_CHUNK_LIMIT", "4096"),
+                ("ALPHABET_MASK_NUM_THREADS", " 2 "),
+                ("ALPHABET_MASK_ON_INVALID", "skip"),
+                ("ALPHABET_MASK_ON_CONTROL", "space"),
+                ("ALPHABET_MASK_WORKER_STATS", "true"),
+            ])
+            .unwrap(),
+            Settings {
+                chunk_limit: 4096,
+                num_threads: Some(2),
+                on_invalid: InvalidCharPolicy::Skip,
+                on_control: ControlCharPolicy::Space,
+                worker_stats: true,
+                limits: Limits::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let limits = settings(&[
+            ("ALPHABET_MASK_MAX_BYTES", "10"),
+            ("ALPHABET_MASK_MAX_STRINGS", "3"),
+            ("ALPHABET_MASK_MAX_STRING_LENGTH", "5"),
+        ])
+        .unwrap()
+        .limits;
+
+        assert!(limits.check(["abc", "de", "fghij"]).is_ok());
+        assert!(Limits::default().check(["abcdef"; 10]).is_ok());
+        for (strings, limit) in [
+            (&["a", "b", "c", "d"][..], "max_strings"),
+            (&["abcde", "fghij", "k"][..], "max_bytes"),
+            (&["abcdef"][..], "max_string_length"),
+        ] {
+            assert!(
+                matches!(
+                    limits.check(strings.iter().copied()),
+                    Err(LocalError::InputTooLarge { limit: error, .. }) if error == limit
+                ),
+                "{limit}"
+            );
+        }
+    }
+
+    #[test]
+    fn calibration() {
+        let dir = std::env::temp_dir().join("alphabet_mask_settings_calibration");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("calibration.txt");
+        Calibration {
+            chunk_limit: 8192,
+            threads: 4,
+            samples: Vec::new(),
+        }
+        .save(&path)
+        .unwrap();
+        let path = path.to_str().unwrap();
+
+        let calibrated = settings(&[("ALPHABET_MASK_CALIBRATION", path)]).unwrap();
+        assert_eq!(calibrated.chunk_limit, 8192);
+        // An explicit chunk limit takes precedence.
+        let explicit = settings(&[
+            ("ALPHABET_MASK_CALIBRATION", path),
+            ("ALPHABET_MASK_CHUNK_LIMIT", "4096"),
+        ])
+        .unwrap();
+        assert_eq!(explicit.chunk_limit, 4096);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(
+            settings(&[("ALPHABET_MASK_CALIBRATION", path)]),
+            Err(LocalError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn invalid_vars() {
+        for (name, value) in [
+            ("ALPHABET_MASK_CHUNK_LIMIT", "0"),
+            ("ALPHABET_MASK_CHUNK_LIMIT", "1 MiB"),
+            ("ALPHABET_MASK_NUM_THREADS", "-1"),
+            ("ALPHABET_MASK_ON_INVALID", "ignore"),
+            ("ALPHABET_MASK_ON_CONTROL", "tab"),
+            ("ALPHABET_MASK_WORKER_STATS", "1"),
+            ("ALPHABET_MASK_MAX_BYTES", "1e6"),
+        ] {
+            assert!(
+                matches!(
+                    settings(&[(name, value)]),
+                    Err(LocalError::InvalidArgument { name: error, .. }) if error == name
+                ),
+                "{name}={value}"
+            );
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! The `SharedStrings` Python class, for masking strings straight from a buffer
+//! shared between processes.
+//!
+// pyo3 0.20 expands `#[new]` into trait impls inside a function body.
+#![allow(non_local_definitions)]
+
+use pyo3::buffer::PyBuffer;
+use pyo3::prelude::*;
+
+use crate::models::LocalError;
+
+/// Copy the offsets from a buffer of any integer type, or from a sequence.
+fn extract_offsets(offsets: &PyAny) -> PyResult<Vec<usize>> {
+    let py = offsets.py();
+    let invalid = |_| LocalError::InvalidArgument {
+        name: "offsets",
+        value: "a negative offset".to_owned(),
+    };
+
+    macro_rules! from_buffer {
+        ($($ty:ty),*) => {
+            $(
+                if let Ok(buffer) = PyBuffer::<$ty>::get(offsets) {
+                    return Ok(buffer
+                        .to_vec(py)?
+                        .into_iter()
+                        .map(usize::try_from)
+                        .collect::<Result<_, _>>()
+                        .map_err(invalid)?);
+                }
+            )*
+        };
+    }
+    from_buffer!(i64, u64, i32, u32);
+
+    offsets.extract()
+}
+
+/// Strings stored back to back as UTF-8 in a buffer, such as the ``buf`` of a
+/// :class:`multiprocessing.shared_memory.SharedMemory`, without copying them.
+///
+/// `offsets` holds the start of each string in `buffer` followed by the end of
+/// the last, as in an Arrow string array; it is either a sequence of `int` or a
+/// buffer of integers, such as a NumPy array, which may itself live in shared
+/// memory. Only the strings from `start` up to but excluding `end` are
+/// included, so that each process can take its own shard of one corpus.
+///
+/// Pass these to :func:`common_mask`, :func:`partial_mask` or
+/// :func:`alphabet_masks` in place of a sequence of `str`. The buffer must not be
+/// written to while they are being masked.
+#[pyclass(name = "SharedStrings", module = "alphabet_mask", frozen)]
+pub(crate) struct SharedStrings {
+    buffer: PyBuffer<u8>,
+    /// The offsets of the included strings only, of one more than their number.
+    offsets: Vec<usize>,
+}
+
+impl SharedStrings {
+    /// Returns every string, borrowed from the buffer.
+    pub(crate) fn values(&self) -> Result<Vec<&str>, LocalError> {
+        span!(DEBUG, "extract_shared_values", length = self.len());
+        // SAFETY: the buffer is C-contiguous and at least as long as the last
+        // offset, as checked on construction, and held until `self` is dropped.
+        let data = unsafe {
+            std::slice::from_raw_parts(self.buffer.buf_ptr() as *const u8, self.buffer.len_bytes())
+        };
+
+        self.offsets
+            .windows(2)
+            .enumerate()
+            .map(|(index, range)| {
+                std::str::from_utf8(&data[range[0]..range[1]]).map_err(|_| {
+                    LocalError::InvalidArgument {
+                        name: "buffer",
+                        value: format!("string {index} is not valid UTF-8"),
+                    }
+                })
+            })
+            .collect()
+    }
+
+    fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+}
+
+#[pymethods]
+impl SharedStrings {
+    #[new]
+    #[pyo3(signature = (buffer, offsets, start = 0, end = None))]
+    fn new(buffer: &PyAny, offsets: &PyAny, start: usize, end: Option<usize>) -> PyResult<Self> {
+        let buffer = PyBuffer::<u8>::get(buffer)?;
+        if !buffer.is_c_contiguous() {
+            return Err(LocalError::InvalidArgument {
+                name: "buffer",
+                value: "a non-contiguous buffer".to_owned(),
+            }
+            .into());
+        }
+
+        let offsets = extract_offsets(offsets)?;
+        let end = end.unwrap_or(offsets.len().saturating_sub(1));
+        let Some(offsets) = offsets.get(start..=end).filter(|_| start <= end) else {
+            return Err(LocalError::InvalidArgument {
+                name: "end",
+                value: format!("{start}..{end} of {} offsets", offsets.len()),
+            }
+            .into());
+        };
+        if offsets.windows(2).any(|range| range[0] > range[1])
+            || offsets
+                .last()
+                .is_some_and(|&last| last > buffer.len_bytes())
+        {
+            return Err(LocalError::InvalidArgument {
+                name: "offsets",
+                value: format!(
+                    "offsets which are not ascending within {} bytes",
+                    buffer.len_bytes()
+                ),
+            }
+            .into());
+        }
+
+        Ok(Self {
+            buffer,
+            offsets: offsets.to_vec(),
+        })
+    }
+
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("SharedStrings(len={})", self.len())
+    }
+}
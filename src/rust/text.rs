@@ -0,0 +1,107 @@
+//! Extracting Python strings which may contain lone surrogates.
+//!
+//! Decoding bytes with ``errors="surrogateescape"`` maps every invalid byte to a
+//! lone surrogate, which cannot be encoded as UTF-8, so such strings cannot be
+//! borrowed as `&str`. Instead of the `UnicodeEncodeError` raised by pyo3, the
+//! first lone surrogate is reported as a [`LocalError::LoneSurrogate`] with its
+//! position, or each one is masked as [`OTHER_SYMBOL`] where the invalid
+//! character policy allows it.
+//!
+use std::borrow::Cow;
+
+use pyo3::exceptions::PyUnicodeEncodeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyString};
+
+use crate::models::{LocalError, OTHER_SYMBOL};
+
+/// A Python string, with every lone surrogate replaced by [`OTHER_SYMBOL`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Text<'a> {
+    text: Cow<'a, str>,
+    /// The position and code of the first lone surrogate, if any.
+    surrogate: Option<(usize, u16)>,
+}
+
+impl<'a> Text<'a> {
+    /// Convert a string containing lone surrogates one code point at a time.
+    fn escape(string: &PyString) -> PyResult<Self> {
+        let encoded = string.call_method1("encode", ("utf-32-le", "surrogatepass"))?;
+        let mut surrogate = None;
+        let text = encoded
+            .downcast::<PyBytes>()?
+            .as_bytes()
+            .chunks_exact(4)
+            .enumerate()
+            .map(|(position, unit)| {
+                let code = u32::from_le_bytes([unit[0], unit[1], unit[2], unit[3]]);
+                char::from_u32(code).unwrap_or_else(|| {
+                    surrogate.get_or_insert((position, code as u16));
+                    OTHER_SYMBOL
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            text: Cow::Owned(text),
+            surrogate,
+        })
+    }
+
+    /// Returns the string, or an error if it contains a lone surrogate.
+    pub(crate) fn checked(&self) -> Result<&str, LocalError> {
+        match self.surrogate {
+            Some((position, code)) => Err(LocalError::LoneSurrogate { code, position }),
+            None => Ok(&self.text),
+        }
+    }
+
+    /// Returns the string with every lone surrogate replaced by [`OTHER_SYMBOL`].
+    pub(crate) fn lossy(&self) -> &str {
+        &self.text
+    }
+}
+
+impl<'a> FromPyObject<'a> for Text<'a> {
+    fn extract(object: &'a PyAny) -> PyResult<Self> {
+        let string = object.downcast::<PyString>()?;
+        match string.to_str() {
+            Ok(text) => Ok(Self {
+                text: Cow::Borrowed(text),
+                surrogate: None,
+            }),
+            Err(err) if err.is_instance_of::<PyUnicodeEncodeError>(object.py()) => {
+                Self::escape(string)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Extract a `str` argument, failing on lone surrogates with their position.
+pub(crate) fn to_str(object: &PyAny) -> PyResult<&str> {
+    match object.extract() {
+        Ok(string) => Ok(string),
+        Err(err) if err.is_instance_of::<PyUnicodeEncodeError>(object.py()) => {
+            Text::extract(object)?.checked()?;
+            Err(err)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Extract a sequence of `str` argument, failing on lone surrogates with their
+/// position.
+pub(crate) fn to_strs(object: &PyAny) -> PyResult<Vec<&str>> {
+    match object.extract() {
+        Ok(strings) => Ok(strings),
+        Err(err) if err.is_instance_of::<PyUnicodeEncodeError>(object.py()) => {
+            // Find the string at fault, only once extraction has failed.
+            for item in object.iter()? {
+                to_str(item?)?;
+            }
+            Err(err)
+        }
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,118 @@
+//! Instrumentation with `tracing` spans, compiled out without the `trace` feature,
+//! and a bridge forwarding them to Python's `logging`.
+//!
+
+/// Enter a `tracing` span at `$level` until the end of the enclosing scope with
+/// the `trace` feature, or do nothing without it.
+// Some combinations of features compile out every span.
+#[allow(unused_macros)]
+macro_rules! span {
+    ($level:ident, $name:literal $(, $($fields:tt)*)?) => {
+        #[cfg(feature = "trace")]
+        let _span = tracing::span!(tracing::Level::$level, $name $(, $($fields)*)?).entered();
+    };
+}
+
+/// Forwarding spans and events to the `alphabet_mask` logger of Python.
+#[cfg(all(feature = "python", feature = "trace"))]
+pub(crate) mod logging {
+    use std::fmt::{Debug, Write};
+    use std::time::Instant;
+
+    use pyo3::prelude::*;
+    use tracing::field::{Field, Visit};
+    use tracing::level_filters::LevelFilter;
+    use tracing::{span, Event, Level, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+    use tracing_subscriber::registry::LookupSpan;
+
+    /// The name of the Python logger records are sent to.
+    const LOGGER: &str = "alphabet_mask";
+
+    /// The fields of a span or event, formatted as ` name=value` pairs, with the
+    /// `message` field of an event first and unnamed.
+    #[derive(Default)]
+    struct Fields(String);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            if field.name() == "message" {
+                self.0.insert_str(0, &format!("{value:?}"));
+            } else {
+                // Writing to a `String` cannot fail.
+                let _ = write!(self.0, " {}={value:?}", field.name());
+            }
+        }
+    }
+
+    /// When a span was created, with its fields.
+    struct Started(Instant, String);
+
+    /// A layer logging every event, and every span with its duration once closed.
+    struct PythonLogging;
+
+    impl<S> Layer<S> for PythonLogging
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attributes: &span::Attributes<'_>,
+            id: &span::Id,
+            ctx: Context<'_, S>,
+        ) {
+            let mut fields = Fields::default();
+            attributes.record(&mut fields);
+            if let Some(span) = ctx.span(id) {
+                span.extensions_mut()
+                    .insert(Started(Instant::now(), fields.0));
+            }
+        }
+
+        fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+            let Some(span) = ctx.span(&id) else {
+                return;
+            };
+            let extensions = span.extensions();
+            if let Some(Started(started, fields)) = extensions.get::<Started>() {
+                log(
+                    span.metadata().level(),
+                    format!("{} took {:?}{fields}", span.name(), started.elapsed()),
+                );
+            }
+        }
+
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            log(event.metadata().level(), fields.0);
+        }
+    }
+
+    /// Send one record to the Python logger, dropping it if logging fails.
+    fn log(level: &Level, message: String) {
+        let level = match *level {
+            Level::TRACE => 5,
+            Level::DEBUG => 10,
+            Level::INFO => 20,
+            Level::WARN => 30,
+            Level::ERROR => 40,
+        };
+
+        Python::with_gil(|py| {
+            let _ = py
+                .import("logging")
+                .and_then(|logging| logging.call_method1("getLogger", (LOGGER,)))
+                .and_then(|logger| logger.call_method1("log", (level, message)));
+        });
+    }
+
+    /// Forward every span and event at `level` or above to Python's `logging`,
+    /// returning whether this succeeded; it fails if a global subscriber is
+    /// already set.
+    pub(crate) fn enable(level: Level) -> bool {
+        let subscriber = tracing_subscriber::registry()
+            .with(PythonLogging.with_filter(LevelFilter::from_level(level)));
+        tracing::subscriber::set_global_default(subscriber).is_ok()
+    }
+}
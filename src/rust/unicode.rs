@@ -0,0 +1,223 @@
+//! The `UnicodeMask` Python class, the set of characters of any Unicode text.
+//!
+// pyo3 0.20 expands `#[new]` into trait impls inside a function body.
+#![allow(non_local_definitions)]
+
+use pyo3::basic::CompareOp;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyString};
+
+use crate::models::{CasePolicy, LocalError};
+use alphabet_mask_core::unicode::CharSet;
+
+/// The number of characters up to which a mask displays them all.
+const REPR_LIMIT: u64 = 64;
+
+/// The case policy selected by `case_sensitive`.
+pub(crate) fn case_policy(case_sensitive: bool) -> CasePolicy {
+    if case_sensitive {
+        CasePolicy::Sensitive
+    } else {
+        CasePolicy::Insensitive
+    }
+}
+
+/// The set of characters of Unicode text, as returned by :func:`unicode_mask`
+/// and :func:`common_unicode_alphabets`: the mask of any text, in which no
+/// character is an error.
+///
+/// Masks combine with ``&`` into their common alphabet, ``|`` into their
+/// combined alphabet, ``^`` into the characters of only one of them and ``-``
+/// into the characters of the first only, with another :class:`UnicodeMask` or a
+/// string of characters. ``"é" in mask`` tests a single character, iterating
+/// over a mask gives its characters in code point order, and :meth:`ranges` gives
+/// its runs of consecutive characters.
+///
+/// Letters are folded to lowercase unless ``case_sensitive=True``, and masks only
+/// combine with masks of the same case sensitivity. Masks are stored as roaring
+/// bitmaps, with :meth:`to_bytes` in the portable format.
+#[pyclass(name = "UnicodeMask", module = "alphabet_mask", frozen)]
+pub(crate) struct PyUnicodeMask {
+    set: CharSet,
+    case: CasePolicy,
+}
+
+impl PyUnicodeMask {
+    pub(crate) fn new_with(set: CharSet, case: CasePolicy) -> Self {
+        Self { set, case }
+    }
+
+    /// The set of `other`, another mask of the same case sensitivity or a string
+    /// of characters.
+    fn operand(&self, other: &PyAny) -> PyResult<CharSet> {
+        if let Ok(other) = other.downcast::<PyCell<Self>>() {
+            let other = other.get();
+            if other.case != self.case {
+                return Err(LocalError::InvalidArgument {
+                    name: "other",
+                    value: format!(
+                        "a mask with case_sensitive={}",
+                        if other.case_sensitive() {
+                            "True"
+                        } else {
+                            "False"
+                        }
+                    ),
+                }
+                .into());
+            }
+            return Ok(other.set.clone());
+        }
+        Ok(CharSet::of(other.extract()?, self.case))
+    }
+
+    fn with_set(&self, set: CharSet) -> Self {
+        Self::new_with(set, self.case)
+    }
+}
+
+#[pymethods]
+impl PyUnicodeMask {
+    /// The mask of the characters of `value`; without it, the empty mask.
+    #[new]
+    #[pyo3(signature = (value = "", case_sensitive = false))]
+    fn new(value: &str, case_sensitive: bool) -> Self {
+        let case = case_policy(case_sensitive);
+        Self::new_with(CharSet::of(value, case), case)
+    }
+
+    /// Returns the mask serialised by :meth:`to_bytes`, or raises a `ValueError`
+    /// if `data` is not a serialised mask.
+    #[staticmethod]
+    #[pyo3(signature = (data, case_sensitive = false))]
+    fn from_bytes(data: &[u8], case_sensitive: bool) -> PyResult<Self> {
+        let mut mask = Self::new("", case_sensitive);
+        mask.set = CharSet::from_bytes(data)?;
+        Ok(mask)
+    }
+
+    /// Whether letters are told apart by case.
+    #[getter]
+    fn case_sensitive(&self) -> bool {
+        self.case == CasePolicy::Sensitive
+    }
+
+    /// Returns the characters of this mask, in code point order.
+    fn chars(&self) -> String {
+        self.set.chars().collect()
+    }
+
+    /// Returns the runs of consecutive characters of this mask, in order, as
+    /// pairs of their first and last characters.
+    fn ranges(&self) -> Vec<(char, char)> {
+        self.set
+            .ranges()
+            .into_iter()
+            .map(|range| (*range.start(), *range.end()))
+            .collect()
+    }
+
+    /// Returns this mask in the portable roaring bitmap format.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, &self.set.to_bytes())
+    }
+
+    fn __and__(&self, other: &PyAny) -> PyResult<Self> {
+        Ok(self.with_set(&self.set & &self.operand(other)?))
+    }
+
+    fn __rand__(&self, other: &PyAny) -> PyResult<Self> {
+        self.__and__(other)
+    }
+
+    fn __or__(&self, other: &PyAny) -> PyResult<Self> {
+        Ok(self.with_set(&self.set | &self.operand(other)?))
+    }
+
+    fn __ror__(&self, other: &PyAny) -> PyResult<Self> {
+        self.__or__(other)
+    }
+
+    fn __xor__(&self, other: &PyAny) -> PyResult<Self> {
+        Ok(self.with_set(&self.set ^ &self.operand(other)?))
+    }
+
+    fn __rxor__(&self, other: &PyAny) -> PyResult<Self> {
+        self.__xor__(other)
+    }
+
+    fn __sub__(&self, other: &PyAny) -> PyResult<Self> {
+        Ok(self.with_set(&self.set - &self.operand(other)?))
+    }
+
+    fn __rsub__(&self, other: &PyAny) -> PyResult<Self> {
+        Ok(self.with_set(&self.operand(other)? - &self.set))
+    }
+
+    /// Returns whether `character`, a single character, is in this mask.
+    fn __contains__(&self, character: &str) -> PyResult<bool> {
+        let mut chars = character.chars();
+        match (chars.next(), chars.next()) {
+            (Some(_), None) => Ok(CharSet::of(character, self.case)
+                .chars()
+                .all(|character| self.set.contains(character))),
+            _ => Err(LocalError::InvalidArgument {
+                name: "character",
+                value: character.to_owned(),
+            }
+            .into()),
+        }
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<&'py PyAny> {
+        Ok(PyString::new(py, &self.chars()).iter()?.into())
+    }
+
+    fn __len__(&self) -> usize {
+        self.set.len() as usize
+    }
+
+    fn __bool__(&self) -> bool {
+        !self.set.is_empty()
+    }
+
+    fn __richcmp__(&self, other: &PyAny, op: CompareOp, py: Python<'_>) -> PyObject {
+        let Ok(other) = other.downcast::<PyCell<Self>>() else {
+            return py.NotImplemented();
+        };
+        let other = other.get();
+        let equal = self.case == other.case && self.set == other.set;
+        match op {
+            CompareOp::Eq => equal.into_py(py),
+            CompareOp::Ne => (!equal).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn __reduce__(&self, py: Python<'_>) -> PyResult<(PyObject, (PyObject, bool))> {
+        Ok((
+            py.get_type::<Self>().getattr("from_bytes")?.into(),
+            (self.to_bytes(py).into(), self.case_sensitive()),
+        ))
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let arguments = if self.case_sensitive() {
+            ", case_sensitive=True"
+        } else {
+            ""
+        };
+        Ok(if self.set.len() <= REPR_LIMIT {
+            format!(
+                "UnicodeMask({}{arguments})",
+                PyString::new(py, &self.chars()).repr()?
+            )
+        } else {
+            format!(
+                "<UnicodeMask of {} characters in {} ranges{arguments}>",
+                self.set.len(),
+                self.set.ranges().len()
+            )
+        })
+    }
+}
@@ -0,0 +1,65 @@
+//! WebAssembly bindings, enabled by the `wasm` feature.
+//!
+//! Build with `wasm-pack build --no-default-features --features wasm`. Without
+//! thread support in the browser there is no `parallel` feature, so every input is
+//! masked on the calling thread, with results identical to the parallel path.
+//!
+use wasm_bindgen::prelude::*;
+
+use alphabet_mask_core::{chunks, find_common_mask_dispatch, mask_string, mask_to_chars};
+
+/// A bit mask representing an alphabet.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mask(crate::models::Mask);
+
+#[wasm_bindgen]
+impl Mask {
+    /// Wrap a raw bit mask.
+    #[wasm_bindgen(constructor)]
+    pub fn new(value: u32) -> Mask {
+        Mask(value.into())
+    }
+
+    /// The mask of the alphabet of the given string.
+    #[wasm_bindgen(js_name = fromString)]
+    pub fn from_string(string: &str) -> Result<Mask, JsError> {
+        Ok(Mask(mask_string(string)?))
+    }
+
+    /// The raw bit mask.
+    #[wasm_bindgen(getter)]
+    pub fn value(&self) -> u32 {
+        self.0.bits()
+    }
+
+    /// The characters represented by this mask.
+    pub fn chars(&self) -> String {
+        mask_to_chars(self.0)
+    }
+
+    /// The characters present in both masks.
+    pub fn intersect(&self, other: &Mask) -> Mask {
+        Mask(self.0 & other.0)
+    }
+
+    /// The characters present in either mask.
+    pub fn union(&self, other: &Mask) -> Mask {
+        Mask(self.0 | other.0)
+    }
+}
+
+/// Returns a bit mask representing the alphabet of the given string.
+#[wasm_bindgen(js_name = alphabetMask)]
+pub fn alphabet_mask(string: &str) -> Result<u32, JsError> {
+    Ok(mask_string(string)?.bits())
+}
+
+/// Returns the common alphabet of the given strings.
+#[wasm_bindgen(js_name = commonAlphabets)]
+pub fn common_alphabets(strings: Vec<String>) -> Result<String, JsError> {
+    let strings: Vec<&str> = strings.iter().map(String::as_str).collect();
+    let mask = find_common_mask_dispatch(&strings, chunks::LENGTH_LIMIT_PER_CHUNK)?;
+
+    Ok(mask_to_chars(mask))
+}
@@ -0,0 +1,253 @@
+//! The `BatchWorker` Python class, which coalesces strings submitted one at a
+//! time into micro-batches masked on a background thread.
+//!
+// pyo3 0.20 expands `#[new]` into trait impls inside a function body.
+#![allow(non_local_definitions)]
+
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use pyo3::prelude::*;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::call_stats::Counters;
+use crate::lanes;
+use crate::models::{LocalError, Mask};
+use crate::settings::Settings;
+use crate::text::Text;
+use alphabet_mask_core::mask_string;
+
+/// The default number of strings in a micro-batch.
+pub(crate) const DEFAULT_MAX_BATCH: usize = 1024;
+
+/// The default time in seconds to wait for a micro-batch to fill.
+pub(crate) const DEFAULT_MAX_DELAY: f64 = 0.001;
+
+/// A string waiting to be masked, with the `concurrent.futures.Future` of its
+/// mask.
+struct Request {
+    string: String,
+    future: PyObject,
+}
+
+/// Receive the next micro-batch: block for its first request, then take more
+/// until there are `max_batch` or `max_delay` has passed. Returns [`None`] once
+/// every sender is dropped and no request is left.
+fn next_batch(
+    receiver: &Receiver<Request>,
+    max_batch: usize,
+    max_delay: Duration,
+) -> Option<Vec<Request>> {
+    let mut batch = vec![receiver.recv().ok()?];
+    let deadline = Instant::now() + max_delay;
+
+    while batch.len() < max_batch {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(timeout) {
+            Ok(request) => batch.push(request),
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    Some(batch)
+}
+
+/// Mask each of `batch` in parallel, then resolve its future.
+fn process(batch: Vec<Request>) {
+    span!(DEBUG, "process_micro_batch", strings = batch.len());
+    let strings: Vec<&str> = batch
+        .iter()
+        .map(|request| request.string.as_str())
+        .collect();
+    let masks: Vec<Result<Mask, LocalError>> = lanes::install(strings.iter().copied(), || {
+        strings
+            .par_iter()
+            .map(|string| mask_string(string))
+            .collect()
+    });
+    Counters::add_strings(&strings);
+
+    Python::with_gil(|py| {
+        for (request, mask) in batch.into_iter().zip(masks) {
+            let future = request.future.as_ref(py);
+            // A future cancelled while waiting has no result to set.
+            if future
+                .call_method0("cancelled")
+                .and_then(|cancelled| cancelled.is_true())
+                .unwrap_or(true)
+            {
+                continue;
+            }
+            let result = match mask {
+                Ok(mask) => future.call_method1("set_result", (mask.bits(),)),
+                Err(err) => future.call_method1("set_exception", (PyErr::from(err).value(py),)),
+            };
+            if let Err(err) = result {
+                err.print(py);
+            }
+        }
+    });
+}
+
+/// Masks strings submitted one at a time, such as by the handlers of a web
+/// server, in micro-batches on a background thread.
+///
+/// :meth:`submit` returns a :class:`concurrent.futures.Future` of the mask of
+/// the string; wrap it with :func:`asyncio.wrap_future` to await it. Strings
+/// are masked together once `max_batch` are waiting, or `max_delay` seconds
+/// after the first of a batch was submitted. A string outside the alphabet fails
+/// only its own future.
+///
+/// :meth:`close` masks every string already submitted and stops the thread; the
+/// worker can also be used as a context manager.
+#[pyclass(name = "BatchWorker", module = "alphabet_mask", frozen)]
+pub(crate) struct PyBatchWorker {
+    sender: Mutex<Option<Sender<Request>>>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+    max_batch: usize,
+    max_delay: Duration,
+}
+
+impl PyBatchWorker {
+    /// Send `string` to the background thread, with a new future of its mask.
+    fn send(&self, string: &str, py: Python<'_>) -> PyResult<PyObject> {
+        Settings::get().limits.check([string])?;
+        let future: PyObject = py
+            .import("concurrent.futures")?
+            .getattr("Future")?
+            .call0()?
+            .into();
+
+        let sender = self.sender.lock().unwrap_or_else(|err| err.into_inner());
+        sender
+            .as_ref()
+            .and_then(|sender| {
+                sender
+                    .send(Request {
+                        string: string.to_owned(),
+                        future: future.clone_ref(py),
+                    })
+                    .ok()
+            })
+            .ok_or(LocalError::Closed {
+                name: "BatchWorker",
+            })?;
+        Ok(future)
+    }
+}
+
+#[pymethods]
+impl PyBatchWorker {
+    #[new]
+    #[pyo3(signature = (max_batch = DEFAULT_MAX_BATCH, max_delay = DEFAULT_MAX_DELAY))]
+    fn new(max_batch: usize, max_delay: f64) -> PyResult<Self> {
+        if max_batch == 0 {
+            return Err(LocalError::InvalidArgument {
+                name: "max_batch",
+                value: max_batch.to_string(),
+            }
+            .into());
+        }
+        let max_delay =
+            Duration::try_from_secs_f64(max_delay).map_err(|_| LocalError::InvalidArgument {
+                name: "max_delay",
+                value: max_delay.to_string(),
+            })?;
+
+        let (sender, receiver) = mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name("alphabet-mask-batch-worker".to_owned())
+            .spawn(move || {
+                while let Some(batch) = next_batch(&receiver, max_batch, max_delay) {
+                    process(batch);
+                }
+            })
+            .map_err(|err| LocalError::Unknown {
+                context: format!("could not start a batch worker: {err}"),
+            })?;
+
+        Ok(Self {
+            sender: Mutex::new(Some(sender)),
+            thread: Mutex::new(Some(thread)),
+            max_batch,
+            max_delay,
+        })
+    }
+
+    /// The largest number of strings masked together.
+    #[getter]
+    fn max_batch(&self) -> usize {
+        self.max_batch
+    }
+
+    /// The longest time in seconds to wait for a micro-batch to fill.
+    #[getter]
+    fn max_delay(&self) -> f64 {
+        self.max_delay.as_secs_f64()
+    }
+
+    /// Whether :meth:`close` has been called.
+    #[getter]
+    fn closed(&self) -> bool {
+        self.sender
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .is_none()
+    }
+
+    /// Returns a :class:`concurrent.futures.Future` of the mask of `string`.
+    ///
+    /// Raises a `ValueError` if the worker is closed, or if `string` contains a
+    /// lone surrogate; the input limits are checked for each string.
+    fn submit(&self, string: Text<'_>, py: Python<'_>) -> PyResult<PyObject> {
+        self.send(string.checked()?, py)
+    }
+
+    /// Returns a `list` of the futures of :meth:`submit` for each of `strings`.
+    fn submit_many(&self, strings: Vec<Text<'_>>, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        strings
+            .iter()
+            .map(|string| self.send(string.checked()?, py))
+            .collect()
+    }
+
+    /// Masks every string already submitted, then stops the background thread.
+    ///
+    /// Later calls to :meth:`submit` raise a `ValueError`; closing again does
+    /// nothing.
+    fn close(&self, py: Python<'_>) {
+        self.sender
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+        let thread = self
+            .thread
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+
+        // The thread needs the GIL to resolve the last futures.
+        if let Some(thread) = thread {
+            py.allow_threads(|| thread.join())
+                .expect("The batch worker thread panicked.");
+        }
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __exit__(&self, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny, py: Python<'_>) {
+        self.close(py);
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "BatchWorker(max_batch={}, max_delay={}, closed={})",
+            self.max_batch,
+            self.max_delay.as_secs_f64(),
+            if self.closed() { "True" } else { "False" }
+        )
+    }
+}
@@ -0,0 +1,63 @@
+# -*- coding: utf-8 -*-
+import pytest
+
+from alphabet_mask import Alphabet, rust
+
+
+def test_alphabet():
+    greek = Alphabet([" ", "α", "β", "γ", "σς"])
+
+    assert greek.width == len(greek) == 5
+    assert greek.symbols == [" ", "α", "β", "γ", "σς"]
+    assert greek.bit("Σ") == greek.bit("ς") == 4
+    assert greek.bit("a") is None
+    assert greek.chars(greek.mask("ΓΑΣ βας")) == " αβγσ"
+    assert repr(greek) == "Alphabet([' ', 'α', 'β', 'γ', 'σς'])"
+    with pytest.raises(ValueError):
+        greek.chars(1 << 5)
+
+
+def test_mask_string_with():
+    notes = Alphabet("♩♪♫").extend(["♭b", "♯"])
+
+    assert rust.mask_string_with(notes, "♪b♫") == 0b1110
+    assert rust.mask_string_with(notes, "") == 0
+    assert rust.common_alphabets_with(notes, ["♩♯♪", "♪♩", "♭♪♩"]) == "♩♪"
+    assert rust.common_alphabets_with(notes, ["♩♪♫"] * 1000, length_limit=64) == "♩♪♫"
+    with pytest.raises(ValueError):
+        rust.mask_string_with(notes, "♮")
+
+
+def test_alphabet_limits():
+    wide = Alphabet("".join(map(chr, range(0x4E00, 0x4E80))))
+
+    assert wide.width == 128
+    assert rust.mask_string_with(wide, "乿") == 1 << 127
+    assert Alphabet("aA", case_sensitive=True).mask("A") == 0b10
+    with pytest.raises(ValueError):
+        wide.extend("!")
+    with pytest.raises(ValueError):
+        Alphabet("aA")
+    with pytest.raises(ValueError):
+        Alphabet(["ab", ""])
+
+
+def test_presets():
+    assert Alphabet.preset("greek").width == 30
+    latin = Alphabet.preset("latin_extended")
+    assert latin.mask("a") == rust.alphabet_mask("a")
+    assert rust.common_alphabets(["Добрый день", "Ночь"], alphabet="cyrillic") == "ноь"
+    assert rust.common_alphabets(
+        {"a": ["Καλή", "καφές"], "b": ["νύχτα"]}, alphabet=Alphabet.preset("greek")
+    ) == {"a": "ακ", "b": "αντυχ"}
+    assert rust.alphabet_mask("Ωμέγα", alphabet="greek") == Alphabet.preset(
+        "greek"
+    ).mask("Ωμέγα")
+    with pytest.raises(ValueError):
+        Alphabet.preset("klingon")
+    with pytest.raises(ValueError):
+        rust.common_alphabets(["Ночь"], alphabet="klingon")
+    with pytest.raises(ValueError):
+        rust.common_alphabets(["Ночь"], alphabet="cyrillic", as_mask=True)
+    with pytest.raises(ValueError):
+        rust.alphabet_mask("Ночь", alphabet="cyrillic", digits=True)
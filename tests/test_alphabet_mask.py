@@ -0,0 +1,86 @@
+# -*- coding: utf-8 -*-
+import pickle
+
+import pytest
+
+from alphabet_mask import AlphabetMask, rust
+
+
+def test_alphabet_mask_operators():
+    cat = rust.alphabet_mask("the cat", as_mask=True)
+    hat = AlphabetMask("a hat")
+
+    assert isinstance(cat, AlphabetMask)
+    assert (cat & hat).chars() == " aht"
+    assert (cat | "dog").chars() == " acdeghot"
+    assert (cat ^ hat).chars() == "ce"
+    assert (~cat & "abc").chars() == "b"
+    assert (rust.alphabet_mask("abc") & cat) == AlphabetMask("ac")
+    assert cat.to_int() == rust.alphabet_mask("the cat")
+    assert int(cat) == cat.to_int()
+    assert hash(cat) == hash(cat.to_int())
+    assert repr(hat) == "AlphabetMask(' aht')"
+    assert len(hat) == 4
+    assert not AlphabetMask()
+
+
+def test_alphabet_mask_contains():
+    cat = AlphabetMask("the cat")
+
+    assert "T" in cat
+    assert "d" not in cat
+    assert "1" not in cat
+    with pytest.raises(ValueError):
+        "th" in cat
+
+
+def test_alphabet_mask_as_int():
+    cat = AlphabetMask("the cat")
+
+    assert rust.mask_to_chars(cat) == " aceht"
+    assert pickle.loads(pickle.dumps(cat)) == cat
+
+
+def test_alphabet_mask_digits():
+    code = rust.alphabet_mask("SKU 42", as_mask=True, digits=True)
+
+    assert code.digits
+    assert (code & "k2").chars() == "k2"
+    assert "4" in code and "5" not in code
+    assert (~code & AlphabetMask("a5", digits=True)).chars() == "a5"
+    assert repr(code) == "AlphabetMask(' ksu24', digits=True)"
+    assert pickle.loads(pickle.dumps(code)) == code
+    with pytest.raises(ValueError):
+        code & AlphabetMask("a")
+
+
+def test_alphabet_mask_extended():
+    note = AlphabetMask("Note: (1)", extended=True)
+
+    assert note.extended and note.digits
+    assert ":" in note and ";" not in note
+    assert repr(note) == "AlphabetMask(' enot1():', extended=True)"
+    assert pickle.loads(pickle.dumps(note)) == note
+    with pytest.raises(ValueError):
+        note | AlphabetMask("1", digits=True)
+
+
+def test_alphabet_mask_case_sensitive():
+    name = AlphabetMask("Ada", case_sensitive=True)
+
+    assert name.case_sensitive and not name.digits
+    assert "A" in name and "D" not in name and "d" in name
+    assert (name & "aA").chars() == "aA"
+    assert repr(name) == "AlphabetMask('adA', case_sensitive=True)"
+    assert pickle.loads(pickle.dumps(name)) == name
+
+
+def test_as_mask():
+    strings = ["the cat", "a hat"]
+
+    assert rust.common_alphabets(strings, as_mask=True) == AlphabetMask(" aht")
+    assert rust.union_alphabets(strings, as_mask=True).chars() == " aceht"
+    assert rust.common_mask(strings, as_mask=True) == rust.common_mask(strings)
+    assert rust.common_alphabets({"k": strings}, as_mask=True) == {
+        "k": AlphabetMask(" aht")
+    }
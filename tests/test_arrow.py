@@ -0,0 +1,121 @@
+# -*- coding: utf-8 -*-
+import pytest
+from alphabet_mask import rust
+
+pa = pytest.importorskip("pyarrow")
+
+
+@pytest.mark.parametrize("type_", [pa.string(), pa.large_string()])
+def test_common_alphabets_arrow(type_):
+    """
+    Assert that Arrow arrays give the same result as lists, ignoring nulls.
+    """
+    strings = ["The quick brown fox.", None, "A lazy dog."]
+    array = pa.array(strings, type=type_)
+
+    assert rust.common_alphabets_arrow(array) == rust.common_alphabets(
+        [s for s in strings if s is not None]
+    )
+
+
+def test_common_alphabets_arrow_sliced():
+    """
+    Assert that the offset of a sliced array is respected.
+    """
+    array = pa.array(["xyz", "abc", "cab"]).slice(1)
+
+    assert rust.common_alphabets_arrow(array) == "abc"
+
+
+def test_common_alphabets_arrow_wrong_type():
+    """
+    Assert that non-string arrays are rejected.
+    """
+    with pytest.raises(ValueError):
+        rust.common_alphabets_arrow(pa.array([1, 2, 3]))
+
+
+def test_common_alphabets_arrow_wrong_capsule():
+    """
+    Assert that capsules of the wrong name are rejected rather than read.
+    """
+    import ctypes
+
+    new_capsule = ctypes.pythonapi.PyCapsule_New
+    new_capsule.restype = ctypes.py_object
+    new_capsule.argtypes = [ctypes.c_void_p, ctypes.c_char_p, ctypes.c_void_p]
+    buffer = ctypes.create_string_buffer(128)
+    capsule = new_capsule(ctypes.addressof(buffer), b"not_arrow", None)
+
+    class Exporter:
+        def __arrow_c_array__(self, requested_schema=None):
+            return capsule, capsule
+
+    class StreamExporter:
+        def __arrow_c_stream__(self, requested_schema=None):
+            return capsule
+
+    with pytest.raises(ValueError, match="arrow_schema"):
+        rust.common_alphabets_arrow(Exporter())
+    with pytest.raises(ValueError, match="arrow_array_stream"):
+        rust.common_alphabets_arrow(StreamExporter())
+
+
+def test_alphabet_masks_arrow_output():
+    """
+    Assert that per-string masks come back as a ``UInt32Array``, with nulls preserved.
+    """
+    masks = rust.alphabet_masks(pa.array(["abc", None, "b"]), output="arrow")
+
+    assert masks.type == pa.uint32()
+    assert masks.to_pylist() == [0b1110, None, 0b100]
+
+
+def test_alphabet_masks_list_to_arrow():
+    """
+    Assert that list input can also produce Arrow output.
+    """
+    strings = ["The quick brown fox.", "A lazy dog."]
+    masks = rust.alphabet_masks(strings, output="arrow")
+
+    assert masks.to_pylist() == rust.alphabet_masks(strings)
+
+
+def test_common_alphabets_arrow_stream():
+    """
+    Assert that a stream of record batches is read batch by batch from its column.
+    """
+    batches = [
+        pa.record_batch({"id": [1, 2], "text": ["the cat", None]}),
+        pa.record_batch({"id": [3], "text": ["a hat"]}),
+    ]
+    reader = pa.RecordBatchReader.from_batches(batches[0].schema, batches)
+
+    assert rust.common_alphabets_arrow(reader, column="text") == " at"
+
+
+def test_common_alphabets_arrow_chunked():
+    """
+    Assert that a chunked array is read chunk by chunk without a column.
+    """
+    chunked = pa.chunked_array([["the cat"], ["a hat", None]])
+
+    assert rust.common_alphabets_arrow(chunked) == " at"
+    with pytest.raises(ValueError):
+        rust.common_alphabets_arrow(pa.array(["a"]), column="text")
+
+
+def test_iter_arrow_masks():
+    """
+    Assert that the masks of each batch of a stream are yielded in turn.
+    """
+    table = pa.table({"id": [1, 2, 3], "text": ["abc", None, "b"]})
+    reader = pa.RecordBatchReader.from_batches(
+        table.schema, table.to_batches(max_chunksize=2)
+    )
+    chunks = rust.iter_arrow_masks(reader, column="text")
+
+    assert list(chunks) == [[0b1110, None], [0b100]]
+    assert chunks.position == 3
+    with pytest.raises(ValueError):
+        rust.iter_arrow_masks(table.to_reader())
@@ -0,0 +1,38 @@
+# -*- coding: utf-8 -*-
+import pytest
+
+from alphabet_mask import rust
+
+
+def test_invalid_bases():
+    assert rust.invalid_bases(["GATTACA", "GAUUACA", "acgtn", "AX-X"]) == [
+        ("", []),
+        ("U", [2, 3]),
+        ("", []),
+        ("-X", [1, 2, 3]),
+    ]
+
+
+@pytest.mark.parametrize(
+    "content",
+    [
+        ">ok description\nACGT\n>bad\nACGU\nXA\n",
+        "@ok\nACGT\n+\nIIII\n@bad\nACGUXA\n+bad\nIIIIII\n",
+    ],
+)
+def test_invalid_bases_file(tmp_path, content):
+    path = tmp_path / "reads"
+    path.write_text(content)
+
+    assert rust.invalid_bases_file(path) == [("bad", "UX", [3, 4])]
+
+
+def test_invalid_bases_file_errors(tmp_path):
+    path = tmp_path / "reads.fq"
+    path.write_text("@ok\nACGT\n+\nII\n")
+
+    with pytest.raises(ValueError):
+        rust.invalid_bases_file(path)
+
+    with pytest.raises(OSError):
+        rust.invalid_bases_file(tmp_path / "missing.fa")
@@ -0,0 +1,109 @@
+# -*- coding: utf-8 -*-
+import threading
+
+import alphabet_mask
+from alphabet_mask import rust
+
+
+def test_last_call_stats():
+    strings = ["abcdefghij"] * 1000
+    rust.common_alphabets(strings, 100)
+
+    stats = alphabet_mask.last_call_stats()
+    assert isinstance(stats, alphabet_mask.CallStats)
+    assert stats.function == "common_alphabets"
+    assert stats.reference_memory >= 1000 * 8
+    assert stats.chunk_memory > 0
+    assert stats.peak_memory == (
+        stats.reference_memory + stats.chunk_memory + stats.thread_memory
+    )
+    assert (stats.strings, stats.bytes) == (1000, 10000)
+
+    # Below the chunk limit, the strings are masked without chunks.
+    rust.common_alphabets(strings, None)
+    assert alphabet_mask.last_call_stats().chunk_memory == 0
+
+
+def test_last_call_stats_per_thread(tmp_path):
+    path = tmp_path / "lines.txt"
+    path.write_text("abc\ndef\n")
+    rust.mask_file(path, tmp_path / "masks.txt", length_limit=4)
+    stats = alphabet_mask.last_call_stats()
+    assert stats.function == "mask_file"
+    assert (stats.strings, stats.bytes) == (2, 8)
+
+    other = []
+    thread = threading.Thread(target=lambda: other.append(rust.last_call_stats()))
+    thread.start()
+    thread.join()
+    assert other == [None]
+
+
+def test_counters(tmp_path):
+    alphabet_mask.reset_counters()
+    rust.alphabet_mask("abc")
+    rust.common_alphabets(["ab", "abc"], None)
+    alphabet_mask.Masker().common_mask(["abcd"])
+
+    path = tmp_path / "lines.txt"
+    path.write_text("abc\ndef\n")
+    rust.common_alphabets_files([path])
+
+    # A failed call does not count.
+    try:
+        rust.common_alphabets(["ab", "a1"], None)
+    except ValueError:
+        pass
+
+    counts = {"calls": 4, "strings": 6, "bytes": 20}
+    assert alphabet_mask.get_counters() == counts
+    assert alphabet_mask.reset_counters() == counts
+    assert alphabet_mask.get_counters() == {"calls": 0, "strings": 0, "bytes": 0}
+
+
+def test_worker_stats():
+    strings = ["abcdefghij"] * 1000 + ["abcdefghij" * 500]
+    rust.common_alphabets(strings, 100)
+    assert alphabet_mask.last_call_stats().workers is None
+
+    alphabet_mask.set_worker_stats()
+    try:
+        rust.common_alphabets(strings, 100)
+        workers = alphabet_mask.last_call_stats().workers
+        assert all(thread is not None for thread, _, _ in workers)
+        assert sum(chunks for _, chunks, _ in workers) == 101
+        assert sum(size for _, _, size in workers) == 15000
+        # The giant string is one chunk of its own, on a single thread.
+        assert max(size for _, _, size in workers) >= 5000
+
+        rust.common_mask(["abc", "de"], None)
+        assert alphabet_mask.last_call_stats().workers == [(None, 1, 5)]
+    finally:
+        alphabet_mask.set_worker_stats(False)
+
+
+def test_counters_of_other_functions(tmp_path):
+    path = tmp_path / "reads.fa"
+    path.write_text(">one\nACGU\n")
+    calls = [
+        (lambda: rust.filter_wordle(["crane", "slate"]), "filter_wordle", 10),
+        (lambda: rust.playable_from_rack(["tea"], "eat"), "playable_from_rack", 3),
+        (lambda: rust.group_anagrams(["tea", "eat"]), "group_anagrams", 6),
+        (lambda: rust.validate_charset(["ab"], "a"), "validate_charset", 2),
+        (lambda: rust.invalid_bases(["ACGU"]), "invalid_bases", 4),
+        (lambda: rust.invalid_bases_file(path), "invalid_bases_file", 4),
+        (lambda: rust.validate_corpus(["ab", "c"]), "validate_corpus", 3),
+        (lambda: rust.script_coverage(["αβ", "c"]), "script_coverage", 5),
+        (lambda: rust.analyse_classes(["aB", "1"]), "analyse_classes", 3),
+        (lambda: rust.compare_corpora(["ab"], ["c"]), "compare_corpora", 3),
+        (lambda: rust.profile(["abc", "1"]), "profile", 4),
+        (lambda: rust.diff_alphabets(["ab"], ["c"]), "diff_alphabets", 3),
+    ]
+
+    for call, function, bytes in calls:
+        alphabet_mask.reset_counters()
+        call()
+        assert alphabet_mask.last_call_stats().function == function
+        assert alphabet_mask.get_counters()["calls"] == 1
+        assert alphabet_mask.get_counters()["bytes"] == bytes
+    alphabet_mask.reset_counters()
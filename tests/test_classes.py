@@ -0,0 +1,38 @@
+# -*- coding: utf-8 -*-
+from alphabet_mask import rust
+
+
+def test_analyse_classes():
+    report = rust.analyse_classes(["password", "Pa55 w0rd!", "", "PASSWORD1"])
+
+    assert report["masks"] == [
+        rust.CLASS_LOWER,
+        rust.CLASS_LOWER
+        | rust.CLASS_UPPER
+        | rust.CLASS_DIGIT
+        | rust.CLASS_SYMBOL
+        | rust.CLASS_SPACE,
+        0,
+        rust.CLASS_UPPER | rust.CLASS_DIGIT,
+    ]
+    assert report["strings"] == 4
+    assert report["common"] == 0
+    assert report["union"] == 0b11111
+    assert report["coverage"] == {
+        "lower": 2,
+        "upper": 2,
+        "digit": 2,
+        "symbol": 1,
+        "space": 1,
+    }
+    assert report["class_counts"] == [1, 1, 1, 0, 0, 1]
+
+
+def test_analyse_classes_unicode():
+    report = rust.analyse_classes(["Ünïcødé", "密码\t"])
+
+    assert report["masks"] == [
+        rust.CLASS_LOWER | rust.CLASS_UPPER,
+        rust.CLASS_LOWER | rust.CLASS_SPACE,
+    ]
+    assert report["common"] == rust.CLASS_LOWER
@@ -0,0 +1,66 @@
+# -*- coding: utf-8 -*-
+import threading
+import time
+
+from alphabet_mask import rust
+
+THREADS = 8
+
+
+def test_concurrent_calls():
+    # Every thread checks its own results while all of them run at once.
+    words = ["the cat", "a hat", "that"]
+    expected = {
+        "alphabet_masks": [rust.alphabet_mask(word) for word in words],
+        "common_alphabets": rust.common_alphabets(words, None),
+        "group_anagrams": rust.group_anagrams(words),
+    }
+    errors = []
+    barrier = threading.Barrier(THREADS)
+
+    def work(index):
+        barrier.wait()
+        for _ in range(200):
+            strings = words * (index + 1)
+            results = {
+                "alphabet_masks": rust.alphabet_masks(words),
+                "common_alphabets": rust.common_alphabets(strings, 10),
+            }
+            # The statistics of other threads' calls are not seen.
+            if rust.last_call_stats().strings != len(strings):
+                errors.append(rust.last_call_stats())
+            results["group_anagrams"] = rust.group_anagrams(words)
+            if results != expected:
+                errors.append(results)
+
+    threads = [threading.Thread(target=work, args=(i,)) for i in range(THREADS)]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+
+    assert errors == []
+
+
+def test_small_calls_during_large_call():
+    large = ["abcdefghijklmnopqrstuvwxyz"] * 2_000_000
+    started = threading.Event()
+
+    def large_call():
+        started.set()
+        rust.alphabet_masks(large)
+
+    thread = threading.Thread(target=large_call)
+    thread.start()
+    started.wait()
+
+    # Small calls go to a lane of their own, rather than queueing behind every
+    # block of the large call.
+    latencies = []
+    while thread.is_alive() and len(latencies) < 50:
+        start = time.perf_counter()
+        assert rust.alphabet_masks(["abc", "def"]) == [0b1110, 0b1110000]
+        latencies.append(time.perf_counter() - start)
+    thread.join()
+
+    assert max(latencies, default=0) < 1
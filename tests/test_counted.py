@@ -0,0 +1,34 @@
+# -*- coding: utf-8 -*-
+import pytest
+
+from alphabet_mask import CountedAlphabet, rust
+
+
+def test_counted_alphabet():
+    counted = CountedAlphabet(["the cat", "the hat"])
+    counted.add(["xyz"])
+    assert len(counted) == 3
+    assert counted.common == ""
+    assert counted.counts()["t"] == 2
+
+    counted.remove(["xyz"])
+    assert counted.common == rust.common_alphabets(["the cat", "the hat"])
+    assert counted.union == " aceht"
+
+    counted.remove(["the cat", "the hat"])
+    assert len(counted) == 0
+    assert counted.union == ""
+    assert counted.counts() == {}
+
+
+def test_counted_alphabet_invalid():
+    counted = CountedAlphabet(["the cat"])
+    with pytest.raises(ValueError, match="string 1, which was not added"):
+        counted.remove(["the cat", "a dog"])
+    # A failed call leaves the counts unchanged.
+    assert len(counted) == 1
+    assert counted.common == " aceht"
+
+    with pytest.raises(ValueError, match="invalid character '1'"):
+        counted.add(["the bat", "1"])
+    assert len(counted) == 1
@@ -0,0 +1,46 @@
+# -*- coding: utf-8 -*-
+import pickle
+
+import pytest
+from alphabet_mask import distributed, rust
+
+
+def test_tree_reduce():
+    """
+    Assert that any grouping of partial masks gives the common mask of everything.
+    """
+    strings = ["The quick brown fox.", "A lazy dog.", "Another fox, quick."]
+    partials = [distributed.partial_mask([s]) for s in strings]
+
+    left = distributed.combine_masks(
+        [distributed.combine_masks(partials[:2]), partials[2]]
+    )
+    right = distributed.combine_masks(
+        [partials[0], distributed.combine_masks(partials[1:])]
+    )
+
+    assert left == right
+    assert distributed.decode(left) == rust.common_mask(strings)
+
+
+def test_stable_format():
+    """
+    Assert the serialised format, which must never change within a version.
+    """
+    assert distributed.partial_mask(["a"]) == b"AM\x01\x02\x00\x00\x00"
+
+
+def test_picklable():
+    """
+    Assert that the map and reduce steps pickle by reference.
+    """
+    for func in (distributed.partial_mask, distributed.combine_masks):
+        assert pickle.loads(pickle.dumps(func)) is func
+
+
+def test_decode_invalid():
+    """
+    Assert that unrecognised bytes are rejected.
+    """
+    with pytest.raises(ValueError):
+        distributed.decode(b"not a mask")
@@ -1,4 +1,8 @@
 # -*- coding: utf-8 -*-
+import os
+import subprocess
+import sys
+
 from alphabet_mask import config
 
 
@@ -7,3 +11,87 @@ def test_env():
     Assert that the PYTEST flag is actually set.
     """
     assert config.env.PYTEST_IS_RUNNING
+
+
+def _run_with_env(code, **env):
+    """
+    Run ``code`` in a fresh interpreter, so that the module reads ``env`` on import.
+    """
+    return subprocess.run(
+        [sys.executable, "-c", code],
+        env={**os.environ, **env},
+        capture_output=True,
+        text=True,
+    )
+
+
+def test_env_defaults():
+    code = (
+        "import alphabet_mask as am;"
+        "am.rust.common_alphabets(['abc'] * 10, None);"
+        "print(am.last_call_stats().chunk_memory > 0);"
+        "print(am.Masker().mask('a?'));"
+        "print(am.Masker().mask('a\\0'))"
+    )
+    result = _run_with_env(
+        code,
+        ALPHABET_MASK_CHUNK_LIMIT="8",
+        ALPHABET_MASK_NUM_THREADS="2",
+        ALPHABET_MASK_ON_INVALID="skip",
+        ALPHABET_MASK_ON_CONTROL="space",
+    )
+
+    assert result.returncode == 0, result.stderr
+    assert result.stdout.split() == ["True", "2", "3"]
+
+
+def test_env_overridden_per_call():
+    code = (
+        "import alphabet_mask as am;"
+        "am.Masker(on_invalid='error').mask('a?')"
+    )
+    result = _run_with_env(code, ALPHABET_MASK_ON_INVALID="skip")
+
+    assert result.returncode != 0
+    assert "ValueError" in result.stderr
+
+
+def test_env_invalid():
+    result = _run_with_env("import alphabet_mask", ALPHABET_MASK_NUM_THREADS="many")
+
+    assert result.returncode != 0
+    assert "ALPHABET_MASK_NUM_THREADS" in result.stderr
+
+
+def test_calibrate(tmp_path):
+    path = tmp_path / "calibration.txt"
+    code = (
+        "import alphabet_mask as am;"
+        f"result = am.calibrate({str(path)!r}, max_chunk_limit=8192, repeats=1);"
+        "print(result['chunk_limit']);"
+        "print([sample[0] for sample in result['samples']]);"
+        "print(am.rust.plan([], None)['length_limit'] == result['chunk_limit'])"
+    )
+    result = _run_with_env(code)
+
+    assert result.returncode == 0, result.stderr
+    chunk_limit, limits, applied = result.stdout.splitlines()
+    assert int(chunk_limit) in (4096, 8192, 16384)
+    assert limits == "[4096, 8192]"
+    assert applied == "True"
+
+    loaded = _run_with_env(
+        "import alphabet_mask as am; print(am.rust.plan([], None)['length_limit'])",
+        ALPHABET_MASK_CALIBRATION=str(path),
+    )
+    assert loaded.returncode == 0, loaded.stderr
+    assert loaded.stdout.strip() == chunk_limit
+
+
+def test_calibrate_invalid(tmp_path):
+    path = tmp_path / "calibration.txt"
+    path.write_text("chunk_limit=4096\n")
+    result = _run_with_env("import alphabet_mask", ALPHABET_MASK_CALIBRATION=str(path))
+
+    assert result.returncode != 0
+    assert "ALPHABET_MASK_CALIBRATION" in result.stderr
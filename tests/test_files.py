@@ -0,0 +1,225 @@
+# -*- coding: utf-8 -*-
+import asyncio
+
+import pytest
+from alphabet_mask import aio, rust
+
+
+@pytest.fixture
+def text_files(tmp_path):
+    """
+    Two files of newline-delimited records.
+    """
+    first = tmp_path / "first.txt"
+    first.write_text("The quick brown fox.\nA lazy dog.\n")
+    second = tmp_path / "second.txt"
+    second.write_text("Another fox, quick.\n")
+    return [first, second]
+
+
+def test_common_alphabets_files(text_files):
+    """
+    Assert that every line of every file is intersected.
+    """
+    assert rust.common_alphabets_files(text_files) == " o."
+
+
+def test_common_alphabets_files_async(text_files):
+    """
+    Assert that the awaitable variant agrees with the synchronous one.
+    """
+    assert asyncio.run(aio.common_alphabets_files(text_files)) == " o."
+
+
+def test_alphabet_masks_for_files(text_files):
+    """
+    Assert that each file has the union of its lines, keyed by the path given,
+    with the intersection of every line of every file.
+    """
+    first, second = text_files
+    result = rust.alphabet_masks_for_files([first, str(second)])
+
+    assert result["masks"] == {
+        first: rust.alphabet_mask("The quick brown fox. A lazy dog."),
+        str(second): rust.alphabet_mask("Another fox, quick."),
+    }
+    assert rust.mask_to_chars(result["common"]) == " o."
+
+
+@pytest.mark.parametrize(
+    "predicate, mask, expected",
+    [
+        ("subset", "abcdefghijklmnopqrstuvwxyz ", ["the cat", "", "a dog"]),
+        ("superset", "at", ["the cat", "the hat."]),
+        ("contains", 0b110, ["the cat", "the hat.", "a dog"]),
+        ("avoids", "o", ["the cat", "", "the hat."]),
+        ("contains(a,t) and not any(.)", None, ["the cat"]),
+        ("subset(' adgo') or avoids(' ')", None, ["", "a dog"]),
+    ],
+)
+def test_filter_file(tmp_path, predicate, mask, expected):
+    """
+    Assert that only the matching lines are kept, in order, and lines outside
+    the alphabet are dropped.
+    """
+    path_in, path_out = tmp_path / "in.txt", tmp_path / "out.txt"
+    path_in.write_text("the cat\nerror 404\n\nthe hat.\na dog\n")
+
+    assert rust.filter_file(path_in, path_out, predicate, mask) == len(expected)
+    assert path_out.read_text().split("\n")[:-1] == expected
+
+
+def test_filter_file_invalid_predicate(tmp_path):
+    path = tmp_path / "in.txt"
+    path.write_text("abc\n")
+
+    with pytest.raises(ValueError, match="predicate"):
+        rust.filter_file(path, tmp_path / "out.txt", "within", "abc")
+    with pytest.raises(ValueError, match="predicate expression"):
+        rust.filter_file(path, tmp_path / "out.txt", "contains(a")
+
+
+def test_common_alphabets_files_missing(tmp_path):
+    """
+    Assert that missing files raise an ``OSError``.
+    """
+    with pytest.raises(OSError):
+        rust.common_alphabets_files([tmp_path / "missing.txt"])
+
+
+def test_common_alphabets_files_checkpoint(text_files, tmp_path):
+    """
+    Assert that a checkpointed scan gives the same result, and cleans up after itself.
+    """
+    checkpoint = tmp_path / "scan.checkpoint"
+
+    assert (
+        rust.common_alphabets_files(
+            text_files, checkpoint=checkpoint, checkpoint_interval=8
+        )
+        == " o."
+    )
+    assert not checkpoint.exists()
+
+
+
+def _outcome(call):
+    try:
+        return call()
+    except ValueError as err:
+        return type(err), str(err)
+
+
+@pytest.mark.parametrize("seed", range(10))
+def test_common_alphabets_files_parallel_matches_serial(seed, tmp_path):
+    """
+    Assert that reading in parallel blocks fails on the first invalid line, as
+    reading in one block does, with invalid lines in several blocks.
+    """
+    import random
+
+    rng = random.Random(seed)
+    lines = [
+        "".join(rng.choices("abcdefghijklmnopqrstuvwxyz .,", k=rng.randrange(40)))
+        for _ in range(200)
+    ]
+    first = rng.randrange(100)
+    lines[first] += "1"
+    lines[199 - first] += "\0"
+    path = tmp_path / "lines.txt"
+    path.write_text("\n".join(lines))
+
+    serial = _outcome(lambda: rust.common_alphabets_files([path], 1 << 30))
+    assert "'1'" in serial[1]
+    for length_limit in (1, 40, 400):
+        parallel = _outcome(lambda: rust.common_alphabets_files([path], length_limit))
+        assert parallel == serial
+        checkpoint = tmp_path / f"{length_limit}.checkpoint"
+        assert (
+            _outcome(
+                lambda: rust.common_alphabets_files(
+                    [path], length_limit, checkpoint, checkpoint_interval=400
+                )
+            )
+            == serial
+        )
+
+def test_common_alphabets_files_checkpoint_invalid(text_files, tmp_path):
+    """
+    Assert that a zero interval, or a checkpoint of other files, is rejected.
+    """
+    checkpoint = tmp_path / "scan.checkpoint"
+
+    with pytest.raises(ValueError):
+        rust.common_alphabets_files(
+            text_files, checkpoint=checkpoint, checkpoint_interval=0
+        )
+    checkpoint.write_text(
+        "alphabet_mask checkpoint v2\nfile_index=1\nbyte_offset=0\npaths=0\n"
+    )
+    with pytest.raises(ValueError, match="different list of files"):
+        rust.common_alphabets_files(text_files, checkpoint=checkpoint)
+
+
+@pytest.mark.parametrize(
+    ("format", "expected"),
+    [
+        ("int", [str(rust.alphabet_mask("A lazy dog."))]),
+        ("chars", [" adgloyz."]),
+    ],
+)
+def test_mask_file(tmp_path, format, expected):
+    """
+    Assert that one mask is written per input line.
+    """
+    path_in = tmp_path / "in.txt"
+    path_in.write_text("A lazy dog.\n")
+    path_out = tmp_path / "out.txt"
+
+    assert rust.mask_file(path_in, path_out, format) == 1
+    assert path_out.read_text().splitlines() == expected
+
+
+def test_column_alphabets_csv(tmp_path):
+    path = tmp_path / "table.csv"
+    path.write_text('id;name;notes\n1;the cat;"hello, world"\n2;the hat;hi\n')
+
+    assert rust.column_alphabets_csv(path, ["name", "notes"], delimiter=";") == {
+        "name": {"common": " aeht", "union": " aceht"},
+        "notes": {"common": "h", "union": " dehilorw,"},
+    }
+    assert rust.column_alphabets_csv(path, "name", delimiter=";") == {
+        "name": {"common": " aeht", "union": " aceht"},
+    }
+
+    with pytest.raises(ValueError, match="columns"):
+        rust.column_alphabets_csv(path, ["age"], delimiter=";")
+    with pytest.raises(ValueError, match="invalid character '1'"):
+        rust.column_alphabets_csv(path, ["id"], delimiter=";")
+    with pytest.raises(ValueError, match="delimiter"):
+        rust.column_alphabets_csv(path, ["name"], delimiter=";;")
+
+
+def test_iter_file_masks(tmp_path):
+    """
+    Assert that the masks of every line are yielded block by block, in order.
+    """
+    lines = [f"line {'abc' * (index % 5)}" for index in range(1000)]
+    path = tmp_path / "in.txt"
+    path.write_text("\n".join(lines) + "\n")
+
+    chunks = rust.iter_file_masks(path, length_limit=1024)
+    masks = []
+    for chunk in chunks:
+        assert 0 < len(chunk) < len(lines)
+        masks.extend(chunk)
+        assert chunks.position == len(masks)
+
+    assert masks == rust.alphabet_masks(lines)
+    assert list(chunks) == []
+
+    path.write_text("the cat\nerror 404\n")
+    with pytest.raises(ValueError):
+        list(rust.iter_file_masks(path))
+    with pytest.raises(OSError):
+        rust.iter_file_masks(tmp_path / "missing.txt")
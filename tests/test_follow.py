@@ -0,0 +1,118 @@
+# -*- coding: utf-8 -*-
+import time
+
+import pytest
+
+import alphabet_mask
+
+
+def wait_for(stream, strings, timeout=10):
+    deadline = time.monotonic() + timeout
+    while time.monotonic() < deadline:
+        snapshot = stream.snapshot()
+        if snapshot["strings"] >= strings:
+            return snapshot
+        time.sleep(0.01)
+    raise TimeoutError(snapshot)
+
+
+def test_file_stream(tmp_path):
+    path = tmp_path / "records.txt"
+    path.write_text("the cat\nthe hat")
+
+    stream = alphabet_mask.FileStream(path)
+    assert stream.wait() == {
+        "strings": 2,
+        "bytes": 14,
+        "common": " aeht",
+        "union": " aceht",
+    }
+    assert stream.done
+
+
+def test_file_stream_tail(tmp_path):
+    path = tmp_path / "app.log"
+    path.write_text("the cat\n")
+
+    with alphabet_mask.FileStream(path, tail=True, poll_interval=0.01) as stream:
+        assert wait_for(stream, 1)["union"] == " aceht"
+
+        with path.open("a") as file:
+            file.write("a dog")
+            file.flush()
+            time.sleep(0.1)
+            # The partial record is not masked until its line ends.
+            assert stream.snapshot()["strings"] == 1
+            file.write("\n")
+
+        snapshot = wait_for(stream, 2)
+        assert snapshot["common"] == " a"
+        assert snapshot["union"] == " acdeghot"
+        assert not stream.done
+
+    assert stream.done
+
+
+def test_file_stream_invalid_line(tmp_path):
+    path = tmp_path / "records.txt"
+    path.write_text("the cat\nerror 404\nthe hat\n")
+
+    stream = alphabet_mask.FileStream(path)
+    with pytest.raises(ValueError, match="invalid character '4'"):
+        stream.wait()
+    assert stream.snapshot()["strings"] == 1
+
+
+def test_file_stream_missing(tmp_path):
+    with pytest.raises(OSError):
+        alphabet_mask.FileStream(tmp_path / "missing.log", tail=True)
+
+
+def test_file_stream_approx(tmp_path):
+    path = tmp_path / "records.txt"
+    path.write_text("\n".join(["the cat", "a dog", "the hat", "a cow"] * 1000))
+
+    snapshot = alphabet_mask.FileStream(
+        path, approx=True, sample_size=100, seed=1
+    ).wait()
+    assert snapshot["strings"] == 4000
+    assert snapshot["bytes"] == 24000
+    assert snapshot["sample_size"] == 100
+    assert snapshot["common"] == " a"
+    assert set(snapshot["union"]) <= set(" acdeghotw")
+
+    ratio, low, high = snapshot["coverage"]["t"]
+    assert low <= 0.5 <= high
+    assert low <= ratio <= high
+    ratio, low, high = snapshot["coverage"]["a"]
+    assert (ratio, high) == (1.0, 1.0)
+    assert 0.96 < low < 0.97
+    assert snapshot["coverage"]["z"][0] == 0.0
+
+    # The same seed samples the same lines.
+    assert (
+        alphabet_mask.FileStream(path, approx=True, sample_size=100, seed=1).wait()
+        == snapshot
+    )
+
+
+def test_file_stream_approx_invalid(tmp_path):
+    path = tmp_path / "records.txt"
+    path.write_text("the cat\n")
+    with pytest.raises(ValueError, match="sample_size"):
+        alphabet_mask.FileStream(path, approx=True, sample_size=0)
+
+
+def test_file_stream_top_letters(tmp_path):
+    path = tmp_path / "records.txt"
+    path.write_text("the cat\nthe hat\na bat\n")
+
+    stream = alphabet_mask.FileStream(path)
+    stream.wait()
+    assert stream.top_letters(3) == [("a", 3), ("t", 3), ("e", 2)]
+    assert stream.top_letters(0) == []
+    assert len(stream.top_letters(26)) == 6
+
+    approx = alphabet_mask.FileStream(path, approx=True, sample_size=10)
+    approx.wait()
+    assert approx.top_letters(3) == [("a", 3), ("t", 3), ("e", 2)]
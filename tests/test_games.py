@@ -0,0 +1,62 @@
+# -*- coding: utf-8 -*-
+import pytest
+
+from alphabet_mask import rust
+
+WORDS = ["crane", "cacao", "coast", "cigar", "apple", "Allee", "eerie", "sheep"]
+
+
+@pytest.mark.parametrize(
+    ("greens", "yellows", "grays", "expected"),
+    [
+        ({}, {}, "", WORDS),
+        ({0: "c"}, {2: "a"}, "ts", ["cacao", "cigar"]),
+        ({4: "e"}, {}, "c", ["apple", "Allee", "eerie"]),
+        ({}, {0: "e", 1: "p"}, "r", ["sheep"]),
+        ({4: "e"}, {}, "e", ["crane", "apple", "Allee", "eerie"]),
+    ],
+)
+def test_filter_wordle(greens, yellows, grays, expected):
+    assert rust.filter_wordle(WORDS, greens, yellows, grays) == expected
+
+
+def test_filter_wordle_invalid():
+    with pytest.raises(ValueError):
+        rust.filter_wordle(WORDS, grays="1")
+
+    with pytest.raises(ValueError):
+        rust.filter_wordle(["ok", "n0"])
+
+
+@pytest.mark.parametrize(
+    ("blanks", "expected"),
+    [
+        (0, ["bat", "tabs", "stab", ""]),
+        (1, ["bat", "abbas", "tabs", "stab", "sabbat", ""]),
+        (2, ["bat", "abbas", "tabs", "stab", "sabbat", "zz", ""]),
+    ],
+)
+def test_playable_from_rack(blanks, expected):
+    words = ["bat", "abbas", "tabs", "stab", "sabbat", "zz", ""]
+
+    assert rust.playable_from_rack(words, "AABST", blanks=blanks) == expected
+
+
+def test_playable_from_rack_invalid():
+    with pytest.raises(ValueError):
+        rust.playable_from_rack(["bat"], "ab1")
+
+
+def test_group_anagrams():
+    words = ["listen", "google", "Silent", "enlist", "tinsel", "gogle", "ogle gog"]
+
+    assert rust.group_anagrams(words) == [
+        ["listen", "Silent", "enlist", "tinsel"],
+        ["google"],
+        ["gogle"],
+        ["ogle gog"],
+    ]
+    assert rust.group_anagrams([]) == []
+
+    with pytest.raises(ValueError):
+        rust.group_anagrams(["a", "1"])
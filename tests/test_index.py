@@ -0,0 +1,39 @@
+# -*- coding: utf-8 -*-
+import pytest
+
+from alphabet_mask import WordIndex, rust
+
+WORDS = ["tea", "eat", "treat", "rat", "a", "zebra", "tee"]
+
+
+def test_queries():
+    index = WordIndex(WORDS)
+
+    assert len(index) == len(WORDS)
+    assert index.subsets("aet") == ["a", "tee", "tea", "eat"]
+    assert index.subsets(rust.alphabet_mask("aet")) == index.subsets("aet")
+    assert index.supersets("ra") == ["rat", "treat", "zebra"]
+    assert index.equal("ate") == ["tea", "eat"]
+    assert index.similar("tear", k=2) == [("treat", 1.0), ("tea", 0.75)]
+
+
+def test_invalid_words():
+    with pytest.raises(ValueError):
+        WordIndex(["ok", "n0"])
+
+    with pytest.raises(ValueError):
+        WordIndex(WORDS).subsets("a1")
+
+
+def test_save_and_load(tmp_path):
+    path = tmp_path / "words.idx"
+    index = WordIndex(WORDS)
+    index.save(path)
+
+    loaded = WordIndex.load(path)
+    assert len(loaded) == len(WORDS)
+    assert loaded.subsets("aet") == index.subsets("aet")
+
+    path.write_bytes(b"AW\x02")
+    with pytest.raises(ValueError):
+        WordIndex.load(path)
@@ -0,0 +1,73 @@
+# -*- coding: utf-8 -*-
+import pytest
+
+from alphabet_mask import (
+    InputTooLargeError,
+    Masker,
+    input_limits,
+    rust,
+    set_input_limits,
+)
+
+
+@pytest.fixture
+def limits():
+    set_input_limits(max_bytes=10, max_strings=3, max_string_length=5)
+    yield
+    set_input_limits()
+
+
+def test_within_limits(limits):
+    assert input_limits() == {"max_bytes": 10, "max_strings": 3, "max_string_length": 5}
+    assert rust.common_alphabets(["abc", "bcd", "cde"], None) == "c"
+    assert rust.alphabet_mask("abcde") == 0b111110
+
+
+@pytest.mark.parametrize(
+    "call, limit",
+    [
+        (lambda: rust.common_alphabets(["a"] * 4, None), "max_strings"),
+        (lambda: rust.union_alphabets(["abcde", "abcde", "a"]), "max_bytes"),
+        (lambda: rust.alphabet_mask("abcdef"), "max_string_length"),
+        (lambda: rust.alphabet_masks({"x": ["a"], "y": ["a"] * 3}), "max_strings"),
+        (lambda: rust.common_mask(["abcdef"], None), "max_string_length"),
+        (lambda: Masker().common_mask(["a"] * 4), "max_strings"),
+        (lambda: rust.filter_wordle(["a"] * 4), "max_strings"),
+        (lambda: rust.playable_from_rack(["abcdef"], "abc"), "max_string_length"),
+        (lambda: rust.group_anagrams(["abcde", "edcba", "a"]), "max_bytes"),
+        (lambda: rust.validate_charset(["a"] * 4, "a"), "max_strings"),
+        (lambda: rust.invalid_bases(["ACGTNA"]), "max_string_length"),
+        (lambda: rust.validate_corpus(["a"] * 4), "max_strings"),
+        (lambda: rust.script_coverage(["abcdef"]), "max_string_length"),
+        (
+            lambda: rust.summarise_alphabets(["a"] * 4, approx=True, sample_size=1),
+            "max_strings",
+        ),
+    ],
+)
+def test_too_large(limits, call, limit):
+    with pytest.raises(InputTooLargeError, match=limit):
+        call()
+
+
+def test_files_too_large(limits, tmp_path):
+    path = tmp_path / "corpus.txt"
+    path.write_text("abc\n" * 3)
+
+    with pytest.raises(InputTooLargeError, match="max_bytes"):
+        rust.common_alphabets_files([str(path)], None, None, None)
+    with pytest.raises(InputTooLargeError, match="max_bytes"):
+        rust.invalid_bases_file(str(path))
+    # Lines are only limited by their total size.
+    path.write_text("a\nb\nc\nd\n")
+    assert rust.common_alphabets_files([str(path)], None, None, None) == ""
+
+
+def test_no_limits():
+    assert input_limits() == {
+        "max_bytes": None,
+        "max_strings": None,
+        "max_string_length": None,
+    }
+    assert issubclass(InputTooLargeError, ValueError)
+    assert rust.common_alphabets(["abcdef"] * 100, None) == "abcdef"
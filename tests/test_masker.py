@@ -0,0 +1,160 @@
+# -*- coding: utf-8 -*-
+import json
+
+import pytest
+
+from alphabet_mask import Masker, rust
+
+SPEC = {
+    "width": 4,
+    "case": "sensitive",
+    "symbols": [["a", 0], ["b", 1], ["A", 2]],
+    "reserved": [3],
+}
+
+
+def test_standard_masker():
+    masker = Masker()
+
+    assert masker.width == 32
+    assert masker.mask("The cat.") == rust.alphabet_mask("The cat.")
+    assert masker.common_alphabets(["the cat", "a hat"]) == " aht"
+    with pytest.raises(ValueError):
+        masker.mask("a1")
+
+    assert Masker(on_invalid="skip", threads=2).mask("a1") == rust.alphabet_mask("a")
+    with pytest.raises(ValueError):
+        Masker(on_invalid="ignore")
+
+
+@pytest.mark.parametrize("suffix", [".json", ".toml"])
+def test_from_spec_file(tmp_path, suffix):
+    path = tmp_path / f"spec{suffix}"
+    if suffix == ".json":
+        path.write_text(json.dumps(SPEC))
+    else:
+        path.write_text(
+            'width = 4\ncase = "sensitive"\n'
+            'symbols = [["a", 0], ["b", 1], ["A", 2]]\nreserved = [3]\n'
+        )
+
+    masker = Masker.from_spec_file(str(path))
+
+    assert masker.mask("abA") == 0b111
+    assert masker.mask_to_chars(0b101) == "aA"
+    assert masker.union_mask(["a", "b"]) == 0b011
+    # The reserved bit 3 and every bit beyond the width are cleared.
+    assert masker.normalize_mask(0xFF) == 0b111
+    assert masker.validate_mask(0b101) == 0b101
+    with pytest.raises(ValueError, match=r"bits \[3\]"):
+        masker.mask_to_chars(0b1001)
+    with pytest.raises(ValueError, match="more than once"):
+        Masker.from_spec_file(str(path), case_sensitive=False)
+
+
+def test_custom_order(tmp_path):
+    frequency = Masker.from_order("etaoinshrdlcumwfgypbvkjxqz .,'-\"")
+    standard = Masker()
+
+    mask = frequency.chars_to_mask("Tea")
+    assert mask == 0b111
+    assert frequency.mask_to_chars(mask) == "eta"
+    assert frequency.convert_mask(mask, standard) == standard.chars_to_mask("tea")
+    assert standard.convert_mask(rust.alphabet_mask("tea"), frequency) == mask
+
+    with pytest.raises(ValueError, match="no bit in the target layout"):
+        standard.convert_mask(rust.alphabet_mask("z"), Masker.from_order("abc"))
+    with pytest.raises(ValueError, match="more than once"):
+        Masker.from_order("abca")
+
+    # Spec files can give the symbols in bit order too.
+    path = tmp_path / "spec.json"
+    path.write_text(json.dumps({**SPEC, "symbols": "abA"}))
+    assert Masker.from_spec_file(str(path)).mask_to_chars(0b101) == "aA"
+
+
+def test_other_bit(tmp_path):
+    path = tmp_path / "spec.json"
+    path.write_text(json.dumps({**SPEC, "reserved": [], "other": 3}))
+
+    masker = Masker.from_spec_file(str(path))
+
+    assert masker.other_bit == 3
+    assert Masker().other_bit is None
+    # Characters outside the alphabet set the other bit instead of failing.
+    assert masker.mask("ab?!") == 0b1011
+    assert masker.common_mask(["a1", "ab2"]) == 0b1001
+    assert masker.mask_to_chars(0b1001) == "a\ufffd"
+    assert masker.validate_mask(0b1000) == 0b1000
+
+    path.write_text(json.dumps({**SPEC, "other": 3}))
+    with pytest.raises(ValueError, match="reserved"):
+        Masker.from_spec_file(str(path))
+
+
+def test_control_characters():
+    with pytest.raises(ValueError, match=r"'\\0' at position 2"):
+        Masker(on_invalid="skip").mask("ab\x00c")
+    with pytest.raises(ValueError, match="position 1"):
+        rust.alphabet_mask("a\tb")
+
+    assert Masker(on_control="skip").mask("a\tb\x7f") == rust.alphabet_mask("ab")
+    assert Masker(on_control="space").mask("a\tb\x85") == rust.alphabet_mask("a b")
+    with pytest.raises(ValueError):
+        Masker(on_control="tab")
+
+
+def test_lone_surrogates(tmp_path):
+    escaped = b"ab\xff".decode("utf-8", "surrogateescape")
+
+    with pytest.raises(ValueError, match="position 2"):
+        Masker().mask(escaped)
+    assert Masker(on_invalid="skip").mask(escaped) == rust.alphabet_mask("ab")
+    assert Masker(on_invalid="skip").common_alphabets([escaped, "b"]) == "b"
+
+    path = tmp_path / "spec.json"
+    path.write_text(json.dumps({**SPEC, "reserved": [], "other": 3}))
+    assert Masker.from_spec_file(str(path)).mask(escaped) == 0b1011
+
+
+def test_fallbacks(tmp_path):
+    masker = Masker(fallbacks="typographic")
+
+    assert masker.mask("It\u2019s \u201cdone\u201d\u2026") == rust.alphabet_mask(
+        "It's \"done\"."
+    )
+    assert Masker(fallbacks={"\u00e9": "e"}).mask("caf\u00e9") == rust.alphabet_mask("cafe")
+    with pytest.raises(ValueError):
+        Masker().mask("\u2019")
+    with pytest.raises(ValueError):
+        Masker(fallbacks="fancy")
+    with pytest.raises(ValueError, match="not a symbol"):
+        Masker(fallbacks={"!": "?"})
+
+    path = tmp_path / "spec.json"
+    path.write_text(json.dumps({**SPEC, "fallbacks": [["\u00e1", "a"]]}))
+    masker = Masker.from_spec_file(str(path), fallbacks={"B": "b"})
+    assert masker.mask("\u00e1B") == 0b011
+
+
+def test_invalid_spec_file(tmp_path):
+    path = tmp_path / "spec.json"
+    path.write_text(json.dumps({**SPEC, "symbols": [["a", 3]]}))
+
+    with pytest.raises(ValueError, match="reserved"):
+        Masker.from_spec_file(str(path))
+
+
+def test_transforms():
+    masker = Masker(transforms=["fold_diacritics", "lowercase", {"ß": "s", "€": None}])
+
+    assert masker.mask_to_chars(masker.mask("Café ß€")) == " acefs"
+    assert masker.common_alphabets(["Été", "ÉTÉ"]) == "et"
+
+    stripped = Masker(transforms=["strip_punctuation", "fold_whitespace"])
+    assert stripped.mask("Hi!\tyou?") == rust.alphabet_mask("hi you")
+
+    with pytest.raises(ValueError):
+        Masker(transforms=["uppercase"])
+    with pytest.raises(ValueError):
+        Masker(transforms=[{"ß": "ss"}])
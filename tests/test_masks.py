@@ -1,7 +1,8 @@
 # -*- coding: utf-8 -*-
+import struct
 from typing import Callable
 import pytest
-from alphabet_mask import python, rust
+from alphabet_mask import CountedAlphabet, bits, build_info, python, rust
 
 
 @pytest.mark.parametrize(
@@ -53,3 +54,537 @@ def test_masks(mask: Callable[[str], int], input: str, expected: int):
     Assert that the alphabet mask is correct.
     """
     assert mask(input) == expected
+
+
+def test_alphabet_masks():
+    """
+    Assert that the batch masks match the individual masks.
+    """
+    strings = ["The quick brown fox jumps over the lazy dog.", "a c e.", ""]
+
+    assert rust.alphabet_masks(strings) == [rust.alphabet_mask(s) for s in strings]
+
+
+def test_alphabet_masks_as_bytes():
+    """
+    Assert that the batch masks can be returned as little-endian ``u32`` bytes.
+    """
+    strings = ["The quick brown fox jumps over the lazy dog.", "a c e.", ""]
+    result = rust.alphabet_masks(strings, output="bytes")
+
+    assert isinstance(result, bytes)
+    assert list(struct.unpack(f"<{len(strings)}I", result)) == rust.alphabet_masks(
+        strings
+    )
+
+
+def test_named_bits():
+    assert python.alphabet_mask(" ") == 1 << bits.SPACE_BIT
+    assert python.alphabet_mask("Q") == 1 << bits.LETTER_Q_BIT
+    assert python.alphabet_mask('".') == (1 << bits.DOUBLE_QUOTE_BIT) | (
+        1 << bits.FULL_STOP_BIT
+    )
+    assert bits.LETTERS_MASK == python.alphabet_mask("abcdefghijklmnopqrstuvwxyz")
+    assert bits.PUNCT_MASK == python.alphabet_mask(".,'-\"")
+
+
+def test_union_alphabets():
+    """
+    Assert that the union contains every character of every string.
+    """
+    assert rust.union_alphabets(["cab", "a-b", ""]) == "abc-"
+
+
+def test_build_info():
+    info = build_info()
+
+    assert info["version"]
+    assert "parallel" in info["features"]
+    assert info["layout_version"] == 1
+    assert isinstance(info["cpu_features"], list)
+
+
+def test_compare_corpora():
+    report = rust.compare_corpora(["abc", "ab"], ["bcd", "bd", "b", "x"])
+
+    assert report["strings_a"] == 2
+    assert report["strings_b"] == 4
+    assert report["common_a"] == "ab"
+    assert report["common_b"] == ""
+    assert report["union_a"] == "abc"
+    assert report["union_b"] == "bcdx"
+    assert report["only_a"] == "a"
+    assert report["only_b"] == "dx"
+    assert report["coverage_delta"] == {
+        "a": -1.0,
+        "b": -0.25,
+        "c": -0.25,
+        "d": 0.5,
+        "x": 0.25,
+    }
+
+    with pytest.raises(ValueError):
+        rust.compare_corpora(["a"], ["1"])
+
+
+def test_char_transitions():
+    matrix = rust.char_transitions(["The cat", "the hat"])
+    assert len(matrix) == 32 and all(len(row) == 32 for row in matrix)
+    assert matrix[bits.LETTER_T_BIT][bits.LETTER_H_BIT] == 2
+    assert matrix[bits.SPACE_BIT][bits.LETTER_C_BIT] == 1
+    assert sum(map(sum, matrix)) == 12
+    assert sum(map(sum, rust.char_transitions([]))) == 0
+
+    with pytest.raises(ValueError):
+        rust.char_transitions(["ab", "a1"])
+
+
+def test_diff_alphabets():
+    diff = rust.diff_alphabets(["cafe", "face"], ["cafes", "faces", "ace"])
+    assert diff == {
+        "union_gained": "s",
+        "union_lost": "",
+        "common_gained": "",
+        "common_lost": "f",
+    }
+
+    counted = CountedAlphabet(["cafe", "face"])
+    assert rust.diff_alphabets(counted, ["cafes", "faces", "ace"]) == diff
+    assert rust.diff_alphabets(counted, counted)["union_lost"] == ""
+
+    with pytest.raises(ValueError):
+        rust.diff_alphabets(["a"], ["1"])
+
+
+def test_profile():
+    columns = rust.profile(
+        ["Pack my box with 5 dozen liquor jugs, five!", "abcd", "?! ...", ""]
+    )
+
+    assert columns["mask"] == [
+        rust.alphabet_mask("Pack my box with dozen liquor jugs, five"),
+        0b11110,
+        rust.alphabet_mask(" ..."),
+        0,
+    ]
+    assert columns["coverage"] == [1.0, 4 / 26, 0.0, 0.0]
+    assert columns["pangram"] == [True, False, False, False]
+    assert columns["has_digits"] == [True, False, False, False]
+    assert columns["punctuation_only"] == [False, False, True, False]
+
+
+@pytest.mark.parametrize(
+    "mask",
+    [0, 1, 0b1110, bits.LETTERS_MASK, bits.PUNCT_MASK, 0xFFFFFFFF, 0x1234_5678],
+)
+def test_normalize_round_trip(mask: int):
+    assert rust.chars_to_mask(rust.mask_to_chars(mask)) == rust.normalize_mask(mask)
+
+
+def test_normalize_mask():
+    assert rust.normalize_mask(0b1110) == 0b1110
+    # Persisted as a signed 32-bit integer, or with stray high bits.
+    assert rust.normalize_mask(-1) == 0xFFFFFFFF
+    assert rust.normalize_mask((1 << 40) | 0b10) == 0b10
+
+
+def test_plan():
+    strings = ["ab", "cd", "efgh"]
+
+    assert rust.plan(strings) == {
+        "parallel": False,
+        "length_limit": 1 << 20,
+        "chunks": [(0, 3, 8)],
+    }
+    assert rust.plan(strings, 3) == {
+        "parallel": True,
+        "length_limit": 3,
+        "chunks": [(0, 1, 2), (1, 2, 2), (2, 3, 4)],
+    }
+    assert rust.plan([], 3)["chunks"] == []
+
+
+@pytest.mark.parametrize(
+    ("kwargs", "expected"),
+    [
+        ({}, " dehlorw.,"),
+        ({"order": "bit"}, " dehlorw.,"),
+        ({"order": "alphabetical"}, " ,.dehlorw"),
+        ({"order": "custom", "custom": "World"}, "world eh.,"),
+    ],
+)
+def test_mask_to_chars_order(kwargs, expected):
+    mask = rust.alphabet_mask("Hello, world.")
+
+    assert rust.mask_to_chars(mask, **kwargs) == expected
+
+
+@pytest.mark.parametrize(
+    "kwargs",
+    [{"order": "reverse"}, {"order": "custom"}, {"order": "bit", "custom": "abc"}],
+)
+def test_mask_to_chars_invalid_order(kwargs):
+    with pytest.raises(ValueError):
+        rust.mask_to_chars(0b1110, **kwargs)
+
+
+def test_digits():
+    mask = rust.alphabet_mask("SKU 42-b", digits=True)
+
+    assert mask == rust.alphabet_mask("SKU -b") | 1 << 36 | 1 << 34
+    assert rust.mask_to_chars(mask, digits=True) == " bksu-24"
+    assert rust.mask_to_chars(mask, order="alphabetical", digits=True) == " -24bksu"
+    assert rust.common_alphabets(["AB12", "b2", "2b"], digits=True) == "b2"
+    assert rust.common_alphabets({"k": ["a1", "1a"]}, digits=True) == {"k": "a1"}
+    with pytest.raises(ValueError):
+        rust.alphabet_mask("SKU 42")
+    with pytest.raises(ValueError):
+        rust.mask_to_chars(mask)
+    with pytest.raises(ValueError):
+        rust.mask_to_chars(1 << 42, digits=True)
+
+
+def test_extended():
+    mask = rust.alphabet_mask("Why? (Because.) 42;", extended=True)
+
+    assert mask & 0xFFFF_FFFF == rust.alphabet_mask("Why Because.")
+    assert rust.mask_to_chars(mask, extended=True) == " abcehsuwy.24();?"
+    assert rust.common_alphabets(["a:b/1", "1/b;a"], extended=True) == "ab1/"
+    assert rust.mask_to_chars(1 << 68, extended=True) == "~"
+    with pytest.raises(ValueError):
+        rust.alphabet_mask("a;b", digits=True)
+    with pytest.raises(ValueError):
+        rust.mask_to_chars(1 << 69, extended=True)
+
+
+def test_case_sensitive():
+    mask = rust.alphabet_mask("Hello World", case_sensitive=True)
+
+    assert mask & 0xFFFF_FFFF == rust.alphabet_mask("ello orld")
+    assert mask >> 32 == 1 << 7 | 1 << 22
+    assert rust.mask_to_chars(mask, case_sensitive=True) == " delorHW"
+    assert rust.mask_to_chars(mask, order="alphabetical", case_sensitive=True) == (
+        " HWdelor"
+    )
+    assert rust.common_alphabets(["Ab", "bA", "AB"], case_sensitive=True) == "A"
+    assert rust.common_alphabets(["Ab", "bA", "AB"]) == "ab"
+    with pytest.raises(ValueError):
+        rust.alphabet_mask("Ab", digits=True, case_sensitive=True)
+    with pytest.raises(ValueError):
+        rust.mask_to_chars(1 << 58, case_sensitive=True)
+
+
+def test_letter_count():
+    assert rust.letter_count("Hello, world.") == 7
+    assert rust.letter_count("Hello, world.", include_punctuation=True) == 9
+    assert rust.letter_count(rust.alphabet_mask("Hello")) == 4
+    assert rust.letter_count(0xFFFFFFFF) == 26
+    with pytest.raises(ValueError):
+        rust.letter_count("1")
+    with pytest.raises(TypeError):
+        rust.letter_count(None)
+
+
+def test_missing_letters_batch():
+    strings = ["The quick brown fox jumps over the lazy dog.", "Hello, world.", ""]
+
+    assert rust.missing_letters_batch(strings) == [
+        "",
+        "abcfgijkmnpqstuvxyz",
+        "abcdefghijklmnopqrstuvwxyz",
+    ]
+    assert rust.missing_letters_batch([]) == []
+    with pytest.raises(ValueError):
+        rust.missing_letters_batch(["abc", "a1"])
+
+
+def test_segment_by_common_alphabet():
+    strings = ["the cat", "a hat", "that", "xyz", "zyx", "y"]
+
+    assert rust.segment_by_common_alphabet(strings, 2) == [
+        (0, 3, "aht"),
+        (3, 5, "xyz"),
+        (5, 6, "y"),
+    ]
+    assert rust.segment_by_common_alphabet(strings, 0) == [(0, 6, "")]
+    assert rust.segment_by_common_alphabet([], 1) == []
+    with pytest.raises(ValueError):
+        rust.segment_by_common_alphabet(["abc", "a1"], 1)
+
+
+def test_common_alphabets_threshold():
+    strings = ["the cat", "the hat", "a bat", "xyz"]
+
+    assert rust.common_alphabets_threshold(strings, 0.75) == " at"
+    assert rust.common_alphabets_threshold(strings, 0.5) == " aeht"
+    assert rust.common_alphabets_threshold(strings, 1) == rust.common_alphabets(strings)
+    # The default keeps characters despite one noisy string in twenty.
+    assert rust.common_alphabets_threshold(["the cat"] * 19 + ["xyz"]) == " aceht"
+    with pytest.raises(ValueError, match="threshold"):
+        rust.common_alphabets_threshold(strings, 1.5)
+
+
+def test_common_alphabets_threshold_weights():
+    strings = ["the cat sat on the mat", "xyz"]
+
+    # One short noisy string outweighed by a long document.
+    lengths = [len(string) for string in strings]
+    assert rust.common_alphabets_threshold(strings, 0.8, lengths) == " acehmnost"
+    assert rust.common_alphabets_threshold(strings, 0.8) == ""
+    assert rust.common_alphabets_threshold(strings, 0.8, [0, 1]) == "xyz"
+    assert rust.common_alphabets_threshold(strings, 0.8, [0, 0]) == rust.common_alphabets([])
+
+    for weights in ([1.0], [1.0, -1.0], [1.0, float("nan")]):
+        with pytest.raises(ValueError, match="weights"):
+            rust.common_alphabets_threshold(strings, 0.8, weights)
+
+
+def test_summarise_alphabets():
+    strings = ["the cat"] * 990 + ["xyz"] * 10
+
+    assert rust.summarise_alphabets(strings) == {
+        "union": " acehtxyz",
+        "common": " aceht",
+        "strings": 1000,
+        "sample_size": 1000,
+    }
+
+    approx = rust.summarise_alphabets(strings, approx=True, sample_size=100, seed=1)
+    assert approx["sample_size"] == 100 and approx["strings"] == 1000
+    assert approx["common"] == " aceht"
+    assert approx == rust.summarise_alphabets(strings, approx=True, sample_size=100, seed=1)
+    assert rust.summarise_alphabets(strings, approx=True, fraction=0.25)["sample_size"] == 250
+    # A sample no smaller than the corpus masks every string.
+    assert rust.summarise_alphabets(strings, approx=True)["sample_size"] == 1000
+
+    for kwargs, message in (
+        ({"sample_size": 10}, "without approx=True"),
+        ({"approx": True, "sample_size": 0}, '"0" for `sample_size`'),
+        ({"approx": True, "fraction": 0}, '"0" for `fraction`'),
+        ({"fraction": 0.5}, "a fraction without approx=True"),
+        ({"approx": True, "sample_size": 10, "fraction": 0.5}, "with a fraction"),
+    ):
+        with pytest.raises(ValueError, match=message):
+            rust.summarise_alphabets(strings, **kwargs)
+
+
+def test_validate_mask():
+    assert rust.validate_mask(0xFFFFFFFF) == 0xFFFFFFFF
+    for invalid in [-1, 1 << 32, 1 << 100]:
+        with pytest.raises(ValueError, match="invalid mask"):
+            rust.validate_mask(invalid)
+        with pytest.raises(ValueError, match="invalid mask"):
+            rust.mask_to_chars(invalid)
+        with pytest.raises(ValueError, match="invalid mask"):
+            rust.letter_count(invalid)
+
+
+def test_positional_conversions():
+    mask = rust.alphabet_mask('ab"')
+
+    assert rust.mask_to_bits(mask) == [1, 2, 31]
+    assert rust.bits_to_mask([31, 2, 1]) == mask
+    bools = rust.mask_to_bools(mask)
+    assert len(bools) == 32
+    assert [bit for bit, flag in enumerate(bools) if flag] == [1, 2, 31]
+    assert rust.bools_to_mask(bools) == mask
+
+    with pytest.raises(ValueError):
+        rust.bits_to_mask([32])
+    with pytest.raises(ValueError):
+        rust.bools_to_mask([True])
+
+
+@pytest.mark.parametrize(
+    ("format_spec", "expected"),
+    [
+        ("", "a."),
+        ("c", "a."),
+        ("d", "134217730"),
+        ("#034b", "0b" + format(134217730, "032b")),
+        ("#x", "0x8000002"),
+        ("010X", "0008000002"),
+        ("l", "1:'a' 27:'.'"),
+    ],
+)
+def test_format_mask(format_spec, expected):
+    mask = rust.alphabet_mask("a.")
+
+    assert rust.format_mask(mask, format_spec) == expected
+    if format_spec not in ("", "c", "l"):
+        assert rust.format_mask(mask, format_spec) == format(mask, format_spec)
+
+
+def test_format_mask_invalid():
+    with pytest.raises(ValueError):
+        rust.format_mask(1, "#c")
+
+
+def test_grouped_inputs():
+    groups = {"en": ["the cat", "a hat"], ("fr", 1): ["le chat", "un chapeau"], 3: "Hi"}
+
+    assert rust.common_alphabets(groups) == {"en": " aht", ("fr", 1): " aceh", 3: "hi"}
+    assert rust.alphabet_masks(groups) == {
+        "en": rust.alphabet_masks(groups["en"]),
+        ("fr", 1): rust.alphabet_masks(groups[("fr", 1)]),
+        3: rust.alphabet_mask("Hi"),
+    }
+    assert rust.alphabet_masks({"a": ["ab"]}, output="bytes") == {
+        "a": struct.pack("<I", rust.alphabet_mask("ab"))
+    }
+    with pytest.raises(ValueError):
+        rust.common_alphabets({"bad": ["1"]})
+
+
+def test_chunk_limit():
+    import alphabet_mask
+
+    strings = ["abcdefghij"] * 100
+    assert alphabet_mask.chunk_limit() == alphabet_mask.LENGTH_LIMIT_PER_CHUNK
+    assert not rust.plan(strings)["parallel"]
+
+    alphabet_mask.set_chunk_limit(100)
+    try:
+        assert alphabet_mask.chunk_limit() == 100
+        plan = rust.plan(strings)
+        assert plan["parallel"] and plan["length_limit"] == 100
+        assert rust.common_alphabets(strings) == "abcdefghij"
+        # An explicit limit still takes precedence.
+        assert not rust.plan(strings, 1000)["parallel"]
+
+        with pytest.raises(ValueError, match="length_limit"):
+            alphabet_mask.set_chunk_limit(0)
+        assert alphabet_mask.chunk_limit() == 100
+    finally:
+        alphabet_mask.set_chunk_limit(alphabet_mask.LENGTH_LIMIT_PER_CHUNK)
+
+
+def _outcome(call):
+    try:
+        return call()
+    except ValueError as err:
+        return type(err), str(err)
+
+
+@pytest.mark.parametrize("seed", range(20))
+def test_parallel_matches_serial(seed):
+    import random
+
+    rng = random.Random(seed)
+    chars = "abcdefghijklmnopqrstuvwxyz ABC.,'-\"" * 4 + "12\0"
+    strings = [
+        "".join(rng.choices(chars, k=rng.randrange(40))) for _ in range(rng.randrange(300))
+    ]
+
+    for call in (rust.common_alphabets, rust.common_mask):
+        serial = _outcome(lambda: call(strings, 1 << 30))
+        for length_limit in (1, 40, 400):
+            assert _outcome(lambda: call(strings, length_limit)) == serial
+
+
+def test_intersect_with():
+    strings = ["the cat", "a hat"]
+    references = [rust.alphabet_mask("abc"), rust.alphabet_mask("the"), 0]
+
+    assert rust.intersect_with(strings, references) == [
+        rust.alphabet_mask("a"),
+        rust.alphabet_mask("th"),
+        0,
+    ]
+    assert rust.intersect_with(strings, [], 1) == []
+    with pytest.raises(ValueError):
+        rust.intersect_with(strings, [-1])
+
+
+ESCAPED = b"ab\xff".decode("utf-8", "surrogateescape")
+
+
+@pytest.mark.parametrize(
+    "call",
+    [
+        lambda: rust.alphabet_mask(ESCAPED),
+        lambda: rust.common_alphabets(["ab", ESCAPED]),
+        lambda: rust.common_alphabets({"a": ESCAPED}),
+        lambda: rust.union_alphabets([ESCAPED]),
+        lambda: rust.alphabet_masks([ESCAPED]),
+        lambda: rust.common_mask([ESCAPED]),
+        lambda: rust.filter_wordle([ESCAPED]),
+        lambda: rust.playable_from_rack([ESCAPED], "ab"),
+        lambda: rust.group_anagrams([ESCAPED]),
+        lambda: rust.validate_charset([ESCAPED], "ab"),
+        lambda: rust.invalid_bases([ESCAPED]),
+        lambda: rust.validate_corpus([ESCAPED]),
+        lambda: rust.script_coverage([ESCAPED]),
+    ],
+)
+def test_lone_surrogates(call):
+    with pytest.raises(ValueError, match="lone surrogate U\\+DCFF at position 2"):
+        call()
+
+
+def test_token_masks():
+    result = rust.token_masks("The  cat\tsat,\non the mat.")
+
+    assert result["masks"] == [
+        rust.alphabet_mask(token) for token in "The  cat\tsat,\non the mat.".split()
+    ]
+    assert result["common"] == ""
+    assert result["union"] == "acehmnost.,"
+    assert rust.token_masks("the cat", output="bytes")["masks"] == b"".join(
+        rust.alphabet_mask(token).to_bytes(4, "little") for token in ["the", "cat"]
+    )
+    empty = rust.token_masks(" \n")
+    assert (empty["masks"], empty["union"]) == ([], "")
+
+    with pytest.raises(ValueError):
+        rust.token_masks("the cat 42")
+
+
+def test_line_masks():
+    text = "The cat\r\nsat,\n\non the mat.\n"
+    result = rust.line_masks(text)
+
+    assert result["masks"] == [rust.alphabet_mask(line) for line in text.splitlines()]
+    assert result["common"] == ""
+    assert result["union"] == rust.union_alphabets(text.splitlines())
+    assert rust.line_masks("the cat\nthe hat")["common"] == " aeht"
+    assert rust.line_masks("")["masks"] == []
+
+    with pytest.raises(ValueError):
+        rust.line_masks("the cat\nline 2")
+
+
+def test_alphabet_masks_chunks():
+    words = (word for word in ["the", "cat", "sat", "on", "a", "mat"])
+    chunks = rust.alphabet_masks(words, chunk_size=4)
+
+    first = next(chunks)
+    assert first == [rust.alphabet_mask(word) for word in ["the", "cat", "sat", "on"]]
+    assert chunks.position == 4
+    assert list(chunks) == [[rust.alphabet_mask("a"), rust.alphabet_mask("mat")]]
+
+    as_bytes = rust.alphabet_masks(iter(["ab", "c"]), output="bytes", chunk_size=1)
+    assert list(as_bytes) == [struct.pack("<I", 0b110), struct.pack("<I", 0b1000)]
+
+    with pytest.raises(ValueError):
+        list(rust.alphabet_masks(["ab", "a1"], chunk_size=1))
+    with pytest.raises(ValueError):
+        rust.alphabet_masks(["ab"], chunk_size=0)
+
+
+def test_fold_diacritics():
+    assert rust.alphabet_mask("Café", fold_diacritics=True) == rust.alphabet_mask("cafe")
+    assert rust.alphabet_mask("ﬁancé", fold_diacritics=True) == (
+        rust.alphabet_mask("fiance")
+    )
+    strings = ["Crème brûlée", "Ångström"]
+    assert rust.common_alphabets(strings, fold_diacritics=True) == "mr"
+    assert rust.common_alphabets({"k": strings[:1]}, fold_diacritics=True) == {
+        "k": " bcelmru"
+    }
+    naive = rust.common_alphabets(["naïve"] * 1000, length_limit=64, fold_diacritics=True)
+    assert naive == "aeinv"
+    with pytest.raises(ValueError):
+        rust.alphabet_mask("Café")
+    with pytest.raises(ValueError):
+        rust.alphabet_mask("Straße", fold_diacritics=True)
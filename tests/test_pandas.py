@@ -0,0 +1,50 @@
+# -*- coding: utf-8 -*-
+import pytest
+import alphabet_mask
+from alphabet_mask import rust
+
+pd = pytest.importorskip("pandas")
+pytest.importorskip("pyarrow")
+
+import alphabet_mask.integrations.pandas  # noqa: E402, F401
+
+
+@pytest.fixture
+def series():
+    """
+    A Series with a non-default index and a missing value.
+    """
+    return pd.Series(["abc", None, "b"], index=["x", "y", "z"], name="text")
+
+
+@pytest.mark.parametrize(
+    ("null_policy", "expected"),
+    [
+        ("propagate", [0b1110, pd.NA, 0b100]),
+        ("empty", [0b1110, 0, 0b100]),
+    ],
+)
+def test_from_pandas(series, null_policy, expected):
+    """
+    Assert that masks are aligned to the index, with nulls handled per the policy.
+    """
+    masks = alphabet_mask.from_pandas(series, null_policy=null_policy)
+
+    assert masks.index.tolist() == ["x", "y", "z"]
+    assert masks.name == "text"
+    assert masks.tolist() == expected
+
+
+def test_from_pandas_raise(series):
+    """
+    Assert that the ``raise`` policy rejects missing values.
+    """
+    with pytest.raises(ValueError):
+        alphabet_mask.from_pandas(series, null_policy="raise")
+
+
+def test_accessor(series):
+    """
+    Assert that the accessor ignores missing values.
+    """
+    assert series.alphabet_mask.common() == rust.common_alphabets(["abc", "b"])
@@ -0,0 +1,64 @@
+# -*- coding: utf-8 -*-
+import pytest
+
+from alphabet_mask import Pipeline, rust
+
+WORDS = [
+    "great", "tree", "gate", "trees", "zebra", "rag", "tag", "eel", "teal", "grate"
+]
+
+
+def test_pipeline_aggregates():
+    kept = Pipeline().mask().filter(subset_of=" aeglrst", contains="e")
+    expected = [word for word in WORDS if set(word) <= set(" aeglrst") and "e" in word]
+
+    assert kept.count().run(WORDS) == len(expected)
+    assert kept.common().run(WORDS) == rust.common_alphabets(expected)
+    assert kept.union().run(WORDS) == rust.union_alphabets(expected)
+    assert kept.positions().run(WORDS) == [WORDS.index(word) for word in expected]
+    without_e = Pipeline().mask().filter(avoids="e")
+    assert without_e.filter(superset_of="ag").count().run(WORDS) == 2
+
+    expression = "contains(a,e) and not contains(z) and subset(' aeglrst')"
+    assert Pipeline().mask().filter(expression).positions().run(WORDS) == [0, 2, 8, 9]
+
+
+def test_pipeline_group_by_mask():
+    result = (
+        Pipeline()
+        .mask()
+        .filter(subset_of=" aeglrst")
+        .group_by_mask()
+        .positions()
+        .run(WORDS)
+    )
+
+    assert result == {
+        "aegrt": [0, 9],
+        "ert": [1],
+        "aegt": [2],
+        "erst": [3],
+        "agr": [5],
+        "agt": [6],
+        "el": [7],
+        "aelt": [8],
+    }
+    assert Pipeline().mask().group_by_mask().count().run([]) == {}
+
+
+def test_pipeline_stages():
+    pipeline = Pipeline().mask().filter(subset_of="abc").group_by_mask().count()
+    assert repr(pipeline) == (
+        'Pipeline().mask().filter(subset_of="abc").group_by_mask().count()'
+    )
+
+    with pytest.raises(ValueError):
+        Pipeline().filter(subset_of="abc")
+    with pytest.raises(ValueError):
+        Pipeline().mask().filter("contains(a) or")
+    with pytest.raises(ValueError):
+        pipeline.filter(contains="a")
+    with pytest.raises(ValueError):
+        Pipeline().mask().run(WORDS)
+    with pytest.raises(ValueError):
+        Pipeline().mask().count().run(["the cat", "4 cats"])
@@ -0,0 +1,41 @@
+# -*- coding: utf-8 -*-
+import pytest
+from alphabet_mask import rust
+
+pl = pytest.importorskip("polars")
+pytest.importorskip("pyarrow")
+
+import alphabet_mask.integrations.polars  # noqa: E402, F401
+
+
+def test_polars_mask():
+    """
+    Assert that the expression namespace masks each value, keeping nulls.
+    """
+    df = pl.DataFrame({"text": ["abc", None, "b"]})
+
+    assert df.select(pl.col("text").alphabet_mask.mask())["text"].to_list() == [
+        rust.alphabet_mask("abc"),
+        None,
+        rust.alphabet_mask("b"),
+    ]
+
+
+def test_polars_common_per_group():
+    """
+    Assert that the common alphabet is aggregated per group.
+    """
+    df = pl.DataFrame(
+        {
+            "group": [1, 1, 2, 2],
+            "text": ["abc", "cab", "xyz", "zz"],
+        }
+    )
+    result = (
+        df.group_by("group")
+        .agg(pl.col("text").alphabet_mask.common())
+        .sort("group")["text"]
+        .to_list()
+    )
+
+    assert result == ["abc", "z"]
@@ -0,0 +1,91 @@
+# -*- coding: utf-8 -*-
+import concurrent.futures
+from multiprocessing import shared_memory
+
+import pytest
+
+import alphabet_mask
+from alphabet_mask import rust
+
+WORDS = ["the cat", "a hat", "that", "chat"]
+
+
+def pack(words):
+    data = "".join(words).encode()
+    offsets = [0]
+    for word in words:
+        offsets.append(offsets[-1] + len(word.encode()))
+    return data, offsets
+
+
+def mask_shard(name, offsets, start, end):
+    # Only the name of the shared memory and the offsets are pickled.
+    memory = shared_memory.SharedMemory(name=name)
+    strings = alphabet_mask.SharedStrings(memory.buf, offsets, start, end)
+    partial = rust.partial_mask(strings)
+    # The buffer can only be closed once no `SharedStrings` holds it.
+    del strings
+    memory.close()
+    return partial
+
+
+def test_shared_strings():
+    data, offsets = pack(WORDS)
+    strings = alphabet_mask.SharedStrings(data, offsets)
+
+    assert len(strings) == len(WORDS)
+    assert rust.alphabet_masks(strings) == rust.alphabet_masks(WORDS)
+    assert rust.common_mask(strings) == rust.common_mask(WORDS)
+
+    shard = alphabet_mask.SharedStrings(bytearray(data), offsets, 1, 3)
+    assert rust.alphabet_masks(shard) == rust.alphabet_masks(WORDS[1:3])
+
+
+def test_shared_strings_offsets_buffer():
+    np = pytest.importorskip("numpy")
+    data, offsets = pack(WORDS)
+
+    for dtype in ["int64", "uint32"]:
+        strings = alphabet_mask.SharedStrings(data, np.array(offsets, dtype=dtype))
+        assert rust.alphabet_masks(strings) == rust.alphabet_masks(WORDS)
+
+
+def test_shared_memory_across_processes():
+    words = WORDS * 1000
+    data, offsets = pack(words)
+    memory = shared_memory.SharedMemory(create=True, size=len(data))
+    try:
+        memory.buf[: len(data)] = data
+        shards = [(i, min(i + 1000, len(words))) for i in range(0, len(words), 1000)]
+
+        with concurrent.futures.ProcessPoolExecutor(2) as pool:
+            partials = list(
+                pool.map(mask_shard, *zip(*[(memory.name, offsets, *s) for s in shards]))
+            )
+    finally:
+        memory.close()
+        memory.unlink()
+
+    assert rust.decode_partial_mask(rust.combine_masks(partials)) == rust.common_mask(
+        WORDS
+    )
+
+
+@pytest.mark.parametrize(
+    "offsets, start, end",
+    [
+        ([0, 4, 2], 0, None),
+        ([0, 4, 100], 0, None),
+        ([0, 4], 1, 0),
+        ([0, 4], 0, 5),
+    ],
+)
+def test_shared_strings_invalid_offsets(offsets, start, end):
+    with pytest.raises(ValueError):
+        alphabet_mask.SharedStrings(b"abcdefgh", offsets, start, end)
+
+
+def test_shared_strings_invalid_utf8():
+    strings = alphabet_mask.SharedStrings(b"ab\xff", [0, 2, 3])
+    with pytest.raises(ValueError, match="string 1 is not valid UTF-8"):
+        rust.alphabet_masks(strings)
@@ -0,0 +1,42 @@
+# -*- coding: utf-8 -*-
+import pickle
+
+import pytest
+from alphabet_mask import rust
+
+pd = pytest.importorskip("pandas")
+pa = pytest.importorskip("pyarrow")
+
+from alphabet_mask.integrations import spark  # noqa: E402
+
+
+def test_entry_points_are_picklable():
+    """
+    Assert that the entry points pickle by reference.
+    """
+    for func in (spark.masks, spark.partial_mask, spark.merge):
+        assert pickle.loads(pickle.dumps(func)) is func
+
+
+def test_partial_masks_merge():
+    """
+    Assert that merging partial masks equals the common mask of everything.
+    """
+    strings = ["The quick brown fox.", "A lazy dog.", "Another fox, quick."]
+    partials = [
+        spark.partial_mask(pd.Series(strings[:2])),
+        spark.partial_mask(pd.Series(strings[2:])),
+    ]
+
+    assert spark.merge(partials) == rust.common_mask(strings)
+    assert spark.merge([]) == spark.IDENTITY
+
+
+def test_mask_record_batches():
+    """
+    Assert that record batches are mapped to batches of masks.
+    """
+    batch = pa.RecordBatch.from_pydict({"text": ["abc", None, "b"]})
+    (result,) = spark.mask_record_batches([batch], "text")
+
+    assert result.column("mask").to_pylist() == [0b1110, None, 0b100]
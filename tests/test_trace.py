@@ -0,0 +1,44 @@
+# -*- coding: utf-8 -*-
+import logging
+
+import pytest
+
+from alphabet_mask import build_info, rust
+
+pytestmark = pytest.mark.skipif(
+    not hasattr(rust, "enable_tracing"), reason="built without the trace feature"
+)
+
+
+class Records(logging.Handler):
+    def __init__(self):
+        super().__init__(level=logging.DEBUG)
+        self.messages = []
+
+    def emit(self, record):
+        self.messages.append(record.getMessage())
+
+
+def test_enable_tracing():
+    logger = logging.getLogger("alphabet_mask")
+    handler = Records()
+    logger.addHandler(handler)
+    logger.setLevel(logging.DEBUG)
+    try:
+        rust.enable_tracing("debug")
+        assert rust.enable_tracing("debug") is False
+
+        assert rust.alphabet_masks(["abc", "de"]) == [0b1110, 0b110000]
+    finally:
+        logger.removeHandler(handler)
+
+    assert any(
+        message.startswith("find_masks took ") and message.endswith(" strings=2")
+        for message in handler.messages
+    )
+    assert "trace" in build_info()["features"]
+
+
+def test_enable_tracing_invalid_level():
+    with pytest.raises(ValueError):
+        rust.enable_tracing("loud")
@@ -0,0 +1,45 @@
+# -*- coding: utf-8 -*-
+import pickle
+
+import pytest
+
+from alphabet_mask import UnicodeMask, rust
+
+
+def test_unicode_mask():
+    mask = rust.unicode_mask("Grüße, 世界!")
+
+    assert isinstance(mask, UnicodeMask)
+    assert len(mask) == 10
+    assert "ü" in mask and "G" in mask and "界" in mask and "a" not in mask
+    assert mask.chars() == " !,egrßü世界"
+    assert list(mask) == list(mask.chars())
+    assert mask.ranges()[0] == (" ", "!")
+    assert repr(mask) == "UnicodeMask(' !,egrßü世界')"
+    assert rust.unicode_mask("Ab", case_sensitive=True).chars() == "Ab"
+
+
+def test_unicode_operators():
+    greek = UnicodeMask("καλή νύχτα")
+    cafe = UnicodeMask("καφές")
+
+    assert (greek & cafe).chars() == "ακ"
+    assert (greek - cafe).chars() == " ήλντχύ"
+    assert (greek | "Ω") == greek | UnicodeMask("ω")
+    assert not (greek ^ greek)
+    assert pickle.loads(pickle.dumps(greek)) == greek
+    assert UnicodeMask.from_bytes(greek.to_bytes()) == greek
+    with pytest.raises(ValueError):
+        greek & UnicodeMask("a", case_sensitive=True)
+    with pytest.raises(ValueError):
+        UnicodeMask.from_bytes(b"not a mask")
+
+
+def test_common_unicode_alphabets():
+    strings = ["Добрый день", "день и ночь", "Ночь"]
+
+    assert rust.common_unicode_alphabets(strings).chars() == "ноь"
+    common = rust.common_unicode_alphabets(strings * 500, length_limit=64)
+    assert common.chars() == "ноь"
+    assert len(rust.common_unicode_alphabets([])) == 0x110000 - 0x800
+    assert repr(rust.common_unicode_alphabets([])).startswith("<UnicodeMask of")
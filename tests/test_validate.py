@@ -0,0 +1,54 @@
+# -*- coding: utf-8 -*-
+import pytest
+
+from alphabet_mask import rust
+
+USERNAME = "abcdefghijklmnopqrstuvwxyz0123456789_"
+
+
+@pytest.mark.parametrize(
+    ("string", "expected"),
+    [
+        ("user_01", (True, [])),
+        ("", (True, [])),
+        ("Ülrich", (False, [(0, "Ü")])),
+        ("a b!", (False, [(1, " "), (3, "!")])),
+        ("日本_x", (False, [(0, "日"), (1, "本")])),
+    ],
+)
+def test_validate_charset(string, expected):
+    assert rust.validate_charset([string], USERNAME) == [expected]
+
+
+def test_validate_charset_unicode_allowlist():
+    assert rust.validate_charset(["café", "cafè"], "acfé") == [
+        (True, []),
+        (False, [(3, "è")]),
+    ]
+
+
+def test_validate_corpus():
+    report = rust.validate_corpus(["The cat.", "café", "naïve café", "ok"])
+    assert report == {
+        "valid": False,
+        "strings": 4,
+        "violations": [(1, [(3, "é")]), (2, [(2, "ï"), (9, "é")])],
+        "characters": {"é": 2, "ï": 1},
+    }
+
+    assert rust.validate_corpus(["GATTACA", "acgtn"], alphabet="dna")["valid"]
+    assert rust.validate_corpus(["a1!"], alphabet="ascii")["valid"]
+    assert rust.validate_corpus([])["valid"]
+    with pytest.raises(ValueError, match="alphabet"):
+        rust.validate_corpus(["a"], alphabet="klingon")
+
+
+def test_script_coverage():
+    report = rust.script_coverage(["the cat", "καλή νύχτα", "Привет, мир 42"])
+    assert report["strings"] == 3
+    assert report["union"] == " aceht,"
+    assert report["scripts"]["Latin"] == {"strings": 1, "characters": 6}
+    assert report["scripts"]["Greek"] == {"strings": 1, "characters": 9}
+    assert report["scripts"]["Cyrillic"]["characters"] == 9
+    assert report["scripts"]["Common"]["strings"] == 3
+    assert rust.script_coverage([])["scripts"] == {}
@@ -0,0 +1,67 @@
+# -*- coding: utf-8 -*-
+import asyncio
+import concurrent.futures
+
+import pytest
+
+import alphabet_mask
+from alphabet_mask import rust
+
+
+def test_batch_worker():
+    words = ["the cat", "a hat", "that"] * 100
+
+    with alphabet_mask.BatchWorker(max_batch=64, max_delay=0.01) as worker:
+        futures = [worker.submit(word) for word in words]
+        futures += worker.submit_many(words)
+        invalid = worker.submit("cat1")
+
+        masks = [future.result(timeout=10) for future in futures]
+        assert masks == [rust.alphabet_mask(word) for word in words] * 2
+
+        # Only the future of the invalid string fails.
+        with pytest.raises(ValueError, match="invalid character '1'"):
+            invalid.result(timeout=10)
+
+    assert worker.closed
+    with pytest.raises(ValueError, match="BatchWorker is closed"):
+        worker.submit("cat")
+
+
+def test_batch_worker_close_finishes_pending():
+    worker = alphabet_mask.BatchWorker(max_delay=10)
+    future = worker.submit("abc")
+    worker.close()
+    worker.close()
+
+    assert future.done()
+    assert future.result() == 0b1110
+
+
+def test_batch_worker_asyncio():
+    worker = alphabet_mask.BatchWorker()
+
+    async def mask(word):
+        return await asyncio.wrap_future(worker.submit(word))
+
+    async def main():
+        return await asyncio.gather(*(mask(word) for word in ["ab", "cd"]))
+
+    assert asyncio.run(main()) == [0b110, 0b11000]
+    worker.close()
+
+
+def test_batch_worker_threads():
+    words = [f"word {chr(ord('a') + i % 26)}" for i in range(1000)]
+
+    with alphabet_mask.BatchWorker() as worker:
+        with concurrent.futures.ThreadPoolExecutor(8) as pool:
+            masks = list(pool.map(lambda word: worker.submit(word).result(), words))
+
+    assert masks == [rust.alphabet_mask(word) for word in words]
+
+
+@pytest.mark.parametrize("kwargs", [{"max_batch": 0}, {"max_delay": -1.0}])
+def test_batch_worker_invalid_arguments(kwargs):
+    with pytest.raises(ValueError):
+        alphabet_mask.BatchWorker(**kwargs)